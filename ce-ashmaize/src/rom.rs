@@ -85,6 +85,13 @@ impl Rom {
         Self { digest, data }
     }
 
+    /// the raw bytes of the [`Rom`]
+    ///
+    /// Useful to pre-fault the memory pages before hashing starts.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
     pub(crate) fn at(&self, i: u32) -> &[u8; DATASET_ACCESS_SIZE] {
         let start = i as usize % (self.data.len() / DATASET_ACCESS_SIZE);
         <&[u8; DATASET_ACCESS_SIZE]>::try_from(&self.data[start..start + DATASET_ACCESS_SIZE])
@@ -0,0 +1,201 @@
+//! Cardano address checks for the wallets file
+//!
+//! Shelley addresses are bech32 strings: a prefix (`addr` or `stake`, `addr_test` /
+//! `stake_test` on testnets), the separator `1`, then 5-bit data ending in a 6-character
+//! checksum. A typo almost always breaks the checksum, so checking it when the wallets are
+//! loaded catches addresses whose solutions could never be claimed. The first data byte is
+//! the header: its high nibble is the address type (14 and 15 are stake addresses), its low
+//! nibble the network (1 = mainnet), and both have to agree with the prefix.
+//!
+//! Addresses are compared after `normalize`, so a line repeated in upper case counts as a
+//! duplicate. Payment addresses also carry the 28-byte hash of their payment key right after
+//! the header; two different addresses sharing it belong to the same wallet with another
+//! stake part, which is worth a warning but not a merge.
+
+use std::collections::HashMap;
+use std::fmt;
+
+const CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+const CHECKSUM_LEN: usize = 6;
+
+/// Address prefixes: (prefix, stake address, mainnet)
+const PREFIXES: &[(&str, bool, bool)] = &[
+    ("addr", false, true),
+    ("addr_test", false, false),
+    ("stake", true, true),
+    ("stake_test", true, false),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,   // Solutions for it can't be claimed; refuses to start without --force
+    Warning, // Probably a mistake, mined anyway
+}
+
+/// Problem with one wallet address
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub line: Option<usize>, // Line in the wallets file, if it came from one
+    pub address: String,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Both ends, so addresses sharing a prefix can be told apart
+        let chars: Vec<char> = self.address.chars().collect();
+        let shown: String = if chars.len() > 24 {
+            format!("{}...{}", chars[..14].iter().collect::<String>(), chars[chars.len() - 8..].iter().collect::<String>())
+        } else {
+            self.address.clone()
+        };
+        match self.line {
+            Some(line) => write!(f, "line {}: {}: {}", line, shown, self.message),
+            None => write!(f, "{}: {}", shown, self.message),
+        }
+    }
+}
+
+fn polymod(values: &[u8]) -> u32 {
+    let mut checksum: u32 = 1;
+    for &value in values {
+        let top = checksum >> 25;
+        checksum = ((checksum & 0x1ffffff) << 5) ^ value as u32;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
+
+/// Split a bech32 string into its prefix and data bytes, checking the checksum
+/// No length limit: Cardano addresses are longer than the 90 characters of the spec
+fn decode_bech32(address: &str) -> Result<(String, Vec<u8>), String> {
+    if address.chars().any(|c| c.is_ascii_lowercase()) && address.chars().any(|c| c.is_ascii_uppercase()) {
+        return Err("mixes upper and lower case".to_string());
+    }
+    let address = address.to_ascii_lowercase();
+    let separator = address.rfind('1').ok_or("not a bech32 address (no '1' separator)")?;
+    let (prefix, data) = (&address[..separator], &address[separator + 1..]);
+    if prefix.is_empty() {
+        return Err("not a bech32 address (no prefix)".to_string());
+    }
+    if data.len() < CHECKSUM_LEN + 2 {
+        return Err("too short".to_string());
+    }
+
+    let mut values = Vec::with_capacity(data.len());
+    for c in data.chars() {
+        match CHARSET.iter().position(|&v| v as char == c) {
+            Some(value) => values.push(value as u8),
+            None => return Err(format!("'{}' is not a bech32 character", c)),
+        }
+    }
+
+    let mut checked: Vec<u8> = prefix.bytes().map(|b| b >> 5).collect();
+    checked.push(0);
+    checked.extend(prefix.bytes().map(|b| b & 31));
+    checked.extend(&values);
+    if polymod(&checked) != 1 {
+        return Err("checksum mismatch (typo?)".to_string());
+    }
+
+    // 5-bit groups to bytes, dropping the checksum and the padding bits
+    let mut bytes = Vec::new();
+    let (mut accumulator, mut bits) = (0u32, 0u32);
+    for &value in &values[..values.len() - CHECKSUM_LEN] {
+        accumulator = (accumulator << 5) | value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((accumulator >> bits) as u8);
+        }
+    }
+    Ok((prefix.to_string(), bytes))
+}
+
+/// Length of a payment or stake key hash
+const CREDENTIAL_LEN: usize = 28;
+
+/// The address as mined: invisible characters (a byte-order mark, zero-width spaces) dropped
+/// and an all-uppercase address lowercased, which bech32 treats as the same address
+pub fn normalize(address: &str) -> String {
+    let cleaned: String = address.chars().filter(|c| !matches!(c, '\u{feff}' | '\u{200b}'..='\u{200d}')).collect();
+    if cleaned.chars().any(|c| c.is_ascii_lowercase()) {
+        cleaned
+    } else {
+        cleaned.to_ascii_lowercase()
+    }
+}
+
+/// Network and payment key hash of a payment address (None for stake or undecodable addresses)
+fn payment_credential(address: &str) -> Option<(u8, Vec<u8>)> {
+    let (_, payload) = decode_bech32(address).ok()?;
+    let header = *payload.first()?;
+    (header >> 4 <= 7 && payload.len() > CREDENTIAL_LEN).then(|| (header & 0x0f, payload[1..=CREDENTIAL_LEN].to_vec()))
+}
+
+/// Check one address: Err for addresses that can't receive anything, Ok(Some) for warnings
+fn check_address(address: &str) -> Result<Option<String>, String> {
+    let (prefix, payload) = decode_bech32(address)?;
+    let Some(&(_, stake, mainnet)) = PREFIXES.iter().find(|(p, _, _)| *p == prefix) else {
+        return Err(format!("unknown prefix '{}' (expected addr or stake)", prefix));
+    };
+    let header = *payload.first().ok_or("no address data")?;
+
+    let is_stake_type = header >> 4 == 14 || header >> 4 == 15;
+    if is_stake_type != stake {
+        return Err(format!("header says {} address but prefix is '{}'", if is_stake_type { "a stake" } else { "a payment" }, prefix));
+    }
+    if (header & 0x0f == 1) != mainnet {
+        return Err(format!("header network {} doesn't match prefix '{}'", header & 0x0f, prefix));
+    }
+    Ok((!mainnet).then(|| "testnet address".to_string()))
+}
+
+/// Check wallet addresses, with their line in the wallets file if known
+pub fn check_addresses<'a>(addresses: impl IntoIterator<Item = (Option<usize>, &'a str)>) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let mut seen: HashMap<String, (Option<usize>, &str)> = HashMap::new();
+    let mut payment_keys: HashMap<(u8, Vec<u8>), Option<usize>> = HashMap::new();
+    let at = |line: Option<usize>| line.map_or("another entry".to_string(), |line| format!("line {}", line));
+    for (line, address) in addresses {
+        let issue = |severity, message| Issue { line, address: address.to_string(), severity, message };
+        let normalized = normalize(address);
+        if let Some(&(first, first_address)) = seen.get(&normalized) {
+            let message = if first_address == address {
+                format!("duplicate of {}, ignored", at(first))
+            } else {
+                format!("same address as {} written differently, ignored", at(first))
+            };
+            issues.push(issue(Severity::Warning, message));
+            continue;
+        }
+        seen.insert(normalized.clone(), (line, address));
+
+        match check_address(&normalized) {
+            Err(message) => issues.push(issue(Severity::Error, message)),
+            Ok(Some(message)) => issues.push(issue(Severity::Warning, message)),
+            Ok(None) => {}
+        }
+        if normalized != address {
+            issues.push(issue(Severity::Warning, "mined as its normalized form (lower case, no invisible characters)".to_string()));
+        }
+        if let Some(key) = payment_credential(&normalized) {
+            match payment_keys.get(&key) {
+                Some(&first) => issues.push(issue(
+                    Severity::Warning,
+                    format!("same payment key as {} with a different stake part (same wallet twice?)", at(first)),
+                )),
+                None => {
+                    payment_keys.insert(key, line);
+                }
+            }
+        }
+    }
+    issues
+}
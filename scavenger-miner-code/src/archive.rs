@@ -0,0 +1,81 @@
+//! Moving records of long-expired challenges out of `solutions/`
+//!
+//! Records found more than `--archive-after-days` days ago (default 30) are moved to
+//! `solutions/archive/<date found>/` at startup and once a day after that, so the index and
+//! the retry scan only deal with recent records. Records still waiting to be resubmitted
+//! are left alone. `reconcile`, `export` and `export-state` still read the archive.
+
+use crate::{now_utc, paths, store, CliArgs};
+use chrono::{DateTime, Utc};
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_ARCHIVE_AFTER_DAYS: u64 = 30;
+
+/// How often the miner looks for records to archive
+const ARCHIVE_CHECK_INTERVAL: Duration = Duration::from_secs(24 * 3600);
+
+fn cutoff(days: u64) -> DateTime<Utc> {
+    now_utc() - chrono::Duration::days(days as i64)
+}
+
+/// Periodic archiving between tasks
+pub struct Archiver {
+    after_days: u64, // 0 = never
+    last_run: Option<Instant>,
+}
+
+impl Archiver {
+    pub fn new(after_days: u64) -> Self {
+        Archiver { after_days, last_run: None }
+    }
+
+    /// Archive old records if enabled and not done in the last day
+    /// A SQLite store keeps old rows in place, so there is nothing to do
+    pub fn tick(&mut self) {
+        if self.after_days == 0 || store::database_path().is_some() || self.last_run.is_some_and(|t| t.elapsed() < ARCHIVE_CHECK_INTERVAL) {
+            return;
+        }
+        self.last_run = Some(Instant::now());
+
+        match store::get().archive(cutoff(self.after_days)) {
+            Ok(0) => {}
+            Ok(moved) => tracing::info!(
+                "🗃️  Archived {} solution record(s) older than {} days to {}/",
+                moved, self.after_days, paths::solutions_dir().join(store::ARCHIVE_DIR).display(),
+            ),
+            Err(e) => tracing::warn!(error = %e, "⚠️  Failed to archive old solution records: {}", e),
+        }
+    }
+}
+
+/// Usage: archive [--older-than <days>]
+pub fn run_archive(args: &[String]) -> bool {
+    let cli = CliArgs::parse(args);
+    let days = match cli.option("--older-than").map(|s| s.parse::<u64>()) {
+        None => DEFAULT_ARCHIVE_AFTER_DAYS,
+        Some(Ok(days)) if days > 0 => days,
+        Some(_) => {
+            println!("❌ --older-than expects a number of days (at least 1)");
+            return false;
+        }
+    };
+
+    if let Some(db) = store::database_path() {
+        println!("🗄️  Records are kept in the SQLite database {}, which has nothing to archive", db.display());
+        return true;
+    }
+
+    match store::get().archive(cutoff(days)) {
+        Ok(moved) => {
+            println!(
+                "🗃️  Archived {} record(s) found more than {} days ago to {}/",
+                moved, days, paths::solutions_dir().join(store::ARCHIVE_DIR).display()
+            );
+            true
+        }
+        Err(e) => {
+            println!("❌ Archiving failed: {}", e);
+            false
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//! Attempt history (`attempts.jsonl`) and the `attempts` report
+//!
+//! Every finished task (solved, too hard, cut off by the deadline, interrupted, skipped) is
+//! appended as one JSON line: wallet, challenge, difficulty, hashes, mining time and outcome.
+//! The file only grows; `attempts` summarizes it per number of constrained bits (see
+//! difficulty.rs), so you can see which difficulty levels pay off on this machine, and
+//! `--csv` writes the raw attempts for a spreadsheet.
+
+use crate::difficulty::{format_count, DifficultyModel};
+use crate::export::{csv_field, parse_date};
+use crate::{format_duration_secs, get_timestamp, paths, wallets, CliArgs};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+pub const ATTEMPTS_FILE: &str = "attempts.jsonl";
+
+const CSV_COLUMNS: &[&str] = &[
+    "finished_at",
+    "wallet_address",
+    "challenge_id",
+    "difficulty",
+    "constrained_bits",
+    "hashes",
+    "duration_secs",
+    "outcome",
+];
+
+/// One mining attempt at a (wallet, challenge) task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attempt {
+    pub finished_at: String,
+    pub wallet_address: String,
+    pub challenge_id: String,
+    pub difficulty: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub constrained_bits: Option<u32>,
+    pub hashes: u64,
+    pub duration_secs: f64,
+    pub outcome: String, // found, too_hard, deadline, interrupted, skipped, not_found
+}
+
+fn path() -> PathBuf {
+    paths::data_dir().join(ATTEMPTS_FILE)
+}
+
+/// Append an attempt to the history
+pub fn record(wallet_address: &str, challenge_id: &str, difficulty: &str, hashes: u64, duration_secs: f64, outcome: &str) {
+    let attempt = Attempt {
+        finished_at: get_timestamp(),
+        wallet_address: wallet_address.to_string(),
+        challenge_id: challenge_id.to_string(),
+        difficulty: difficulty.to_string(),
+        constrained_bits: DifficultyModel::parse(difficulty).ok().map(|m| m.constrained_bits),
+        hashes,
+        duration_secs,
+        outcome: outcome.to_string(),
+    };
+    let Ok(mut line) = serde_json::to_string(&attempt) else {
+        return;
+    };
+    line.push('\n');
+    let result = fs::OpenOptions::new().create(true).append(true).open(path()).and_then(|mut f| f.write_all(line.as_bytes()));
+    if let Err(e) = result {
+        tracing::warn!(error = %e, "⚠️  Failed to record attempt in {}: {}", ATTEMPTS_FILE, e);
+    }
+}
+
+/// Every recorded attempt; unreadable lines are skipped
+pub fn load() -> Vec<Attempt> {
+    let Ok(file) = fs::File::open(path()) else {
+        return Vec::new();
+    };
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+/// Totals for one difficulty level
+#[derive(Default)]
+struct Level {
+    attempts: u64,
+    found: u64,
+    hashes: u64,
+    secs: f64,
+}
+
+/// Usage: attempts [--wallet <addr,...>] [--from <date>] [--to <date>] [--csv <file>]
+pub fn run_attempts(args: &[String]) -> bool {
+    let cli = CliArgs::parse(args);
+    let wallet_filter: Vec<String> = cli
+        .option("--wallet")
+        .map(|v| v.split(',').map(|s| crate::address::normalize(s.trim())).filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default();
+    let from = match cli.option("--from").map(|v| parse_date(v, false).ok_or(v)) {
+        Some(Err(v)) => {
+            println!("❌ Invalid --from '{}', expected YYYY-MM-DD or an RFC 3339 timestamp", v);
+            return false;
+        }
+        other => other.and_then(Result::ok),
+    };
+    let to = match cli.option("--to").map(|v| parse_date(v, true).ok_or(v)) {
+        Some(Err(v)) => {
+            println!("❌ Invalid --to '{}', expected YYYY-MM-DD or an RFC 3339 timestamp", v);
+            return false;
+        }
+        other => other.and_then(Result::ok),
+    };
+
+    let attempts: Vec<Attempt> = load()
+        .into_iter()
+        .filter(|a| wallet_filter.is_empty() || wallet_filter.contains(&a.wallet_address))
+        .filter(|a| {
+            let at = DateTime::parse_from_rfc3339(&a.finished_at).map(|t| t.with_timezone(&Utc)).ok();
+            from.is_none_or(|from| at.is_some_and(|at| at >= from)) && to.is_none_or(|to| at.is_some_and(|at| at < to))
+        })
+        .collect();
+    if attempts.is_empty() {
+        println!("No attempts recorded in {} (matching the filters)", path().display());
+        return true;
+    }
+
+    if let Some(output) = cli.option("--csv") {
+        let mut csv = String::from("\u{feff}");
+        csv.push_str(&CSV_COLUMNS.join(","));
+        csv.push_str("\r\n");
+        for a in &attempts {
+            let row = [
+                a.finished_at.clone(),
+                a.wallet_address.clone(),
+                a.challenge_id.clone(),
+                a.difficulty.clone(),
+                a.constrained_bits.map_or(String::new(), |b| b.to_string()),
+                a.hashes.to_string(),
+                format!("{:.3}", a.duration_secs),
+                a.outcome.clone(),
+            ];
+            csv.push_str(&row.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(","));
+            csv.push_str("\r\n");
+        }
+        if let Err(e) = fs::write(output, csv) {
+            println!("❌ Failed to write {}: {}", output, e);
+            return false;
+        }
+        println!("📄 Wrote {} attempt(s) to {}", attempts.len(), output);
+    }
+
+    let mut levels: BTreeMap<Option<u32>, Level> = BTreeMap::new();
+    let mut outcomes: BTreeMap<&str, u64> = BTreeMap::new();
+    for a in &attempts {
+        let level = levels.entry(a.constrained_bits).or_default();
+        level.attempts += 1;
+        level.found += u64::from(a.outcome == "found");
+        level.hashes += a.hashes;
+        level.secs += a.duration_secs;
+        *outcomes.entry(a.outcome.as_str()).or_default() += 1;
+    }
+
+    println!("🧮 {} attempt(s) from {}", attempts.len(), path().display());
+    if wallet_filter.len() == 1 {
+        println!("   Wallet: {}", wallets::label(&wallet_filter[0]));
+    }
+    println!(
+        "   Outcomes: {}",
+        outcomes.iter().map(|(o, n)| format!("{} {}", n, o)).collect::<Vec<_>>().join(", ")
+    );
+
+    // Hashes and time over all attempts per solution: what a solution really costs at that level
+    println!(
+        "\n   {:>4}  {:>8}  {:>6}  {:>7}  {:>10}  {:>10}  {:>9}  {:>13}",
+        "bits", "attempts", "found", "success", "hashes/sol", "expected", "avg time", "time/solution"
+    );
+    for (bits, level) in &levels {
+        let per_solution = |total: f64| (level.found > 0).then(|| total / level.found as f64);
+        println!(
+            "   {:>4}  {:>8}  {:>6}  {:>6.1}%  {:>10}  {:>10}  {:>9}  {:>13}",
+            bits.map_or("?".to_string(), |b| b.to_string()),
+            level.attempts,
+            level.found,
+            level.found as f64 * 100.0 / level.attempts as f64,
+            per_solution(level.hashes as f64).map_or("-".to_string(), format_count),
+            bits.map_or("-".to_string(), |b| format_count(2f64.powi(b as i32))),
+            format_duration_secs(level.secs / level.attempts as f64),
+            per_solution(level.secs).map_or("never".to_string(), format_duration_secs)
+        );
+    }
+    true
+}
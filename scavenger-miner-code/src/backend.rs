@@ -0,0 +1,239 @@
+//! Hash backends: the memory-hard function used to mine a challenge
+//!
+//! The mining loop only talks to [`HashBackend`] and [`HashRom`], so a network
+//! upgrade that changes ROM parameters or swaps the algorithm is handled by
+//! adding a backend here instead of rewriting the miner.
+
+use ashmaize::{Rom, RomGenerationType, hash};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+/// Parameter set for the AshMaize hash
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AshMaizeParams {
+    pub rom_size: usize,
+    pub pre_size: usize,
+    pub mixing_numbers: usize,
+    pub nb_loops: u32,
+    pub nb_instrs: u32,
+}
+
+/// Scavenger Mine configuration from the whitepaper
+pub const SCAVENGER_V1_PARAMS: AshMaizeParams = AshMaizeParams {
+    rom_size: 1_073_741_824, // 1GB
+    pre_size: 16_777_216,    // 16MB
+    mixing_numbers: 4,
+    nb_loops: 8,
+    nb_instrs: 256,
+};
+
+/// Tiny ROM for the self-test: same hash program, but builds in milliseconds
+/// Never valid for real challenges, so it isn't listed in [`available_backends`]
+pub const TEST_PARAMS: AshMaizeParams = AshMaizeParams {
+    rom_size: 262_144, // 256KB
+    pre_size: 16_384,  // 16KB
+    mixing_numbers: 4,
+    nb_loops: 8,
+    nb_instrs: 256,
+};
+
+/// A ROM that is ready to hash preimages
+pub trait HashRom: Send + Sync {
+    /// Hash a full preimage (nonce + suffix)
+    fn hash(&self, preimage: &[u8]) -> [u8; 64];
+
+    /// Read one byte of every memory page in slice `part` of `parts`
+    /// Called from each mining thread so TLB misses happen before hashing starts. The ROM was
+    /// already written by the thread that built it, so this doesn't decide where pages live
+    fn pre_touch(&self, _part: usize, _parts: usize) {}
+}
+
+/// Page granularity used when pre-touching ROM memory
+const PAGE_SIZE: usize = 4096;
+
+/// A memory-hard hash function: how to build its ROM and which parameters it uses
+pub trait HashBackend: Send + Sync {
+    /// Unique backend name (used for ROM caching and logging)
+    fn name(&self) -> &str;
+
+    /// Human-readable description of the parameter set
+    fn describe(&self) -> String;
+
+    /// Memory used by one ROM, in bytes
+    fn rom_size(&self) -> usize;
+
+    /// Whether this backend implements the version indicator sent in a challenge payload
+    fn handles_version(&self, version: &str) -> bool;
+
+    /// Build the ROM for a challenge's `no_pre_mine` key
+    fn build_rom(&self, key: &[u8]) -> Arc<dyn HashRom>;
+}
+
+/// AshMaize with a fixed parameter set
+pub struct AshMaizeBackend {
+    name: String,
+    params: AshMaizeParams,
+    versions: Vec<String>,
+}
+
+impl AshMaizeBackend {
+    pub fn new(name: &str, params: AshMaizeParams, versions: &[&str]) -> Self {
+        AshMaizeBackend {
+            name: name.to_string(),
+            params,
+            versions: versions.iter().map(|v| v.to_string()).collect(),
+        }
+    }
+}
+
+struct AshMaizeRom {
+    rom: Rom,
+    nb_loops: u32,
+    nb_instrs: u32,
+}
+
+impl HashRom for AshMaizeRom {
+    #[inline(always)]
+    fn hash(&self, preimage: &[u8]) -> [u8; 64] {
+        hash(preimage, &self.rom, self.nb_loops, self.nb_instrs)
+    }
+
+    fn pre_touch(&self, part: usize, parts: usize) {
+        let data = self.rom.as_bytes();
+        let parts = parts.max(1);
+        let start = data.len() * part / parts;
+        let end = data.len() * (part + 1) / parts;
+
+        let mut checksum = 0u8;
+        for offset in (start..end).step_by(PAGE_SIZE) {
+            checksum ^= data[offset];
+        }
+        std::hint::black_box(checksum);
+    }
+}
+
+impl HashBackend for AshMaizeBackend {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn describe(&self) -> String {
+        let p = &self.params;
+        format!(
+            "AshMaize (ROM {} MB, pre {} MB, mixing {}, loops {}, instrs {})",
+            p.rom_size / (1024 * 1024),
+            p.pre_size / (1024 * 1024),
+            p.mixing_numbers,
+            p.nb_loops,
+            p.nb_instrs
+        )
+    }
+
+    fn rom_size(&self) -> usize {
+        self.params.rom_size
+    }
+
+    fn handles_version(&self, version: &str) -> bool {
+        version == self.name || self.versions.iter().any(|v| v.eq_ignore_ascii_case(version))
+    }
+
+    fn build_rom(&self, key: &[u8]) -> Arc<dyn HashRom> {
+        let rom = Rom::new(
+            key,
+            RomGenerationType::TwoStep {
+                pre_size: self.params.pre_size,
+                mixing_numbers: self.params.mixing_numbers,
+            },
+            self.params.rom_size,
+        );
+
+        Arc::new(AshMaizeRom {
+            rom,
+            nb_loops: self.params.nb_loops,
+            nb_instrs: self.params.nb_instrs,
+        })
+    }
+}
+
+/// Name of the backend used when nothing else is selected
+pub const DEFAULT_BACKEND: &str = "ashmaize-v1";
+
+/// All backends this miner version knows about
+pub fn available_backends() -> Vec<Arc<dyn HashBackend>> {
+    #[allow(unused_mut)]
+    let mut backends: Vec<Arc<dyn HashBackend>> =
+        vec![Arc::new(AshMaizeBackend::new(DEFAULT_BACKEND, SCAVENGER_V1_PARAMS, &["1", "v1", "ashmaize"]))];
+    // Mock API challenges carry version "test" so test builds mine them with the tiny ROM
+    #[cfg(feature = "mock-api")]
+    backends.push(test_backend());
+    backends
+}
+
+/// Backend used by the `selftest` subcommand (and the mock API's challenges)
+pub fn test_backend() -> Arc<dyn HashBackend> {
+    Arc::new(AshMaizeBackend::new("ashmaize-test", TEST_PARAMS, &["test"]))
+}
+
+/// Look up a backend by name
+pub fn backend_by_name(name: &str) -> Option<Arc<dyn HashBackend>> {
+    available_backends().into_iter().find(|b| b.name() == name)
+}
+
+/// Picks the backend for each challenge
+///
+/// Priority:
+/// 1. Version indicator in the challenge payload (unknown versions are refused)
+/// 2. Operator schedule: the latest `start=backend` entry at or before the challenge's issue time
+/// 3. The fallback backend (`--hash-backend`)
+pub struct BackendRouter {
+    fallback: Arc<dyn HashBackend>,
+    schedule: Vec<(DateTime<Utc>, Arc<dyn HashBackend>)>,
+}
+
+impl BackendRouter {
+    pub fn new(fallback: Arc<dyn HashBackend>) -> Self {
+        BackendRouter {
+            fallback,
+            schedule: Vec::new(),
+        }
+    }
+
+    /// Parse a comma-separated schedule like `2025-12-01T00:00:00Z=ashmaize-v2`
+    pub fn with_schedule(mut self, spec: &str) -> Result<Self, String> {
+        for entry in spec.split(',').map(|e| e.trim()).filter(|e| !e.is_empty()) {
+            let (start, name) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("schedule entry '{}' must look like <RFC3339>=<backend>", entry))?;
+            let start = DateTime::parse_from_rfc3339(start.trim())
+                .map_err(|e| format!("invalid date in schedule entry '{}': {}", entry, e))?
+                .with_timezone(&Utc);
+            let backend = backend_by_name(name.trim())
+                .ok_or_else(|| format!("unknown backend '{}' in schedule", name.trim()))?;
+            self.schedule.push((start, backend));
+        }
+        self.schedule.sort_by_key(|(start, _)| *start);
+        Ok(self)
+    }
+
+    /// Resolve the backend for a challenge
+    /// `issued_at` is the challenge's issue (or deadline) timestamp used for the schedule lookup
+    pub fn route(&self, version: Option<&str>, issued_at: Option<&str>) -> Result<Arc<dyn HashBackend>, String> {
+        if let Some(version) = version {
+            return available_backends()
+                .into_iter()
+                .find(|b| b.handles_version(version))
+                .ok_or_else(|| format!("challenge requires unsupported hash version '{}'", version));
+        }
+
+        let issued = issued_at
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.with_timezone(&Utc));
+        if let Some(issued) = issued {
+            if let Some((_, backend)) = self.schedule.iter().rev().find(|(start, _)| *start <= issued) {
+                return Ok(Arc::clone(backend));
+            }
+        }
+
+        Ok(Arc::clone(&self.fallback))
+    }
+}
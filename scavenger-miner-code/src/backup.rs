@@ -0,0 +1,248 @@
+//! `backup` / `restore`: password-encrypted bundle of every solution with a crypto receipt
+//!
+//! Receipts are the only proof of the work done, so `backup` packs every record that has one
+//! into a single file encrypted with a password (key from PBKDF2-HMAC-SHA256, sealed with
+//! ChaCha20-Poly1305), and `restore` puts them back into the configured store on another
+//! machine. The file is JSON with the parameters in the clear; everything about the records,
+//! including the machine they came from, is inside the ciphertext. A wrong password or a modified
+//! file fails authentication instead of restoring garbage.
+
+use crate::{paths, store, CliArgs, SolutionRecord};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::num::NonZeroU32;
+
+/// Marker identifying a backup file
+const BACKUP_FORMAT: &str = "scavenger-miner-backup";
+const BACKUP_VERSION: u32 = 2;
+
+const KDF: &str = "pbkdf2-hmac-sha256";
+const CIPHER: &str = "chacha20-poly1305";
+const PBKDF2_ITERATIONS: u32 = 600_000;
+const SALT_LEN: usize = 16;
+const MIN_PASSWORD_LEN: usize = 8;
+
+/// Password source for scripts, instead of the prompt
+const PASSWORD_ENV: &str = "SCAVENGER_BACKUP_PASSWORD";
+
+#[derive(Serialize, Deserialize)]
+struct BackupFile {
+    format: String,
+    version: u32,
+    created_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hostname: Option<String>, // Version 1 only; now encrypted with the records
+    records: usize,
+    kdf: String,
+    iterations: u32,
+    salt: String,       // Base64
+    cipher: String,
+    nonce: String,      // Base64
+    ciphertext: String, // Base64: JSON array of solution records, then the tag
+}
+
+/// What the ciphertext holds (version 1 held only the records)
+#[derive(Deserialize)]
+struct Contents {
+    hostname: String,
+    records: Vec<SolutionRecord>,
+}
+
+impl BackupFile {
+    /// Bound into the ciphertext, so edits to the header are detected too
+    fn aad(&self) -> String {
+        format!("{}/{}/{}/{}/{}", self.format, self.version, self.records, self.kdf, self.iterations)
+    }
+}
+
+fn derive_key(password: &str, salt: &[u8], iterations: u32) -> Result<LessSafeKey, String> {
+    let iterations = NonZeroU32::new(iterations).ok_or("invalid iteration count")?;
+    let mut key = [0u8; 32];
+    pbkdf2::derive(pbkdf2::PBKDF2_HMAC_SHA256, iterations, salt, password.as_bytes(), &mut key);
+    let key = UnboundKey::new(&CHACHA20_POLY1305, &key).map_err(|_| "invalid key")?;
+    Ok(LessSafeKey::new(key))
+}
+
+/// Read from the terminal without echoing what is typed
+fn prompt(label: &str) -> Result<String, String> {
+    rpassword::prompt_password(format!("{}: ", label)).map_err(|e| format!("Cannot read the password from the terminal: {}", e))
+}
+
+/// Password from `--password-file`, `SCAVENGER_BACKUP_PASSWORD` or the terminal
+fn read_password(cli: &CliArgs, confirm: bool) -> Result<String, String> {
+    if let Some(path) = cli.option("--password-file") {
+        let content = fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+        return Ok(content.trim_end_matches(['\r', '\n']).to_string());
+    }
+    if let Ok(password) = std::env::var(PASSWORD_ENV) {
+        return Ok(password);
+    }
+    let password = prompt("🔑 Backup password")?;
+    if confirm && prompt("🔑 Repeat password")? != password {
+        return Err("Passwords don't match".to_string());
+    }
+    Ok(password)
+}
+
+fn encrypt(records: &[SolutionRecord], password: &str) -> Result<BackupFile, String> {
+    let rng = SystemRandom::new();
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rng.fill(&mut salt).and_then(|_| rng.fill(&mut nonce)).map_err(|_| "no system randomness available")?;
+
+    let mut backup = BackupFile {
+        format: BACKUP_FORMAT.to_string(),
+        version: BACKUP_VERSION,
+        created_at: crate::get_timestamp(),
+        hostname: None,
+        records: records.len(),
+        kdf: KDF.to_string(),
+        iterations: PBKDF2_ITERATIONS,
+        salt: BASE64.encode(salt),
+        cipher: CIPHER.to_string(),
+        nonce: BASE64.encode(nonce),
+        ciphertext: String::new(),
+    };
+
+    let hostname = hostname::get().ok().and_then(|h| h.into_string().ok()).unwrap_or_else(|| "unknown".to_string());
+    let mut data = serde_json::to_vec(&serde_json::json!({ "hostname": hostname, "records": records })).map_err(|e| e.to_string())?;
+    derive_key(password, &salt, backup.iterations)?
+        .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(backup.aad().as_bytes()), &mut data)
+        .map_err(|_| "encryption failed")?;
+    backup.ciphertext = BASE64.encode(data);
+    Ok(backup)
+}
+
+fn decrypt(backup: &BackupFile, password: &str) -> Result<Contents, String> {
+    if backup.kdf != KDF || backup.cipher != CIPHER {
+        return Err(format!("unsupported encryption ({} / {})", backup.kdf, backup.cipher));
+    }
+    let decode = |value: &str| BASE64.decode(value).map_err(|e| format!("corrupt backup: {}", e));
+    let salt = decode(&backup.salt)?;
+    let nonce = Nonce::try_assume_unique_for_key(&decode(&backup.nonce)?).map_err(|_| "corrupt backup: bad nonce")?;
+    let mut data = decode(&backup.ciphertext)?;
+
+    let plaintext = derive_key(password, &salt, backup.iterations)?
+        .open_in_place(nonce, Aad::from(backup.aad().as_bytes()), &mut data)
+        .map_err(|_| "wrong password, or the file was modified")?;
+    let contents = if backup.version == 1 {
+        serde_json::from_slice(plaintext)
+            .map(|records| Contents { hostname: backup.hostname.clone().unwrap_or_else(|| "unknown".to_string()), records })
+    } else {
+        serde_json::from_slice(plaintext)
+    };
+    contents.map_err(|e| format!("corrupt backup: {}", e))
+}
+
+/// Usage: backup [--output <file>] [--password-file <file>]
+pub fn run_backup(args: &[String]) -> bool {
+    let cli = CliArgs::parse(args);
+    let output = cli.option("--output").map(|s| s.to_string()).unwrap_or_else(|| {
+        format!("receipts-backup-{}.json", chrono::Utc::now().format("%Y%m%d-%H%M%S"))
+    });
+
+    let mut records: Vec<SolutionRecord> = store::get().all().into_iter().filter(|r| r.crypto_receipt.is_some()).collect();
+    if records.is_empty() {
+        println!("No crypto receipts in {} to back up", store::description());
+        return true;
+    }
+    records.sort_by(|a, b| (&a.wallet_address, &a.challenge_id).cmp(&(&b.wallet_address, &b.challenge_id)));
+
+    let password = match read_password(&cli, true) {
+        Ok(password) if password.chars().count() >= MIN_PASSWORD_LEN => password,
+        Ok(_) => {
+            println!("❌ Use a password of at least {} characters", MIN_PASSWORD_LEN);
+            return false;
+        }
+        Err(e) => {
+            println!("❌ {}", e);
+            return false;
+        }
+    };
+
+    let result = encrypt(&records, &password)
+        .and_then(|backup| serde_json::to_string_pretty(&backup).map_err(|e| e.to_string()))
+        .and_then(|json| fs::write(&output, json).map_err(|e| format!("Failed to write {}: {}", output, e)));
+    if let Err(e) = result {
+        println!("❌ Backup failed: {}", e);
+        return false;
+    }
+
+    println!("🔐 Backed up {} receipt(s) to {}", records.len(), output);
+    println!("   Keep the password somewhere safe: without it the backup can't be opened");
+    true
+}
+
+/// Usage: restore <file> [--password-file <file>] [--force]
+/// Records that already have a receipt locally are kept unless --force is given
+pub fn run_restore(args: &[String]) -> bool {
+    let cli = CliArgs::parse(args);
+    let Some(input) = cli.positional.first() else {
+        println!("Usage: scavenger-miner restore <file> [--password-file <file>] [--force]");
+        return false;
+    };
+    let force = cli.switch("--force");
+
+    let backup: BackupFile = match fs::read_to_string(input)
+        .map_err(|e| e.to_string())
+        .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()))
+    {
+        Ok(backup) => backup,
+        Err(e) => {
+            println!("❌ Could not read backup {}: {}", input, e);
+            return false;
+        }
+    };
+    if backup.format != BACKUP_FORMAT || backup.version > BACKUP_VERSION {
+        println!("❌ {} is not a supported backup ({} v{})", input, backup.format, backup.version);
+        return false;
+    }
+
+    let Contents { hostname, records } = match read_password(&cli, false).and_then(|password| decrypt(&backup, &password)) {
+        Ok(contents) => contents,
+        Err(e) => {
+            println!("❌ Could not open {}: {}", input, e);
+            return false;
+        }
+    };
+    println!("📦 Restoring {} receipt(s) backed up on {} at {}", records.len(), hostname, backup.created_at);
+
+    if store::database_path().is_none() {
+        if let Err(e) = fs::create_dir_all(paths::solutions_dir()) {
+            println!("❌ Failed to create {}/: {}", paths::solutions_dir().display(), e);
+            return false;
+        }
+    }
+
+    // A local record without a receipt (e.g. still being retried) loses to the backed-up one
+    let with_receipt: HashSet<(String, String)> = store::get()
+        .all()
+        .into_iter()
+        .filter(|r| r.crypto_receipt.is_some())
+        .map(|r| (r.wallet_address, r.challenge_id))
+        .collect();
+
+    let (mut written, mut kept, mut failed) = (0, 0, 0);
+    for record in &records {
+        if !force && with_receipt.contains(&(record.wallet_address.clone(), record.challenge_id.clone())) {
+            kept += 1;
+            continue;
+        }
+        match store::get().save(record) {
+            Ok(_) => written += 1,
+            Err(e) => {
+                println!("   ❌ {} / {}: {}", record.wallet_address, record.challenge_id, e);
+                failed += 1;
+            }
+        }
+    }
+
+    println!("✅ Restored: {} written, {} already had a receipt (use --force to overwrite), {} failed", written, kept, failed);
+    failed == 0
+}
@@ -0,0 +1,130 @@
+//! cgroup v2 CPU quota on Linux (`--cgroup <path>`)
+//!
+//! `--cpu` normally becomes a thread count, rounded up: 75% of 6 cores is 5 threads, 83% of the
+//! machine. With `--cgroup` the miner also moves itself into a cgroup and writes the percentage to
+//! its `cpu.max`, so the kernel holds it to exactly that share of the machine, however the thread
+//! count rounds. The quota follows the CPU usage of each task: group `cpu_usage`, schedule window
+//! budgets and gRPC `SetCpuUsage` included.
+//!
+//! A path starting with `/` is taken from the root of the cgroup v2 hierarchy (root only, or a
+//! delegated subtree); any other path is below the miner's own cgroup, which is what a systemd
+//! unit with `Delegate=yes` allows. The cgroup is created if missing and the cpu controller
+//! enabled in its parent if it isn't yet. It is left behind on exit, to be reused next time.
+
+use crate::get_total_logical_processors;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+/// cpu.max period; the quota is a share of it per CPU
+const PERIOD_US: u64 = 100_000;
+
+/// `cpu.max` of the cgroup the miner runs in, once joined
+static CPU_MAX: OnceLock<PathBuf> = OnceLock::new();
+/// Percentage last written to `cpu.max`
+static CURRENT: Mutex<Option<f64>> = Mutex::new(None);
+
+/// The quota for `cpu_usage` percent of the machine, as written to `cpu.max`
+fn cpu_max_value(cpu_usage: f64) -> String {
+    let quota = (PERIOD_US as f64 * get_total_logical_processors() as f64 * cpu_usage / 100.0).round() as u64;
+    format!("{} {}", quota.max(1000), PERIOD_US)
+}
+
+/// Move the miner into the cgroup at `path`, limited to `cpu_usage` percent; returns its directory
+pub fn join(path: &str, cpu_usage: f64) -> Result<PathBuf, String> {
+    let dir = imp::join(path)?;
+    let cpu_max = dir.join("cpu.max");
+    std::fs::write(&cpu_max, cpu_max_value(cpu_usage)).map_err(|e| format!("Cannot write {}: {}", cpu_max.display(), e))?;
+    let _ = CPU_MAX.set(cpu_max);
+    *CURRENT.lock().unwrap() = Some(cpu_usage);
+    Ok(dir)
+}
+
+/// Limit the miner to `cpu_usage` percent from now on (nothing without `--cgroup`)
+pub fn set_cpu_usage(cpu_usage: f64) {
+    let Some(cpu_max) = CPU_MAX.get() else {
+        return;
+    };
+    let mut current = CURRENT.lock().unwrap();
+    if *current == Some(cpu_usage) {
+        return;
+    }
+    match std::fs::write(cpu_max, cpu_max_value(cpu_usage)) {
+        Ok(()) => {
+            tracing::info!("📏 cgroup CPU quota: {}%", cpu_usage);
+            *current = Some(cpu_usage);
+        }
+        Err(e) => tracing::warn!(error = %e, "⚠️  Cannot update {}: {}", cpu_max.display(), e),
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use std::fs;
+    use std::io::ErrorKind;
+    use std::path::{Path, PathBuf};
+
+    const PERMISSION_HINT: &str = "needs root, or a cgroup delegated to this user (Delegate=yes in a systemd unit)";
+
+    /// Where cgroup2 is mounted (`/sys/fs/cgroup`, or `/sys/fs/cgroup/unified` on hybrid systems)
+    fn mount_point() -> Option<PathBuf> {
+        let mountinfo = fs::read_to_string("/proc/self/mountinfo").ok()?;
+        // ID parent major:minor root mount-point options... - fstype source options
+        mountinfo.lines().find_map(|line| {
+            let (mount, fs) = line.split_once(" - ")?;
+            let mount_point = mount.split_whitespace().nth(4)?;
+            (fs.split_whitespace().next() == Some("cgroup2")).then(|| PathBuf::from(mount_point))
+        })
+    }
+
+    /// The miner's own cgroup, relative to the hierarchy root
+    fn own_cgroup() -> Option<String> {
+        let content = fs::read_to_string("/proc/self/cgroup").ok()?;
+        content.lines().find_map(|line| line.strip_prefix("0::")).map(|path| path.trim_start_matches('/').to_string())
+    }
+
+    fn write(path: &Path, value: &str) -> Result<(), String> {
+        fs::write(path, value).map_err(|e| match e.kind() {
+            ErrorKind::PermissionDenied => format!("Cannot write {}: {} ({})", path.display(), e, PERMISSION_HINT),
+            _ => format!("Cannot write {}: {}", path.display(), e),
+        })
+    }
+
+    pub fn join(path: &str) -> Result<PathBuf, String> {
+        let root = mount_point().ok_or("cgroup v2 isn't mounted (cgroup v1 systems can't use --cgroup)")?;
+        let controllers = fs::read_to_string(root.join("cgroup.controllers")).unwrap_or_default();
+        if !controllers.split_whitespace().any(|c| c == "cpu") {
+            return Err(format!("The cpu controller isn't available in cgroup v2 at {} (it is still bound to cgroup v1 here)", root.display()));
+        }
+        let dir = match path.strip_prefix('/') {
+            Some(absolute) => root.join(absolute),
+            None => root.join(own_cgroup().ok_or("Cannot tell which cgroup the miner runs in")?).join(path),
+        };
+        if dir == root {
+            return Err("--cgroup needs a cgroup below the root".to_string());
+        }
+        fs::create_dir_all(&dir).map_err(|e| match e.kind() {
+            ErrorKind::PermissionDenied => format!("Cannot create {}: {} ({})", dir.display(), e, PERMISSION_HINT),
+            _ => format!("Cannot create {}: {}", dir.display(), e),
+        })?;
+
+        // Move in first: a parent only hands the cpu controller to its children once it has no
+        // processes of its own, which in a delegated unit means once the miner has left it
+        write(&dir.join("cgroup.procs"), &std::process::id().to_string())?;
+        if !dir.join("cpu.max").exists() {
+            let parent = dir.parent().expect("below the root");
+            write(&parent.join("cgroup.subtree_control"), "+cpu").map_err(|e| {
+                format!("{}; the cpu controller can only be enabled in {} while no other process runs there", e, parent.display())
+            })?;
+        }
+        Ok(dir)
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod imp {
+    use std::path::PathBuf;
+
+    pub fn join(_path: &str) -> Result<PathBuf, String> {
+        Err("cgroups are Linux-only".to_string())
+    }
+}
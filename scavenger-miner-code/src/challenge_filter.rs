@@ -0,0 +1,150 @@
+//! Per-wallet allow/deny lists of challenge IDs
+//!
+//! Loaded from `challenge_filters.json` if present:
+//!
+//! ```json
+//! {
+//!   "default": { "exclude": ["**D01C*"] },
+//!   "overrides": [
+//!     { "wallets": ["addr1q8up..."], "include": ["**D05C*", "**D06C01"] }
+//!   ]
+//! }
+//! ```
+//!
+//! A wallet mines a challenge if it matches one of the `include` patterns (or there are none)
+//! and none of the `exclude` patterns. Patterns are challenge IDs where `*` matches any run of
+//! characters and `?` a single one. Each override applies to a group of wallets; lists it
+//! leaves out come from `default`.
+
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+pub const CHALLENGE_FILTER_FILE: &str = "challenge_filters.json";
+
+/// Which challenges one wallet may mine
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChallengeFilter {
+    pub include: Vec<String>, // Empty = every challenge
+    pub exclude: Vec<String>,
+}
+
+impl ChallengeFilter {
+    pub fn allows(&self, challenge_id: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|p| glob_match(p, challenge_id)))
+            && !self.exclude.iter().any(|p| glob_match(p, challenge_id))
+    }
+
+    /// Whether the filter rules out anything at all
+    pub fn is_restrictive(&self) -> bool {
+        !self.include.is_empty() || !self.exclude.is_empty()
+    }
+}
+
+/// Match `text` against a pattern with `*` (any run of characters) and `?` (one character)
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None; // Last `*` and where its match currently ends
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, end)) => {
+                    p = star + 1;
+                    t = end + 1;
+                    backtrack = Some((star, end + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[derive(Deserialize, Default)]
+struct FilterFields {
+    include: Option<Vec<String>>,
+    exclude: Option<Vec<String>>,
+}
+
+impl FilterFields {
+    fn apply(&self, base: &ChallengeFilter) -> ChallengeFilter {
+        ChallengeFilter {
+            include: self.include.clone().unwrap_or_else(|| base.include.clone()),
+            exclude: self.exclude.clone().unwrap_or_else(|| base.exclude.clone()),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct FilterOverride {
+    wallets: Vec<String>,
+    #[serde(flatten)]
+    fields: FilterFields,
+}
+
+#[derive(Deserialize, Default)]
+struct FilterFile {
+    #[serde(default)]
+    default: FilterFields,
+    #[serde(default)]
+    overrides: Vec<FilterOverride>,
+}
+
+/// Challenge filters for all wallets
+#[derive(Default)]
+pub struct ChallengeFilters {
+    default: ChallengeFilter,
+    overrides: Vec<(Vec<String>, ChallengeFilter)>,
+}
+
+impl ChallengeFilters {
+    /// Load from `CHALLENGE_FILTER_FILE`; without it every wallet mines every challenge
+    pub fn load() -> Result<Self, String> {
+        if !Path::new(CHALLENGE_FILTER_FILE).exists() {
+            return Ok(ChallengeFilters::default());
+        }
+
+        let content = fs::read_to_string(CHALLENGE_FILTER_FILE).map_err(|e| e.to_string())?;
+        let file: FilterFile = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+
+        let default = file.default.apply(&ChallengeFilter::default());
+        let overrides = file
+            .overrides
+            .into_iter()
+            .map(|o| {
+                let filter = o.fields.apply(&default);
+                (o.wallets, filter)
+            })
+            .collect();
+        Ok(ChallengeFilters { default, overrides })
+    }
+
+    /// Number of wallet groups with their own filter
+    pub fn override_count(&self) -> usize {
+        self.overrides.len()
+    }
+
+    /// Whether any filter is configured
+    pub fn is_empty(&self) -> bool {
+        !self.default.is_restrictive() && self.overrides.iter().all(|(_, f)| !f.is_restrictive())
+    }
+
+    /// Filter for a wallet: the first override listing it, otherwise the default
+    pub fn for_wallet(&self, wallet_address: &str) -> &ChallengeFilter {
+        self.overrides
+            .iter()
+            .find(|(wallets, _)| wallets.iter().any(|w| w == wallet_address))
+            .map_or(&self.default, |(_, filter)| filter)
+    }
+}
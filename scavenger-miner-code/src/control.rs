@@ -0,0 +1,185 @@
+//! Wallet management while the miner runs (`--control-port`)
+//!
+//! A small HTTP endpoint on localhost:
+//!
+//! - `GET /wallets` lists the wallets being mined, with their group, weight and statistics
+//! - `POST /wallets` with `{"address": "...", "weight": 2, "alias": "...", "group": "..."}` adds one
+//! - `DELETE /wallets/<address>` drains one: its current task finishes, then it leaves the rotation
+//!
+//! Every request needs `Authorization: Bearer <token>`: `--control-token`, or else a random token
+//! created once in the data folder (`control-token`), so only someone who can read that file can
+//! change the wallets. Requests from browsers (an `Origin` header) and POSTs that aren't
+//! `application/json` are refused, so a web page can't send one across sites.
+//!
+//! Requests are checked here (the address like at startup, the group against the running ones)
+//! and handed to the main loop, which applies them between tasks like fetched challenges. Changes
+//! last until the miner stops; the wallets file is never written.
+
+use crate::address::{self, Severity};
+use crate::http_server::{self, error, reply, Reply, Settings};
+use crate::{paths, wallet_stats, wallets};
+use serde::Deserialize;
+use std::fs;
+use std::io::Write;
+use std::net::TcpListener;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+/// Largest request body read
+const MAX_BODY_BYTES: usize = 64 * 1024;
+/// Token file in the data folder, used when `--control-token` isn't given
+const TOKEN_FILE: &str = "control-token";
+
+/// A change for the main loop to apply
+pub enum WalletCommand {
+    Add { address: String, weight: f64, group: Option<String> },
+    Remove { address: String },
+}
+
+/// One wallet as the main loop last published it
+#[derive(Clone)]
+pub struct WalletInfo {
+    pub address: String,
+    pub group: String,
+    pub weight: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AddRequest {
+    address: String,
+    weight: Option<f64>,
+    alias: Option<String>,
+    group: Option<String>,
+}
+
+/// The wallets being mined, and the groups wallets can be added to (first = default)
+#[derive(Default)]
+struct Published {
+    wallets: Vec<WalletInfo>,
+    groups: Vec<String>,
+}
+
+pub struct ControlServer {
+    commands: Receiver<WalletCommand>,
+    published: Arc<Mutex<Published>>,
+}
+
+impl ControlServer {
+    /// Listen on 127.0.0.1:`port` (0 picks a free port), with `token` or the one in the data folder
+    pub fn start(port: u16, token: Option<String>) -> std::io::Result<Self> {
+        let (token, source) = match token {
+            Some(token) => (token, "--control-token".to_string()),
+            None => (saved_token()?, paths::data_dir().join(TOKEN_FILE).display().to_string()),
+        };
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        tracing::info!("🎛️  Wallet control endpoint on http://{}/wallets (bearer token: {})", listener.local_addr()?, source);
+        let (commands_tx, commands) = mpsc::channel();
+        let published = Arc::new(Mutex::new(Published::default()));
+
+        let shared = Arc::clone(&published);
+        let settings = Settings { name: "wallet-control", max_body: MAX_BODY_BYTES, token: Some(token) };
+        http_server::serve(listener, settings, move |request| {
+            let published = shared.lock().unwrap();
+            match (request.method.as_str(), request.segments().as_slice()) {
+                ("GET", ["wallets"]) => list_wallets(&published),
+                ("POST", ["wallets"]) => add_wallet(&request.body, &published, &commands_tx),
+                ("DELETE", ["wallets", address]) => remove_wallet(address, &published, &commands_tx),
+                _ => error(404, "not found (GET /wallets, POST /wallets, DELETE /wallets/<address>)"),
+            }
+        })?;
+        Ok(ControlServer { commands, published })
+    }
+
+    /// Changes requested since the last call
+    pub fn take_commands(&self) -> Vec<WalletCommand> {
+        self.commands.try_iter().collect()
+    }
+
+    /// What `GET /wallets` shows and which groups `POST` accepts
+    pub fn publish(&self, wallets: Vec<WalletInfo>, groups: Vec<String>) {
+        *self.published.lock().unwrap() = Published { wallets, groups };
+    }
+}
+
+/// The token in the data folder, created on first use (readable by this user only)
+fn saved_token() -> std::io::Result<String> {
+    let path = paths::data_dir().join(TOKEN_FILE);
+    if let Ok(token) = fs::read_to_string(&path) {
+        let token = token.trim();
+        if !token.is_empty() {
+            return Ok(token.to_string());
+        }
+    }
+
+    let token = hex::encode(rand::random::<[u8; 16]>());
+    fs::create_dir_all(paths::data_dir())?;
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(&path)?.write_all(token.as_bytes())?;
+    Ok(token)
+}
+
+fn list_wallets(published: &Published) -> Reply {
+    let stats = wallet_stats::snapshot();
+    let wallets: Vec<serde_json::Value> = published
+        .wallets
+        .iter()
+        .map(|wallet| {
+            let stats = stats.get(&wallet.address).copied().unwrap_or_default();
+            serde_json::json!({
+                "address": wallet.address,
+                "label": wallets::label(&wallet.address),
+                "group": wallet.group,
+                "weight": wallet.weight,
+                "tasks": stats.tasks,
+                "hashes": stats.hashes,
+                "solutions": stats.solutions,
+                "accepted": stats.accepted,
+            })
+        })
+        .collect();
+    reply(200, serde_json::json!({ "wallets": wallets }))
+}
+
+fn add_wallet(body: &[u8], published: &Published, commands: &Sender<WalletCommand>) -> Reply {
+    let request: AddRequest = match serde_json::from_slice(body) {
+        Ok(request) => request,
+        Err(e) => return error(400, &format!("invalid body: {}", e)),
+    };
+    let address = address::normalize(request.address.trim());
+    let issues = address::check_addresses([(None, address.as_str())]);
+    if let Some(issue) = issues.iter().find(|i| i.severity == Severity::Error) {
+        return error(400, &issue.to_string());
+    }
+    let weight = request.weight.unwrap_or(1.0);
+    if !(weight.is_finite() && weight > 0.0) {
+        return error(400, "weight must be a positive number");
+    }
+    if let Some(group) = &request.group {
+        if !published.groups.contains(group) {
+            return error(400, &format!("no group '{}' (groups: {})", group, published.groups.join(", ")));
+        }
+    }
+    if published.wallets.iter().any(|w| w.address == address) {
+        return error(409, "wallet is already being mined");
+    }
+
+    if let Some(alias) = request.alias.filter(|a| !a.trim().is_empty()) {
+        wallets::remember_alias(&address, alias.trim());
+    }
+    let warnings: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+    let _ = commands.send(WalletCommand::Add { address: address.clone(), weight, group: request.group });
+    reply(202, serde_json::json!({ "address": address, "status": "added before the next task", "warnings": warnings }))
+}
+
+fn remove_wallet(address: &str, published: &Published, commands: &Sender<WalletCommand>) -> Reply {
+    let address = address::normalize(address);
+    if !published.wallets.iter().any(|w| w.address == address) {
+        return error(404, "wallet is not being mined");
+    }
+    let _ = commands.send(WalletCommand::Remove { address: address.clone() });
+    reply(202, serde_json::json!({ "address": address, "status": "draining: leaves the rotation after its current task" }))
+}
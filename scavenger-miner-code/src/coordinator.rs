@@ -0,0 +1,708 @@
+//! `coordinator`: hand out nonce ranges to worker miners on other machines
+//!
+//! The coordinator fetches the challenges, pairs every wallet with every active challenge it
+//! hasn't solved, and gives workers (`worker`, see worker.rs) disjoint nonce ranges of one pair at
+//! a time over HTTP:
+//!
+//! - `POST /work` with `{"worker": "...", "threads": 8}` returns a job: wallet, challenge and range
+//!   (`{"job": null, "retry_secs": 30}` when there is nothing to do)
+//! - `POST /jobs/<id>/progress` with `{"hashes": ...}` every few seconds; the answer says whether to
+//!   give up the range (the pair was solved elsewhere or its challenge closed)
+//! - `POST /jobs/<id>/result` with `{"outcome": "found", "nonce": "<hex>", "hashes": ...}`
+//! - `GET /status` lists the pairs, jobs and workers
+//!
+//! Found nonces are submitted here, through the same queue as the miner's, so only the coordinator
+//! needs API access and the solutions folder. Ranges of a pair follow each other from a random
+//! offset; a range whose worker reported no result and went quiet for `JOB_TIMEOUT` is handed
+//! out again. A found nonce must lie in the range handed out and is hashed again with the
+//! challenge's ROM (built with the first one) before it is submitted; if the submission fails, the
+//! pair is handed out again. The server listens on localhost unless `--bind` says otherwise, and
+//! other interfaces need `--token`: every request must then carry `Authorization: Bearer <token>`.
+//!
+//! `pool` runs the same server in pool mode: jobs also ask for shares (hashes meeting the first
+//! `--share-bits` constrained bits), which workers send with their reports and which are kept in
+//! the share ledger for `pool-report` (see pool.rs). Every share is hashed again before it is
+//! counted, like found nonces, unless `--trust-shares` says all workers are the operator's own.
+
+use crate::difficulty::{format_count, relaxed_mask};
+use crate::mine::selected_wallets;
+use crate::pool::{self, ShareRecord};
+use crate::error::Rejection;
+use crate::http_server::{self, error, reply, Reply, Request, Settings};
+use crate::retry_policy::RetryPolicies;
+use crate::{
+    backend_for_challenge, build_preimage_suffix, check_difficulty, construct_preimage_fast, fetch_active_challenge_list,
+    fetch_current_challenge, get_timestamp, install_shutdown_handler, notify, setup_directories,
+    shutdown_requested, sleep_unless_shutdown, solution_exists, Challenge, CliArgs, FoundSolution, HashRom, SubmitQueue,
+    CHALLENGE_POLL_ERROR_RETRY_SECS, CHALLENGE_POLL_INTERVAL_SECS,
+};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_PORT: u16 = 8790;
+pub const DEFAULT_BIND: &str = "127.0.0.1";
+/// Nonces per range: a few minutes of work for a desktop CPU
+pub const DEFAULT_RANGE_SIZE: u64 = 1 << 22;
+
+/// A job without progress reports for this long is handed out again
+const JOB_TIMEOUT: Duration = Duration::from_secs(120);
+/// Suggested wait for workers when there is nothing to hand out
+const IDLE_RETRY_SECS: u64 = 30;
+/// Largest request body read
+const MAX_BODY_BYTES: usize = 256 * 1024;
+/// Shares taken per report; a worker sending more is finding them far too often
+const MAX_SHARES_PER_REPORT: usize = 1000;
+
+/// One wallet mining one challenge
+struct Pair {
+    wallet: String,
+    challenge: Challenge,
+    base: u64,                 // Random offset the ranges count from
+    cursor: u64,               // Next untouched nonce after `base`
+    returned: Vec<(u64, u64)>, // Ranges (offset, count) given back unfinished
+    hashes: u64,
+    solved: bool,
+    reopened: bool, // Its submission failed: the record on disk no longer counts as solved
+}
+
+impl Pair {
+    /// Constrained bits of the difficulty (unparseable difficulties sort last)
+    fn bits(&self) -> u32 {
+        self.challenge.difficulty_model().map_or(u32::MAX, |m| m.constrained_bits)
+    }
+}
+
+struct Job {
+    pair: usize,
+    offset: u64,
+    count: u64,
+    worker: String,
+    issued: Instant,
+    last_seen: Instant,
+    hashes: u64,
+    shares: HashSet<u64>, // Pool mode: shares taken for this range
+}
+
+impl Job {
+    /// Whether `nonce` lies in the range handed out
+    fn covers(&self, pair: &Pair, nonce: u64) -> bool {
+        nonce.wrapping_sub(pair.base.wrapping_add(self.offset)) < self.count
+    }
+}
+
+#[derive(Default)]
+struct WorkerInfo {
+    threads: Option<usize>,
+    last_seen: Option<Instant>,
+    jobs: u64,
+    hashes: u64,
+    found: u64,
+    shares: u64,
+    rejected: u64, // Shares and nonces refused (outside the range, repeated, or failing verification)
+}
+
+/// Pool mode settings (`pool`)
+#[derive(Clone, Copy)]
+struct PoolSettings {
+    share_bits: u32,
+    verify: bool,
+}
+
+/// A share or a found nonce reported by a worker, waiting to be counted or submitted
+struct Claim {
+    solution: bool,
+    worker: String,
+    pair: usize,
+    nonce: u64,
+    nonce_start: u64,
+}
+
+#[derive(Default)]
+struct State {
+    pairs: Vec<Pair>,
+    jobs: HashMap<u64, Job>,
+    next_job: u64,
+    workers: HashMap<String, WorkerInfo>,
+    claims: Vec<Claim>, // Waiting for the main loop to verify, record and submit them
+    range_size: u64,
+    pool: Option<PoolSettings>,
+}
+
+impl State {
+    /// Add pairs for new challenges, and drop closed challenges and solved pairs (their jobs are cancelled
+    /// at the next progress report)
+    fn refresh(&mut self, wallets: &[String], challenges: &[Challenge]) {
+        let mut added = 0;
+        for challenge in challenges.iter().filter(|c| c.is_active()) {
+            for wallet in wallets {
+                let known = self.pairs.iter().any(|p| p.wallet == *wallet && p.challenge.challenge_id == challenge.challenge_id);
+                if !known && !solution_exists(wallet, &challenge.challenge_id) {
+                    self.pairs.push(Pair {
+                        wallet: wallet.clone(),
+                        challenge: challenge.clone(),
+                        base: rand::random::<u64>(),
+                        cursor: 0,
+                        returned: Vec::new(),
+                        hashes: 0,
+                        solved: false,
+                        reopened: false,
+                    });
+                    added += 1;
+                }
+            }
+        }
+        for pair in &mut self.pairs {
+            if !pair.solved && !pair.reopened && solution_exists(&pair.wallet, &pair.challenge.challenge_id) {
+                pair.solved = true;
+            }
+        }
+        if added > 0 {
+            tracing::info!("🧩 {} new wallet/challenge pair(s), {} open", added, self.open_pairs().count());
+        }
+    }
+
+    fn open_pairs(&self) -> impl Iterator<Item = (usize, &Pair)> {
+        self.pairs.iter().enumerate().filter(|(_, p)| !p.solved && p.challenge.is_active())
+    }
+
+    /// Hand a pair out again after its submission failed (unless someone else already has it)
+    fn reopen(&mut self, wallet: &str, challenge_id: &str, error: &str) {
+        if Rejection::from_message(error) == Rejection::Duplicate {
+            return;
+        }
+        if let Some(pair) = self.pairs.iter_mut().find(|p| p.solved && p.wallet == wallet && p.challenge.challenge_id == challenge_id) {
+            pair.solved = false;
+            pair.reopened = true;
+            tracing::info!(challenge_id = %challenge_id, "🔓 Submission for {} on challenge {} failed; handing the pair out again", crate::wallets::label(wallet), challenge_id);
+        }
+    }
+
+    /// Whether a job is still worth working on
+    fn job_wanted(&self, job: &Job) -> bool {
+        let pair = &self.pairs[job.pair];
+        !pair.solved && pair.challenge.is_active()
+    }
+
+    /// Next range for `worker`: the open pair with the fewest jobs out, easiest first
+    fn assign(&mut self, worker: &str) -> Option<u64> {
+        let mut jobs_out: HashMap<usize, usize> = HashMap::new();
+        for job in self.jobs.values() {
+            *jobs_out.entry(job.pair).or_default() += 1;
+        }
+        let (index, _) = self.open_pairs().min_by_key(|(i, p)| (jobs_out.get(i).copied().unwrap_or(0), p.bits(), p.challenge.latest_submission.clone()))?;
+
+        let pair = &mut self.pairs[index];
+        let (offset, count) = match pair.returned.pop() {
+            Some(range) => range,
+            None => {
+                let range = (pair.cursor, self.range_size);
+                pair.cursor = pair.cursor.wrapping_add(self.range_size);
+                range
+            }
+        };
+        let id = self.next_job;
+        self.next_job += 1;
+        let now = Instant::now();
+        let job = Job {
+            pair: index,
+            offset,
+            count,
+            worker: worker.to_string(),
+            issued: now,
+            last_seen: now,
+            hashes: 0,
+            shares: HashSet::new(),
+        };
+        self.jobs.insert(id, job);
+        Some(id)
+    }
+
+    /// Close a job; unfinished ranges of open pairs go back to be handed out again
+    fn close(&mut self, id: u64, finished: bool) -> Option<Job> {
+        let job = self.jobs.remove(&id)?;
+        if !finished && self.job_wanted(&job) {
+            self.pairs[job.pair].returned.push((job.offset, job.count));
+        }
+        Some(job)
+    }
+
+    /// Hand out ranges again whose worker went quiet
+    fn expire_jobs(&mut self) {
+        let expired: Vec<u64> = self.jobs.iter().filter(|(_, job)| job.last_seen.elapsed() > JOB_TIMEOUT).map(|(id, _)| *id).collect();
+        for id in expired {
+            if let Some(job) = self.close(id, false) {
+                tracing::info!("⏱️  Job {} of worker {} timed out; its range will be handed out again", id, job.worker);
+            }
+        }
+    }
+
+    /// Take the shares of a report (pool mode only); returns how many were accepted
+    fn take_shares(&mut self, id: u64, shares: &[String]) -> usize {
+        let (Some(job), Some(settings)) = (self.jobs.get_mut(&id), self.pool) else {
+            return 0;
+        };
+        let pair = &self.pairs[job.pair];
+        let mut accepted = 0;
+        for share in shares.iter().take(MAX_SHARES_PER_REPORT) {
+            let Ok(nonce) = u64::from_str_radix(share.trim_start_matches("0x"), 16) else {
+                continue;
+            };
+            if job.covers(pair, nonce) && job.shares.insert(nonce) {
+                let nonce_start = pair.base.wrapping_add(job.offset);
+                self.claims.push(Claim { solution: false, worker: job.worker.clone(), pair: job.pair, nonce, nonce_start });
+                accepted += 1;
+            }
+        }
+        let worker = job.worker.clone();
+        let rejected = shares.len() - accepted;
+        let info = self.worker(&worker);
+        info.rejected += rejected as u64;
+        if !settings.verify {
+            info.shares += accepted as u64;
+        }
+        accepted
+    }
+
+    fn worker(&mut self, name: &str) -> &mut WorkerInfo {
+        let worker = self.workers.entry(name.to_string()).or_default();
+        worker.last_seen = Some(Instant::now());
+        worker
+    }
+}
+
+/// Usage: coordinator [--wallet <addr>[,<addr>...] | --wallets <file>] [--port <n>] [--bind <addr>]
+///        [--token <secret>] [--range-size <nonces>]
+pub fn run_coordinator(args: &[String]) -> bool {
+    run(args, false)
+}
+
+/// Usage: pool, with the options of `coordinator` and [--share-bits <n>] [--trust-shares]
+pub fn run_pool(args: &[String]) -> bool {
+    run(args, true)
+}
+
+fn run(args: &[String], pool_mode: bool) -> bool {
+    let cli = CliArgs::parse(args);
+    let Some(wallets) = selected_wallets(&cli) else {
+        return false;
+    };
+    if wallets.is_empty() {
+        println!("❌ No wallets to mine");
+        return false;
+    }
+    let port = match cli.option("--port").map(|s| s.parse::<u16>()) {
+        None => DEFAULT_PORT,
+        Some(Ok(port)) => port,
+        Some(Err(_)) => {
+            println!("❌ Invalid --port");
+            return false;
+        }
+    };
+    let range_size = match cli.option("--range-size").map(|s| s.parse::<u64>()) {
+        None => DEFAULT_RANGE_SIZE,
+        Some(Ok(size)) if size > 0 => size,
+        Some(_) => {
+            println!("❌ --range-size must be a positive number of nonces");
+            return false;
+        }
+    };
+    let share_bits = match cli.option("--share-bits").map(|s| s.parse::<u32>()) {
+        None => pool::DEFAULT_SHARE_BITS,
+        Some(Ok(bits)) if (1..=64).contains(&bits) => bits,
+        Some(_) => {
+            println!("❌ --share-bits must be between 1 and 64");
+            return false;
+        }
+    };
+    let verify = !cli.switch("--trust-shares");
+    let pool = pool_mode.then_some(PoolSettings { share_bits, verify });
+    let token = cli.option("--token").map(|t| t.to_string());
+    let retry_policies = match RetryPolicies::load() {
+        Ok(policies) => policies,
+        Err(e) => {
+            println!("❌ Invalid retry policy: {}", e);
+            return false;
+        }
+    };
+    if let Err(e) = setup_directories() {
+        println!("❌ Failed to create output directories: {}", e);
+        return false;
+    }
+
+    let bind = cli.option("--bind").unwrap_or(DEFAULT_BIND);
+    let listener = match TcpListener::bind((bind, port)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            println!("❌ Cannot listen on {}:{}: {}", bind, port, e);
+            return false;
+        }
+    };
+    let Ok(address) = listener.local_addr() else {
+        println!("❌ Cannot listen on {}:{}", bind, port);
+        return false;
+    };
+    // Reported nonces are submitted for these wallets, so the fleet's port isn't left open
+    if token.is_none() && !address.ip().is_loopback() {
+        println!("❌ Listening on {} needs --token, so that only your workers can take jobs and report nonces", address.ip());
+        return false;
+    }
+    println!(
+        "🛰️  {} for {} wallet(s) on http://{} ({} nonces per range{})",
+        if pool_mode { "Pool" } else { "Coordinator" },
+        wallets.len(),
+        address,
+        range_size,
+        if pool_mode { format!(", a share every 2^{} hashes", share_bits) } else { String::new() }
+    );
+
+    install_shutdown_handler();
+    let state = Arc::new(Mutex::new(State { range_size, pool, ..State::default() }));
+    let shared = Arc::clone(&state);
+    let settings = Settings { name: "coordinator", max_body: MAX_BODY_BYTES, token };
+    if let Err(e) = http_server::serve(listener, settings, move |request| handle_request(request, &shared)) {
+        println!("❌ Cannot start the coordinator: {}", e);
+        return false;
+    }
+
+    let mut submit_queue = SubmitQueue::start(Arc::new(retry_policies));
+    let mut verifier = Verifier { cli: &cli, rom: None };
+    let (mut accepted, mut failed) = (0, 0);
+    let mut next_refresh = Instant::now();
+    while !shutdown_requested() {
+        if Instant::now() >= next_refresh {
+            let challenges = match fetch_active_challenge_list() {
+                Ok(Some(challenges)) => Ok(challenges),
+                Ok(None) => fetch_current_challenge().map(|c| vec![c]),
+                Err(e) => Err(e),
+            };
+            let wait = match challenges {
+                Ok(challenges) => {
+                    state.lock().unwrap().refresh(&wallets, &challenges);
+                    CHALLENGE_POLL_INTERVAL_SECS
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, "⚠️  Failed to fetch challenges: {}", e);
+                    CHALLENGE_POLL_ERROR_RETRY_SECS
+                }
+            };
+            next_refresh = Instant::now() + Duration::from_secs(wait);
+        }
+
+        state.lock().unwrap().expire_jobs();
+        settle_claims(&state, &mut verifier, &submit_queue);
+        for event in submit_queue.take_events() {
+            match event {
+                notify::Event::Receipt { .. } => accepted += 1,
+                notify::Event::SubmitFailed { wallet_address, challenge_id, error } => {
+                    failed += 1;
+                    state.lock().unwrap().reopen(&wallet_address, &challenge_id, &error);
+                }
+            }
+        }
+        sleep_unless_shutdown(Duration::from_secs(1));
+    }
+
+    tracing::info!("🛑 Coordinator stopping");
+    settle_claims(&state, &mut verifier, &submit_queue);
+    for event in submit_queue.finish() {
+        match event {
+            notify::Event::Receipt { .. } => accepted += 1,
+            notify::Event::SubmitFailed { .. } => failed += 1,
+        }
+    }
+    println!("📊 {} solution(s) accepted, {} failed", accepted, failed);
+    failed == 0
+}
+
+/// Hashes found nonces (and shares, unless `--trust-shares`) again, keeping the ROM of the last challenge
+struct Verifier<'a> {
+    cli: &'a CliArgs,
+    rom: Option<(String, Arc<dyn HashRom>)>,
+}
+
+impl Verifier<'_> {
+    /// Whether `nonce` meets `mask` for the wallet and challenge
+    fn check(&mut self, wallet: &str, challenge: &Challenge, nonce: u64, mask: &[u8]) -> Result<bool, String> {
+        let key = format!("{}:{}", challenge.challenge_id, challenge.no_pre_mine);
+        let rom = match &self.rom {
+            Some((rom_key, rom)) if *rom_key == key => Arc::clone(rom),
+            _ => {
+                let backend = backend_for_challenge(self.cli, challenge)?;
+                tracing::info!(challenge_id = %challenge.challenge_id, "🔄 Building the ROM of challenge {} to verify shares...", challenge.challenge_id);
+                let rom = backend.build_rom(challenge.no_pre_mine.as_bytes());
+                self.rom = Some((key, Arc::clone(&rom)));
+                rom
+            }
+        };
+        let hash = rom.hash(&construct_preimage_fast(nonce, &build_preimage_suffix(wallet, challenge)));
+        Ok(check_difficulty(&hash, mask))
+    }
+}
+
+/// Verify found nonces (and shares if asked), record and submit the claims reported since the last call
+fn settle_claims(state: &Mutex<State>, verifier: &mut Verifier, submit_queue: &SubmitQueue) {
+    let (claims, pool) = {
+        let mut state = state.lock().unwrap();
+        let claims = std::mem::take(&mut state.claims);
+        let claims: Vec<(Claim, String, Challenge)> = claims
+            .into_iter()
+            .map(|claim| {
+                let pair = &state.pairs[claim.pair];
+                let (wallet, challenge) = (pair.wallet.clone(), pair.challenge.clone());
+                (claim, wallet, challenge)
+            })
+            .collect();
+        (claims, state.pool)
+    };
+
+    for (claim, wallet, challenge) in claims {
+        let Ok(full_mask) = hex::decode(&challenge.difficulty) else {
+            continue;
+        };
+        let mask = match pool {
+            Some(settings) if !claim.solution => relaxed_mask(&full_mask, settings.share_bits),
+            _ => full_mask,
+        };
+        if claim.solution || pool.is_some_and(|settings| settings.verify) {
+            let valid = match verifier.check(&wallet, &challenge, claim.nonce, &mask) {
+                Ok(valid) => valid,
+                Err(e) => {
+                    tracing::warn!(error = %e, "⚠️  Cannot verify {:016x} from worker {}: {}", claim.nonce, claim.worker, e);
+                    false
+                }
+            };
+            let mut state = state.lock().unwrap();
+            let worker = state.worker(&claim.worker);
+            if !valid {
+                worker.rejected += 1;
+                tracing::info!(
+                    "🚫 {} {:016x} from worker {} does not meet its mask; refused",
+                    if claim.solution { "Solution" } else { "Share" },
+                    claim.nonce,
+                    claim.worker,
+                );
+                if claim.solution {
+                    // The pair was marked solved when the nonce came in
+                    state.pairs[claim.pair].solved = false;
+                }
+                continue;
+            }
+            if !claim.solution {
+                worker.shares += 1;
+            }
+        }
+
+        if let Some(settings) = pool {
+            let kind = if claim.solution { "solution" } else { "share" };
+            pool::record(&ShareRecord::new(kind, &claim.worker, &wallet, &challenge.challenge_id, claim.nonce, settings.share_bits));
+        }
+        if claim.solution {
+            submit_queue.push(FoundSolution {
+                wallet_address: wallet,
+                challenge_id: challenge.challenge_id.clone(),
+                nonce: claim.nonce,
+                nonce_start: claim.nonce_start,
+                found_at: get_timestamp(),
+            });
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct WorkRequest {
+    worker: Option<String>,
+    threads: Option<usize>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct ProgressRequest {
+    hashes: u64,
+    shares: Vec<String>, // Pool mode
+}
+
+#[derive(Deserialize)]
+struct ResultRequest {
+    outcome: String, // found, exhausted, cancelled, interrupted or deadline
+    nonce: Option<String>,
+    #[serde(default)]
+    hashes: u64,
+    #[serde(default)]
+    shares: Vec<String>, // Pool mode
+}
+
+fn parse_body<'a, T: Deserialize<'a> + Default>(body: &'a [u8]) -> Result<T, Reply> {
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return Ok(T::default());
+    }
+    serde_json::from_slice(body).map_err(|e| error(400, &format!("invalid body: {}", e)))
+}
+
+fn take_work(body: &[u8], state: &mut State, peer: &str) -> Reply {
+    let request: WorkRequest = match parse_body(body) {
+        Ok(request) => request,
+        Err(reply) => return reply,
+    };
+    let name = request.worker.filter(|w| !w.trim().is_empty()).unwrap_or_else(|| peer.to_string());
+    let worker = state.worker(&name);
+    worker.threads = request.threads.or(worker.threads);
+
+    let Some(id) = state.assign(&name) else {
+        return reply(200, serde_json::json!({ "job": null, "retry_secs": IDLE_RETRY_SECS }));
+    };
+    let job = &state.jobs[&id];
+    let pair = &state.pairs[job.pair];
+    let body = serde_json::json!({
+        "job": {
+            "id": id,
+            "wallet": pair.wallet,
+            "challenge": pair.challenge,
+            "nonce_start": format!("{:016x}", pair.base.wrapping_add(job.offset)),
+            "nonce_count": job.count,
+            "share_bits": state.pool.map(|settings| settings.share_bits),
+        }
+    });
+    state.worker(&name).jobs += 1;
+    reply(200, body)
+}
+
+fn report_progress(id: u64, body: &[u8], state: &mut State) -> Reply {
+    let request: ProgressRequest = match parse_body(body) {
+        Ok(request) => request,
+        Err(reply) => return reply,
+    };
+    let Some(job) = state.jobs.get_mut(&id) else {
+        return reply(200, serde_json::json!({ "cancel": true, "reason": "unknown job (timed out?)" }));
+    };
+    job.last_seen = Instant::now();
+    job.hashes = job.hashes.max(request.hashes);
+    let shares = state.take_shares(id, &request.shares);
+    // The worker gives the range up with its result
+    let cancel = !state.job_wanted(&state.jobs[&id]);
+    reply(200, serde_json::json!({ "cancel": cancel, "shares_accepted": shares }))
+}
+
+fn report_result(id: u64, body: &[u8], state: &mut State) -> Reply {
+    let request: ResultRequest = match serde_json::from_slice(body) {
+        Ok(request) => request,
+        Err(e) => return error(400, &format!("invalid body: {}", e)),
+    };
+    let nonce = match (request.outcome.as_str(), request.nonce.as_deref()) {
+        ("found", Some(nonce)) => match u64::from_str_radix(nonce.trim_start_matches("0x"), 16) {
+            Ok(nonce) => Some(nonce),
+            Err(_) => return error(400, "nonce must be hex"),
+        },
+        ("found", None) => return error(400, "a found result needs the nonce"),
+        _ => None,
+    };
+    let Some(job) = state.jobs.get(&id) else {
+        return error(404, "unknown job (timed out?)");
+    };
+    if nonce.is_some_and(|nonce| !job.covers(&state.pairs[job.pair], nonce)) {
+        let worker = job.worker.clone();
+        state.worker(&worker).rejected += 1;
+        return error(400, "nonce is outside the range handed out");
+    }
+    let shares = state.take_shares(id, &request.shares);
+    // A found or exhausted range needs no further work; anything else goes back in the pool
+    let finished = matches!(request.outcome.as_str(), "found" | "exhausted");
+    let Some(job) = state.close(id, finished) else {
+        return error(404, "unknown job (timed out?)");
+    };
+    let pair = &mut state.pairs[job.pair];
+    pair.hashes += request.hashes;
+    let (wallet, challenge_id) = (pair.wallet.clone(), pair.challenge.challenge_id.clone());
+
+    let worker = state.worker(&job.worker);
+    worker.hashes += request.hashes;
+    if let Some(nonce) = nonce {
+        worker.found += 1;
+        let pair = &mut state.pairs[job.pair];
+        if pair.solved {
+            tracing::info!("🔁 Worker {} found {:016x} for an already solved pair; ignored", job.worker, nonce);
+        } else {
+            pair.solved = true;
+            tracing::info!(
+                challenge_id = %challenge_id,
+                "✅ Worker {} found {:016x} for {} on challenge {} after {} hashes; submitting",
+                job.worker,
+                nonce,
+                crate::wallets::label(&wallet),
+                challenge_id,
+                format_count(pair.hashes as f64),
+            );
+            let nonce_start = pair.base.wrapping_add(job.offset);
+            state.claims.push(Claim { solution: true, worker: job.worker.clone(), pair: job.pair, nonce, nonce_start });
+        }
+    }
+    reply(200, serde_json::json!({ "ok": true, "shares_accepted": shares }))
+}
+
+fn status(state: &State) -> Reply {
+    let pairs: Vec<serde_json::Value> = state
+        .pairs
+        .iter()
+        .map(|pair| {
+            serde_json::json!({
+                "wallet": pair.wallet,
+                "challenge_id": pair.challenge.challenge_id,
+                "difficulty": pair.challenge.difficulty,
+                "solved": pair.solved,
+                "active": pair.challenge.is_active(),
+                "nonces_handed_out": pair.cursor,
+                "hashes": pair.hashes,
+            })
+        })
+        .collect();
+    let jobs: Vec<serde_json::Value> = state
+        .jobs
+        .iter()
+        .map(|(id, job)| {
+            serde_json::json!({
+                "id": id,
+                "worker": job.worker,
+                "wallet": state.pairs[job.pair].wallet,
+                "challenge_id": state.pairs[job.pair].challenge.challenge_id,
+                "nonce_count": job.count,
+                "hashes": job.hashes,
+                "age_secs": job.issued.elapsed().as_secs(),
+            })
+        })
+        .collect();
+    let workers: Vec<serde_json::Value> = state
+        .workers
+        .iter()
+        .map(|(name, worker)| {
+            serde_json::json!({
+                "worker": name,
+                "threads": worker.threads,
+                "last_seen_secs": worker.last_seen.map(|at| at.elapsed().as_secs()),
+                "jobs": worker.jobs,
+                "hashes": worker.hashes,
+                "found": worker.found,
+                "shares": worker.shares,
+                "rejected": worker.rejected,
+            })
+        })
+        .collect();
+    let pool = state.pool.map(|settings| serde_json::json!({ "share_bits": settings.share_bits, "verify_shares": settings.verify }));
+    reply(200, serde_json::json!({ "pool": pool, "pairs": pairs, "jobs": jobs, "workers": workers }))
+}
+
+fn handle_request(request: &Request, state: &Mutex<State>) -> Reply {
+    let mut state = state.lock().unwrap();
+    match (request.method.as_str(), request.segments().as_slice()) {
+        ("POST", ["work"]) => take_work(&request.body, &mut state, &request.peer.to_string()),
+        ("POST", ["jobs", id, action @ ("progress" | "result")]) => match id.parse::<u64>() {
+            Ok(id) if *action == "progress" => report_progress(id, &request.body, &mut state),
+            Ok(id) => report_result(id, &request.body, &mut state),
+            Err(_) => error(404, "unknown job"),
+        },
+        ("GET", ["status"]) => status(&state),
+        _ => error(404, "not found (POST /work, POST /jobs/<id>/progress, POST /jobs/<id>/result, GET /status)"),
+    }
+}
@@ -0,0 +1,116 @@
+//! Solutions the API already credits to a wallet (`--credited-refresh-minutes`)
+//!
+//! Local records only know what this machine submitted; another rig mining the same wallets
+//! may have solved a challenge already. Before a wallet picks a challenge, its solved challenges
+//! are fetched from `GET /solutions/<wallet>` (again once they are older than the refresh
+//! interval) and those pairs are treated as solved. If the API has no such endpoint (404) the
+//! check is switched off for the rest of the run; any other failure keeps the last answer and
+//! tries again after the interval. Offline runs never ask.
+
+use crate::wallets;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_REFRESH_MINUTES: f64 = 30.0;
+
+struct Credited {
+    client: reqwest::blocking::Client,
+    refresh: Duration,
+    unsupported: bool,                                  // The API answered 404
+    wallets: HashMap<String, (Instant, HashSet<String>)>, // Wallet -> (fetched at, challenge IDs)
+    reported: HashSet<(String, String)>,                // Pairs already logged as skipped
+}
+
+/// Set by `enable`; `None` means nothing is checked
+static CREDITED: Mutex<Option<Credited>> = Mutex::new(None);
+
+/// Start checking the API, refreshing each wallet's list after `refresh`
+pub fn enable(refresh: Duration) {
+    match crate::http::client() {
+        Ok(client) => {
+            *CREDITED.lock().unwrap() = Some(Credited {
+                client,
+                refresh,
+                unsupported: false,
+                wallets: HashMap::new(),
+                reported: HashSet::new(),
+            });
+        }
+        Err(e) => tracing::warn!(error = %e, "⚠️  Not checking server-side solutions (HTTP client: {})", e),
+    }
+}
+
+/// Challenge IDs in a solutions response: a list of IDs or of objects with `challenge_id`,
+/// bare or under `solutions`
+fn parse_challenge_ids(body: &serde_json::Value) -> Option<HashSet<String>> {
+    let list = body.get("solutions").unwrap_or(body).as_array()?;
+    Some(
+        list.iter()
+            .filter_map(|entry| entry.as_str().or_else(|| entry.get("challenge_id").and_then(|id| id.as_str())))
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+enum Fetch {
+    Solved(HashSet<String>),
+    Unsupported,
+    Failed(String),
+}
+
+fn fetch(client: &reqwest::blocking::Client, wallet: &str) -> Fetch {
+    let url = format!("{}/solutions/{}", crate::http::api_base(), wallet);
+    let response = match crate::http::send(crate::http::get(client, &url)) {
+        Ok(response) => response,
+        Err(e) => return Fetch::Failed(e.to_string()),
+    };
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Fetch::Unsupported;
+    }
+    if !response.status().is_success() {
+        return Fetch::Failed(format!("HTTP {}", response.status()));
+    }
+    match response.json::<serde_json::Value>() {
+        Ok(body) => parse_challenge_ids(&body).map_or_else(|| Fetch::Failed("no solution list in response".to_string()), Fetch::Solved),
+        Err(e) => Fetch::Failed(e.to_string()),
+    }
+}
+
+/// Whether the API credits `wallet_address` with a solution to `challenge_id`
+pub fn is_credited(wallet_address: &str, challenge_id: &str) -> bool {
+    let mut guard = CREDITED.lock().unwrap();
+    let Some(credited) = guard.as_mut().filter(|c| !c.unsupported) else {
+        return false;
+    };
+
+    let stale = credited.wallets.get(wallet_address).is_none_or(|(at, _)| at.elapsed() >= credited.refresh);
+    if stale {
+        match fetch(&credited.client, wallet_address) {
+            Fetch::Solved(solved) => {
+                credited.wallets.insert(wallet_address.to_string(), (Instant::now(), solved));
+            }
+            Fetch::Unsupported => {
+                tracing::info!("ℹ️  The API has no per-wallet solutions endpoint; only local records count as solved");
+                credited.unsupported = true;
+                return false;
+            }
+            Fetch::Failed(e) => {
+                tracing::warn!(error = %e, "⚠️  Could not fetch server-side solutions for {}: {}", wallets::label(wallet_address), e);
+                let entry = credited.wallets.entry(wallet_address.to_string()).or_insert_with(|| (Instant::now(), HashSet::new()));
+                entry.0 = Instant::now();
+            }
+        }
+    }
+
+    let solved = credited.wallets.get(wallet_address).is_some_and(|(_, solved)| solved.contains(challenge_id));
+    if solved && credited.reported.insert((wallet_address.to_string(), challenge_id.to_string())) {
+        tracing::info!(
+            challenge_id = %challenge_id,
+            "☁️  {} is already credited to {} on the server, skipping it",
+            challenge_id,
+            wallets::label(wallet_address),
+        );
+    }
+    solved
+}
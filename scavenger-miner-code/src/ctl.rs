@@ -0,0 +1,122 @@
+//! `ctl`: manage a running miner over its gRPC control API
+//!
+//! The client half of grpc.rs in the same binary, so a headless miner can be checked and steered
+//! over SSH without grpcurl. The token can also come from `SCAVENGER_GRPC_TOKEN`, which keeps it
+//! out of the shell history.
+
+use crate::grpc::{self, CommandReply, Status};
+use crate::{format_duration_secs, now_utc, CliArgs};
+
+/// Where `--addr` points by default (the port used in the docs)
+const DEFAULT_ADDR: &str = "127.0.0.1:8791";
+const DEFAULT_STATS_INTERVAL_SECS: u32 = 5;
+
+const USAGE: &str = "Usage: scavenger-miner ctl <status|stats|pause|resume|cpu <percent>|refresh|retry> [--addr <host:port>] [--token <token>] [--interval <secs>]";
+
+/// Usage: ctl <status|stats|pause|resume|cpu <percent>|refresh|retry> [--addr <host:port>] [--token <token>] [--interval <secs>]
+pub fn run_ctl(args: &[String]) -> bool {
+    let cli = CliArgs::parse(args);
+    let Some(action) = cli.positional.first().map(|s| s.as_str()) else {
+        println!("{}", USAGE);
+        return false;
+    };
+    let addr = cli.option("--addr").unwrap_or(DEFAULT_ADDR);
+    let token = cli.option("--token").map(|s| s.to_string()).or_else(|| std::env::var("SCAVENGER_GRPC_TOKEN").ok());
+    let client = match grpc::Client::new(addr, token) {
+        Ok(client) => client,
+        Err(e) => {
+            println!("❌ {}", e);
+            return false;
+        }
+    };
+
+    let result = match action {
+        "status" => client.status().map(|status| {
+            print_status(&status);
+            true
+        }),
+        "stats" => {
+            let interval = cli.option("--interval").and_then(|s| s.parse().ok()).unwrap_or(DEFAULT_STATS_INTERVAL_SECS);
+            client.stream_stats(interval, |status| {
+                println!("{}", stats_line(&status));
+                true
+            }).map(|()| true)
+        }
+        "pause" => client.pause().map(print_reply),
+        "resume" => client.resume().map(print_reply),
+        "cpu" => match cli.positional.get(1).and_then(|s| s.trim_end_matches('%').parse::<f64>().ok()) {
+            Some(percent) => client.set_cpu_usage(percent).map(print_reply),
+            None => {
+                println!("Usage: scavenger-miner ctl cpu <percent> [--addr <host:port>]");
+                return false;
+            }
+        },
+        "refresh" => client.refresh_challenges().map(print_reply),
+        "retry" => client.retry_submissions().map(print_reply),
+        _ => {
+            println!("{}", USAGE);
+            return false;
+        }
+    };
+    match result {
+        Ok(done) => done,
+        Err(e) => {
+            println!("❌ {}: {}", addr, e);
+            false
+        }
+    }
+}
+
+/// Print the miner's answer to a command, returning whether it was accepted
+fn print_reply(reply: CommandReply) -> bool {
+    println!("{} {}", if reply.accepted { "✅" } else { "⚠️ " }, reply.message);
+    reply.accepted
+}
+
+fn print_status(status: &Status) {
+    println!("🛰️  {} (v{}), up {}", status.miner_id, status.version, format_duration_secs(status.uptime_secs as f64));
+    if status.paused {
+        println!("   State:       ⏸️  paused ({})", status.pause_reason);
+    } else {
+        println!("   State:       mining");
+    }
+    println!("   Threads:     {}", status.threads);
+    if status.hashrate > 0.0 {
+        println!("   Hashrate:    {:.1} H/s", status.hashrate);
+    } else {
+        println!("   Hashrate:    not measured yet");
+    }
+    println!("   Hashes:      {} since startup", status.hashes);
+    if status.challenge_id.is_empty() {
+        println!("   Task:        none (between tasks)");
+    } else {
+        println!(
+            "   Task:        {} for {} ({}, {} hashes)",
+            status.challenge_id,
+            status.wallet,
+            format_duration_secs(status.task_secs as f64),
+            status.task_hashes
+        );
+    }
+    println!("   Challenges:  {} active", status.active_challenges);
+    println!("   Solutions:   {} found, {} accepted (all sessions)", status.solutions_found, status.solutions_accepted);
+}
+
+fn stats_line(status: &Status) -> String {
+    let state = if status.paused {
+        format!("⏸️  paused ({})", status.pause_reason)
+    } else if status.challenge_id.is_empty() {
+        "between tasks".to_string()
+    } else {
+        format!("{} for {} ({} hashes)", status.challenge_id, format_duration_secs(status.task_secs as f64), status.task_hashes)
+    };
+    format!(
+        "[{}] {:.1} H/s · {} thread(s) · {} · {} found, {} accepted",
+        now_utc().format("%H:%M:%S"),
+        status.hashrate,
+        status.threads,
+        state,
+        status.solutions_found,
+        status.solutions_accepted
+    )
+}
@@ -0,0 +1,188 @@
+//! Classic Unix daemon mode (`--daemon`, `--stop`)
+//!
+//! For VPSes without systemd: once the configuration checked out, `--daemon` forks, detaches
+//! from the terminal (new session, stdin from /dev/null) and writes the child's pid to
+//! `miner.pid` in the data directory, or `--pid-file`. Log lines go to `logs/mining.log` as
+//! always; the console copy is dropped, and anything printed outside the log (statistics tables,
+//! a panic) lands in `logs/daemon.log`. The working directory is kept, so relative paths and the
+//! PAUSE file work as they do in the foreground. `--stop` sends SIGTERM to the pidfile's process,
+//! the same graceful shutdown as Ctrl-C, and waits for it to exit.
+//!
+//! The fork happens before any thread is started: only the forking thread survives a fork.
+
+use crate::paths;
+use std::path::{Path, PathBuf};
+
+pub const DAEMON_SWITCH: &str = "--daemon";
+pub const STOP_SWITCH: &str = "--stop";
+const DEFAULT_PID_FILE: &str = "miner.pid";
+/// Where stdout and stderr go once detached, in the logs folder
+const OUTPUT_FILE: &str = "daemon.log";
+/// How long `--stop` waits: the current step finishes and found solutions are submitted
+const STOP_TIMEOUT_SECS: u64 = 120;
+
+/// `--pid-file`, or `miner.pid` in the data directory
+pub fn pid_file_path(option: Option<&str>) -> PathBuf {
+    option.map_or_else(|| paths::data_dir().join(DEFAULT_PID_FILE), PathBuf::from)
+}
+
+/// Removes the pidfile when the daemon exits normally
+pub struct PidFile(PathBuf);
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Stop the daemon if asked to (`--stop`), returning the exit code
+pub fn handle_stop_args(args: &[String]) -> Option<i32> {
+    if !args.iter().any(|a| a == STOP_SWITCH) {
+        return None;
+    }
+    let cli = crate::load_cli_args(&args[1..]);
+    let path = pid_file_path(cli.option("--pid-file"));
+    Some(match imp::stop(&path) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            1
+        }
+    })
+}
+
+/// Detach from the terminal; returns in the daemon only (the parent exits)
+pub fn daemonize(pid_file: &Path) -> PidFile {
+    match imp::daemonize(pid_file) {
+        Ok(()) => PidFile(pid_file.to_path_buf()),
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// The pid recorded in a pidfile (None if there is none, or it was left empty)
+fn read_pid(path: &Path) -> Result<Option<i32>, String> {
+    match std::fs::read_to_string(path) {
+        Ok(content) if content.trim().is_empty() => Ok(None),
+        Ok(content) => match content.trim().parse::<i32>() {
+            // kill() with 0 or a negative pid would signal whole process groups
+            Ok(pid) if pid > 0 => Ok(Some(pid)),
+            _ => Err(format!("{} doesn't hold a process id", path.display())),
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("Cannot read {}: {}", path.display(), e)),
+    }
+}
+
+#[cfg(unix)]
+mod imp {
+    use super::{read_pid, OUTPUT_FILE, STOP_TIMEOUT_SECS};
+    use crate::paths;
+    use std::fs::{self, File, OpenOptions};
+    use std::os::unix::io::AsRawFd;
+    use std::path::Path;
+    use std::time::{Duration, Instant};
+
+    const SIGTERM: i32 = 15;
+    const EPERM: i32 = 1;
+
+    extern "C" {
+        fn fork() -> i32;
+        fn setsid() -> i32;
+        fn dup2(oldfd: i32, newfd: i32) -> i32;
+        fn kill(pid: i32, sig: i32) -> i32;
+    }
+
+    /// Whether `pid` is running (EPERM: it is, but belongs to someone else)
+    fn alive(pid: i32) -> bool {
+        let result = unsafe { kill(pid, 0) };
+        result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(EPERM)
+    }
+
+    pub fn daemonize(pid_file: &Path) -> Result<(), String> {
+        // An unreadable pidfile is only stale; the instance lock still stops a second miner
+        if let Some(pid) = read_pid(pid_file).ok().flatten().filter(|pid| alive(*pid)) {
+            return Err(format!("The miner is already running in the background (pid {}, {}); stop it with --stop", pid, pid_file.display()));
+        }
+        // Everything that can fail is opened while errors still reach the terminal
+        let null = File::open("/dev/null").map_err(|e| format!("Cannot open /dev/null: {}", e))?;
+        let output_path = paths::logs_dir().join(OUTPUT_FILE);
+        let output = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&output_path)
+            .map_err(|e| format!("Cannot open {}: {}", output_path.display(), e))?;
+        if let Some(parent) = pid_file.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).map_err(|e| format!("Cannot create {}: {}", parent.display(), e))?;
+        }
+        fs::write(pid_file, "").map_err(|e| format!("Cannot write {}: {}", pid_file.display(), e))?;
+
+        match unsafe { fork() } {
+            -1 => return Err(format!("Cannot fork: {}", std::io::Error::last_os_error())),
+            0 => {}
+            child => {
+                println!("🌙 Miner running in the background (pid {})", child);
+                println!("   Log:  {}", paths::logs_dir().join("mining.log").display());
+                println!("   Stop: scavenger-miner --stop{}", pid_file_hint(pid_file));
+                std::process::exit(0);
+            }
+        }
+
+        // In the child: own session without a controlling terminal, then swap out stdio
+        unsafe {
+            setsid();
+            dup2(null.as_raw_fd(), 0);
+            dup2(output.as_raw_fd(), 1);
+            dup2(output.as_raw_fd(), 2);
+        }
+        fs::write(pid_file, format!("{}\n", std::process::id())).map_err(|e| format!("Cannot write {}: {}", pid_file.display(), e))
+    }
+
+    /// `--pid-file` as the user has to repeat it for `--stop` (nothing for the default)
+    fn pid_file_hint(pid_file: &Path) -> String {
+        if pid_file == super::pid_file_path(None) {
+            String::new()
+        } else {
+            format!(" --pid-file {}", pid_file.display())
+        }
+    }
+
+    pub fn stop(pid_file: &Path) -> Result<(), String> {
+        let pid = read_pid(pid_file)?.ok_or_else(|| format!("No miner running in the background ({} not found)", pid_file.display()))?;
+        if !alive(pid) {
+            let _ = fs::remove_file(pid_file);
+            return Err(format!("pid {} from {} isn't running (removed the stale pidfile)", pid, pid_file.display()));
+        }
+        if unsafe { kill(pid, SIGTERM) } != 0 {
+            return Err(format!("Cannot signal pid {}: {}", pid, std::io::Error::last_os_error()));
+        }
+        println!("🛑 Asked pid {} to stop, waiting for it to finish its current step...", pid);
+
+        let deadline = Instant::now() + Duration::from_secs(STOP_TIMEOUT_SECS);
+        while alive(pid) {
+            if Instant::now() >= deadline {
+                return Err(format!("pid {} is still running after {}s; `kill -9 {}` ends it at once", pid, STOP_TIMEOUT_SECS, pid));
+            }
+            std::thread::sleep(Duration::from_millis(250));
+        }
+        println!("👋 Miner stopped");
+        Ok(())
+    }
+}
+
+#[cfg(not(unix))]
+mod imp {
+    use std::path::Path;
+
+    const UNSUPPORTED: &str = "--daemon and --stop are Unix-only; on Windows install the miner as a service (--install-service)";
+
+    pub fn daemonize(_pid_file: &Path) -> Result<(), String> {
+        Err(UNSUPPORTED.to_string())
+    }
+
+    pub fn stop(_pid_file: &Path) -> Result<(), String> {
+        Err(UNSUPPORTED.to_string())
+    }
+}
@@ -0,0 +1,460 @@
+//! Full-screen live dashboard (`--tui`)
+//!
+//! Replaces the scrolling log with a screen redrawn every second: a hashrate sparkline, the task
+//! being mined with its deadline countdown, per-thread rates, the wallet table and the latest log
+//! lines (the log sinks keep writing `mining.log` and `--log-json` as usual). Keys:
+//!
+//! - `p` pauses or resumes mining
+//! - `s` skips the current task; the pair isn't picked again this session
+//! - `r` retries failed submissions after the current task, due or not
+//! - `q` quits like Ctrl-C
+//!
+//! Drawn with ratatui on the alternate screen, with the terminal in raw mode (crossterm) so keys
+//! arrive one by one; Ctrl-C is read as a key and quits like `q` (twice forces it). The terminal is restored when
+//! the dashboard closes.
+
+use crate::difficulty::format_count;
+use crate::logging::Sink;
+use crate::{format_duration_secs, wallet_stats, wallets, SHUTDOWN_REQUESTED};
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::{cursor, execute, terminal};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Paragraph, Row, Sparkline, Table};
+use ratatui::{Frame, Terminal};
+use std::collections::{HashSet, VecDeque};
+use std::io::{IsTerminal, Stdout};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Log lines kept for the events panel
+const MAX_EVENTS: usize = 200;
+/// Hashrate samples in the sparkline (one per redraw)
+const HISTORY_LEN: usize = 120;
+/// How long the key reader waits for input before checking whether the dashboard closed
+const KEY_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The task being mined, as `mine_single_solution` reports it
+struct Task {
+    challenge_id: String,
+    wallet: String,
+    difficulty: String,
+    started: Instant,
+    deadline: Option<Instant>,
+    thread_hashes: Arc<Vec<AtomicU64>>,
+}
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+static PAUSED: AtomicBool = AtomicBool::new(false);
+static SKIP: AtomicBool = AtomicBool::new(false);
+static RETRY: AtomicBool = AtomicBool::new(false);
+
+static TASK: Mutex<Option<Task>> = Mutex::new(None);
+/// Hashes of the tasks finished so far
+static FINISHED_HASHES: AtomicU64 = AtomicU64::new(0);
+static EVENTS: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+static WALLETS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+static SKIPPED: Mutex<Option<HashSet<(String, String)>>> = Mutex::new(None);
+
+/// Log sink feeding the events panel
+pub struct Events;
+
+impl Sink for Events {
+    fn write(&self, line: &str) {
+        let mut events = EVENTS.lock().unwrap();
+        if events.len() == MAX_EVENTS {
+            events.pop_front();
+        }
+        events.push_back(line.trim_end().to_string());
+    }
+}
+
+/// Whether the dashboard is on screen (plain console output is held back meanwhile)
+pub fn active() -> bool {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+/// Paused with `p`
+pub fn paused() -> bool {
+    PAUSED.load(Ordering::Relaxed)
+}
+
+/// `s` was pressed during the current task; checked by the hashing threads
+pub fn skip_requested() -> bool {
+    SKIP.load(Ordering::Relaxed)
+}
+
+/// Give up the current task as if `s` had been pressed (a coordinator withdrew the range)
+pub fn request_skip() {
+    SKIP.store(true, Ordering::SeqCst);
+}
+
+/// Whether `r` was pressed since the last call
+pub fn take_retry() -> bool {
+    RETRY.swap(false, Ordering::Relaxed)
+}
+
+/// Remember a skipped task so it isn't picked again this session
+pub fn mark_skipped(wallet: &str, challenge_id: &str) {
+    SKIPPED.lock().unwrap().get_or_insert_with(HashSet::new).insert((wallet.to_string(), challenge_id.to_string()));
+}
+
+pub fn was_skipped(wallet: &str, challenge_id: &str) -> bool {
+    SKIPPED.lock().unwrap().as_ref().is_some_and(|s| s.contains(&(wallet.to_string(), challenge_id.to_string())))
+}
+
+/// The wallets in the rotation, for the wallet table
+pub fn set_wallets(wallets: &[String]) {
+    *WALLETS.lock().unwrap() = wallets.to_vec();
+}
+
+/// A task started hashing with these per-thread counters
+pub fn task_started(challenge_id: &str, wallet: &str, difficulty: &str, deadline: Option<Instant>, thread_hashes: Arc<Vec<AtomicU64>>) {
+    SKIP.store(false, Ordering::Relaxed);
+    *TASK.lock().unwrap() = Some(Task {
+        challenge_id: challenge_id.to_string(),
+        wallet: wallet.to_string(),
+        difficulty: difficulty.to_string(),
+        started: Instant::now(),
+        deadline,
+        thread_hashes,
+    });
+}
+
+/// Challenge, wallet and running time of the task being mined (also without the dashboard)
+pub fn current_task() -> Option<(String, String, Duration)> {
+    TASK.lock().unwrap().as_ref().map(|t| (t.challenge_id.clone(), t.wallet.clone(), t.started.elapsed()))
+}
+
+/// Hashes so far, difficulty, start and deadline of the task being mined (also without the dashboard)
+pub fn task_progress() -> Option<(u64, String, Instant, Option<Instant>)> {
+    TASK.lock().unwrap().as_ref().map(|t| {
+        (t.thread_hashes.iter().map(|h| h.load(Ordering::Relaxed)).sum(), t.difficulty.clone(), t.started, t.deadline)
+    })
+}
+
+pub fn task_finished() {
+    // Under the lock, so `hashes_mined` never sees the task in neither place
+    let mut task = TASK.lock().unwrap();
+    if let Some(task) = task.take() {
+        FINISHED_HASHES.fetch_add(task.thread_hashes.iter().map(|h| h.load(Ordering::Relaxed)).sum(), Ordering::Relaxed);
+    }
+}
+
+/// Hashes computed since startup, including the task being mined (also without the dashboard)
+pub fn hashes_mined() -> u64 {
+    let task = TASK.lock().unwrap();
+    let running: u64 = task.as_ref().map_or(0, |t| t.thread_hashes.iter().map(|h| h.load(Ordering::Relaxed)).sum());
+    FINISHED_HASHES.load(Ordering::Relaxed) + running
+}
+
+/// Open while mining; dropping it closes the dashboard and gives the terminal back
+pub struct Dashboard {
+    renderer: Option<thread::JoinHandle<()>>,
+    keys: Option<thread::JoinHandle<()>>,
+}
+
+impl Dashboard {
+    /// Take over the terminal; `None` (with a warning) if stdin or stdout isn't one
+    pub fn start() -> Option<Self> {
+        if !std::io::stdout().is_terminal() || !std::io::stdin().is_terminal() {
+            tracing::warn!("⚠️  --tui needs an interactive terminal, showing the plain log instead");
+            return None;
+        }
+        let mut terminal = match open_terminal() {
+            Ok(terminal) => terminal,
+            Err(e) => {
+                restore_terminal();
+                tracing::warn!(error = %e, "⚠️  Could not open the dashboard ({}), showing the plain log instead", e);
+                return None;
+            }
+        };
+        ACTIVE.store(true, Ordering::SeqCst);
+
+        let keys = thread::Builder::new().name("dashboard-keys".to_string()).spawn(read_keys).ok();
+        let renderer = thread::Builder::new()
+            .name("dashboard".to_string())
+            .spawn(move || {
+                let mut screen = Screen::default();
+                while ACTIVE.load(Ordering::SeqCst) {
+                    screen.sample();
+                    let _ = terminal.draw(|frame| screen.render(frame));
+                    thread::sleep(Duration::from_secs(1));
+                }
+            })
+            .ok();
+        Some(Dashboard { renderer, keys })
+    }
+}
+
+impl Drop for Dashboard {
+    fn drop(&mut self) {
+        ACTIVE.store(false, Ordering::SeqCst);
+        for handle in [self.renderer.take(), self.keys.take()].into_iter().flatten() {
+            let _ = handle.join();
+        }
+        restore_terminal();
+
+        // Leave the last few events on the normal screen for context
+        for line in EVENTS.lock().unwrap().iter().rev().take(10).collect::<Vec<_>>().into_iter().rev() {
+            println!("{}", line);
+        }
+    }
+}
+
+/// Raw mode, alternate screen, hidden cursor
+fn open_terminal() -> std::io::Result<Terminal<CrosstermBackend<Stdout>>> {
+    terminal::enable_raw_mode()?;
+    let mut stdout = std::io::stdout();
+    execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+    Terminal::new(CrosstermBackend::new(stdout))
+}
+
+fn restore_terminal() {
+    let _ = execute!(std::io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+    let _ = terminal::disable_raw_mode();
+}
+
+fn read_keys() {
+    while ACTIVE.load(Ordering::SeqCst) {
+        if !event::poll(KEY_POLL_INTERVAL).unwrap_or(false) {
+            continue;
+        }
+        let Ok(Event::Key(key)) = event::read() else {
+            continue;
+        };
+        // Windows also reports releases
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                // Raw mode turns off the signal, so a second Ctrl-C forces the quit here
+                if SHUTDOWN_REQUESTED.load(Ordering::SeqCst) {
+                    restore_terminal();
+                    std::process::exit(130);
+                }
+                quit();
+            }
+            KeyCode::Char(c) => match c.to_ascii_lowercase() {
+                'p' => {
+                    let paused = !PAUSED.fetch_xor(true, Ordering::SeqCst);
+                    if paused {
+                        tracing::info!("⏸️  Pause requested from the dashboard");
+                    } else {
+                        tracing::info!("▶️  Resume requested from the dashboard");
+                    }
+                }
+                's' if TASK.lock().unwrap().is_some() => {
+                    SKIP.store(true, Ordering::SeqCst);
+                    tracing::info!("⏭️  Skipping the current task (dashboard)");
+                }
+                'r' => {
+                    RETRY.store(true, Ordering::SeqCst);
+                    tracing::info!("🔁 Retry pass over failed submissions after the current task (dashboard)");
+                }
+                'q' => quit(),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+}
+
+fn quit() {
+    SHUTDOWN_REQUESTED.store(true, Ordering::SeqCst);
+    tracing::info!("🛑 Quit requested from the dashboard, finishing current step...");
+}
+
+/// Redraw state: hashrate history and the counters seen at the last redraw
+#[derive(Default)]
+struct Screen {
+    history: VecDeque<f64>,
+    task_key: Option<(String, String)>,
+    last_hashes: Vec<u64>,
+    last_at: Option<Instant>,
+    thread_rates: Vec<f64>,
+}
+
+impl Screen {
+    /// Sample the task's counters: per-thread rates since the previous redraw
+    fn sample(&mut self) {
+        let task = TASK.lock().unwrap();
+        let now = Instant::now();
+        let Some(task) = task.as_ref() else {
+            self.task_key = None;
+            self.thread_rates.clear();
+            self.push_rate(0.0);
+            return;
+        };
+
+        let hashes: Vec<u64> = task.thread_hashes.iter().map(|h| h.load(Ordering::Relaxed)).collect();
+        let key = Some((task.challenge_id.clone(), task.wallet.clone()));
+        if self.task_key != key {
+            // New task: counters restarted, no rate until the next sample
+            self.task_key = key;
+            self.thread_rates = vec![0.0; hashes.len()];
+        } else if let Some(last_at) = self.last_at {
+            let secs = now.duration_since(last_at).as_secs_f64().max(1e-3);
+            self.thread_rates = hashes
+                .iter()
+                .zip(&self.last_hashes)
+                .map(|(now, before)| now.saturating_sub(*before) as f64 / secs)
+                .collect();
+        }
+        self.last_hashes = hashes;
+        self.last_at = Some(now);
+        let total = self.thread_rates.iter().sum();
+        self.push_rate(total);
+    }
+
+    fn push_rate(&mut self, rate: f64) {
+        if self.history.len() == HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back(rate);
+    }
+
+    fn render(&self, frame: &mut Frame) {
+        let area = frame.area();
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+
+        // Per-thread rates, as many per line as fit
+        let cells: Vec<String> = self.thread_rates.iter().enumerate().map(|(i, r)| format!("#{:<3}{:>8.1}", i, r)).collect();
+        let per_line = ((area.width as usize).saturating_sub(11) / 14).max(1);
+        let thread_lines: Vec<Line> = cells
+            .chunks(per_line)
+            .enumerate()
+            .map(|(n, chunk)| Line::from(format!(" {:<9} {}", if n == 0 { "Threads" } else { "" }, chunk.join("  "))))
+            .collect();
+
+        // The wallet table gets what the events panel (at least 3 lines) leaves over
+        let wallet_list = WALLETS.lock().unwrap().clone();
+        let fixed = 3 + thread_lines.len() + 1 + 1 + 1 + 3 + 1;
+        let wallet_rows = (area.height as usize).saturating_sub(fixed).min(wallet_list.len()).max(1);
+        let more = wallet_list.len().saturating_sub(wallet_rows);
+        let table_height = 1 + wallet_rows + usize::from(more > 0);
+
+        let [header, hashrate, task, threads, _, table, _, events_title, events, keys] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(thread_lines.len() as u16),
+            Constraint::Length(1),
+            Constraint::Length(table_height as u16),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+
+        let state = if paused() {
+            "⏸  paused (p to resume)".to_string()
+        } else if let Some(reason) = crate::pause_reason() {
+            format!("⏸  paused ({})", reason)
+        } else if TASK.lock().unwrap().is_some() {
+            "⛏  mining".to_string()
+        } else {
+            "… between tasks".to_string()
+        };
+        frame.render_widget(Line::from(vec![Span::styled(" Scavenger Miner", bold), Span::raw(format!("   {}", state))]), header);
+
+        self.render_hashrate(frame, hashrate);
+        frame.render_widget(Line::from(task_line()), task);
+        frame.render_widget(Paragraph::new(thread_lines), threads);
+
+        let stats = wallet_stats::snapshot();
+        let mut rows: Vec<Row> = wallet_list
+            .iter()
+            .take(wallet_rows)
+            .map(|wallet| {
+                let s = stats.get(wallet).copied().unwrap_or_default();
+                Row::new(vec![
+                    format!(" {}", wallets::label(wallet)),
+                    format!("{:>6}", s.tasks),
+                    format!("{:>8}", format_count(s.hashes as f64)),
+                    format!("{:>8}", s.hashrate().map_or("-".to_string(), |rate| format!("{:.1}", rate))),
+                    format!("{:>6}", s.solutions),
+                    format!("{:>8}", s.accepted),
+                ])
+            })
+            .collect();
+        if more > 0 {
+            rows.push(Row::new(vec![format!(" ... {} more", more)]));
+        }
+        let widths = [25, 6, 8, 8, 6, 8].map(Constraint::Length);
+        let header_row = Row::new(vec![
+            format!(" {}", "wallet"),
+            format!("{:>6}", "tasks"),
+            format!("{:>8}", "hashes"),
+            format!("{:>8}", "H/s"),
+            format!("{:>6}", "found"),
+            format!("{:>8}", "accepted"),
+        ])
+        .style(bold);
+        frame.render_widget(Table::new(rows, widths).header(header_row).column_spacing(2), table);
+
+        // Latest events fill what is left above the key help
+        frame.render_widget(Line::from(Span::styled(" Recent events", bold)), events_title);
+        let room = events.height as usize;
+        let lines: Vec<Line> = {
+            let events = EVENTS.lock().unwrap();
+            let skip = events.len().saturating_sub(room);
+            events.iter().skip(skip).map(|e| Line::from(format!(" {}", e))).collect()
+        };
+        frame.render_widget(Paragraph::new(lines), events);
+
+        let key = |k: &'static str| Span::styled(k, Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_widget(
+            Line::from(vec![
+                key(" p "),
+                Span::raw(" pause/resume  "),
+                key(" s "),
+                Span::raw(" skip task  "),
+                key(" r "),
+                Span::raw(" retry failed submissions  "),
+                key(" q "),
+                Span::raw(" quit"),
+            ]),
+            keys,
+        );
+    }
+
+    /// Current hashrate, then a sparkline of the history scaled to its highest sample
+    fn render_hashrate(&self, frame: &mut Frame, area: Rect) {
+        let current = self.history.back().copied().unwrap_or(0.0);
+        let [label, chart] = Layout::horizontal([Constraint::Length(27), Constraint::Min(0)]).areas(area);
+        frame.render_widget(Line::from(format!(" Hashrate  {:>10} H/s", format!("{:.1}", current))), label);
+
+        let width = (chart.width as usize).min(HISTORY_LEN);
+        // Tenths of a hash per second, so slow test ROMs still show a shape
+        let samples: Vec<u64> = self.history.iter().skip(self.history.len().saturating_sub(width)).map(|&r| (r * 10.0) as u64).collect();
+        frame.render_widget(Sparkline::default().data(samples), chart);
+    }
+}
+
+/// The task being mined, with its deadline countdown
+fn task_line() -> String {
+    let task = TASK.lock().unwrap();
+    match task.as_ref() {
+        Some(task) => {
+            let deadline = task.deadline.map_or("no deadline".to_string(), |d| {
+                format!("deadline in {}", format_duration_secs(d.saturating_duration_since(Instant::now()).as_secs_f64()))
+            });
+            format!(
+                " Task      {} for {}   difficulty {}   running {}   {}",
+                task.challenge_id,
+                wallets::label(&task.wallet),
+                task.difficulty,
+                format_duration_secs(task.started.elapsed().as_secs_f64()),
+                deadline
+            )
+        }
+        None => " Task      -".to_string(),
+    }
+}
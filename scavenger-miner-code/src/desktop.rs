@@ -0,0 +1,54 @@
+//! Desktop notifications (`--desktop-notify`)
+//!
+//! Shows a toast when a solution is found and when its receipt arrives, through notify-rust:
+//! the notification service over D-Bus on Linux and the BSDs, Notification Center on macOS and
+//! toast notifications on Windows.
+//!
+//! Toasts are shown from one background thread so a slow notification service never holds up
+//! mining. If none is available, that is logged once and toasts stop.
+
+use notify_rust::Notification;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Mutex;
+use std::thread;
+
+const APP_NAME: &str = "Scavenger Miner";
+
+/// Set after the first failure so a missing notification service is only reported once
+static UNAVAILABLE: AtomicBool = AtomicBool::new(false);
+
+/// Toasts waiting for the notifier thread, started with the first one
+static QUEUE: Mutex<Option<Sender<(String, String)>>> = Mutex::new(None);
+
+/// Show a toast in the background
+pub fn notify(title: &str, body: &str) {
+    if UNAVAILABLE.load(Ordering::Relaxed) {
+        return;
+    }
+    let mut queue = QUEUE.lock().unwrap();
+    if queue.is_none() {
+        let (tx, rx) = mpsc::channel::<(String, String)>();
+        let spawned = thread::Builder::new().name("desktop-notify".to_string()).spawn(move || {
+            for (title, body) in rx {
+                if let Err(e) = show(&title, &body) {
+                    if !UNAVAILABLE.swap(true, Ordering::Relaxed) {
+                        tracing::warn!(error = %e, "⚠️  Desktop notifications unavailable ({}), turning them off", e);
+                    }
+                    return;
+                }
+            }
+        });
+        if spawned.is_err() {
+            return;
+        }
+        *queue = Some(tx);
+    }
+    if let Some(tx) = queue.as_ref() {
+        let _ = tx.send((title.to_string(), body.to_string()));
+    }
+}
+
+fn show(title: &str, body: &str) -> Result<(), notify_rust::error::Error> {
+    Notification::new().appname(APP_NAME).summary(title).body(body).show().map(|_| ())
+}
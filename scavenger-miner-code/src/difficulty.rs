@@ -0,0 +1,159 @@
+//! What a difficulty mask means for the time to a solution
+//!
+//! A hash qualifies when every bit that is 0 in the mask is also 0 in the hash, over the first
+//! `min(mask length, 64)` bytes (see `check_difficulty`). Hash bits are uniformly random, so
+//! where the constrained bits sit doesn't matter, only how many there are: with `k` of them each
+//! hash qualifies with probability `q = 2^-k`, the number of hashes to a solution is geometric
+//! with mean `2^k`, and `n` hashes solve it with probability `1 - (1 - q)^n`.
+//! `analyze <difficulty>` prints the distribution; the mining log shows the summary.
+
+use crate::{format_duration_secs, CliArgs};
+
+/// Length of a hash; mask bytes past it are ignored
+const HASH_BYTES: usize = 64;
+
+/// Solve probabilities listed by `analyze`
+const PERCENTILES: &[f64] = &[0.5, 0.9, 0.99, 0.999];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DifficultyModel {
+    pub constrained_bits: u32, // Bits the hash must have at 0
+    pub checked_bytes: usize,  // Mask bytes that take part in the check
+}
+
+impl DifficultyModel {
+    /// Model of a hex difficulty mask
+    pub fn parse(difficulty: &str) -> Result<Self, String> {
+        let bytes = hex::decode(difficulty.trim()).map_err(|e| format!("difficulty '{}' is not valid hex: {}", difficulty, e))?;
+        let checked = &bytes[..bytes.len().min(HASH_BYTES)];
+        Ok(DifficultyModel {
+            constrained_bits: checked.iter().map(|b| b.count_zeros()).sum(),
+            checked_bytes: checked.len(),
+        })
+    }
+
+    /// Chance that a single hash qualifies
+    pub fn hash_probability(&self) -> f64 {
+        2f64.powi(-(self.constrained_bits as i32))
+    }
+
+    /// Mean number of hashes to a solution
+    pub fn expected_hashes(&self) -> f64 {
+        2f64.powi(self.constrained_bits as i32)
+    }
+
+    /// Chance that `hashes` hashes include a solution
+    pub fn solve_probability(&self, hashes: f64) -> f64 {
+        -(hashes * (-self.hash_probability()).ln_1p()).exp_m1()
+    }
+
+    /// Hashes needed to have found a solution with probability `probability`
+    pub fn hashes_for_probability(&self, probability: f64) -> f64 {
+        if self.constrained_bits == 0 {
+            return 1.0;
+        }
+        ((-probability).ln_1p() / (-self.hash_probability()).ln_1p()).ceil()
+    }
+
+    /// One-line summary for the mining log, with times if the hashrate is known
+    pub fn describe(&self, hashrate: Option<f64>) -> String {
+        let expected = self.expected_hashes();
+        let median = self.hashes_for_probability(0.5);
+        let p99 = self.hashes_for_probability(0.99);
+        match hashrate.filter(|r| *r > 0.0) {
+            Some(rate) => format!(
+                "{} constrained bits, {} hashes expected (~{}; 50% within {}, 99% within {} at {:.0} H/s)",
+                self.constrained_bits, format_count(expected), format_duration_secs(expected / rate),
+                format_duration_secs(median / rate), format_duration_secs(p99 / rate), rate
+            ),
+            None => format!(
+                "{} constrained bits, {} hashes expected (50% within {}, 99% within {})",
+                self.constrained_bits, format_count(expected), format_count(median), format_count(p99)
+            ),
+        }
+    }
+}
+
+/// Easier mask that keeps only the first `bits` constrained bits of `mask` (pool shares); a mask
+/// with fewer constrained bits comes back unchanged
+pub fn relaxed_mask(mask: &[u8], bits: u32) -> Vec<u8> {
+    let mut kept = 0;
+    mask.iter()
+        .map(|&byte| {
+            let mut relaxed = byte;
+            for bit in (0..8).rev() {
+                if byte & (1 << bit) == 0 {
+                    if kept < bits {
+                        kept += 1;
+                    } else {
+                        relaxed |= 1 << bit;
+                    }
+                }
+            }
+            relaxed
+        })
+        .collect()
+}
+
+/// Hash count with a K/M/G/T suffix
+pub fn format_count(count: f64) -> String {
+    const UNITS: &[(f64, &str)] = &[(1e12, "T"), (1e9, "G"), (1e6, "M"), (1e3, "K")];
+    if !count.is_finite() {
+        return "∞".to_string();
+    }
+    match UNITS.iter().find(|(scale, _)| count >= *scale) {
+        Some((scale, unit)) => format!("{:.1}{}", count / scale, unit),
+        None => format!("{:.0}", count),
+    }
+}
+
+/// Usage: analyze <difficulty> [--hashrate <H/s>]
+pub fn run_analyze(args: &[String]) -> bool {
+    let cli = CliArgs::parse(args);
+    let Some(difficulty) = cli.positional.first() else {
+        println!("Usage: scavenger-miner analyze <difficulty hex> [--hashrate <H/s>]");
+        return false;
+    };
+    let model = match DifficultyModel::parse(difficulty) {
+        Ok(model) => model,
+        Err(e) => {
+            println!("❌ {}", e);
+            return false;
+        }
+    };
+    let hashrate = match cli.option("--hashrate").map(|s| s.parse::<f64>()) {
+        None => None,
+        Some(Ok(rate)) if rate > 0.0 => Some(rate),
+        Some(_) => {
+            println!("❌ --hashrate expects a positive number of hashes per second");
+            return false;
+        }
+    };
+
+    println!("🎯 Difficulty {}", difficulty);
+    println!("   Mask bytes checked:  {}", model.checked_bytes);
+    println!("   Constrained bits:    {}", model.constrained_bits);
+    println!("   Per-hash chance:     1 in 2^{}", model.constrained_bits);
+    println!("   Expected hashes:     {}", format_count(model.expected_hashes()));
+    if let Some(rate) = hashrate {
+        println!("   Expected time:       {} at {:.0} H/s", format_duration_secs(model.expected_hashes() / rate), rate);
+    }
+
+    println!("\n📈 Hashes needed to have found a solution with a given chance:");
+    if hashrate.is_some() {
+        println!("   {:>7}  {:>12}  {:>12}", "chance", "hashes", "time");
+    } else {
+        println!("   {:>7}  {:>12}", "chance", "hashes");
+    }
+    for &probability in PERCENTILES {
+        let hashes = model.hashes_for_probability(probability);
+        match hashrate {
+            Some(rate) => println!(
+                "   {:>6}%  {:>12}  {:>12}",
+                probability * 100.0, format_count(hashes), format_duration_secs(hashes / rate)
+            ),
+            None => println!("   {:>6}%  {:>12}", probability * 100.0, format_count(hashes)),
+        }
+    }
+    true
+}
@@ -0,0 +1,106 @@
+//! Crate-wide error type
+//!
+//! Errors are split by what the caller can do about them: network failures and rate limits
+//! are retried, filesystem and parse errors are reported, and API rejections carry a
+//! [`Rejection`] so submission handling can tell a duplicate from a transient failure.
+
+use std::time::Duration;
+
+/// Why the API refused a submission, as far as the miner needs to know
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejection {
+    /// The solution was already submitted (by this rig or another one)
+    Duplicate,
+    /// The nonce doesn't meet the challenge difficulty
+    InvalidNonce,
+    /// The challenge's submission window has closed
+    WindowClosed,
+    /// Anything else; worth retrying
+    Other,
+}
+
+impl Rejection {
+    /// Classify an API error message (the API only returns text, so this is the one place it is matched)
+    pub fn from_message(message: &str) -> Self {
+        let message = message.to_lowercase();
+        if message.contains("already exists") {
+            Rejection::Duplicate
+        } else if message.contains("difficulty") && message.contains("not meet") {
+            Rejection::InvalidNonce
+        } else if message.contains("window closed") {
+            Rejection::WindowClosed
+        } else {
+            Rejection::Other
+        }
+    }
+
+    /// Whether resubmitting can never succeed
+    pub fn is_final(self) -> bool {
+        self != Rejection::Other
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MinerError {
+    /// Connection, TLS, timeout or other transport failure
+    #[error("{0}")]
+    Network(reqwest::Error),
+
+    /// The API answered with an error status
+    #[error("HTTP {status}: {message}")]
+    Api { status: u16, message: String, rejection: Rejection },
+
+    /// The API asked us to slow down (429, or 503 with Retry-After)
+    #[error("rate limited by the API (retry after {}s)", .retry_after.as_secs())]
+    RateLimited { retry_after: Duration },
+
+    /// A response or file that doesn't have the expected shape
+    #[error("{0}")]
+    Parse(String),
+
+    #[error("{0}")]
+    Filesystem(#[from] std::io::Error),
+
+    /// The API answered successfully but not the way the protocol says it should
+    #[error("{0}")]
+    Protocol(String),
+}
+
+impl MinerError {
+    /// An error response from the API, classified from its body
+    pub fn api(status: u16, message: String) -> Self {
+        let rejection = Rejection::from_message(&message);
+        MinerError::Api { status, message, rejection }
+    }
+
+    /// Why the API rejected the request, if it did
+    pub fn rejection(&self) -> Option<Rejection> {
+        match self {
+            MinerError::Api { rejection, .. } => Some(*rejection),
+            _ => None,
+        }
+    }
+
+    /// Whether the request may not have reached the API at all
+    pub fn is_network(&self) -> bool {
+        matches!(self, MinerError::Network(_))
+    }
+}
+
+impl From<reqwest::Error> for MinerError {
+    fn from(e: reqwest::Error) -> Self {
+        if let Some(status) = e.status() {
+            MinerError::api(status.as_u16(), e.to_string())
+        } else if e.is_decode() {
+            MinerError::Parse(e.to_string())
+        } else {
+            MinerError::Network(e)
+        }
+    }
+}
+
+impl From<serde_json::Error> for MinerError {
+    fn from(e: serde_json::Error) -> Self {
+        MinerError::Parse(e.to_string())
+    }
+}
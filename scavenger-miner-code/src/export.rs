@@ -0,0 +1,157 @@
+//! `export`: flatten solution records into a spreadsheet for reward-claim bookkeeping
+//!
+//! One row per record, receipt fields in their own columns. CSV is written as UTF-8 with a
+//! byte order mark so Excel opens it with the right encoding.
+
+use crate::{store, CliArgs, SolutionRecord};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::fs;
+
+const CSV_COLUMNS: &[&str] = &[
+    "wallet_address",
+    "challenge_id",
+    "nonce",
+    "nonce_start",
+    "status",
+    "found_at",
+    "submitted_at",
+    "retry_count",
+    "last_retry_at",
+    "next_retry_at",
+    "error_message",
+    "receipt_verified",
+    "receipt_preimage",
+    "receipt_timestamp",
+    "receipt_signature",
+];
+
+/// Which records to export
+struct ExportFilter {
+    wallets: Vec<String>,
+    statuses: Vec<String>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>, // Exclusive
+}
+
+impl ExportFilter {
+    fn matches(&self, record: &SolutionRecord) -> bool {
+        if !self.wallets.is_empty() && !self.wallets.contains(&record.wallet_address) {
+            return false;
+        }
+        if !self.statuses.is_empty() && !self.statuses.contains(&record.status) {
+            return false;
+        }
+        if self.from.is_none() && self.to.is_none() {
+            return true;
+        }
+        // Date filters apply to when the solution was found
+        let Ok(found_at) = DateTime::parse_from_rfc3339(&record.found_at).map(|t| t.with_timezone(&Utc)) else {
+            return false;
+        };
+        self.from.is_none_or(|from| found_at >= from) && self.to.is_none_or(|to| found_at < to)
+    }
+}
+
+/// Parse `YYYY-MM-DD` (midnight UTC, or the following midnight for an end date) or RFC 3339
+pub fn parse_date(value: &str, end_of_day: bool) -> Option<DateTime<Utc>> {
+    if let Ok(date) = NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d") {
+        let date = if end_of_day { date.succ_opt()? } else { date };
+        return Some(date.and_hms_opt(0, 0, 0)?.and_utc());
+    }
+    DateTime::parse_from_rfc3339(value.trim()).ok().map(|t| t.with_timezone(&Utc))
+}
+
+/// Comma-separated option values
+fn list_option(cli: &CliArgs, name: &str) -> Vec<String> {
+    cli.option(name)
+        .map(|v| v.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+/// Quote a CSV field if needed (RFC 4180)
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn csv_row(record: &SolutionRecord) -> String {
+    let optional = |value: &Option<String>| value.clone().unwrap_or_default();
+    let receipt = record.crypto_receipt.as_ref();
+    let fields = [
+        record.wallet_address.clone(),
+        record.challenge_id.clone(),
+        record.nonce.clone(),
+        optional(&record.nonce_start),
+        record.status.clone(),
+        record.found_at.clone(),
+        optional(&record.submitted_at),
+        record.retry_count.to_string(),
+        optional(&record.last_retry_at),
+        optional(&record.next_retry_at),
+        optional(&record.error_message),
+        record.verified.map(|v| v.to_string()).unwrap_or_default(),
+        receipt.map(|r| r.preimage.clone()).unwrap_or_default(),
+        receipt.map(|r| r.timestamp.clone()).unwrap_or_default(),
+        receipt.map(|r| r.signature.clone()).unwrap_or_default(),
+    ];
+    fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(",")
+}
+
+/// Usage: export [--format csv] [--output <file>] [--wallet <addr,...>] [--status <status,...>]
+///               [--from <date>] [--to <date>]
+pub fn run_export(args: &[String]) -> bool {
+    let cli = CliArgs::parse(args);
+
+    let format = cli.option("--format").unwrap_or("csv").to_ascii_lowercase();
+    if format != "csv" {
+        println!("❌ Unsupported --format '{}' (supported: csv)", format);
+        return false;
+    }
+
+    let mut filter = ExportFilter {
+        wallets: list_option(&cli, "--wallet"),
+        statuses: list_option(&cli, "--status"),
+        from: None,
+        to: None,
+    };
+    for (name, end_of_day) in [("--from", false), ("--to", true)] {
+        let Some(value) = cli.option(name) else {
+            continue;
+        };
+        let Some(date) = parse_date(value, end_of_day) else {
+            println!("❌ Invalid {} '{}' (expected YYYY-MM-DD or an RFC 3339 timestamp)", name, value);
+            return false;
+        };
+        if end_of_day {
+            filter.to = Some(date);
+        } else {
+            filter.from = Some(date);
+        }
+    }
+
+    let mut records: Vec<SolutionRecord> = store::get().all().into_iter().filter(|r| filter.matches(r)).collect();
+    records.sort_by(|a, b| (&a.found_at, &a.wallet_address).cmp(&(&b.found_at, &b.wallet_address)));
+
+    let mut csv = String::from("\u{feff}");
+    csv.push_str(&CSV_COLUMNS.join(","));
+    csv.push_str("\r\n");
+    for record in &records {
+        csv.push_str(&csv_row(record));
+        csv.push_str("\r\n");
+    }
+
+    let output = cli.option("--output").map(|s| s.to_string()).unwrap_or_else(|| {
+        format!("solutions-{}.csv", Utc::now().format("%Y%m%d-%H%M%S"))
+    });
+    if let Err(e) = fs::write(&output, csv) {
+        println!("❌ Failed to write {}: {}", output, e);
+        return false;
+    }
+
+    let receipts = records.iter().filter(|r| r.crypto_receipt.is_some()).count();
+    println!("📄 Exported {} record(s) ({} with receipts) to {}", records.len(), receipts, output);
+    true
+}
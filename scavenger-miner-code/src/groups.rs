@@ -0,0 +1,164 @@
+//! Wallet groups: several logical miners in one process
+//!
+//! The `groups` key of the config file splits the wallets into groups with their own settings:
+//!
+//! ```json
+//! {
+//!   "groups": [
+//!     { "name": "main", "wallets": "wallets.txt", "share": 3, "cpu_usage": 100, "selection": "spread", "rotation": "least-hashes" },
+//!     { "name": "side", "wallets": ["addr1q8up...", "addr1qpxv... 2"], "cpu_usage": 25, "max_hashes_factor": 4 }
+//!   ]
+//! }
+//! ```
+//!
+//! `wallets` is a wallets file or a list of `<address> [weight]` entries. A group without it
+//! takes the rows of the main wallets file whose `group` column names it (see wallets.rs); without
+//! `groups` at all, that column alone splits the wallets into groups. Groups take turns by
+//! the same weighted round-robin as the wallets within them (see rotation.rs), `share` being a
+//! group's weight, and all of them mine through the one ROM cache. Settings a group leaves out
+//! come from the command line.
+
+use crate::rotation::{RotationPolicy, WalletRotation};
+use crate::spread::{SelectionMode, Spreader};
+use crate::MiningLimits;
+use serde::Deserialize;
+
+/// Where a group's wallets come from
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum GroupWallets {
+    File(String),
+    List(Vec<String>),
+}
+
+/// One entry of the `groups` config key
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GroupConfig {
+    pub name: Option<String>,
+    #[serde(default)]
+    pub wallets: Option<GroupWallets>, // None: the wallets file's rows tagged with this group's name
+    pub share: Option<f64>,
+    pub cpu_usage: Option<f64>,
+    pub selection: Option<String>,
+    pub rotation: Option<String>,
+    pub max_hashes_millions: Option<f64>,
+    pub max_hashes_factor: Option<f64>,
+    pub max_minutes_per_task: Option<f64>,
+}
+
+impl GroupConfig {
+    /// The single group of a run without `groups`: the wallets file and the command-line settings
+    pub fn from_wallets_file(path: &str) -> Self {
+        GroupConfig { wallets: Some(GroupWallets::File(path.to_string())), ..GroupConfig::tagged(None) }
+    }
+
+    /// A group of the wallets file's rows whose `group` column is `name`, with the command-line settings
+    pub fn tagged(name: Option<String>) -> Self {
+        GroupConfig {
+            name,
+            wallets: None,
+            share: None,
+            cpu_usage: None,
+            selection: None,
+            rotation: None,
+            max_hashes_millions: None,
+            max_hashes_factor: None,
+            max_minutes_per_task: None,
+        }
+    }
+}
+
+/// Parse the `groups` config value, checking the settings that have to make sense on their own
+pub fn parse(value: &serde_json::Value) -> Result<Vec<GroupConfig>, String> {
+    let groups: Vec<GroupConfig> = serde_json::from_value(value.clone()).map_err(|e| format!("invalid groups: {}", e))?;
+    if groups.is_empty() {
+        return Err("groups is empty".to_string());
+    }
+    for (index, group) in groups.iter().enumerate() {
+        let label = group.name.clone().unwrap_or_else(|| format!("#{}", index + 1));
+        if group.share.is_some_and(|s| !(s.is_finite() && s > 0.0)) {
+            return Err(format!("group {}: share must be a positive number", label));
+        }
+        if group.cpu_usage.is_some_and(|c| !(c > 0.0 && c <= 100.0)) {
+            return Err(format!("group {}: cpu_usage must be between 1 and 100", label));
+        }
+        if let Some(selection) = &group.selection {
+            if SelectionMode::parse(selection).is_none() {
+                return Err(format!("group {}: selection '{}' is not easiest or spread", label, selection));
+            }
+        }
+        if let Some(rotation) = &group.rotation {
+            if RotationPolicy::parse(rotation).is_none() {
+                return Err(format!("group {}: rotation '{}' is not round-robin, least-recent or least-hashes", label, rotation));
+            }
+        }
+        if group.wallets.is_none() && group.name.is_none() {
+            return Err(format!("group {}: needs a name to take wallets from the wallets file's group column, or wallets", label));
+        }
+        if groups.iter().take(index).any(|g| g.name.is_some() && g.name == group.name) {
+            return Err(format!("group {}: name used twice", label));
+        }
+    }
+    Ok(groups)
+}
+
+/// A group ready to mine: its wallets, settings and rotation state
+pub struct Group {
+    pub name: String,
+    pub wallets: Vec<(String, f64)>,
+    pub share: f64,
+    pub num_threads: usize,
+    pub cpu_usage: f64, // Percentage num_threads was derived from (the cgroup quota, see cgroup.rs)
+    pub limits: MiningLimits,
+    pub max_hashes_factor: Option<f64>,
+    pub rotation: WalletRotation,
+    pub spreader: Option<Spreader>, // Spread mode only
+}
+
+impl Group {
+    /// Address of the wallet whose turn it is among the ones `eligible` accepts
+    pub fn next_wallet(&mut self, eligible: impl Fn(&str) -> bool) -> Option<String> {
+        let wallets = &self.wallets;
+        self.rotation.next(|index| eligible(&wallets[index].0)).map(|index| wallets[index].0.clone())
+    }
+
+    /// The wallet's task ended after `hashes` hashes
+    pub fn served(&mut self, wallet_address: &str, hashes: u64) {
+        if let Some(index) = self.wallets.iter().position(|(w, _)| w == wallet_address) {
+            self.rotation.served(index, hashes);
+        }
+    }
+
+    /// The wallet's turn ended without mining
+    pub fn pass(&mut self, wallet_address: &str) {
+        if let Some(index) = self.wallets.iter().position(|(w, _)| w == wallet_address) {
+            self.rotation.pass(index);
+        }
+    }
+
+    /// Start mining a wallet (false if it is already in the group)
+    pub fn add_wallet(&mut self, wallet_address: &str, weight: f64) -> bool {
+        if self.wallets.iter().any(|(w, _)| w == wallet_address) {
+            return false;
+        }
+        self.wallets.push((wallet_address.to_string(), weight));
+        self.rotation.add(weight);
+        true
+    }
+
+    /// Stop mining a wallet (false if it isn't in the group)
+    pub fn remove_wallet(&mut self, wallet_address: &str) -> bool {
+        let Some(index) = self.wallets.iter().position(|(w, _)| w == wallet_address) else {
+            return false;
+        };
+        self.wallets.remove(index);
+        self.rotation.remove(index);
+        true
+    }
+
+    /// Limits for a task with this group's settings, the hash limit fixed per challenge with `max_hashes_factor`
+    pub fn task_limits(&self, max_hashes: Option<u64>) -> MiningLimits {
+        MiningLimits { max_hashes: max_hashes.or(self.limits.max_hashes), ..self.limits }
+    }
+}
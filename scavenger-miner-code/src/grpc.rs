@@ -0,0 +1,735 @@
+//! gRPC control and status API (`--grpc-port`)
+//!
+//! Service `scavenger.miner.v1.MinerControl`, described in `proto/miner_control.proto`, so fleet
+//! tooling can generate a typed client in any language instead of scraping logs:
+//!
+//! - `GetStatus`, and `StreamStats` for a status every few seconds
+//! - `Pause` / `Resume`, acting at once like the other pause controls
+//! - `SetCpuUsage`, `RefreshChallenges` and `RetrySubmissions`, handed to the main loop and applied
+//!   between tasks like the wallet control endpoint's changes (see control.rs)
+//!
+//! Served over cleartext HTTP/2 (h2c; `grpcurl -plaintext`) on its own thread, with the messages
+//! encoded by hand: the handful of scalar fields here doesn't call for a code generator (the
+//! tests below pin the encoding to protoc's output for the proto file). Listens
+//! on localhost unless `--grpc-bind` says otherwise; with `--grpc-token` every call needs
+//! `authorization: Bearer <token>` metadata. The `ctl` subcommand (ctl.rs) uses the client half.
+
+use crate::{dashboard, pause_reason, ranking, shutdown_requested, threads_for_cpu_usage, wallet_stats};
+use hyper::body::Bytes;
+use hyper::header::{HeaderMap, HeaderValue};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server};
+use std::convert::Infallible;
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_BIND: &str = "127.0.0.1";
+const SERVICE: &str = "scavenger.miner.v1.MinerControl";
+
+/// Largest request message accepted
+const MAX_MESSAGE_BYTES: usize = 64 * 1024;
+const DEFAULT_STREAM_INTERVAL_SECS: u64 = 5;
+
+/// gRPC status codes used here
+const OK: u32 = 0;
+const INVALID_ARGUMENT: u32 = 3;
+const INTERNAL: u32 = 13;
+const UNIMPLEMENTED: u32 = 12;
+const UNAUTHENTICATED: u32 = 16;
+
+/// Set by `Pause`, cleared by `Resume`
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// Paused over gRPC
+pub fn paused() -> bool {
+    PAUSED.load(Ordering::Relaxed)
+}
+
+/// A change for the main loop to apply before the next task
+pub enum Command {
+    SetCpuUsage(f64),
+    RefreshChallenges,
+    RetrySubmissions,
+}
+
+/// What the main loop last published for `Status`
+#[derive(Default)]
+struct Published {
+    threads: usize,
+    active_challenges: usize,
+}
+
+struct Shared {
+    commands: Sender<Command>,
+    published: Mutex<Published>,
+    token: Option<String>,
+    miner_id: String,
+    started: Instant,
+}
+
+pub struct GrpcServer {
+    commands: Receiver<Command>,
+    shared: Arc<Shared>,
+}
+
+impl GrpcServer {
+    /// Listen on `bind`:`port` (0 picks a free port)
+    pub fn start(bind: &str, port: u16, token: Option<String>, miner_id: &str) -> std::io::Result<Self> {
+        let listener = TcpListener::bind((bind, port))?;
+        listener.set_nonblocking(true)?;
+        let address = listener.local_addr()?;
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        let (commands_tx, commands) = mpsc::channel();
+        let shared = Arc::new(Shared {
+            commands: commands_tx,
+            published: Mutex::new(Published::default()),
+            token,
+            miner_id: miner_id.to_string(),
+            started: Instant::now(),
+        });
+
+        let server_shared = Arc::clone(&shared);
+        thread::Builder::new().name("grpc-control".to_string()).spawn(move || {
+            runtime.block_on(async move {
+                let make_service = make_service_fn(move |_| {
+                    let shared = Arc::clone(&server_shared);
+                    async move { Ok::<_, Infallible>(service_fn(move |request| handle(request, Arc::clone(&shared)))) }
+                });
+                let served = match Server::from_tcp(listener) {
+                    Ok(builder) => builder.http2_only(true).serve(make_service).await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = served {
+                    tracing::warn!(error = %e, "⚠️  gRPC control API stopped: {}", e);
+                }
+            })
+        })?;
+        tracing::info!(
+            "🛰️  gRPC control API ({}) on {}{}",
+            SERVICE,
+            address,
+            if shared.token.is_some() { ", token required" } else { "" },
+        );
+        Ok(GrpcServer { commands, shared })
+    }
+
+    /// Changes requested since the last call
+    pub fn take_commands(&self) -> Vec<Command> {
+        self.commands.try_iter().collect()
+    }
+
+    /// Figures `Status` reports that only the main loop knows
+    pub fn publish(&self, threads: usize, active_challenges: usize) {
+        *self.shared.published.lock().unwrap() = Published { threads, active_challenges };
+    }
+}
+
+/// A protobuf message being encoded (proto3: fields holding their default value are left out)
+#[derive(Default)]
+struct Message(Vec<u8>);
+
+impl Message {
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.0.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.0.push(value as u8);
+    }
+
+    fn key(&mut self, field: u32, wire_type: u8) {
+        self.varint(((field as u64) << 3) | wire_type as u64);
+    }
+
+    fn uint(mut self, field: u32, value: u64) -> Self {
+        if value != 0 {
+            self.key(field, 0);
+            self.varint(value);
+        }
+        self
+    }
+
+    fn boolean(self, field: u32, value: bool) -> Self {
+        self.uint(field, value as u64)
+    }
+
+    fn double(mut self, field: u32, value: f64) -> Self {
+        if value != 0.0 {
+            self.key(field, 1);
+            self.0.extend_from_slice(&value.to_le_bytes());
+        }
+        self
+    }
+
+    fn string(mut self, field: u32, value: &str) -> Self {
+        if !value.is_empty() {
+            self.key(field, 2);
+            self.varint(value.len() as u64);
+            self.0.extend_from_slice(value.as_bytes());
+        }
+        self
+    }
+
+    /// The message in a gRPC length-prefixed frame (uncompressed)
+    fn framed(&self) -> Bytes {
+        let mut frame = Vec::with_capacity(self.0.len() + 5);
+        frame.push(0);
+        frame.extend_from_slice(&(self.0.len() as u32).to_be_bytes());
+        frame.extend_from_slice(&self.0);
+        Bytes::from(frame)
+    }
+}
+
+/// A decoded field: varint and fixed-width values as their raw 64 bits, length-delimited as bytes
+enum Field<'a> {
+    Number(u64),
+    Bytes(&'a [u8]),
+}
+
+/// Every field of an encoded message, in order
+fn decode(mut bytes: &[u8]) -> Result<Vec<(u32, Field<'_>)>, String> {
+    fn varint(bytes: &mut &[u8]) -> Result<u64, String> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let (&byte, rest) = bytes.split_first().ok_or("truncated varint")?;
+            *bytes = rest;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte < 0x80 {
+                return Ok(value);
+            }
+        }
+        Err("varint too long".to_string())
+    }
+    fn take<'a>(bytes: &mut &'a [u8], n: usize) -> Result<&'a [u8], String> {
+        if bytes.len() < n {
+            return Err("truncated field".to_string());
+        }
+        let (taken, rest) = bytes.split_at(n);
+        *bytes = rest;
+        Ok(taken)
+    }
+
+    let mut fields = Vec::new();
+    while !bytes.is_empty() {
+        let key = varint(&mut bytes)?;
+        let field = (key >> 3) as u32;
+        let value = match key & 7 {
+            0 => Field::Number(varint(&mut bytes)?),
+            1 => Field::Number(u64::from_le_bytes(take(&mut bytes, 8)?.try_into().unwrap_or_default())),
+            2 => {
+                let len = varint(&mut bytes)? as usize;
+                Field::Bytes(take(&mut bytes, len)?)
+            }
+            5 => Field::Number(u32::from_le_bytes(take(&mut bytes, 4)?.try_into().unwrap_or_default()) as u64),
+            wire_type => return Err(format!("unsupported wire type {}", wire_type)),
+        };
+        fields.push((field, value));
+    }
+    Ok(fields)
+}
+
+/// Raw value of a scalar field (the last one wins, like protobuf parsers do)
+fn number(fields: &[(u32, Field<'_>)], field: u32) -> Option<u64> {
+    fields.iter().rev().find_map(|(f, value)| match value {
+        Field::Number(n) if *f == field => Some(*n),
+        _ => None,
+    })
+}
+
+/// Value of a string field, empty if absent
+fn text(fields: &[(u32, Field<'_>)], field: u32) -> String {
+    fields
+        .iter()
+        .rev()
+        .find_map(|(f, value)| match value {
+            Field::Bytes(bytes) if *f == field => Some(String::from_utf8_lossy(bytes).into_owned()),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+/// `Status` of the proto file
+#[derive(Debug, Default, PartialEq)]
+pub struct Status {
+    pub miner_id: String,
+    pub version: String,
+    pub uptime_secs: u64,
+    pub paused: bool,
+    pub pause_reason: String,
+    pub threads: u64,
+    pub hashrate: f64,
+    pub hashes: u64,
+    pub challenge_id: String,
+    pub wallet: String,
+    pub task_secs: u64,
+    pub task_hashes: u64,
+    pub active_challenges: u64,
+    pub solutions_found: u64,
+    pub solutions_accepted: u64,
+}
+
+impl Status {
+    fn encode(&self) -> Message {
+        Message::default()
+            .string(1, &self.miner_id)
+            .string(2, &self.version)
+            .uint(3, self.uptime_secs)
+            .boolean(4, self.paused)
+            .string(5, &self.pause_reason)
+            .uint(6, self.threads)
+            .double(7, self.hashrate)
+            .uint(8, self.hashes)
+            .string(9, &self.challenge_id)
+            .string(10, &self.wallet)
+            .uint(11, self.task_secs)
+            .uint(12, self.task_hashes)
+            .uint(13, self.active_challenges)
+            .uint(14, self.solutions_found)
+            .uint(15, self.solutions_accepted)
+    }
+
+    fn decode(message: &[u8]) -> Result<Self, String> {
+        let fields = decode(message)?;
+        let number = |field| number(&fields, field).unwrap_or(0);
+        Ok(Status {
+            miner_id: text(&fields, 1),
+            version: text(&fields, 2),
+            uptime_secs: number(3),
+            paused: number(4) != 0,
+            pause_reason: text(&fields, 5),
+            threads: number(6),
+            hashrate: f64::from_bits(number(7)),
+            hashes: number(8),
+            challenge_id: text(&fields, 9),
+            wallet: text(&fields, 10),
+            task_secs: number(11),
+            task_hashes: number(12),
+            active_challenges: number(13),
+            solutions_found: number(14),
+            solutions_accepted: number(15),
+        })
+    }
+}
+
+/// `CommandReply` of the proto file
+#[derive(Debug, PartialEq)]
+pub struct CommandReply {
+    pub accepted: bool,
+    pub message: String,
+}
+
+impl CommandReply {
+    fn encode(&self) -> Message {
+        Message::default().boolean(1, self.accepted).string(2, &self.message)
+    }
+
+    fn decode(message: &[u8]) -> Result<Self, String> {
+        let fields = decode(message)?;
+        Ok(CommandReply { accepted: number(&fields, 1).unwrap_or(0) != 0, message: text(&fields, 2) })
+    }
+}
+
+/// The message inside a request body: one gRPC frame, or nothing for an empty message
+fn unframe(body: &[u8]) -> Result<&[u8], (u32, String)> {
+    if body.is_empty() {
+        return Ok(body);
+    }
+    if body.len() < 5 {
+        return Err((INVALID_ARGUMENT, "truncated gRPC frame".to_string()));
+    }
+    if body[0] != 0 {
+        return Err((UNIMPLEMENTED, "compressed messages are not supported".to_string()));
+    }
+    let len = u32::from_be_bytes([body[1], body[2], body[3], body[4]]) as usize;
+    body.get(5..5 + len).ok_or((INVALID_ARGUMENT, "truncated gRPC message".to_string()))
+}
+
+fn trailers(code: u32, message: &str) -> HeaderMap {
+    let mut trailers = HeaderMap::new();
+    trailers.insert("grpc-status", HeaderValue::from(code));
+    // grpc-message is percent-encoded; everything here is plain ASCII apart from spaces
+    if let Ok(value) = HeaderValue::from_str(&message.replace('%', "%25").replace(|c: char| !c.is_ascii() || c.is_ascii_control(), "?")) {
+        if !message.is_empty() {
+            trailers.insert("grpc-message", value);
+        }
+    }
+    trailers
+}
+
+fn grpc_response(body: Body) -> Response<Body> {
+    let mut response = Response::new(body);
+    response.headers_mut().insert("content-type", HeaderValue::from_static("application/grpc"));
+    response
+}
+
+/// Trailers-only response: the call failed before any message
+fn error(code: u32, message: &str) -> Response<Body> {
+    let mut response = grpc_response(Body::empty());
+    response.headers_mut().extend(trailers(code, message));
+    response
+}
+
+fn unary(message: Message) -> Response<Body> {
+    let (mut sender, body) = Body::channel();
+    tokio::spawn(async move {
+        if sender.send_data(message.framed()).await.is_ok() {
+            let _ = sender.send_trailers(trailers(OK, "")).await;
+        }
+    });
+    grpc_response(body)
+}
+
+fn reply(accepted: bool, message: &str) -> Response<Body> {
+    unary(CommandReply { accepted, message: message.to_string() }.encode())
+}
+
+fn status(shared: &Shared) -> Message {
+    let published = shared.published.lock().unwrap();
+    let reason = pause_reason();
+    let (challenge_id, wallet, running) = dashboard::current_task().unwrap_or_default();
+    let stats = wallet_stats::snapshot();
+    let (found, accepted) = stats.values().fold((0, 0), |(f, a), s| (f + s.solutions, a + s.accepted));
+    Status {
+        miner_id: shared.miner_id.clone(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        uptime_secs: shared.started.elapsed().as_secs(),
+        paused: reason.is_some(),
+        pause_reason: reason.unwrap_or_default().to_string(),
+        threads: published.threads as u64,
+        hashrate: ranking::measured_hashrate().unwrap_or(0.0),
+        hashes: dashboard::hashes_mined(),
+        challenge_id,
+        wallet,
+        task_secs: running.as_secs(),
+        task_hashes: dashboard::task_progress().map_or(0, |(hashes, ..)| hashes),
+        active_challenges: published.active_challenges as u64,
+        solutions_found: found,
+        solutions_accepted: accepted,
+    }
+    .encode()
+}
+
+/// A `Status` every `interval` until the client goes away or the miner stops
+fn stream_stats(shared: Arc<Shared>, interval: Duration) -> Response<Body> {
+    let (mut sender, body) = Body::channel();
+    tokio::spawn(async move {
+        loop {
+            let frame = status(&shared).framed();
+            if sender.send_data(frame).await.is_err() {
+                return;
+            }
+            let next = Instant::now() + interval;
+            while Instant::now() < next {
+                if shutdown_requested() {
+                    let _ = sender.send_trailers(trailers(OK, "miner stopping")).await;
+                    return;
+                }
+                tokio::time::sleep(Duration::from_millis(250)).await;
+            }
+        }
+    });
+    grpc_response(body)
+}
+
+fn queue(shared: &Shared, command: Command, message: &str) -> Response<Body> {
+    match shared.commands.send(command) {
+        Ok(()) => reply(true, message),
+        Err(_) => error(INTERNAL, "the miner is shutting down"),
+    }
+}
+
+async fn handle(request: Request<Body>, shared: Arc<Shared>) -> Result<Response<Body>, Infallible> {
+    let is_grpc = request
+        .headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/grpc"));
+    if !is_grpc {
+        let mut response = Response::new(Body::from("gRPC only (content-type: application/grpc)\n"));
+        *response.status_mut() = hyper::StatusCode::UNSUPPORTED_MEDIA_TYPE;
+        return Ok(response);
+    }
+    if let Some(token) = &shared.token {
+        let authorized = request
+            .headers()
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .is_some_and(|given| given.trim() == token);
+        if !authorized {
+            return Ok(error(UNAUTHENTICATED, "missing or wrong bearer token"));
+        }
+    }
+    let Some(method) = request.uri().path().strip_prefix(&format!("/{}/", SERVICE)).map(str::to_string) else {
+        return Ok(error(UNIMPLEMENTED, &format!("unknown service (this is {})", SERVICE)));
+    };
+    let body = match hyper::body::to_bytes(request.into_body()).await {
+        Ok(body) if body.len() <= MAX_MESSAGE_BYTES + 5 => body,
+        Ok(_) => return Ok(error(INVALID_ARGUMENT, "request message too large")),
+        Err(e) => return Ok(error(INTERNAL, &e.to_string())),
+    };
+    let fields = match unframe(&body).and_then(|message| decode(message).map_err(|e| (INVALID_ARGUMENT, e))) {
+        Ok(fields) => fields,
+        Err((code, message)) => return Ok(error(code, &message)),
+    };
+
+    Ok(match method.as_str() {
+        "GetStatus" => unary(status(&shared)),
+        "StreamStats" => {
+            let secs = number(&fields, 1).filter(|s| *s > 0).unwrap_or(DEFAULT_STREAM_INTERVAL_SECS).min(3600);
+            stream_stats(shared, Duration::from_secs(secs))
+        }
+        "Pause" => {
+            PAUSED.store(true, Ordering::SeqCst);
+            tracing::info!("⏸️  Pause requested over gRPC");
+            reply(true, "pausing after the current hashing slice")
+        }
+        "Resume" => {
+            let was_paused = PAUSED.swap(false, Ordering::SeqCst);
+            tracing::info!("▶️  Resume requested over gRPC");
+            match (was_paused, pause_reason()) {
+                (_, Some(reason)) => reply(false, &format!("still paused ({})", reason)),
+                (true, None) => reply(true, "resumed"),
+                (false, None) => reply(true, "not paused"),
+            }
+        }
+        "SetCpuUsage" => match number(&fields, 1).map(f64::from_bits) {
+            Some(percent) if (1.0..=100.0).contains(&percent) => queue(
+                &shared,
+                Command::SetCpuUsage(percent),
+                &format!("{} thread(s) from the next task", threads_for_cpu_usage(percent)),
+            ),
+            _ => error(INVALID_ARGUMENT, "percent must be between 1 and 100"),
+        },
+        "RefreshChallenges" => queue(&shared, Command::RefreshChallenges, "challenges are fetched again before the next task"),
+        "RetrySubmissions" => queue(&shared, Command::RetrySubmissions, "pending solutions are resubmitted before the next task"),
+        _ => error(UNIMPLEMENTED, &format!("unknown method {}", method)),
+    })
+}
+
+/// Client side of the API, used by `ctl`
+pub struct Client {
+    runtime: tokio::runtime::Runtime,
+    client: hyper::Client<hyper::client::HttpConnector>,
+    base: String,
+    token: Option<String>,
+}
+
+impl Client {
+    pub fn new(addr: &str, token: Option<String>) -> Result<Self, String> {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().map_err(|e| e.to_string())?;
+        let client = hyper::Client::builder().http2_only(true).build_http();
+        Ok(Client { runtime, client, base: format!("http://{}/{}/", addr.trim_start_matches("http://"), SERVICE), token })
+    }
+
+    pub fn status(&self) -> Result<Status, String> {
+        Status::decode(&self.unary("GetStatus", Message::default())?)
+    }
+
+    pub fn pause(&self) -> Result<CommandReply, String> {
+        self.command("Pause", Message::default())
+    }
+
+    pub fn resume(&self) -> Result<CommandReply, String> {
+        self.command("Resume", Message::default())
+    }
+
+    pub fn set_cpu_usage(&self, percent: f64) -> Result<CommandReply, String> {
+        self.command("SetCpuUsage", Message::default().double(1, percent))
+    }
+
+    pub fn refresh_challenges(&self) -> Result<CommandReply, String> {
+        self.command("RefreshChallenges", Message::default())
+    }
+
+    pub fn retry_submissions(&self) -> Result<CommandReply, String> {
+        self.command("RetrySubmissions", Message::default())
+    }
+
+    /// Call `each` with every `Status` streamed until it returns false or the miner ends the stream
+    pub fn stream_stats(&self, interval_secs: u32, mut each: impl FnMut(Status) -> bool) -> Result<(), String> {
+        let mut error = None;
+        self.call("StreamStats", Message::default().uint(1, interval_secs as u64), None, |message| {
+            match Status::decode(message) {
+                Ok(status) => each(status),
+                Err(e) => {
+                    error = Some(e);
+                    false
+                }
+            }
+        })?;
+        error.map_or(Ok(()), Err)
+    }
+
+    fn command(&self, method: &str, request: Message) -> Result<CommandReply, String> {
+        CommandReply::decode(&self.unary(method, request)?)
+    }
+
+    fn unary(&self, method: &str, request: Message) -> Result<Vec<u8>, String> {
+        let mut reply = None;
+        self.call(method, request, Some(Duration::from_secs(15)), |message| {
+            reply = Some(message.to_vec());
+            true
+        })?;
+        reply.ok_or_else(|| "the miner sent no reply".to_string())
+    }
+
+    /// Make one call, handing each response message to `each` until it returns false
+    fn call(&self, method: &str, request: Message, timeout: Option<Duration>, mut each: impl FnMut(&[u8]) -> bool) -> Result<(), String> {
+        use hyper::body::HttpBody;
+
+        let mut builder = Request::post(format!("{}{}", self.base, method))
+            .header("content-type", "application/grpc")
+            .header("te", "trailers");
+        if let Some(token) = &self.token {
+            builder = builder.header("authorization", format!("Bearer {}", token));
+        }
+        let request = builder.body(Body::from(request.framed())).map_err(|e| e.to_string())?;
+
+        let call = async {
+            let response = self.client.request(request).await.map_err(|e| e.to_string())?;
+            if !response.status().is_success() {
+                return Err(format!("HTTP {} (is this the gRPC port?)", response.status()));
+            }
+            // A trailers-only response carries the status in the headers
+            if call_status(response.headers())? {
+                return Ok(());
+            }
+            let mut body = response.into_body();
+            let mut buffer = Vec::new();
+            while let Some(chunk) = body.data().await {
+                buffer.extend_from_slice(&chunk.map_err(|e| e.to_string())?);
+                while buffer.len() >= 5 {
+                    let len = u32::from_be_bytes([buffer[1], buffer[2], buffer[3], buffer[4]]) as usize;
+                    if buffer.len() < 5 + len {
+                        break;
+                    }
+                    let keep_going = each(&buffer[5..5 + len]);
+                    buffer.drain(..5 + len);
+                    if !keep_going {
+                        return Ok(());
+                    }
+                }
+            }
+            match body.trailers().await.map_err(|e| e.to_string())? {
+                Some(trailers) if call_status(&trailers)? => Ok(()),
+                _ => Err("the call ended without a gRPC status".to_string()),
+            }
+        };
+        self.runtime.block_on(async {
+            match timeout {
+                Some(timeout) => tokio::time::timeout(timeout, call).await.unwrap_or_else(|_| Err("timed out".to_string())),
+                None => call.await,
+            }
+        })
+    }
+}
+
+/// Whether `headers` hold a successful gRPC status (false if they hold none)
+fn call_status(headers: &HeaderMap) -> Result<bool, String> {
+    let Some(code) = headers.get("grpc-status").and_then(|v| v.to_str().ok()) else {
+        return Ok(false);
+    };
+    if code == "0" {
+        return Ok(true);
+    }
+    let message = headers.get("grpc-message").and_then(|v| v.to_str().ok()).unwrap_or("").replace("%25", "%");
+    Err(match code.parse::<u32>() {
+        Ok(UNAUTHENTICATED) => format!("{} (pass the miner's --grpc-token with --token)", message),
+        _ => format!("{} (gRPC status {})", message, code),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Golden messages encoded by protoc from proto/miner_control.proto, e.g.
+    //   echo 'accepted: true message: "paused"' | protoc --encode=scavenger.miner.v1.CommandReply proto/miner_control.proto
+
+    fn sample_status() -> Status {
+        Status {
+            miner_id: "rig-07".to_string(),
+            version: "1.0.0".to_string(),
+            uptime_secs: 3600,
+            paused: true,
+            pause_reason: "gRPC".to_string(),
+            threads: 16,
+            hashrate: 1234.5,
+            hashes: 370350,
+            challenge_id: "**D05C12".to_string(),
+            wallet: "addr1q".to_string(),
+            task_secs: 300,
+            task_hashes: 150000,
+            active_challenges: 3,
+            solutions_found: 14,
+            solutions_accepted: 12,
+        }
+    }
+
+    const STATUS_BYTES: &[u8] = &[
+        0x0a, 0x06, 0x72, 0x69, 0x67, 0x2d, 0x30, 0x37, 0x12, 0x05, 0x31, 0x2e,
+        0x30, 0x2e, 0x30, 0x18, 0x90, 0x1c, 0x20, 0x01, 0x2a, 0x04, 0x67, 0x52,
+        0x50, 0x43, 0x30, 0x10, 0x39, 0x00, 0x00, 0x00, 0x00, 0x00, 0x4a, 0x93,
+        0x40, 0x40, 0xae, 0xcd, 0x16, 0x4a, 0x08, 0x2a, 0x2a, 0x44, 0x30, 0x35,
+        0x43, 0x31, 0x32, 0x52, 0x06, 0x61, 0x64, 0x64, 0x72, 0x31, 0x71, 0x58,
+        0xac, 0x02, 0x60, 0xf0, 0x93, 0x09, 0x68, 0x03, 0x70, 0x0e, 0x78, 0x0c,
+    ];
+
+    const COMMAND_REPLY_BYTES: &[u8] = &[0x08, 0x01, 0x12, 0x06, 0x70, 0x61, 0x75, 0x73, 0x65, 0x64];
+
+    #[test]
+    fn status_matches_protoc() {
+        assert_eq!(sample_status().encode().0, STATUS_BYTES);
+        assert_eq!(Status::decode(STATUS_BYTES).unwrap(), sample_status());
+    }
+
+    #[test]
+    fn command_reply_matches_protoc() {
+        let reply = CommandReply { accepted: true, message: "paused".to_string() };
+        assert_eq!(reply.encode().0, COMMAND_REPLY_BYTES);
+        assert_eq!(CommandReply::decode(COMMAND_REPLY_BYTES).unwrap(), reply);
+    }
+
+    #[test]
+    fn default_fields_are_omitted() {
+        assert!(Status::default().encode().0.is_empty());
+        assert_eq!(Status::decode(&[]).unwrap(), Status::default());
+        let reply = CommandReply { accepted: false, message: String::new() };
+        assert!(reply.encode().0.is_empty());
+    }
+
+    #[test]
+    fn request_fields_match_protoc() {
+        // SetCpuUsageRequest { percent: 37.5 }: double as fixed64
+        let set_cpu = Message::default().double(1, 37.5);
+        assert_eq!(set_cpu.0, [0x09, 0x00, 0x00, 0x00, 0x00, 0x00, 0xc0, 0x42, 0x40]);
+        let fields = decode(&set_cpu.0).unwrap();
+        assert_eq!(number(&fields, 1).map(f64::from_bits), Some(37.5));
+
+        // StreamStatsRequest { interval_secs: 10 }
+        let stream = Message::default().uint(1, 10);
+        assert_eq!(stream.0, [0x08, 0x0a]);
+        assert_eq!(number(&decode(&stream.0).unwrap(), 1), Some(10));
+    }
+
+    #[test]
+    fn frames_round_trip() {
+        let framed = CommandReply { accepted: true, message: "paused".to_string() }.encode().framed();
+        assert_eq!(&framed[..5], &[0, 0, 0, 0, COMMAND_REPLY_BYTES.len() as u8]);
+        assert_eq!(unframe(&framed).unwrap(), COMMAND_REPLY_BYTES);
+        assert_eq!(unframe(&[]).unwrap(), &[] as &[u8]);
+        assert_eq!(unframe(&framed[..framed.len() - 1]).unwrap_err().0, INVALID_ARGUMENT);
+        assert_eq!(unframe(&[1, 0, 0, 0, 0]).unwrap_err().0, UNIMPLEMENTED);
+    }
+
+    #[test]
+    fn truncated_messages_are_rejected() {
+        assert!(decode(&STATUS_BYTES[..STATUS_BYTES.len() - 1]).is_err());
+        assert!(decode(&[0x0a, 0x06, 0x72]).is_err());
+    }
+}
@@ -0,0 +1,172 @@
+//! Hashrate history (`hashrate_history.jsonl`) and `export-hashrate`
+//!
+//! A background thread samples the aggregate hashrate every `--hashrate-interval` seconds
+//! (default 60, 0 turns it off) and appends it to the history in the data folder, along with
+//! whether mining was paused. The file is a ring buffer of the last `HISTORY_SAMPLES` samples:
+//! it is cut back to them once it holds twice as many, so appending stays cheap.
+//! `export-hashrate` writes the history as JSON or CSV, to line up dips with thermal throttling
+//! or other workloads on the machine.
+
+use crate::export::{csv_field, parse_date};
+use crate::{dashboard, get_timestamp, paths, shutdown_requested, CliArgs};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const HASHRATE_HISTORY_FILE: &str = "hashrate_history.jsonl";
+pub const DEFAULT_INTERVAL_SECS: u64 = 60;
+
+/// Samples kept: a week at the default interval
+const HISTORY_SAMPLES: usize = 7 * 24 * 60;
+
+const CSV_COLUMNS: &[&str] = &["at", "hashrate", "hashes", "secs", "paused"];
+
+/// Hashrate over one sampling interval
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sample {
+    pub at: String,
+    pub hashrate: f64, // H/s
+    pub hashes: u64,
+    pub secs: f64,
+    pub paused: bool,
+}
+
+fn path() -> PathBuf {
+    paths::data_dir().join(HASHRATE_HISTORY_FILE)
+}
+
+/// Every sample in the history, oldest first (unreadable lines are skipped)
+pub fn load() -> Vec<Sample> {
+    let Ok(file) = fs::File::open(path()) else {
+        return Vec::new();
+    };
+    let samples: Vec<Sample> = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect();
+    let skip = samples.len().saturating_sub(HISTORY_SAMPLES);
+    samples.into_iter().skip(skip).collect()
+}
+
+/// Sample in the background until shutdown; `interval_secs` 0 does nothing
+pub fn start(interval_secs: u64) {
+    if interval_secs == 0 {
+        return;
+    }
+    let spawned = thread::Builder::new().name("hashrate-history".to_string()).spawn(move || {
+        let mut lines = load().len();
+        let (mut last_hashes, mut last_at) = (dashboard::hashes_mined(), Instant::now());
+        while !shutdown_requested() {
+            thread::sleep(Duration::from_secs(1));
+            if last_at.elapsed() < Duration::from_secs(interval_secs) {
+                continue;
+            }
+            let (hashes, secs) = (dashboard::hashes_mined(), last_at.elapsed().as_secs_f64());
+            let sample = Sample {
+                at: get_timestamp(),
+                hashrate: hashes.saturating_sub(last_hashes) as f64 / secs,
+                hashes: hashes.saturating_sub(last_hashes),
+                secs,
+                paused: crate::pause_reason().is_some(),
+            };
+            (last_hashes, last_at) = (hashes, Instant::now());
+            if let Err(e) = append(&sample, &mut lines) {
+                tracing::warn!(error = %e, "⚠️  Failed to record hashrate in {}: {}", HASHRATE_HISTORY_FILE, e);
+            }
+        }
+    });
+    if let Err(e) = spawned {
+        tracing::warn!(error = %e, "⚠️  Cannot start hashrate history: {}", e);
+    }
+}
+
+/// Append a sample, cutting the file back to the last `HISTORY_SAMPLES` once it holds twice as many
+fn append(sample: &Sample, lines: &mut usize) -> std::io::Result<()> {
+    let path = path();
+    if *lines >= 2 * HISTORY_SAMPLES {
+        let mut content = String::new();
+        for kept in load() {
+            content.push_str(&serde_json::to_string(&kept)?);
+            content.push('\n');
+        }
+        let tmp = path.with_extension("jsonl.tmp");
+        fs::write(&tmp, content)?;
+        fs::rename(&tmp, &path)?;
+        *lines = HISTORY_SAMPLES;
+    }
+    let mut line = serde_json::to_string(sample)?;
+    line.push('\n');
+    fs::OpenOptions::new().create(true).append(true).open(&path)?.write_all(line.as_bytes())?;
+    *lines += 1;
+    Ok(())
+}
+
+/// Usage: export-hashrate [--format csv|json] [--output <file>] [--from <date>] [--to <date>]
+pub fn run_export_hashrate(args: &[String]) -> bool {
+    let cli = CliArgs::parse(args);
+
+    let format = cli.option("--format").unwrap_or("csv").to_ascii_lowercase();
+    if format != "csv" && format != "json" {
+        println!("❌ Unsupported --format '{}' (supported: csv, json)", format);
+        return false;
+    }
+
+    let (mut from, mut to) = (None, None);
+    for (name, end_of_day) in [("--from", false), ("--to", true)] {
+        let Some(value) = cli.option(name) else {
+            continue;
+        };
+        let Some(date) = parse_date(value, end_of_day) else {
+            println!("❌ Invalid {} '{}' (expected YYYY-MM-DD or an RFC 3339 timestamp)", name, value);
+            return false;
+        };
+        if end_of_day {
+            to = Some(date);
+        } else {
+            from = Some(date);
+        }
+    }
+
+    let samples: Vec<Sample> = load()
+        .into_iter()
+        .filter(|s| {
+            let at = DateTime::parse_from_rfc3339(&s.at).map(|t| t.with_timezone(&Utc)).ok();
+            from.is_none_or(|from| at.is_some_and(|at| at >= from)) && to.is_none_or(|to| at.is_some_and(|at| at < to))
+        })
+        .collect();
+
+    let content = if format == "json" {
+        match serde_json::to_string_pretty(&samples) {
+            Ok(json) => json,
+            Err(e) => {
+                println!("❌ Failed to serialize the hashrate history: {}", e);
+                return false;
+            }
+        }
+    } else {
+        let mut csv = String::from("\u{feff}");
+        csv.push_str(&CSV_COLUMNS.join(","));
+        csv.push_str("\r\n");
+        for s in &samples {
+            let row = [s.at.clone(), format!("{:.2}", s.hashrate), s.hashes.to_string(), format!("{:.1}", s.secs), s.paused.to_string()];
+            csv.push_str(&row.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(","));
+            csv.push_str("\r\n");
+        }
+        csv
+    };
+
+    let output = cli.option("--output").map(|s| s.to_string()).unwrap_or_else(|| {
+        format!("hashrate-{}.{}", Utc::now().format("%Y%m%d-%H%M%S"), format)
+    });
+    if let Err(e) = fs::write(&output, content) {
+        println!("❌ Failed to write {}: {}", output, e);
+        return false;
+    }
+    println!("📄 Exported {} hashrate sample(s) from {} to {}", samples.len(), path().display(), output);
+    true
+}
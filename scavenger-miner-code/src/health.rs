@@ -0,0 +1,107 @@
+//! Liveness endpoint for container orchestration (`--health-port`)
+//!
+//! `GET /healthz` answers 200 while the miner is making progress and 503 once it is wedged, so
+//! Docker or Kubernetes can restart it. Progress means, within the last `--health-stale-minutes`
+//! (default 10):
+//!
+//! - the hash counters advanced or the main loop came around, and
+//! - an API call succeeded (skipped with `--challenges-file`)
+//!
+//! A paused miner (signal, PAUSE file, dashboard, Telegram, idle-only mode) counts as healthy, since
+//! neither hashing nor polling happens then. The endpoint only reports timings, never wallets or
+//! credentials, and listens on localhost unless `--health-bind` says otherwise (Kubernetes probes
+//! need `0.0.0.0`, a Docker `HEALTHCHECK` running `curl` inside the container doesn't).
+
+use crate::http_server::{self, error, reply, Reply, Settings};
+use crate::{dashboard, pause_reason};
+use std::net::TcpListener;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_BIND: &str = "127.0.0.1";
+pub const DEFAULT_STALE_MINUTES: u64 = 10;
+
+/// How often the hash counters are checked for movement
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// When progress and API contact were last seen; `None` until the endpoint starts
+struct Progress {
+    started: Instant,
+    hashes: u64,
+    last_progress: Instant,
+    last_api: Instant,
+}
+
+static PROGRESS: Mutex<Option<Progress>> = Mutex::new(None);
+
+/// The main loop came around
+pub fn loop_tick() {
+    if let Some(progress) = PROGRESS.lock().unwrap().as_mut() {
+        progress.last_progress = Instant::now();
+    }
+}
+
+/// An API call succeeded
+pub fn api_reachable() {
+    if let Some(progress) = PROGRESS.lock().unwrap().as_mut() {
+        progress.last_api = Instant::now();
+    }
+}
+
+/// Serve `/healthz` on `bind`:`port` (0 picks a free port); `offline` skips the API check
+pub fn start(bind: &str, port: u16, stale_minutes: u64, offline: bool) -> std::io::Result<()> {
+    let listener = TcpListener::bind((bind, port))?;
+    tracing::info!("🩺 Health endpoint on http://{}/healthz", listener.local_addr()?);
+    let now = Instant::now();
+    *PROGRESS.lock().unwrap() = Some(Progress { started: now, hashes: dashboard::hashes_mined(), last_progress: now, last_api: now });
+
+    thread::Builder::new().name("health-sampler".to_string()).spawn(|| loop {
+        thread::sleep(SAMPLE_INTERVAL);
+        let hashes = dashboard::hashes_mined();
+        if let Some(progress) = PROGRESS.lock().unwrap().as_mut() {
+            if hashes != progress.hashes {
+                progress.hashes = hashes;
+                progress.last_progress = Instant::now();
+            }
+        }
+    })?;
+
+    let stale = Duration::from_secs(stale_minutes.max(1) * 60);
+    let settings = Settings { name: "health", max_body: 0, token: None };
+    http_server::serve(listener, settings, move |request| match (request.method.as_str(), request.path.as_str()) {
+        ("GET" | "HEAD", "/healthz") => check(stale, offline),
+        _ => error(404, "not found (GET /healthz)"),
+    })
+}
+
+/// Status code and JSON body for `/healthz`
+fn check(stale: Duration, offline: bool) -> Reply {
+    let guard = PROGRESS.lock().unwrap();
+    let Some(progress) = guard.as_ref() else {
+        return reply(503, serde_json::json!({ "status": "starting" }));
+    };
+    let progress_secs = progress.last_progress.elapsed().as_secs();
+    let api_secs = progress.last_api.elapsed().as_secs();
+    let paused = pause_reason();
+
+    let mut problems = Vec::new();
+    if paused.is_none() {
+        if progress.last_progress.elapsed() > stale {
+            problems.push(format!("no mining progress for {}s", progress_secs));
+        }
+        if !offline && progress.last_api.elapsed() > stale {
+            problems.push(format!("no successful API call for {}s", api_secs));
+        }
+    }
+    let body = serde_json::json!({
+        "status": if problems.is_empty() { "ok" } else { "stalled" },
+        "problems": problems,
+        "paused": paused,
+        "uptime_secs": progress.started.elapsed().as_secs(),
+        "last_progress_secs": progress_secs,
+        "last_api_secs": if offline { None } else { Some(api_secs) },
+        "hashes": progress.hashes,
+    });
+    reply(if problems.is_empty() { 200 } else { 503 }, body)
+}
@@ -0,0 +1,304 @@
+//! Shared HTTP client setup for every request the miner makes
+//!
+//! All API calls, webhooks and subcommands build their client here, so network
+//! settings like the proxy and TLS options apply everywhere at once. API requests
+//! are started with `get` / `post` / `head` below, which add the API credentials.
+
+use crate::CliArgs;
+use reqwest::blocking::{Client, ClientBuilder, RequestBuilder, Response};
+use reqwest::header::{HeaderName, HeaderValue, AUTHORIZATION};
+use reqwest::Method;
+use reqwest::tls::{Certificate, Version};
+use std::env;
+use std::fs;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// Explicit proxy (`--proxy` / config file / `SCAVENGER_PROXY`)
+/// Without one, reqwest still honors the standard `HTTPS_PROXY` / `HTTP_PROXY` / `ALL_PROXY` variables
+struct ProxySettings {
+    url: String,
+    user: Option<String>,
+    password: Option<String>,
+}
+
+static PROXY: OnceLock<Option<ProxySettings>> = OnceLock::new();
+
+/// TLS and proxy-detection options (`--ca-cert`, `--tls-min`, `--no-system-proxy`)
+#[derive(Default)]
+struct TlsSettings {
+    extra_roots: Vec<Certificate>, // Trusted in addition to the built-in roots
+    min_version: Option<Version>,
+    no_system_proxy: bool,
+}
+
+static TLS: OnceLock<TlsSettings> = OnceLock::new();
+
+/// API base URL (`--api-base` / `SCAVENGER_API_BASE`), e.g. a staging server or the mock API
+static API_BASE: OnceLock<String> = OnceLock::new();
+
+fn api_base_from(cli: Option<&CliArgs>) -> Result<String, String> {
+    let base = cli
+        .and_then(|c| c.option("--api-base"))
+        .map(|s| s.to_string())
+        .or_else(|| env::var("SCAVENGER_API_BASE").ok())
+        .filter(|s| !s.trim().is_empty());
+    match base {
+        Some(base) if base.starts_with("http://") || base.starts_with("https://") => {
+            Ok(base.trim().trim_end_matches('/').to_string())
+        }
+        Some(base) => Err(format!("Invalid API base '{}' (expected an http:// or https:// URL)", base)),
+        None => Ok(crate::SCAVENGER_API_BASE.to_string()),
+    }
+}
+
+/// Base URL every API endpoint is relative to
+pub fn api_base() -> &'static str {
+    API_BASE.get_or_init(|| api_base_from(None).unwrap_or_else(|_| crate::SCAVENGER_API_BASE.to_string()))
+}
+
+/// Credentials for the Scavenger API (`--api-key` / `--api-token`), never sent to webhooks
+#[derive(Default)]
+struct ApiAuth {
+    headers: Vec<(HeaderName, HeaderValue)>,
+}
+
+static AUTH: OnceLock<ApiAuth> = OnceLock::new();
+
+/// Header carrying `--api-key` unless `--api-key-header` names another one
+const DEFAULT_API_KEY_HEADER: &str = "X-API-Key";
+
+fn auth_from(cli: &CliArgs) -> Result<ApiAuth, String> {
+    let setting = |option: &str, var: &str| {
+        cli.option(option)
+            .map(|s| s.to_string())
+            .or_else(|| env::var(var).ok())
+            .filter(|s| !s.trim().is_empty())
+    };
+    let sensitive = |value: String, what: &str| {
+        let mut value = HeaderValue::from_str(value.trim()).map_err(|_| format!("Invalid {} (must be printable ASCII)", what))?;
+        value.set_sensitive(true);
+        Ok::<_, String>(value)
+    };
+
+    let mut headers = Vec::new();
+    if let Some(key) = setting("--api-key", "SCAVENGER_API_KEY") {
+        let name = cli.option("--api-key-header").unwrap_or(DEFAULT_API_KEY_HEADER);
+        let name = HeaderName::from_bytes(name.as_bytes()).map_err(|_| format!("Invalid --api-key-header '{}'", name))?;
+        headers.push((name, sensitive(key, "--api-key")?));
+    }
+    if let Some(token) = setting("--api-token", "SCAVENGER_API_TOKEN") {
+        headers.push((AUTHORIZATION, sensitive(format!("Bearer {}", token.trim()), "--api-token")?));
+    }
+    Ok(ApiAuth { headers })
+}
+
+/// Time allowed to establish a connection (`--connect-timeout`)
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Time allowed for a whole request, including reading the body (`--request-timeout`)
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Copy)]
+struct Timeouts {
+    connect: Duration,
+    request: Duration,
+}
+
+static TIMEOUTS: OnceLock<Timeouts> = OnceLock::new();
+
+fn default_timeouts() -> Timeouts {
+    Timeouts {
+        connect: DEFAULT_CONNECT_TIMEOUT,
+        request: DEFAULT_REQUEST_TIMEOUT,
+    }
+}
+
+fn timeouts_from(cli: &CliArgs) -> Result<Timeouts, String> {
+    let seconds = |option: &str, default: Duration| match cli.option(option) {
+        Some(value) => value
+            .parse::<f64>()
+            .ok()
+            .filter(|secs| secs.is_finite() && *secs > 0.0)
+            .map(Duration::from_secs_f64)
+            .ok_or_else(|| format!("Invalid {} '{}' (expected a number of seconds)", option, value)),
+        None => Ok(default),
+    };
+
+    Ok(Timeouts {
+        connect: seconds("--connect-timeout", DEFAULT_CONNECT_TIMEOUT)?,
+        request: seconds("--request-timeout", DEFAULT_REQUEST_TIMEOUT)?,
+    })
+}
+
+fn proxy_from(cli: Option<&CliArgs>) -> Option<ProxySettings> {
+    let setting = |option: &str, var: &str| {
+        cli.and_then(|c| c.option(option))
+            .map(|s| s.to_string())
+            .or_else(|| env::var(var).ok())
+            .filter(|s| !s.trim().is_empty())
+    };
+
+    Some(ProxySettings {
+        url: setting("--proxy", "SCAVENGER_PROXY")?,
+        user: setting("--proxy-user", "SCAVENGER_PROXY_USER"),
+        password: setting("--proxy-password", "SCAVENGER_PROXY_PASSWORD"),
+    })
+}
+
+fn build_proxy(settings: &ProxySettings) -> reqwest::Result<reqwest::Proxy> {
+    let proxy = reqwest::Proxy::all(&settings.url)?;
+    Ok(match &settings.user {
+        Some(user) => proxy.basic_auth(user, settings.password.as_deref().unwrap_or("")),
+        None => proxy,
+    })
+}
+
+fn tls_from(cli: &CliArgs) -> Result<TlsSettings, String> {
+    let extra_roots = match cli.option("--ca-cert") {
+        Some(path) => {
+            let pem = fs::read(path).map_err(|e| format!("Cannot read CA certificate {}: {}", path, e))?;
+            let certs = Certificate::from_pem_bundle(&pem)
+                .map_err(|e| format!("Invalid CA certificate {}: {}", path, e))?;
+            if certs.is_empty() {
+                return Err(format!("No PEM certificates found in {}", path));
+            }
+            certs
+        }
+        None => Vec::new(),
+    };
+
+    let min_version = match cli.option("--tls-min") {
+        Some("1.2") => Some(Version::TLS_1_2),
+        Some("1.3") => Some(Version::TLS_1_3),
+        Some(other) => return Err(format!("Invalid --tls-min '{}' (expected 1.2 or 1.3)", other)),
+        None => None,
+    };
+
+    Ok(TlsSettings {
+        extra_roots,
+        min_version,
+        no_system_proxy: cli.switch("--no-system-proxy"),
+    })
+}
+
+/// Apply network options from the command line / config file (falls back to environment variables)
+/// Must run before the first client is built; later calls are ignored
+pub fn configure(cli: &CliArgs) -> Result<(), String> {
+    let settings = proxy_from(Some(cli));
+    if let Some(settings) = &settings {
+        // Validate now so a typo fails at startup instead of on the first request
+        build_proxy(settings).map_err(|e| format!("Invalid proxy '{}': {}", settings.url, e))?;
+    }
+    let tls = tls_from(cli)?;
+    let timeouts = timeouts_from(cli)?;
+    let auth = auth_from(cli)?;
+    let base = api_base_from(Some(cli))?;
+    let _ = API_BASE.set(base);
+    let _ = PROXY.set(settings);
+    let _ = AUTH.set(auth);
+    let _ = TLS.set(tls);
+    let _ = TIMEOUTS.set(timeouts);
+    Ok(())
+}
+
+/// Short description of the proxy in use, for the startup log (credentials omitted)
+pub fn proxy_description() -> Option<String> {
+    PROXY.get_or_init(|| proxy_from(None)).as_ref().map(|p| {
+        let (scheme, rest) = p.url.split_once("://").unwrap_or(("http", &p.url));
+        let host = rest.rsplit('@').next().unwrap_or(rest);
+        format!("{}://{}", scheme, host)
+    })
+}
+
+/// Short description of non-default TLS settings, for the startup log
+pub fn tls_description() -> Option<String> {
+    let tls = TLS.get_or_init(TlsSettings::default);
+    let mut parts = Vec::new();
+    if !tls.extra_roots.is_empty() {
+        parts.push(format!("{} extra CA certificate(s)", tls.extra_roots.len()));
+    }
+    if let Some(version) = tls.min_version {
+        parts.push(format!("minimum {}", if version == Version::TLS_1_3 { "TLS 1.3" } else { "TLS 1.2" }));
+    }
+    if tls.no_system_proxy {
+        parts.push("system proxy ignored".to_string());
+    }
+    (!parts.is_empty()).then(|| parts.join(", "))
+}
+
+/// Client builder with the shared network settings applied
+/// Long-lived requests (the challenge stream) may lift the request timeout; the connect timeout still applies
+pub fn client_builder() -> ClientBuilder {
+    let tls = TLS.get_or_init(TlsSettings::default);
+    let timeouts = TIMEOUTS.get_or_init(default_timeouts);
+    let mut builder = Client::builder()
+        .gzip(true)
+        .connect_timeout(timeouts.connect)
+        .timeout(timeouts.request);
+    for cert in &tls.extra_roots {
+        builder = builder.add_root_certificate(cert.clone());
+    }
+    if let Some(version) = tls.min_version {
+        builder = builder.min_tls_version(version);
+    }
+    // Must come before the explicit proxy, which is still honored
+    if tls.no_system_proxy {
+        builder = builder.no_proxy();
+    }
+
+    match PROXY.get_or_init(|| proxy_from(None)) {
+        // Validated in configure(); an env-only proxy that fails to parse falls back to system settings
+        Some(settings) => match build_proxy(settings) {
+            Ok(proxy) => builder.proxy(proxy),
+            Err(_) => builder,
+        },
+        None => builder,
+    }
+}
+
+/// Client with the shared network settings applied
+pub fn client() -> reqwest::Result<Client> {
+    client_builder().build()
+}
+
+/// Start a request to the Scavenger API, with the configured credentials attached
+pub fn api_request(client: &Client, method: Method, url: &str) -> RequestBuilder {
+    let auth = AUTH.get_or_init(ApiAuth::default);
+    auth.headers
+        .iter()
+        .fold(client.request(method, url), |request, (name, value)| request.header(name, value))
+}
+
+/// GET an API endpoint
+pub fn get(client: &Client, url: &str) -> RequestBuilder {
+    api_request(client, Method::GET, url)
+}
+
+/// POST to an API endpoint
+pub fn post(client: &Client, url: &str) -> RequestBuilder {
+    api_request(client, Method::POST, url)
+}
+
+/// HEAD an API endpoint
+pub fn head(client: &Client, url: &str) -> RequestBuilder {
+    api_request(client, Method::HEAD, url)
+}
+
+/// Send an API request; method, URL, status and time are logged at debug level (`--verbose`)
+pub fn send(request: RequestBuilder) -> reqwest::Result<Response> {
+    let described = request.try_clone().and_then(|r| r.build().ok()).map(|r| format!("{} {}", r.method(), r.url()));
+    let started = Instant::now();
+    let result = request.send();
+    let target = described.as_deref().unwrap_or("API request");
+    let millis = started.elapsed().as_millis() as u64;
+    match &result {
+        Ok(response) => tracing::debug!(status = response.status().as_u16(), millis, "↔️  {} → {} in {}ms", target, response.status(), millis),
+        Err(e) => tracing::debug!(millis, "↔️  {} failed after {}ms: {}", target, millis, e),
+    }
+    result
+}
+
+/// Whether API credentials are configured, for the startup log
+pub fn has_api_credentials() -> bool {
+    AUTH.get().is_some_and(|auth| !auth.headers.is_empty())
+}
@@ -0,0 +1,197 @@
+//! Small HTTP/1.1 server shared by the local endpoints (health, wallet control, coordinator, mock API)
+//!
+//! Each connection gets its own thread and must deliver its request within `REQUEST_DEADLINE`
+//! overall, so a slow or idle client can't hold up the others. One request per connection,
+//! JSON in and out. Before the handler runs, every request is checked the same way:
+//!
+//! - bodies over the server's limit are refused (413)
+//! - requests from browsers (an `Origin` header) are refused (403), so a web page can't reach
+//!   a local endpoint across sites
+//! - with a token, `Authorization: Bearer <token>` is required (401)
+//! - POSTs and requests with a body must be `application/json` (415)
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Time a client gets to send its whole request, and again to take the response
+const REQUEST_DEADLINE: Duration = Duration::from_secs(10);
+/// Largest request line plus headers read
+const MAX_HEAD_BYTES: u64 = 16 * 1024;
+/// Connections served at once; more are answered 503 straight away
+const MAX_CONNECTIONS: usize = 32;
+
+/// How one endpoint listens
+pub struct Settings {
+    pub name: &'static str,    // Thread name, and the label in warnings
+    pub max_body: usize,       // Largest request body read
+    pub token: Option<String>, // Bearer token every request needs
+}
+
+pub struct Request {
+    pub method: String,
+    pub path: String, // Without the query string
+    pub body: Vec<u8>,
+    pub peer: IpAddr,
+}
+
+impl Request {
+    /// Path segments, `/jobs/3/result` gives `["jobs", "3", "result"]`
+    pub fn segments(&self) -> Vec<&str> {
+        self.path.trim_matches('/').split('/').collect()
+    }
+}
+
+pub struct Reply {
+    pub status: u16,
+    pub headers: Vec<(&'static str, String)>,
+    pub body: String,
+}
+
+pub fn reply(status: u16, body: serde_json::Value) -> Reply {
+    Reply { status, headers: Vec::new(), body: body.to_string() }
+}
+
+pub fn error(status: u16, message: &str) -> Reply {
+    reply(status, serde_json::json!({ "error": message }))
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        415 => "Unsupported Media Type",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    }
+}
+
+/// Serve `listener` on a thread named after the endpoint, `handler` answering each request
+pub fn serve<H>(listener: TcpListener, settings: Settings, handler: H) -> io::Result<()>
+where
+    H: Fn(&Request) -> Reply + Send + Sync + 'static,
+{
+    let settings = Arc::new(settings);
+    let handler = Arc::new(handler);
+    let open = Arc::new(AtomicUsize::new(0));
+    thread::Builder::new().name(settings.name.to_string()).spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if open.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                open.fetch_sub(1, Ordering::SeqCst);
+                let _ = write_reply(stream, false, error(503, "too many connections"));
+                continue;
+            }
+            let (settings, handler, served) = (Arc::clone(&settings), Arc::clone(&handler), Arc::clone(&open));
+            let spawned = thread::Builder::new().name(settings.name.to_string()).spawn(move || {
+                if let Err(e) = serve_connection(stream, &settings, handler.as_ref()) {
+                    tracing::warn!(error = %e, "⚠️  {} request failed: {}", settings.name, e);
+                }
+                served.fetch_sub(1, Ordering::SeqCst);
+            });
+            if spawned.is_err() {
+                open.fetch_sub(1, Ordering::SeqCst);
+            }
+        }
+    })?;
+    Ok(())
+}
+
+/// Reads from the stream until the deadline, however the time is split between reads
+struct Deadline {
+    stream: TcpStream,
+    until: Instant,
+}
+
+impl Read for Deadline {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let left = self.until.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "request not received in time"));
+        }
+        self.stream.set_read_timeout(Some(left))?;
+        self.stream.read(buf)
+    }
+}
+
+fn serve_connection<H: Fn(&Request) -> Reply>(stream: TcpStream, settings: &Settings, handler: &H) -> io::Result<()> {
+    let peer = stream.peer_addr().map(|a| a.ip()).unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+    let mut reader = BufReader::new(Deadline { stream: stream.try_clone()?, until: Instant::now() + REQUEST_DEADLINE });
+
+    let mut head = (&mut reader).take(MAX_HEAD_BYTES);
+    let mut request_line = String::new();
+    head.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("").to_string();
+    let path = parts.next().unwrap_or("/").split('?').next().unwrap_or("/").to_string();
+
+    // Headers: Content-Length, Authorization, Content-Type and Origin
+    let (mut content_length, mut authorization, mut content_type, mut origin) = (0usize, None, None, false);
+    loop {
+        let mut line = String::new();
+        if head.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            let name = name.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            } else if name.eq_ignore_ascii_case("authorization") {
+                authorization = Some(value.trim().to_string());
+            } else if name.eq_ignore_ascii_case("content-type") {
+                content_type = value.split(';').next().map(|t| t.trim().to_ascii_lowercase());
+            } else if name.eq_ignore_ascii_case("origin") {
+                origin = true;
+            }
+        }
+    }
+    let head_read = head.limit() > 0;
+
+    let reply = if !head_read {
+        error(413, "request headers too large")
+    } else if content_length > settings.max_body {
+        error(413, "request body too large")
+    } else if origin {
+        error(403, "requests from web pages are refused")
+    } else if settings.token.as_deref().is_some_and(|token| authorization.as_deref().and_then(|a| a.strip_prefix("Bearer ")) != Some(token)) {
+        error(401, "missing or wrong bearer token")
+    } else if (method == "POST" || content_length > 0) && content_type.as_deref() != Some("application/json") {
+        error(415, "the body must be sent as application/json")
+    } else {
+        let mut body = vec![0u8; content_length];
+        reader.read_exact(&mut body)?;
+        handler(&Request { method: method.clone(), path, body, peer })
+    };
+    write_reply(stream, method == "HEAD", reply)
+}
+
+fn write_reply(mut stream: TcpStream, head_only: bool, reply: Reply) -> io::Result<()> {
+    stream.set_write_timeout(Some(REQUEST_DEADLINE))?;
+    let mut response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nDate: {}\r\nConnection: close\r\n",
+        reply.status,
+        reason(reply.status),
+        reply.body.len(),
+        chrono::Utc::now().format("%a, %d %b %Y %H:%M:%S GMT")
+    );
+    for (name, value) in &reply.headers {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str("\r\n");
+    if !head_only {
+        response.push_str(&reply.body);
+    }
+    stream.write_all(response.as_bytes())?;
+    stream.flush()
+}
@@ -0,0 +1,142 @@
+//! User idle detection for idle-only mining
+//!
+//! A background monitor samples how long the user has been idle and flips
+//! [`user_is_active`], which the pause logic in the miner consults.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// How often the idle time is sampled
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// True while the user has been active more recently than the configured idle threshold
+static USER_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Whether idle-only mode currently wants mining paused
+pub fn user_is_active() -> bool {
+    USER_ACTIVE.load(Ordering::Relaxed)
+}
+
+/// Start the monitor thread; mining is paused until the user has been idle for `min_idle`
+pub fn start_monitor(min_idle: Duration) {
+    // Assume the user is present until the first sample says otherwise
+    USER_ACTIVE.store(true, Ordering::Relaxed);
+
+    thread::Builder::new()
+        .name("idle-monitor".to_string())
+        .spawn(move || loop {
+            let active = match user_idle_time() {
+                Some(idle) => idle < min_idle,
+                None => false, // Can't tell (headless, no session) - treat as idle
+            };
+            USER_ACTIVE.store(active, Ordering::Relaxed);
+            thread::sleep(SAMPLE_INTERVAL);
+        })
+        .expect("failed to spawn idle monitor thread");
+}
+
+// Windows: time since the last keyboard/mouse input of the interactive session
+#[cfg(windows)]
+fn user_idle_time() -> Option<Duration> {
+    #[repr(C)]
+    #[allow(non_snake_case)] // Windows API requires exact field names
+    struct LASTINPUTINFO {
+        cbSize: u32,
+        dwTime: u32,
+    }
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetLastInputInfo(plii: *mut LASTINPUTINFO) -> i32;
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetTickCount() -> u32;
+    }
+
+    unsafe {
+        let mut info = LASTINPUTINFO {
+            cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+            dwTime: 0,
+        };
+        if GetLastInputInfo(&mut info) == 0 {
+            return None;
+        }
+        // Both are 32-bit tick counts, wrapping_sub handles the 49.7-day rollover
+        let idle_ms = GetTickCount().wrapping_sub(info.dwTime);
+        Some(Duration::from_millis(idle_ms as u64))
+    }
+}
+
+// macOS: HIDIdleTime (nanoseconds) reported by the IOHIDSystem registry entry
+#[cfg(target_os = "macos")]
+fn user_idle_time() -> Option<Duration> {
+    let output = std::process::Command::new("ioreg")
+        .args(["-c", "IOHIDSystem", "-d", "4"])
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+
+    text.lines()
+        .find(|line| line.contains("\"HIDIdleTime\""))
+        .and_then(|line| line.rsplit('=').next())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_nanos)
+}
+
+// Linux: logind IdleHint/IdleSinceHint across graphical sessions (the most recently active one wins)
+// SSH/tty sessions are skipped: they never report idle and would block mining on servers
+#[cfg(all(unix, not(target_os = "macos")))]
+fn user_idle_time() -> Option<Duration> {
+    use std::process::Command;
+
+    let sessions = Command::new("loginctl")
+        .args(["list-sessions", "--no-legend"])
+        .output()
+        .ok()?;
+    let sessions = String::from_utf8_lossy(&sessions.stdout);
+
+    let now_micros = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?
+        .as_micros() as u64;
+
+    let mut min_idle: Option<Duration> = None;
+    for session_id in sessions.lines().filter_map(|line| line.split_whitespace().next()) {
+        let Ok(output) = Command::new("loginctl")
+            .args(["show-session", session_id, "-p", "Type", "-p", "IdleHint", "-p", "IdleSinceHint"])
+            .output()
+        else {
+            continue;
+        };
+        let text = String::from_utf8_lossy(&output.stdout);
+
+        let mut graphical = false;
+        let mut idle_hint = false;
+        let mut idle_since = 0u64;
+        for line in text.lines() {
+            match line.split_once('=') {
+                Some(("Type", value)) => graphical = matches!(value.trim(), "x11" | "wayland" | "mir"),
+                Some(("IdleHint", value)) => idle_hint = value.trim() == "yes",
+                Some(("IdleSinceHint", value)) => idle_since = value.trim().parse().unwrap_or(0),
+                _ => {}
+            }
+        }
+
+        if !graphical {
+            continue;
+        }
+
+        // A session that isn't idle means the user is here right now
+        let idle = if idle_hint && idle_since > 0 {
+            Duration::from_micros(now_micros.saturating_sub(idle_since))
+        } else {
+            Duration::ZERO
+        };
+        min_idle = Some(min_idle.map_or(idle, |m| m.min(idle)));
+    }
+
+    min_idle
+}
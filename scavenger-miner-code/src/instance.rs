@@ -0,0 +1,140 @@
+//! Instance lock: one miner per data directory
+//!
+//! Two miners writing the same solution records, stats and logs corrupt each other's files and
+//! mine the same tasks twice. At startup the miner takes an exclusive OS lock (flock on Unix,
+//! LockFileEx on Windows) on `miner.lock` in the data directory, and in the solutions folder too
+//! when that lives elsewhere and isn't shared on purpose (`--shared-solutions`, S3, SQLite).
+//! The OS drops the lock when the process ends, however it ends, so a crash never blocks the next
+//! start; the holder written into the file is only there to tell who has it. A network
+//! filesystem can keep a lock after its client died, which is what `--force-lock` is for.
+
+use crate::{paths, store};
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, TryLockError};
+use std::io::{Read, Seek, Write};
+use std::path::Path;
+
+pub const LOCK_FILE: &str = "miner.lock";
+
+/// Who holds a lock, as written into the lock file
+#[derive(Serialize, Deserialize)]
+struct Holder {
+    pid: u32,
+    host: String,
+    started: String, // RFC 3339
+}
+
+/// The lock files held; dropping them releases the locks
+pub struct InstanceLock {
+    _files: Vec<File>,
+}
+
+/// Lock the data directory (and a separate solutions folder), or explain who holds it and exit.
+/// With `force`, a held lock is reported and mining goes ahead without it.
+pub fn acquire_or_exit(force: bool) -> InstanceLock {
+    let mut dirs = vec![paths::data_dir()];
+    let solutions = paths::solutions_dir();
+    if store::is_json() && !store::is_shared() && !solutions.starts_with(&dirs[0]) {
+        dirs.push(solutions);
+    }
+
+    let mut files = Vec::new();
+    for dir in dirs {
+        let path = dir.join(LOCK_FILE);
+        match lock(&path) {
+            Ok(file) => files.extend(file),
+            Err(holder) if force => {
+                let holder = holder.as_ref().map_or("another miner".to_string(), describe);
+                tracing::warn!("⚠️  {} is locked by {}; starting anyway (--force-lock)", dir.display(), holder);
+            }
+            Err(holder) => {
+                report_held(&dir, holder.as_ref());
+                std::process::exit(1);
+            }
+        }
+    }
+    InstanceLock { _files: files }
+}
+
+/// Take the lock, writing ourselves in as the holder; Err with the current holder if it's taken.
+/// A lock file that can't be opened or locked (read-only folder, filesystem without locks) is
+/// logged and let through as Ok(None), since refusing to mine there would be worse.
+fn lock(path: &Path) -> Result<Option<File>, Option<Holder>> {
+    let mut file = match fs::OpenOptions::new().create(true).truncate(false).read(true).write(true).open(path) {
+        Ok(file) => file,
+        Err(e) => {
+            tracing::warn!(error = %e, "⚠️  Cannot open {}: {}; not guarding against a second miner", path.display(), e);
+            return Ok(None);
+        }
+    };
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => {
+            let mut content = String::new();
+            let _ = file.read_to_string(&mut content);
+            return Err(serde_json::from_str(&content).ok());
+        }
+        Err(TryLockError::Error(e)) => {
+            tracing::warn!(error = %e, "⚠️  Cannot lock {}: {}; not guarding against a second miner", path.display(), e);
+            return Ok(None);
+        }
+    }
+
+    let holder = Holder { pid: std::process::id(), host: this_host(), started: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true) };
+    let written = file
+        .set_len(0)
+        .and_then(|_| file.rewind())
+        .and_then(|_| file.write_all(serde_json::to_string(&holder).unwrap_or_default().as_bytes()));
+    if let Err(e) = written {
+        tracing::warn!(error = %e, "⚠️  Cannot write {}: {}", path.display(), e);
+    }
+    Ok(Some(file))
+}
+
+fn this_host() -> String {
+    hostname::get().ok().and_then(|h| h.into_string().ok()).unwrap_or_else(|| "unknown".to_string())
+}
+
+fn describe(holder: &Holder) -> String {
+    format!("pid {} on {}, started {}", holder.pid, holder.host, holder.started)
+}
+
+fn report_held(dir: &Path, holder: Option<&Holder>) {
+    let advice = match holder {
+        Some(holder) if holder.host != this_host() => "it runs on another machine sharing this folder: give each rig its own \
+            --data-dir (with --shared-solutions to share a solutions folder)"
+            .to_string(),
+        Some(holder) if process_alive(holder.pid) == Some(false) => format!(
+            "pid {} isn't running any more, so the lock is stale (network filesystems can keep a lock after a crash)",
+            holder.pid
+        ),
+        Some(holder) => format!("stop it first (pid {}), or give this one its own --data-dir", holder.pid),
+        None => "stop it first, or give this one its own --data-dir".to_string(),
+    };
+    tracing::error!(
+        "❌ Another miner is already using {}{}: {}. Pass --force-lock to start anyway.",
+        dir.display(),
+        holder.map_or(String::new(), |h| format!(" ({})", describe(h))),
+        advice,
+    );
+}
+
+/// Whether a process with this pid exists on this machine (None if we can't tell)
+#[cfg(unix)]
+fn process_alive(pid: u32) -> Option<bool> {
+    extern "C" {
+        fn kill(pid: i32, sig: i32) -> i32;
+    }
+    const EPERM: i32 = 1;
+    // Signal 0 only checks that the process exists (EPERM: it does, but isn't ours)
+    if unsafe { kill(pid as i32, 0) } == 0 {
+        return Some(true);
+    }
+    Some(std::io::Error::last_os_error().raw_os_error() == Some(EPERM))
+}
+
+#[cfg(not(unix))]
+fn process_alive(_pid: u32) -> Option<bool> {
+    None
+}
@@ -0,0 +1,470 @@
+//! Structured logging (`--log-format`, `--log-json`)
+//!
+//! Log lines are `tracing` events. The mining loop opens a `task` span carrying `challenge_id`
+//! and `wallet`, each hashing thread a `thread` span under it, and events such as a found
+//! solution add their own fields (`nonce`, `hashes`, ...). A record is the event's message plus
+//! the fields of every span it happened in.
+//!
+//! The subscriber is a `tracing-subscriber` registry with one `fmt` layer per output: the console
+//! (pretty `[time] message` lines or JSON lines, or the `--tui` dashboard's event panel),
+//! `logs/mining.log` (always the pretty form) and optionally a JSON-lines file. Each layer writes
+//! whole lines to a `Sink`; `configure` swaps the layers through a reload handle. Events from
+//! other crates are ignored.
+//!
+//! While the console shows a progress line (progress.rs), the periodic hashing lines logged to
+//! the `PROGRESS` target only go to the files.
+//!
+//! `-q`/`--quiet` and `-v`/`--verbose` set the level for every sink alike: quiet keeps errors and
+//! the events logged to the `SOLUTIONS` target (solutions found and accepted), normal is
+//! everything at `INFO` and above, verbose adds `DEBUG` (each API request, see `http::send`).
+//!
+//! Both files rotate (`--log-max-size-mb`, `--log-retention-days`): when the UTC day changes or
+//! the file reaches the size limit it is renamed to `mining-<day>.log` (`.1`, `.2`, ... if that
+//! day already has one) and gzipped in the background. Rotated files older than the retention
+//! period are deleted.
+
+use crate::{get_timestamp, paths};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Mutex, OnceLock};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Metadata, Subscriber};
+use tracing_subscriber::filter::{filter_fn, FilterFn};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::time::FormatTime;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, MakeWriter};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{reload, Layer, Registry};
+
+/// Where formatted log lines end up
+pub trait Sink: Send + Sync {
+    fn write(&self, line: &str);
+}
+
+/// Console output format (`--log-format`)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConsoleFormat {
+    Pretty,
+    Json,
+}
+
+impl ConsoleFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "pretty" | "text" => Some(ConsoleFormat::Pretty),
+            "json" => Some(ConsoleFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Target of the events kept in quiet mode: solutions found and accepted
+pub const SOLUTIONS: &str = concat!(env!("CARGO_CRATE_NAME"), "::solutions");
+
+/// Target of the periodic hashing progress lines, which the console leaves to the progress
+/// line while one is shown (see progress.rs)
+pub const PROGRESS: &str = concat!(env!("CARGO_CRATE_NAME"), "::progress");
+
+/// How much gets logged (`-q`, `-v`)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose,
+}
+
+impl Verbosity {
+    /// `--verbose` wins over `--quiet`
+    pub fn from_switches(quiet: bool, verbose: bool) -> Self {
+        match (quiet, verbose) {
+            (_, true) => Verbosity::Verbose,
+            (true, false) => Verbosity::Quiet,
+            (false, false) => Verbosity::Normal,
+        }
+    }
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+pub fn verbosity() -> Verbosity {
+    match VERBOSITY.load(Ordering::Relaxed) {
+        0 => Verbosity::Quiet,
+        2 => Verbosity::Verbose,
+        _ => Verbosity::Normal,
+    }
+}
+
+pub fn set_verbosity(verbosity: Verbosity) {
+    VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+    // Callsites cache whether they are enabled
+    tracing::callsite::rebuild_interest_cache();
+}
+
+/// Standard output
+pub struct Console;
+
+impl Sink for Console {
+    fn write(&self, line: &str) {
+        if crate::progress::active() {
+            crate::progress::print_above(line);
+            return;
+        }
+        let mut stdout = std::io::stdout().lock();
+        let _ = stdout.write_all(line.as_bytes());
+        let _ = stdout.flush();
+    }
+}
+
+/// No console (`--daemon`, where mining.log already has every line)
+pub struct Discard;
+
+impl Sink for Discard {
+    fn write(&self, _line: &str) {}
+}
+
+pub const DEFAULT_MAX_SIZE_MB: u64 = 100;
+pub const DEFAULT_RETENTION_DAYS: u64 = 14;
+
+/// When log files rotate and how long rotated files are kept
+#[derive(Clone, Copy, Debug)]
+pub struct Rotation {
+    pub max_bytes: Option<u64>,     // None: only daily
+    pub retention_days: Option<u64>, // None: keep everything
+}
+
+impl Default for Rotation {
+    fn default() -> Self {
+        Rotation { max_bytes: Some(DEFAULT_MAX_SIZE_MB * 1024 * 1024), retention_days: Some(DEFAULT_RETENTION_DAYS) }
+    }
+}
+
+impl Rotation {
+    /// From `--log-max-size-mb` and `--log-retention-days`; 0 turns either off
+    pub fn new(max_size_mb: u64, retention_days: u64) -> Self {
+        Rotation {
+            max_bytes: (max_size_mb > 0).then(|| max_size_mb * 1024 * 1024),
+            retention_days: (retention_days > 0).then_some(retention_days),
+        }
+    }
+}
+
+/// What is known about the file currently written to
+struct OpenFile {
+    path: PathBuf,
+    day: String, // UTC day of its first line (YYYY-MM-DD)
+    size: u64,
+}
+
+/// A log file that rotates by day and size
+struct RotatingFile {
+    rotation: Rotation,
+    open: Mutex<Option<OpenFile>>,
+}
+
+impl RotatingFile {
+    fn new(rotation: Rotation) -> Self {
+        RotatingFile { rotation, open: Mutex::new(None) }
+    }
+
+    /// Append `line`, logged on `day`, rotating `path` first if needed
+    fn append(&self, path: &Path, day: &str, line: &str) {
+        let mut guard = self.open.lock().unwrap();
+        let current = match guard.as_mut().filter(|c| c.path == path) {
+            Some(current) => current,
+            None => {
+                // First line (or the logs directory moved): pick up an existing file, tidy up old ones
+                let existing = fs::metadata(path).ok();
+                let current = guard.insert(OpenFile {
+                    path: path.to_path_buf(),
+                    day: existing
+                        .as_ref()
+                        .and_then(|m| m.modified().ok())
+                        .map_or_else(|| day.to_string(), |t| DateTime::<Utc>::from(t).format("%Y-%m-%d").to_string()),
+                    size: existing.map_or(0, |m| m.len()),
+                });
+                self.tidy_up(path, day);
+                current
+            }
+        };
+
+        let too_big = self.rotation.max_bytes.is_some_and(|max| current.size > 0 && current.size + line.len() as u64 > max);
+        if current.size > 0 && (current.day != day || too_big) {
+            if let Some(rotated) = rotated_name(path, &current.day) {
+                if fs::rename(path, &rotated).is_ok() {
+                    current.size = 0;
+                    self.tidy_up(path, day);
+                }
+            }
+        }
+
+        if let Ok(mut file) = fs::OpenOptions::new().create(true).append(true).open(path) {
+            if file.write_all(line.as_bytes()).is_ok() {
+                current.size += line.len() as u64;
+            }
+        }
+        current.day = day.to_string();
+    }
+
+    /// Gzip rotated files and delete expired ones, in the background
+    fn tidy_up(&self, path: &Path, today: &str) {
+        let path = path.to_path_buf();
+        let cutoff = self.rotation.retention_days.and_then(|days| {
+            NaiveDate::parse_from_str(today, "%Y-%m-%d").ok().map(|d| d - Duration::days(days as i64))
+        });
+        let _ = std::thread::Builder::new().name("log-rotation".to_string()).spawn(move || compress_and_prune(&path, cutoff));
+    }
+}
+
+/// `dir/mining-<day>.log`, or with `.1`, `.2`, ... before the extension if taken
+fn rotated_name(path: &Path, day: &str) -> Option<PathBuf> {
+    let (stem, ext) = split_name(path)?;
+    (0..1000).map(|n| {
+        let suffix = if n == 0 { String::new() } else { format!(".{}", n) };
+        path.with_file_name(format!("{}-{}{}{}", stem, day, suffix, ext))
+    })
+    .find(|candidate| !candidate.exists() && !gz_name(candidate).exists())
+}
+
+/// File stem and extension (with its dot, possibly empty)
+fn split_name(path: &Path) -> Option<(String, String)> {
+    let stem = path.file_stem()?.to_string_lossy().into_owned();
+    let ext = path.extension().map_or_else(String::new, |e| format!(".{}", e.to_string_lossy()));
+    Some((stem, ext))
+}
+
+fn gz_name(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".gz");
+    PathBuf::from(name)
+}
+
+/// Serializes background tidy-ups so two never compress the same file
+static TIDY_UP: Mutex<()> = Mutex::new(());
+
+/// Rotated files of `path`: `<stem>-<day>[.n]<ext>[.gz]`, with their day
+fn rotated_files(path: &Path) -> Vec<(PathBuf, NaiveDate)> {
+    let (Some((stem, _)), Some(dir)) = (split_name(path), path.parent()) else {
+        return Vec::new();
+    };
+    let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+    let prefix = format!("{}-", stem);
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let day = name.strip_prefix(&prefix)?.get(..10)?;
+            let day = NaiveDate::parse_from_str(day, "%Y-%m-%d").ok()?;
+            Some((entry.path(), day))
+        })
+        .collect()
+}
+
+fn compress_and_prune(path: &Path, cutoff: Option<NaiveDate>) {
+    let _guard = TIDY_UP.lock().unwrap();
+    for (file, day) in rotated_files(path) {
+        if cutoff.is_some_and(|cutoff| day < cutoff) {
+            let _ = fs::remove_file(&file);
+        } else if file.extension().is_none_or(|e| e != "gz") {
+            if let Err(e) = gzip(&file) {
+                eprintln!("⚠️  Failed to compress {}: {}", file.display(), e);
+            }
+        }
+    }
+}
+
+/// Replace `file` with `file.gz`
+fn gzip(file: &Path) -> std::io::Result<()> {
+    let target = gz_name(file);
+    let mut encoder = GzEncoder::new(fs::File::create(&target)?, Compression::default());
+    std::io::copy(&mut fs::File::open(file)?, &mut encoder)?;
+    encoder.finish()?.sync_all()?;
+    fs::remove_file(file)
+}
+
+/// UTC day of a log line (YYYY-MM-DD)
+fn today() -> String {
+    get_timestamp().get(..10).unwrap_or_default().to_string()
+}
+
+/// `mining.log` in the logs directory (looked up per line, it is configurable)
+pub struct MiningLog(RotatingFile);
+
+impl Sink for MiningLog {
+    fn write(&self, line: &str) {
+        self.0.append(&paths::logs_dir().join("mining.log"), &today(), line);
+    }
+}
+
+/// A JSON-lines file (`--log-json`)
+pub struct JsonFile(PathBuf, RotatingFile);
+
+impl Sink for JsonFile {
+    fn write(&self, line: &str) {
+        self.1.append(&self.0, &today(), line);
+    }
+}
+
+/// Hands each formatted event to a sink in one piece
+struct Lines {
+    sink: Box<dyn Sink>,
+    console: bool, // Leaves the `PROGRESS` lines to the progress line while one is shown
+}
+
+impl<'a> MakeWriter<'a> for Lines {
+    type Writer = Line<'a>;
+
+    fn make_writer(&'a self) -> Line<'a> {
+        Line { sink: self.sink.as_ref(), keep: true, buffer: Vec::new() }
+    }
+
+    fn make_writer_for(&'a self, metadata: &Metadata<'_>) -> Line<'a> {
+        let keep = !(self.console && metadata.target() == PROGRESS && crate::progress::active());
+        Line { sink: self.sink.as_ref(), keep, buffer: Vec::new() }
+    }
+}
+
+/// One event being formatted; written to the sink when dropped
+struct Line<'a> {
+    sink: &'a dyn Sink,
+    keep: bool,
+    buffer: Vec<u8>,
+}
+
+impl Write for Line<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for Line<'_> {
+    fn drop(&mut self) {
+        if self.keep && !self.buffer.is_empty() {
+            self.sink.write(&String::from_utf8_lossy(&self.buffer));
+        }
+    }
+}
+
+/// The miner's own timestamps (UTC, whole seconds, corrected clock)
+struct Timestamp;
+
+impl FormatTime for Timestamp {
+    fn format_time(&self, w: &mut Writer<'_>) -> fmt::Result {
+        w.write_str(&get_timestamp())
+    }
+}
+
+/// `[time] message`, the format the miner always printed; fields are left to the JSON form
+struct Pretty;
+
+impl<S, N> FormatEvent<S, N> for Pretty
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, _ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        let mut message = Message::default();
+        event.record(&mut message);
+        writeln!(writer, "[{}] {}", get_timestamp(), message.0)
+    }
+}
+
+/// Picks the `message` field out of an event
+#[derive(Default)]
+struct Message(String);
+
+impl Visit for Message {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.0 = format!("{:?}", value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.0 = value.to_string();
+        }
+    }
+}
+
+type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+fn pretty_layer(lines: Lines) -> BoxedLayer {
+    tracing_subscriber::fmt::layer().event_format(Pretty).with_writer(lines).boxed()
+}
+
+/// One JSON object per line: timestamp, level, message and the event's fields, then the
+/// fields of the spans it happened in under `spans` (outermost first)
+fn json_layer(lines: Lines) -> BoxedLayer {
+    tracing_subscriber::fmt::layer()
+        .json()
+        .flatten_event(true)
+        .with_current_span(false)
+        .with_span_list(true)
+        .with_target(false)
+        .with_timer(Timestamp)
+        .with_writer(lines)
+        .boxed()
+}
+
+/// Events from this crate at the level `verbosity()` allows
+fn enabled(metadata: &Metadata<'_>) -> bool {
+    if !metadata.target().starts_with(env!("CARGO_CRATE_NAME")) {
+        return false;
+    }
+    match verbosity() {
+        Verbosity::Quiet => *metadata.level() <= Level::ERROR || metadata.target() == SOLUTIONS,
+        Verbosity::Normal => *metadata.level() <= Level::INFO,
+        Verbosity::Verbose => *metadata.level() <= Level::DEBUG,
+    }
+}
+
+/// Swaps the layers when `configure` is called again
+static LAYERS: OnceLock<reload::Handle<Vec<BoxedLayer>, Registry>> = OnceLock::new();
+
+/// Install the subscriber with the default layers (pretty console and `mining.log`)
+/// Called once at startup, before anything logs
+pub fn init() {
+    let (layers, handle) = reload::Layer::new(Vec::new());
+    let filter: FilterFn<fn(&Metadata<'_>) -> bool> = filter_fn(enabled);
+    if tracing::subscriber::set_global_default(Registry::default().with(layers).with(filter)).is_ok() {
+        let _ = LAYERS.set(handle);
+    }
+    configure(Box::new(Console), ConsoleFormat::Pretty, None, Rotation::default());
+}
+
+/// Replace the layers: `console` (`Console` or the dashboard's event list) in `format`,
+/// `mining.log` and the JSON file
+pub fn configure(console: Box<dyn Sink>, format: ConsoleFormat, json_file: Option<PathBuf>, rotation: Rotation) {
+    let console = Lines { sink: console, console: true };
+    let mut layers = vec![
+        match format {
+            ConsoleFormat::Pretty => pretty_layer(console),
+            ConsoleFormat::Json => json_layer(console),
+        },
+        pretty_layer(Lines { sink: Box::new(MiningLog(RotatingFile::new(rotation))), console: false }),
+    ];
+    if let Some(path) = json_file {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            let _ = fs::create_dir_all(parent);
+        }
+        layers.push(json_layer(Lines { sink: Box::new(JsonFile(path, RotatingFile::new(rotation))), console: false }));
+    }
+    if let Some(handle) = LAYERS.get() {
+        let _ = handle.reload(layers);
+    }
+}
@@ -1,6 +1,69 @@
-use ashmaize::{Rom, RomGenerationType, hash};
+mod address;
+mod archive;
+mod attempts;
+mod backend;
+mod backup;
+mod cgroup;
+mod challenge_filter;
+mod control;
+mod coordinator;
+mod credited;
+mod daemon;
+mod ctl;
+mod dashboard;
+mod desktop;
+mod difficulty;
+mod error;
+mod export;
+mod groups;
+mod grpc;
+mod hashrate_history;
+mod health;
+mod http;
+mod http_server;
+mod idle;
+mod instance;
+mod logging;
+mod migrate;
+mod mine;
+#[cfg(feature = "mock-api")]
+mod mock_api;
+mod notify;
+mod paths;
+mod pool;
+mod progress;
+mod qos;
+mod quota;
+mod ranking;
+mod receipt;
+mod reconcile;
+mod redis_claims;
+mod retry_policy;
+mod rotation;
+mod s3_store;
+mod schedule;
+mod service;
+mod session_stats;
+mod simulate;
+mod solve_costs;
+mod spread;
+#[cfg(feature = "sqlite")]
+mod sqlite_store;
+mod state;
+mod status;
+mod store;
+mod systemd;
+mod telegram;
+mod telemetry;
+mod verify;
+mod wallet_stats;
+mod wallets;
+mod worker;
+
+use backend::{HashBackend, HashRom};
+use error::{MinerError, Rejection};
 use rayon::prelude::*;
-use std::sync::{Arc, Mutex, atomic::{AtomicBool, AtomicU64, Ordering}};
+use std::sync::{Arc, Mutex, atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering}};
 use std::thread;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use std::env;
@@ -102,27 +165,309 @@ fn set_thread_processor_group_affinity(thread_index: usize) {
     }
 }
 
+/// Run mining threads at the lowest OS priority (`--nice`) so interactive work always wins
+static LOW_PRIORITY_THREADS: AtomicBool = AtomicBool::new(false);
+
+// Windows: idle thread priority, only scheduled when nothing else wants the core
+#[cfg(windows)]
+fn lower_current_thread_priority() -> bool {
+    const THREAD_PRIORITY_IDLE: i32 = -15;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetCurrentThread() -> *mut std::ffi::c_void;
+        fn SetThreadPriority(hThread: *mut std::ffi::c_void, nPriority: i32) -> i32;
+    }
+
+    unsafe { SetThreadPriority(GetCurrentThread(), THREAD_PRIORITY_IDLE) != 0 }
+}
+
+// Linux: SCHED_IDLE for the calling thread, falling back to nice 19 (per-thread on Linux)
+#[cfg(target_os = "linux")]
+fn lower_current_thread_priority() -> bool {
+    const SCHED_IDLE: i32 = 5;
+    const PRIO_PROCESS: i32 = 0;
+
+    #[repr(C)]
+    struct SchedParam {
+        sched_priority: i32,
+    }
+
+    extern "C" {
+        fn sched_setscheduler(pid: i32, policy: i32, param: *const SchedParam) -> i32;
+        fn setpriority(which: i32, who: u32, prio: i32) -> i32;
+    }
+
+    unsafe {
+        sched_setscheduler(0, SCHED_IDLE, &SchedParam { sched_priority: 0 }) == 0
+            || setpriority(PRIO_PROCESS, 0, 19) == 0
+    }
+}
+
+// macOS: background QoS class for the calling thread
+#[cfg(target_os = "macos")]
+fn lower_current_thread_priority() -> bool {
+    const QOS_CLASS_BACKGROUND: u32 = 0x09;
+
+    extern "C" {
+        fn pthread_set_qos_class_self_np(qos_class: u32, relative_priority: i32) -> i32;
+    }
+
+    unsafe { pthread_set_qos_class_self_np(QOS_CLASS_BACKGROUND, 0) == 0 }
+}
+
+// Other platforms: no per-thread priority control
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+fn lower_current_thread_priority() -> bool {
+    false
+}
+
+/// Priority of the whole miner process (`--process-priority`), ROM generation and helper threads
+/// included, as opposed to `--nice`, which only lowers the mining threads
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ProcessPriority {
+    Idle,
+    BelowNormal,
+    Normal,
+}
+
+impl ProcessPriority {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "idle" => Some(ProcessPriority::Idle),
+            "below-normal" | "below_normal" => Some(ProcessPriority::BelowNormal),
+            "normal" => Some(ProcessPriority::Normal),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ProcessPriority::Idle => "idle",
+            ProcessPriority::BelowNormal => "below-normal",
+            ProcessPriority::Normal => "normal",
+        }
+    }
+}
+
+// Windows: the process priority class, which every thread's priority is relative to
+#[cfg(windows)]
+fn set_process_priority(priority: ProcessPriority) -> bool {
+    const IDLE_PRIORITY_CLASS: u32 = 0x40;
+    const BELOW_NORMAL_PRIORITY_CLASS: u32 = 0x4000;
+    const NORMAL_PRIORITY_CLASS: u32 = 0x20;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetCurrentProcess() -> *mut std::ffi::c_void;
+        fn SetPriorityClass(hProcess: *mut std::ffi::c_void, dwPriorityClass: u32) -> i32;
+    }
+
+    let class = match priority {
+        ProcessPriority::Idle => IDLE_PRIORITY_CLASS,
+        ProcessPriority::BelowNormal => BELOW_NORMAL_PRIORITY_CLASS,
+        ProcessPriority::Normal => NORMAL_PRIORITY_CLASS,
+    };
+    unsafe { SetPriorityClass(GetCurrentProcess(), class) != 0 }
+}
+
+// Unix: the nice value. Linux keeps it per thread, so this runs before the miner starts its
+// threads, which inherit it. Going back to normal from a higher nice value needs root.
+#[cfg(unix)]
+fn set_process_priority(priority: ProcessPriority) -> bool {
+    const PRIO_PROCESS: i32 = 0;
+
+    extern "C" {
+        fn setpriority(which: i32, who: u32, prio: i32) -> i32;
+    }
+
+    let nice = match priority {
+        ProcessPriority::Idle => 19,
+        ProcessPriority::BelowNormal => 10,
+        ProcessPriority::Normal => 0,
+    };
+    unsafe { setpriority(PRIO_PROCESS, 0, nice) == 0 }
+}
+
+#[cfg(not(any(windows, unix)))]
+fn set_process_priority(_priority: ProcessPriority) -> bool {
+    false
+}
+
 // Non-Windows platforms use num_cpus directly
 #[cfg(not(windows))]
 fn get_total_logical_processors() -> usize {
     num_cpus::get()
 }
 
-// Scavenger Mine configuration from the whitepaper
-const ROM_SIZE: usize = 1_073_741_824; // 1GB
-const PRE_SIZE: usize = 16_777_216; // 16MB
-const MIXING_NUMBERS: usize = 4;
-const NB_LOOPS: u32 = 8;
-const NB_INSTRS: u32 = 256;
-
 // Logging and export directories
-const SOLUTIONS_DIR: &str = "solutions";
-const LOGS_DIR: &str = "logs";
-const DIFFICULT_TASKS_FILE: &str = "difficult_tasks.json";
 
 // API endpoints (only need challenges and Scavenger submission for user-only mode)
+// Default base URL; `--api-base` points the miner somewhere else (see http::api_base)
 const SCAVENGER_API_BASE: &str = "https://mine.defensio.io/api";
 
+/// Set by the Ctrl-C / SIGTERM handler; checked by the mining threads and the main loop
+static SHUTDOWN_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+// Unix: SIGINT (Ctrl-C) and SIGTERM (kill, service managers) set the shutdown flag
+#[cfg(unix)]
+fn install_shutdown_handler() {
+    const SIGINT: i32 = 2;
+    const SIGTERM: i32 = 15;
+    const NOTICE: &[u8] = b"\nShutdown requested, finishing current step (press Ctrl-C again to force quit)...\n";
+
+    extern "C" {
+        fn signal(signum: i32, handler: extern "C" fn(i32)) -> usize;
+        fn write(fd: i32, buf: *const u8, count: usize) -> isize;
+        fn _exit(status: i32) -> !;
+    }
+
+    // Only async-signal-safe calls in here (atomics, write, _exit)
+    extern "C" fn handle_signal(_signum: i32) {
+        if SHUTDOWN_REQUESTED.swap(true, Ordering::SeqCst) {
+            unsafe { _exit(130) };
+        }
+        unsafe { write(2, NOTICE.as_ptr(), NOTICE.len()) };
+    }
+
+    unsafe {
+        signal(SIGINT, handle_signal);
+        signal(SIGTERM, handle_signal);
+    }
+}
+
+// Windows: Ctrl-C, Ctrl-Break and console close set the shutdown flag
+#[cfg(windows)]
+fn install_shutdown_handler() {
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn SetConsoleCtrlHandler(HandlerRoutine: Option<unsafe extern "system" fn(u32) -> i32>, Add: i32) -> i32;
+    }
+
+    // Runs on its own thread, so normal printing is fine here
+    unsafe extern "system" fn handle_console_event(_ctrl_type: u32) -> i32 {
+        // A service also gets logoff events; only the service manager stops it
+        if service::active() {
+            return 1;
+        }
+        if SHUTDOWN_REQUESTED.swap(true, Ordering::SeqCst) {
+            std::process::exit(130);
+        }
+        eprintln!("\n🛑 Shutdown requested, finishing current step (press Ctrl-C again to force quit)...");
+        1 // Handled - don't run the default handler (which kills the process)
+    }
+
+    unsafe {
+        if SetConsoleCtrlHandler(Some(handle_console_event), 1) == 0 {
+            tracing::warn!("⚠️  Could not install shutdown handler");
+        }
+    }
+}
+
+/// Ask the miner to finish its current step and exit, like Ctrl-C does (Windows service Stop)
+#[cfg(windows)]
+fn request_shutdown() {
+    SHUTDOWN_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+fn shutdown_requested() -> bool {
+    SHUTDOWN_REQUESTED.load(Ordering::Relaxed)
+}
+
+/// Sleep in short slices so a shutdown request isn't delayed by long waits
+fn sleep_unless_shutdown(duration: Duration) {
+    let deadline = Instant::now() + duration;
+    while !shutdown_requested() {
+        let now = Instant::now();
+        if now >= deadline {
+            break;
+        }
+        thread::sleep((deadline - now).min(Duration::from_millis(250)));
+    }
+}
+
+/// Idle wait in the main loop, which the systemd watchdog counts as the loop coming around
+fn main_loop_sleep(duration: Duration) {
+    let deadline = Instant::now() + duration;
+    while !shutdown_requested() && Instant::now() < deadline {
+        systemd::loop_tick();
+        sleep_unless_shutdown(deadline.saturating_duration_since(Instant::now()).min(Duration::from_secs(1)));
+    }
+}
+
+/// Set by SIGUSR1 (cleared by SIGUSR2); mining and polling are suspended while set
+static PAUSE_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Creating this file pauses the miner, deleting it resumes
+const PAUSE_FILE: &str = "PAUSE";
+
+// Unix: SIGUSR1 pauses, SIGUSR2 resumes
+#[cfg(unix)]
+fn install_pause_handler() {
+    #[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "openbsd", target_os = "netbsd"))]
+    const SIGNALS: (i32, i32) = (30, 31);
+    #[cfg(not(any(target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "openbsd", target_os = "netbsd")))]
+    const SIGNALS: (i32, i32) = (10, 12);
+
+    extern "C" {
+        fn signal(signum: i32, handler: extern "C" fn(i32)) -> usize;
+    }
+
+    extern "C" fn handle_pause(_signum: i32) {
+        PAUSE_REQUESTED.store(true, Ordering::SeqCst);
+    }
+
+    extern "C" fn handle_resume(_signum: i32) {
+        PAUSE_REQUESTED.store(false, Ordering::SeqCst);
+    }
+
+    unsafe {
+        signal(SIGNALS.0, handle_pause);
+        signal(SIGNALS.1, handle_resume);
+    }
+}
+
+// Windows has no user signals; the PAUSE file is the pause control there
+#[cfg(windows)]
+fn install_pause_handler() {}
+
+/// Why mining is currently suspended, if it is
+fn pause_reason() -> Option<&'static str> {
+    if dashboard::paused() {
+        Some("dashboard")
+    } else if telegram::paused() {
+        Some("Telegram /pause")
+    } else if grpc::paused() {
+        Some("gRPC Pause")
+    } else if service::paused() {
+        Some("Windows service paused")
+    } else if PAUSE_REQUESTED.load(Ordering::Relaxed) {
+        Some("SIGUSR1")
+    } else if Path::new(PAUSE_FILE).exists() {
+        Some("PAUSE file")
+    } else if schedule::outside_windows() {
+        Some("outside the mining schedule")
+    } else if idle::user_is_active() {
+        Some("user active, idle-only mode")
+    } else {
+        None
+    }
+}
+
+/// Whether mining should currently be suspended
+fn is_paused() -> bool {
+    pause_reason().is_some()
+}
+
+/// Block while paused (returns immediately if not), returning how long we waited
+fn wait_while_paused() -> Duration {
+    let start = Instant::now();
+    while is_paused() && !shutdown_requested() {
+        thread::sleep(Duration::from_millis(500));
+    }
+    start.elapsed()
+}
+
 /// Difficult task record (challenge-wallet pair that's too hard to mine)
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 struct DifficultTask {
@@ -131,6 +476,48 @@ struct DifficultTask {
     marked_at: String,
     total_hashes: u64,
     mining_duration_secs: u64,
+    // Conditions at marking time, so the task can be retried once they improve
+    // (missing in entries written by older versions)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_hashes: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_duration_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hashrate: Option<f64>,
+}
+
+/// Difficult tasks older than this many days are retried (0 = never)
+const DEFAULT_DIFFICULT_RETRY_DAYS: u64 = 7;
+
+/// A difficult task is retried once a task could now run this many times the hashes it got
+const DIFFICULT_RETRY_GAIN: f64 = 1.5;
+
+impl DifficultTask {
+    /// Why the task deserves another try under the current limits and hashrate, if it does
+    fn retry_reason(&self, limits: &MiningLimits, hashrate: Option<f64>, retry_days: u64) -> Option<String> {
+        if retry_days > 0 {
+            if let Ok(marked) = chrono::DateTime::parse_from_rfc3339(&self.marked_at) {
+                let age_days = (now_utc() - marked.with_timezone(&chrono::Utc)).num_days();
+                if age_days >= retry_days as i64 {
+                    return Some(format!("marked {} day(s) ago", age_days));
+                }
+            }
+        }
+
+        // Hashes a task gets now: the hash limit, or the time limit at the current hashrate
+        let hashrate = hashrate.or(self.hashrate).filter(|r| *r > 0.0)?;
+        let by_time = limits.max_duration.map(|d| d.as_secs_f64() * hashrate);
+        let budget = match (limits.max_hashes.map(|h| h as f64), by_time) {
+            (Some(h), Some(t)) => h.min(t),
+            (Some(h), None) => h,
+            (None, Some(t)) => t,
+            (None, None) => return Some("no hash or time limit any more".to_string()),
+        };
+        (budget >= self.total_hashes as f64 * DIFFICULT_RETRY_GAIN).then(|| {
+            format!("a task now gets ~{} hashes, it gave up after {}",
+                difficulty::format_count(budget), difficulty::format_count(self.total_hashes as f64))
+        })
+    }
 }
 
 /// Response from challenge API (single challenge)
@@ -143,7 +530,7 @@ struct ChallengeResponse {
 }
 
 /// Challenge information from the API
-#[derive(Debug, Clone, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
 struct Challenge {
     challenge_id: String,
     #[serde(default)]
@@ -156,16 +543,28 @@ struct Challenge {
     no_pre_mine: String,
     latest_submission: String,
     no_pre_mine_hour: String,
+    /// Hash algorithm/version indicator (string or number), absent on current challenges
+    #[serde(default, alias = "algorithm", skip_serializing_if = "Option::is_none")]
+    version: Option<serde_json::Value>,
 }
 
 impl Challenge {
+    /// Version indicator as a string, if the payload carried one
+    fn version_indicator(&self) -> Option<String> {
+        match &self.version {
+            Some(serde_json::Value::String(v)) => Some(v.clone()),
+            Some(serde_json::Value::Number(n)) => Some(n.to_string()),
+            _ => None,
+        }
+    }
+
     /// Check if challenge is still active with 1-hour safety buffer
     /// A challenge is considered active only if: current_time + 1 hour < latest_submission
     /// This prevents mining challenges that might expire before solution is found
     fn is_active(&self) -> bool {
         match chrono::DateTime::parse_from_rfc3339(&self.latest_submission) {
             Ok(deadline) => {
-                let now = chrono::Utc::now();
+                let now = now_utc();
                 // Add 1-hour buffer (3600 seconds) to current time
                 // Challenge is active only if deadline is more than 1 hour away
                 let safety_buffer = chrono::Duration::hours(1);
@@ -179,67 +578,38 @@ impl Challenge {
         }
     }
 
-    /// Count total zero bits in difficulty (more zeros = harder)
-    /// Zero bits represent constraints - hash MUST have 0 at those positions
-    fn count_required_zero_bits(&self) -> u32 {
-        match hex::decode(&self.difficulty) {
-            Ok(bytes) => {
-                // Count total zero bits across all bytes
-                bytes.iter().map(|b| b.count_zeros()).sum()
-            }
-            Err(_) => u32::MAX, // Invalid difficulty = hardest
-        }
+    /// Check if solutions can still be submitted (current time < latest_submission, no buffer)
+    /// Assumed true if the deadline can't be parsed
+    fn accepts_submissions(&self) -> bool {
+        chrono::DateTime::parse_from_rfc3339(&self.latest_submission).map_or(true, |deadline| now_utc() < deadline)
     }
 
-    /// Count leading zero bits in difficulty (more leading zeros = easier)
-    /// Leading zeros create consecutive pattern at start = easier to match
-    fn count_leading_zero_bits(&self) -> u32 {
-        match hex::decode(&self.difficulty) {
-            Ok(bytes) => {
-                let mut leading_zeros = 0u32;
-                for byte in bytes.iter() {
-                    let byte_leading = byte.leading_zeros();
-                    leading_zeros += byte_leading;
+    /// Probability model of the difficulty mask (None if it isn't valid hex)
+    fn difficulty_model(&self) -> Option<difficulty::DifficultyModel> {
+        difficulty::DifficultyModel::parse(&self.difficulty).ok()
+    }
 
-                    // If this byte doesn't have all 8 bits as zero, stop counting
-                    if byte_leading < 8 {
-                        break;
-                    }
-                }
-                leading_zeros
-            }
-            Err(_) => 0, // Invalid difficulty = no leading zeros
-        }
+    /// Mean hashes to a solution (invalid difficulty = hardest)
+    fn expected_hashes(&self) -> f64 {
+        self.difficulty_model().map_or(f64::INFINITY, |m| m.expected_hashes())
     }
 
     /// Comprehensive comparison for optimal challenge selection
     /// Priority order:
-    /// 1. Total zero bits (fewer = easier, since zeros are constraints)
-    /// 2. Leading zero bits (more = easier, consecutive pattern at start)
-    /// 3. Latest submission (thread-count dependent for optimization)
-    /// 4. Challenge ID (deterministic tiebreaker)
+    /// 1. Expected hashes to a solution (fewer = easier, see difficulty.rs)
+    /// 2. Latest submission (thread-count dependent for optimization)
+    /// 3. Challenge ID (deterministic tiebreaker)
     fn compare_for_selection(&self, other: &Challenge, num_threads: usize) -> std::cmp::Ordering {
         use std::cmp::Ordering;
 
-        // 1. Primary: Total zero bits (fewer zeros = easier)
-        // Zero bits are constraints - hash must have 0s at those positions
-        let a_zeros = self.count_required_zero_bits();
-        let b_zeros = other.count_required_zero_bits();
-        let zeros_cmp = a_zeros.cmp(&b_zeros); // Ascending order (fewer first)
-        if zeros_cmp != Ordering::Equal {
-            return zeros_cmp;
-        }
-
-        // 2. Secondary: Leading zero bits (more = easier)
-        // Consecutive zeros at start are easier to match than scattered zeros
-        let a_leading = self.count_leading_zero_bits();
-        let b_leading = other.count_leading_zero_bits();
-        let leading_cmp = b_leading.cmp(&a_leading); // Descending order (more first)
-        if leading_cmp != Ordering::Equal {
-            return leading_cmp;
+        // 1. Primary: expected hashes, from the number of constrained bits in the mask
+        // (where the zeros sit doesn't matter, hash bits are uniformly random)
+        let hashes_cmp = self.expected_hashes().total_cmp(&other.expected_hashes()); // Ascending (easier first)
+        if hashes_cmp != Ordering::Equal {
+            return hashes_cmp;
         }
 
-        // 3. Tertiary: Latest submission (thread-count dependent)
+        // 2. Secondary: Latest submission (thread-count dependent)
         // < 6 threads: prefer newer submissions (descending)
         // >= 6 threads: prefer older submissions (ascending) - less competition
         let time_cmp = if num_threads < 6 {
@@ -251,7 +621,7 @@ impl Challenge {
             return time_cmp;
         }
 
-        // 4. Final: Challenge ID (deterministic tiebreaker)
+        // 3. Final: Challenge ID (deterministic tiebreaker)
         self.challenge_id.cmp(&other.challenge_id)
     }
 }
@@ -286,46 +656,87 @@ struct SolutionRecord {
     retry_count: u32,
     #[serde(default)]
     last_retry_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    nonce_start: Option<String>,
+    /// Earliest retry time requested by the API (Retry-After), for "queued" submissions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    next_retry_at: Option<String>,
+    /// Receipt signature check result, if a verification key is configured (`--receipt-pubkey`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    verified: Option<bool>,
 }
 
-/// ROM cache to avoid reinitializing for the same no_pre_mine
-struct RomCache {
-    rom: Option<Arc<Rom>>,
+/// Default memory budget for cached ROMs (one 1GB ROM, so nothing beyond the ROM in use stays resident)
+const DEFAULT_ROM_CACHE_MB: u64 = 1024;
+
+struct RomCacheEntry {
+    backend_name: String,
     no_pre_mine: String,
+    size: usize,
+    rom: Arc<dyn HashRom>,
+}
+
+/// LRU cache of ROMs keyed by (backend, no_pre_mine), bounded by a memory budget
+/// Alternating between challenges with different keys reuses ROMs instead of regenerating them.
+/// The most recently used ROM is always kept, even if it alone exceeds the budget.
+struct RomCache {
+    entries: Vec<RomCacheEntry>, // Least recently used first
+    budget_bytes: usize,
 }
 
 impl RomCache {
-    fn new() -> Self {
+    fn new(budget_mb: u64) -> Self {
         RomCache {
-            rom: None,
-            no_pre_mine: String::new(),
+            entries: Vec::new(),
+            budget_bytes: (budget_mb as usize).saturating_mul(1024 * 1024),
         }
     }
 
-    fn get_or_create(&mut self, no_pre_mine: &str) -> Arc<Rom> {
-        if self.no_pre_mine != no_pre_mine || self.rom.is_none() {
-            println!("\n🔄 ROM cache miss - initializing new ROM...");
-            println!("   no_pre_mine: {}...", &no_pre_mine[..16.min(no_pre_mine.len())]);
-            let start = Instant::now();
+    fn used_bytes(&self) -> usize {
+        self.entries.iter().map(|e| e.size).sum()
+    }
 
-            let rom = Rom::new(
-                no_pre_mine.as_bytes(),
-                RomGenerationType::TwoStep {
-                    pre_size: PRE_SIZE,
-                    mixing_numbers: MIXING_NUMBERS,
-                },
-                ROM_SIZE,
+    fn get_or_create(&mut self, backend: &dyn HashBackend, no_pre_mine: &str) -> Arc<dyn HashRom> {
+        let position = self.entries
+            .iter()
+            .position(|e| e.no_pre_mine == no_pre_mine && e.backend_name == backend.name());
+
+        if let Some(index) = position {
+            println!("\n♻️  ROM cache hit - reusing existing ROM ({} cached)\n", self.entries.len());
+            let entry = self.entries.remove(index);
+            let rom = Arc::clone(&entry.rom);
+            self.entries.push(entry);
+            return rom;
+        }
+
+        // Evict before building so peak memory stays within the budget
+        let size = backend.rom_size();
+        while !self.entries.is_empty() && self.used_bytes() + size > self.budget_bytes {
+            let evicted = self.entries.remove(0);
+            tracing::info!(
+                "🗑️  Evicting least recently used ROM (no_pre_mine: {}...)",
+                &evicted.no_pre_mine[..16.min(evicted.no_pre_mine.len())],
             );
+        }
 
-            println!("   ✓ ROM initialized in {:.2?}\n", start.elapsed());
+        tracing::info!(
+            "🔄 ROM cache miss - initializing new ROM (no_pre_mine: {}..., backend: {})",
+            &no_pre_mine[..16.min(no_pre_mine.len())],
+            backend.describe(),
+        );
+        let start = Instant::now();
 
-            self.rom = Some(Arc::new(rom));
-            self.no_pre_mine = no_pre_mine.to_string();
-        } else {
-            println!("\n♻️  ROM cache hit - reusing existing ROM\n");
-        }
+        let rom = backend.build_rom(no_pre_mine.as_bytes());
 
-        Arc::clone(self.rom.as_ref().unwrap())
+        tracing::info!("   ✓ ROM initialized in {:.2?} ({} cached)", start.elapsed(), self.entries.len() + 1);
+
+        self.entries.push(RomCacheEntry {
+            backend_name: backend.name().to_string(),
+            no_pre_mine: no_pre_mine.to_string(),
+            size,
+            rom: Arc::clone(&rom),
+        });
+        rom
     }
 }
 
@@ -348,119 +759,181 @@ fn check_difficulty(hash: &[u8; 64], diff_bytes: &[u8]) -> bool {
 
 /// Get current timestamp as ISO 8601 string
 fn get_timestamp() -> String {
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap();
-    let datetime = chrono::DateTime::from_timestamp(now.as_secs() as i64, 0)
+    let datetime = chrono::DateTime::from_timestamp(now_unix_secs() as i64, 0)
         .unwrap_or_default();
     datetime.format("%Y-%m-%dT%H:%M:%SZ").to_string()
 }
 
-/// Setup output directories
-fn setup_directories() -> Result<(), Box<dyn std::error::Error>> {
-    fs::create_dir_all(SOLUTIONS_DIR)?;
-    fs::create_dir_all(LOGS_DIR)?;
-    Ok(())
+/// Server time minus local time, applied when running with `--clock-correct`
+static CLOCK_OFFSET_SECS: AtomicI64 = AtomicI64::new(0);
+
+/// Default tolerated difference between the local clock and the API server
+const DEFAULT_MAX_CLOCK_SKEW_SECS: u64 = 300;
+/// Skew worth a warning even below the limit (the API's Date header has 1s resolution)
+const CLOCK_SKEW_WARN_SECS: u64 = 30;
+
+/// Keep watching the skew after the startup check (off with `--max-clock-skew 0`)
+static CLOCK_WATCH: AtomicBool = AtomicBool::new(false);
+/// Keep CLOCK_OFFSET_SECS in line with the server while running (`--clock-correct`)
+static CLOCK_CORRECT: AtomicBool = AtomicBool::new(false);
+/// Uncorrected skew last warned about, so ongoing drift is only logged when it changes
+static LAST_REPORTED_SKEW_SECS: AtomicI64 = AtomicI64::new(0);
+
+/// Current Unix time, corrected by the measured clock offset
+fn now_unix_secs() -> u64 {
+    let local = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    (local + CLOCK_OFFSET_SECS.load(Ordering::Relaxed)).max(0) as u64
 }
 
-/// Log mining progress to file
-fn log_mining_progress(message: &str) {
-    let timestamp = get_timestamp();
-    let log_message = format!("[{}] {}\n", timestamp, message);
+/// Current UTC time, corrected by the measured clock offset
+fn now_utc() -> chrono::DateTime<chrono::Utc> {
+    chrono::Utc::now() + chrono::Duration::seconds(CLOCK_OFFSET_SECS.load(Ordering::Relaxed))
+}
 
-    // Print to console
-    print!("{}", log_message);
-    std::io::stdout().flush().ok();
+/// Compare the local clock with the API server's `Date` header
+/// Rigs without an RTC battery often boot with a clock that's days off, which breaks every
+/// deadline check and timestamp. Returns an error if the skew is too large and not corrected.
+fn check_clock_skew(max_skew_secs: u64, correct: bool) -> Result<(), String> {
+    CLOCK_WATCH.store(true, Ordering::Relaxed);
+    CLOCK_CORRECT.store(correct, Ordering::Relaxed);
 
-    // Write to log file
-    if let Ok(mut file) = fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(format!("{}/mining.log", LOGS_DIR))
-    {
-        let _ = file.write_all(log_message.as_bytes());
+    let url = format!("{}/challenge", http::api_base());
+    let response = match http::client().and_then(|client| http::send(http::get(&client, &url))) {
+        Ok(response) => response,
+        Err(e) => {
+            tracing::warn!(error = %e, "⚠️  Could not check the system clock against the API: {}", e);
+            return Ok(());
+        }
+    };
+
+    let Some(skew) = server_clock_skew(&response) else {
+        tracing::warn!("⚠️  API response has no Date header, skipping clock check");
+        return Ok(());
+    };
+
+    if skew.unsigned_abs() <= CLOCK_SKEW_WARN_SECS {
+        return Ok(());
+    }
+
+    let message = describe_clock_skew(skew);
+    if correct {
+        CLOCK_OFFSET_SECS.store(skew, Ordering::Relaxed);
+        tracing::info!("🕒 {}, correcting timestamps by {}s", message, skew);
+        Ok(())
+    } else if skew.unsigned_abs() <= max_skew_secs {
+        LAST_REPORTED_SKEW_SECS.store(skew, Ordering::Relaxed);
+        tracing::warn!("⚠️  {}; deadlines will be off by that much (use --clock-correct to compensate)", message);
+        Ok(())
+    } else {
+        Err(format!("{}. Fix the system time or run with --clock-correct", message))
     }
 }
 
-/// Export solution to file
-fn export_solution(record: &SolutionRecord) -> Result<(), Box<dyn std::error::Error>> {
-    // Create filename: wallet_challenge.json (using full wallet address)
-    let filename = format!(
-        "{}/{}_{}.json",
-        SOLUTIONS_DIR,
-        record.wallet_address,
-        record.challenge_id.replace("*", "").replace("/", "_")
-    );
+/// Server time minus local (uncorrected) time, from a response's `Date` header
+fn server_clock_skew(response: &reqwest::blocking::Response) -> Option<i64> {
+    let server_time = response
+        .headers()
+        .get(reqwest::header::DATE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| chrono::DateTime::parse_from_rfc2822(v).ok())?;
+    Some(server_time.timestamp() - chrono::Utc::now().timestamp())
+}
+
+fn describe_clock_skew(skew: i64) -> String {
+    let direction = if skew > 0 { "behind" } else { "ahead of" };
+    format!("System clock is {} {} the API server", format_duration_secs(skew.unsigned_abs() as f64), direction)
+}
+
+/// Re-measure the skew from a regular API response, so a clock that drifts (or gets fixed)
+/// while mining is noticed: corrected automatically with `--clock-correct`, warned about otherwise
+fn observe_server_clock(response: &reqwest::blocking::Response) {
+    if !CLOCK_WATCH.load(Ordering::Relaxed) {
+        return;
+    }
+    let Some(skew) = server_clock_skew(response) else {
+        return;
+    };
+
+    if CLOCK_CORRECT.load(Ordering::Relaxed) {
+        let previous = CLOCK_OFFSET_SECS.load(Ordering::Relaxed);
+        // Ignore jitter from the header's 1s resolution and request latency
+        if (skew - previous).unsigned_abs() > 2 {
+            CLOCK_OFFSET_SECS.store(skew, Ordering::Relaxed);
+            if (skew - previous).unsigned_abs() > CLOCK_SKEW_WARN_SECS {
+                tracing::info!("🕒 {}, clock correction updated to {}s", describe_clock_skew(skew), skew);
+            }
+        }
+    } else {
+        let reported = LAST_REPORTED_SKEW_SECS.load(Ordering::Relaxed);
+        if (skew - reported).unsigned_abs() > CLOCK_SKEW_WARN_SECS {
+            LAST_REPORTED_SKEW_SECS.store(skew, Ordering::Relaxed);
+            if skew.unsigned_abs() > CLOCK_SKEW_WARN_SECS {
+                tracing::warn!("⚠️  {}; deadlines will be off by that much (use --clock-correct to compensate)", describe_clock_skew(skew));
+            } else {
+                tracing::info!("🕒 System clock is back in sync with the API server");
+            }
+        }
+    }
+}
+
+/// Setup output directories
+fn setup_directories() -> Result<(), MinerError> {
+    fs::create_dir_all(paths::solutions_dir())?;
+    fs::create_dir_all(paths::logs_dir())?;
+    if let Some(parent) = paths::difficult_tasks_file().parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    Ok(())
+}
 
-    let json = serde_json::to_string_pretty(record)?;
-    fs::write(&filename, json)?;
+/// Send log lines to the console or, while `--tui` is up, to the dashboard (plus mining.log and --log-json)
+fn configure_logging(config: &MinerConfig, dashboard: bool) {
+    let (console, format): (Box<dyn logging::Sink>, _) = if dashboard {
+        (Box::new(dashboard::Events), logging::ConsoleFormat::Pretty)
+    } else if config.daemon.is_some() {
+        (Box::new(logging::Discard), config.log_format)
+    } else {
+        (Box::new(logging::Console), config.log_format)
+    };
+    logging::configure(console, format, config.log_json.as_ref().map(std::path::PathBuf::from), config.log_rotation);
+    logging::set_verbosity(config.verbosity);
+}
 
-    log_mining_progress(&format!("💾 Exported solution to: {}", filename));
+/// Save a solution record to the configured store (see store.rs)
+fn export_solution(record: &SolutionRecord) -> Result<(), MinerError> {
+    let location = store::get().save(record)?;
+    tracing::info!("💾 Exported solution to: {}", location);
     Ok(())
 }
 
 
 /// Update existing solution record
-fn update_solution_record(record: &SolutionRecord) -> Result<(), Box<dyn std::error::Error>> {
+fn update_solution_record(record: &SolutionRecord) -> Result<(), MinerError> {
     export_solution(record)
 }
 
-/// Get all failed solution files that need retry
+/// Get all failed solutions that need retry
 fn get_failed_solutions() -> Vec<SolutionRecord> {
-    let mut failed_solutions = Vec::new();
-
-    if let Ok(entries) = fs::read_dir(SOLUTIONS_DIR) {
-        for entry in entries.flatten() {
-            if let Ok(file_type) = entry.file_type() {
-                if file_type.is_file() && entry.path().extension().and_then(|s| s.to_str()) == Some("json") {
-                    if let Ok(content) = fs::read_to_string(entry.path()) {
-                        if let Ok(record) = serde_json::from_str::<SolutionRecord>(&content) {
-                            // Only include failed submissions that should be retried
-                            if record.crypto_receipt.is_none() &&
-                               (record.status == "rejected" || record.status.starts_with("error:") || record.status == "failed") {
-
-                                // Skip non-retriable errors
-                                if let Some(ref error_msg) = record.error_message {
-                                    let error_lower = error_msg.to_lowercase();
-
-                                    // Don't retry if solution already exists (submitted elsewhere)
-                                    if error_lower.contains("solution already exists") ||
-                                       error_lower.contains("already exists") {
-                                        continue;
-                                    }
-
-                                    // Don't retry if the challenge already closed and the latest submission time has passed
-                                    if error_lower.contains("submission window closed") ||
-                                       error_lower.contains("window closed") {
-                                        continue;
-                                    }
-
-                                    // Don't retry if solution doesn't meet difficulty (invalid nonce)
-                                    if error_lower.contains("does not meet difficulty") ||
-                                       error_lower.contains("difficulty") && error_lower.contains("not meet") {
-                                        continue;
-                                    }
-                                }
-
-                                failed_solutions.push(record);
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    failed_solutions
+    store::get()
+        .pending()
+        .into_iter()
+        // Skip non-retriable errors (duplicate, invalid nonce, window closed) in
+        // records written before their status reflected the rejection
+        .filter(|record| !record.error_message.as_deref().is_some_and(|msg| Rejection::from_message(msg).is_final()))
+        .collect()
 }
 
 /// Load difficult tasks from file
 fn load_difficult_tasks() -> Vec<DifficultTask> {
-    if !Path::new(DIFFICULT_TASKS_FILE).exists() {
+    let path = paths::difficult_tasks_file();
+    if !path.exists() {
         return Vec::new();
     }
 
-    match fs::read_to_string(DIFFICULT_TASKS_FILE) {
+    match fs::read_to_string(path) {
         Ok(content) => {
             serde_json::from_str::<Vec<DifficultTask>>(&content).unwrap_or_else(|_| Vec::new())
         }
@@ -469,7 +942,7 @@ fn load_difficult_tasks() -> Vec<DifficultTask> {
 }
 
 /// Save difficult tasks to file
-fn save_difficult_task(task: DifficultTask) -> Result<(), Box<dyn std::error::Error>> {
+fn save_difficult_task(task: DifficultTask) -> Result<(), MinerError> {
     let mut tasks = load_difficult_tasks();
 
     // Check if already exists (update if found)
@@ -484,15 +957,17 @@ fn save_difficult_task(task: DifficultTask) -> Result<(), Box<dyn std::error::Er
     }
 
     let json = serde_json::to_string_pretty(&tasks)?;
-    fs::write(DIFFICULT_TASKS_FILE, json)?;
+    fs::write(paths::difficult_tasks_file(), json)?;
     Ok(())
 }
 
-/// Check if task is marked as difficult
-fn is_difficult_task(wallet_address: &str, challenge_id: &str, difficult_tasks: &[DifficultTask]) -> bool {
-    difficult_tasks.iter().any(|t| {
-        t.wallet_address == wallet_address && t.challenge_id == challenge_id
-    })
+/// Drop a task from the difficult list so it is mined again
+fn remove_difficult_task(wallet_address: &str, challenge_id: &str) -> Result<(), MinerError> {
+    let mut tasks = load_difficult_tasks();
+    tasks.retain(|t| t.wallet_address != wallet_address || t.challenge_id != challenge_id);
+    let json = serde_json::to_string_pretty(&tasks)?;
+    fs::write(paths::difficult_tasks_file(), json)?;
+    Ok(())
 }
 
 /// Build cached preimage suffix (everything after nonce)
@@ -522,102 +997,1195 @@ fn construct_preimage_fast(nonce: u64, suffix: &[u8]) -> Vec<u8> {
 }
 
 /// Fetch current challenge from Scavenger Mine API
-fn fetch_current_challenge() -> Result<Challenge, Box<dyn std::error::Error>> {
-    let url = format!("{}/challenge", SCAVENGER_API_BASE);
-    let response = reqwest::blocking::get(&url)?;
-    let data: ChallengeResponse = response.json()?;
-    Ok(data.challenge)
+fn fetch_current_challenge() -> Result<Challenge, MinerError> {
+    fetch_challenge_if_changed(&mut ChallengeValidators::default())?
+        .ok_or_else(|| MinerError::Protocol("unexpected 304 Not Modified for an unconditional request".to_string()))
 }
 
-/// Update and filter active challenges list
-/// Adds new challenge if not present, removes expired challenges, and sorts by difficulty
-fn update_active_challenges(
-    challenges_cache: &mut Vec<Challenge>,
-    num_threads: usize,
-) -> Result<(), Box<dyn std::error::Error>> {
-    // Fetch current challenge from API
-    let current_challenge = fetch_current_challenge()?;
+/// Cache validators from the last challenge response, sent back as a conditional request
+#[derive(Debug, Default)]
+struct ChallengeValidators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
 
-    // Add to cache if not already present (check by challenge_id)
-    let already_exists = challenges_cache.iter().any(|c| c.challenge_id == current_challenge.challenge_id);
-    if !already_exists {
-        log_mining_progress(&format!("📥 New challenge discovered: {}", current_challenge.challenge_id));
-        challenges_cache.push(current_challenge);
-    }
+/// Fetch the current challenge unless it's unchanged since `validators` were recorded
+/// Returns `Ok(None)` on 304 Not Modified; updates `validators` from every full response
+fn fetch_challenge_if_changed(validators: &mut ChallengeValidators) -> Result<Option<Challenge>, MinerError> {
+    with_api_retry("fetch challenge", || {
+        let url = format!("{}/challenge", http::api_base());
+        let mut request = http::get(&http::client()?, &url);
+        if let Some(etag) = &validators.etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &validators.last_modified {
+            request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+        }
 
-    // Filter out inactive challenges (where deadline is within 1 hour or already passed)
-    let initial_count = challenges_cache.len();
-    challenges_cache.retain(|c| {
-        let is_active = c.is_active();
-        if !is_active {
-            log_mining_progress(&format!("⏰ Challenge {} expires soon (< 1 hour), removing from active list", c.challenge_id));
+        let response = http::send(request)?;
+        observe_server_clock(&response);
+        if let Some(retry_after) = rate_limit_delay(&response) {
+            return Err(MinerError::RateLimited { retry_after });
+        }
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(None);
         }
-        is_active
-    });
-    let removed_count = initial_count - challenges_cache.len();
-    if removed_count > 0 {
-        log_mining_progress(&format!("🗑️  Removed {} challenge(s) expiring within 1 hour", removed_count));
-    }
 
-    // Sort using comprehensive comparison:
-    // 1. Total zero bits (fewer = easier, zeros are constraints)
-    // 2. Leading zero bits (more = easier, consecutive pattern at start)
-    // 3. Latest submission (thread-count dependent):
-    //    - < 6 threads: newer first (faster refresh strategy)
-    //    - >= 6 threads: older first (less competition strategy)
-    // 4. Challenge ID (deterministic tiebreaker)
-    challenges_cache.sort_by(|a, b| a.compare_for_selection(b, num_threads));
+        let header = |name| response.headers().get(name).and_then(|v| v.to_str().ok()).map(|v| v.to_string());
+        let etag = header(reqwest::header::ETAG);
+        let last_modified = header(reqwest::header::LAST_MODIFIED);
+        let data: ChallengeResponse = response.json()?;
+        *validators = ChallengeValidators { etag, last_modified };
+        Ok(Some(data.challenge))
+    })
+}
 
-    Ok(())
+/// Bulk list of every challenge still accepting submissions
+const CHALLENGE_LIST_PATH: &str = "/challenges";
+
+/// Response from the challenge list endpoint (a bare array is accepted too)
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+enum ChallengeListResponse {
+    Wrapped { challenges: Vec<Challenge> },
+    Bare(Vec<Challenge>),
 }
 
-/// Check if challenge is still open by fetching current challenge
-/// A challenge is open if it's still active (current time < latest_submission)
-fn is_challenge_still_open(solution: &SolutionRecord) -> bool {
-    // Try to fetch the current challenge to see if it matches
-    match fetch_current_challenge() {
-        Ok(current_challenge) => {
-            // If it's the same challenge and still active, it's open
-            if current_challenge.challenge_id == solution.challenge_id {
-                return current_challenge.is_active();
-            }
-            // If it's a different challenge, the old one is likely expired
-            false
+/// Fetch all challenges still inside their submission window
+/// Returns `Ok(None)` if the API doesn't offer the list endpoint
+fn fetch_active_challenge_list() -> Result<Option<Vec<Challenge>>, MinerError> {
+    with_api_retry("fetch challenge list", || {
+        let url = format!("{}{}", http::api_base(), CHALLENGE_LIST_PATH);
+        let response = http::send(http::get(&http::client()?, &url))?;
+        if let Some(retry_after) = rate_limit_delay(&response) {
+            return Err(MinerError::RateLimited { retry_after });
         }
-        Err(_) => {
-            // If we can't fetch, assume it might still be open (network issue)
-            true
+        if matches!(response.status().as_u16(), 404 | 405 | 501) {
+            return Ok(None);
         }
-    }
+        let challenges = match response.error_for_status()?.json::<ChallengeListResponse>()? {
+            ChallengeListResponse::Wrapped { challenges } | ChallengeListResponse::Bare(challenges) => challenges,
+        };
+        Ok(Some(challenges))
+    })
 }
 
-/// Check if a solution already exists for a wallet-challenge pair
-fn solution_exists(wallet_address: &str, challenge_id: &str) -> bool {
-    let clean_challenge_id = challenge_id.replace("*", "").replace("/", "_");
-    let filename = format!("{}/{}_{}.json", SOLUTIONS_DIR, wallet_address, clean_challenge_id);
-
-    Path::new(&filename).exists()
+/// Load operator-provided challenges for offline mode (`--challenges-file`)
+/// Accepts the list endpoint's format, a bare array, or a single challenge response
+fn load_challenges_file(path: &str) -> Result<Vec<Challenge>, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+    match serde_json::from_str::<ChallengeListResponse>(&content) {
+        Ok(ChallengeListResponse::Wrapped { challenges } | ChallengeListResponse::Bare(challenges)) => Ok(challenges),
+        Err(list_err) => serde_json::from_str::<ChallengeResponse>(&content)
+            .map(|r| vec![r.challenge])
+            .map_err(|_| format!("invalid challenges file {}: {}", path, list_err)),
+    }
 }
 
-/// Select the best challenge for a wallet (easiest unsolved challenge)
-fn select_challenge_for_wallet(wallet_address: &str, challenges: &[Challenge]) -> Option<Challenge> {
-    // Iterate through challenges (already sorted by difficulty, easiest first)
-    // This maximizes solutions/hour by solving easy challenges quickly
-    for challenge in challenges {
-        if !solution_exists(wallet_address, &challenge.challenge_id) {
-            return Some(challenge.clone());
-        }
+/// A challenge by ID, for subcommands working on one known challenge (`verify`, `mine`)
+/// Looks in `--challenges-file` if given, otherwise the API's list, the challenges saved by
+/// earlier runs and finally the current challenge
+fn find_known_challenge(cli: &CliArgs, challenge_id: &str) -> Result<Challenge, String> {
+    if let Some(path) = cli.option("--challenges-file") {
+        return load_challenges_file(path)?
+            .into_iter()
+            .find(|c| c.challenge_id == challenge_id)
+            .ok_or_else(|| format!("challenge {} is not in {}", challenge_id, path));
     }
 
-    // If all challenges have been solved, return None
-    None
+    if let Ok(Some(challenges)) = fetch_active_challenge_list() {
+        if let Some(challenge) = challenges.into_iter().find(|c| c.challenge_id == challenge_id) {
+            return Ok(challenge);
+        }
+    }
+    if let Some(challenge) = load_challenge_cache().into_iter().find(|c| c.challenge_id == challenge_id) {
+        return Ok(challenge);
+    }
+    match fetch_current_challenge() {
+        Ok(challenge) if challenge.challenge_id == challenge_id => Ok(challenge),
+        Ok(_) => Err(format!(
+            "challenge {} is no longer offered by the API; pass its parameters with --challenges-file",
+            challenge_id
+        )),
+        Err(e) => Err(format!("cannot fetch challenges from the API: {}", e)),
+    }
+}
+
+/// Hash backend for a challenge, picked like the mining loop does (`--hash-backend`, `--backend-schedule`)
+fn backend_for_challenge(cli: &CliArgs, challenge: &Challenge) -> Result<Arc<dyn HashBackend>, String> {
+    let backend_name = cli.option("--hash-backend").unwrap_or(backend::DEFAULT_BACKEND);
+    let default_backend = backend::backend_by_name(backend_name)
+        .ok_or_else(|| format!("Unknown hash backend '{}'", backend_name))?;
+    let mut router = backend::BackendRouter::new(default_backend);
+    if let Some(spec) = cli.option("--backend-schedule") {
+        router = router.with_schedule(spec).map_err(|e| format!("Invalid --backend-schedule: {}", e))?;
+    }
+    let schedule_time = challenge.issued_at.as_deref().unwrap_or(&challenge.latest_submission);
+    router.route(challenge.version_indicator().as_deref(), Some(schedule_time))
+}
+
+/// Retry settings for API calls (`--api-retries`, `--api-backoff-ms`)
+#[derive(Debug, Clone, Copy)]
+struct ApiRetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+}
+
+const DEFAULT_API_RETRIES: u32 = 4;
+const DEFAULT_API_BACKOFF_MS: u64 = 1000;
+const API_BACKOFF_MAX: Duration = Duration::from_secs(30);
+
+static API_RETRY_POLICY: std::sync::OnceLock<ApiRetryPolicy> = std::sync::OnceLock::new();
+
+fn api_retry_policy() -> ApiRetryPolicy {
+    *API_RETRY_POLICY.get_or_init(|| ApiRetryPolicy {
+        max_attempts: DEFAULT_API_RETRIES,
+        base_delay: Duration::from_millis(DEFAULT_API_BACKOFF_MS),
+        max_delay: API_BACKOFF_MAX,
+    })
+}
+
+/// Run an API call, retrying errors (network failures, unreadable responses) with exponential
+/// backoff and jitter so a transient blip doesn't cost a mining cycle or a submission window
+fn with_api_retry<T>(
+    what: &str,
+    mut call: impl FnMut() -> Result<T, MinerError>,
+) -> Result<T, MinerError> {
+    use rand::Rng;
+
+    let policy = api_retry_policy();
+    let mut attempt = 1;
+    loop {
+        match call() {
+            Ok(value) => {
+                health::api_reachable();
+                return Ok(value);
+            }
+            Err(e) if attempt >= policy.max_attempts || shutdown_requested() => return Err(e),
+            Err(e) => {
+                let delay = match e {
+                    // Honor the server's delay (plus a little jitter), unless it's too long to block on
+                    MinerError::RateLimited { retry_after } if retry_after > MAX_INLINE_RATE_LIMIT_WAIT => return Err(e),
+                    MinerError::RateLimited { retry_after } => retry_after.mul_f64(rand::thread_rng().gen_range(1.0..=1.1)),
+                    // Double the delay each attempt, then pick a random point in its upper half
+                    _ => policy.base_delay
+                        .saturating_mul(1 << (attempt - 1).min(16))
+                        .min(policy.max_delay)
+                        .mul_f64(rand::thread_rng().gen_range(0.5..=1.0)),
+                };
+                tracing::info!(
+                    error = %e,
+                    "   ↻ {} failed (attempt {}/{}): {}, retrying in {:.1}s",
+                    what, attempt, policy.max_attempts, e, delay.as_secs_f64(),
+                );
+                sleep_unless_shutdown(delay);
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// Top-level fields of the challenge endpoint this miner understands
+const KNOWN_CHALLENGE_RESPONSE_FIELDS: &[&str] = &[
+    "challenge",
+    "total_challenges",
+    "starts_at",
+    "next_challenge_starts_at",
+];
+
+/// Fields inside `challenge` this miner understands (required ones feed the preimage)
+const KNOWN_CHALLENGE_FIELDS: &[&str] = &[
+    "challenge_id",
+    "challenge_number",
+    "day",
+    "issued_at",
+    "difficulty",
+    "no_pre_mine",
+    "latest_submission",
+    "no_pre_mine_hour",
+    "version",
+    "algorithm",
+];
+const REQUIRED_CHALLENGE_FIELDS: &[&str] = &[
+    "challenge_id",
+    "difficulty",
+    "no_pre_mine",
+    "latest_submission",
+    "no_pre_mine_hour",
+];
+
+/// Wallet address used by the submission probe (never a valid address, so nothing can be credited)
+const PROBE_WALLET_ADDRESS: &str = "addr1probe";
+
+/// Print the known and unknown keys of a JSON object, returning the unknown ones
+fn report_json_fields(label: &str, object: &serde_json::Map<String, serde_json::Value>, known: &[&str]) -> Vec<String> {
+    let mut unknown = Vec::new();
+    println!("\n🔎 {} fields:", label);
+    for (key, value) in object {
+        if known.contains(&key.as_str()) {
+            println!("   ✓ {} = {}", key, value);
+        } else {
+            println!("   ❓ {} = {} (unknown)", key, value);
+            unknown.push(key.clone());
+        }
+    }
+    unknown
+}
+
+/// Probe the Scavenger Mine API and report whether this miner understands it
+/// Returns true when the challenge payload fully parses and contains no surprises
+fn run_probe_api(args: &[String]) -> bool {
+    let submit_probe = CliArgs::parse(args).switch("--submit-probe");
+    let mut problems: Vec<String> = Vec::new();
+    let mut warnings: Vec<String> = Vec::new();
+
+    println!("🔬 Probing Scavenger Mine API: {}", http::api_base());
+
+    // 1. Fetch the raw challenge document
+    let url = format!("{}/challenge", http::api_base());
+    let response = match http::client().and_then(|client| http::send(http::get(&client, &url))) {
+        Ok(response) => response,
+        Err(e) => {
+            println!("❌ Could not reach {}: {}", url, e);
+            return false;
+        }
+    };
+    let status = response.status();
+    println!("   GET /challenge -> HTTP {}", status.as_u16());
+    let body = response.text().unwrap_or_default();
+    if !status.is_success() {
+        println!("❌ Challenge endpoint returned an error: {}", body);
+        return false;
+    }
+
+    let raw: serde_json::Value = match serde_json::from_str(&body) {
+        Ok(value) => value,
+        Err(e) => {
+            println!("❌ Challenge response is not valid JSON: {}", e);
+            return false;
+        }
+    };
+
+    // 2. Compare raw fields against what this version knows about
+    let Some(top_level) = raw.as_object() else {
+        println!("❌ Challenge response is not a JSON object");
+        return false;
+    };
+    for key in report_json_fields("Response", top_level, KNOWN_CHALLENGE_RESPONSE_FIELDS) {
+        warnings.push(format!("unknown response field `{}`", key));
+    }
+
+    match top_level.get("challenge").and_then(|c| c.as_object()) {
+        Some(challenge) => {
+            for key in report_json_fields("Challenge", challenge, KNOWN_CHALLENGE_FIELDS) {
+                warnings.push(format!("unknown challenge field `{}`", key));
+            }
+            for field in REQUIRED_CHALLENGE_FIELDS {
+                if !challenge.contains_key(*field) {
+                    problems.push(format!("required challenge field `{}` is missing", field));
+                }
+            }
+        }
+        None => problems.push("`challenge` object is missing".to_string()),
+    }
+
+    // 3. Parse with the same types the miner uses and validate the values
+    match serde_json::from_value::<ChallengeResponse>(raw.clone()) {
+        Ok(parsed) => {
+            let challenge = &parsed.challenge;
+            println!("\n📋 Parsed challenge:");
+            println!("   challenge_id:      {}", challenge.challenge_id);
+            println!("   challenge_number:  {:?}", challenge.challenge_number);
+            println!("   day:               {:?}", challenge.day);
+            println!("   issued_at:         {:?}", challenge.issued_at);
+            println!("   difficulty:        {}", challenge.difficulty);
+            println!("   latest_submission: {}", challenge.latest_submission);
+            println!("   version:           {:?}", challenge.version_indicator());
+            println!("   total_challenges:  {:?}", parsed.total_challenges);
+            println!("   starts_at:         {:?}", parsed.starts_at);
+            println!("   next starts at:    {:?}", parsed.next_challenge_starts_at);
+
+            problems.extend(challenge_value_problems(challenge));
+
+            // 4. Optionally exercise the submission path with an address that can never be credited
+            if submit_probe {
+                probe_submission_path(&challenge.challenge_id, &mut problems, &mut warnings);
+            } else {
+                println!("\nℹ️  Submission path not probed (pass --submit-probe to send an invalid test submission)");
+            }
+        }
+        Err(e) => problems.push(format!("challenge response does not parse: {}", e)),
+    }
+
+    // 5. Verdict
+    println!("\n📊 Compatibility report:");
+    for warning in &warnings {
+        println!("   ⚠️  {}", warning);
+    }
+    for problem in &problems {
+        println!("   ❌ {}", problem);
+    }
+
+    if problems.is_empty() && warnings.is_empty() {
+        println!("   ✅ This miner version fully understands the current API");
+    } else if problems.is_empty() {
+        println!("   ⚠️  Mining will work, but the API has fields this version ignores");
+    } else {
+        println!("   ❌ This miner version is NOT compatible with the current API");
+    }
+
+    problems.is_empty()
+}
+
+/// Values in a parsed challenge that the miner can't work with
+fn challenge_value_problems(challenge: &Challenge) -> Vec<String> {
+    let mut problems = Vec::new();
+    if hex::decode(&challenge.difficulty).is_err() {
+        problems.push(format!("difficulty `{}` is not valid hex", challenge.difficulty));
+    }
+    if chrono::DateTime::parse_from_rfc3339(&challenge.latest_submission).is_err() {
+        problems.push(format!("latest_submission `{}` is not RFC 3339", challenge.latest_submission));
+    }
+    if challenge.no_pre_mine.is_empty() {
+        problems.push("no_pre_mine is empty (ROM cannot be generated)".to_string());
+    }
+    if let Some(version) = challenge.version_indicator() {
+        if !backend::available_backends().iter().any(|b| b.handles_version(&version)) {
+            problems.push(format!("challenge requires hash version '{}' which this miner doesn't implement", version));
+        }
+    }
+    problems
+}
+
+/// Lightweight API check before wallets are loaded and ROMs generated: the current challenge
+/// parses with every required field, and the submission endpoint answers a HEAD request.
+/// Returns actionable problems; empty means the API looks usable
+fn startup_health_check() -> Vec<String> {
+    let network_hint = "check the network connection, --proxy and TLS options";
+    let schema_hint = "run `scavenger-miner probe-api` for a full report; a miner update may be needed";
+
+    let client = match http::client() {
+        Ok(client) => client,
+        Err(e) => return vec![format!("cannot create HTTP client: {}", e)],
+    };
+
+    // 1. Current challenge, raw so missing fields can be named
+    let url = format!("{}/challenge", http::api_base());
+    let body = with_api_retry("health check", || {
+        let response = http::send(http::get(&client, &url))?;
+        if let Some(retry_after) = rate_limit_delay(&response) {
+            return Err(MinerError::RateLimited { retry_after });
+        }
+        Ok(response.error_for_status()?.text()?)
+    });
+    let raw: serde_json::Value = match body.map(|b| serde_json::from_str(&b)) {
+        Ok(Ok(raw)) => raw,
+        Ok(Err(e)) => return vec![format!("GET /challenge returned invalid JSON ({}) - {}", e, schema_hint)],
+        Err(e) => return vec![format!("GET /challenge failed: {} - {}", e, network_hint)],
+    };
+
+    let mut problems: Vec<String> = REQUIRED_CHALLENGE_FIELDS
+        .iter()
+        .filter(|field| raw.pointer(&format!("/challenge/{}", field)).is_none())
+        .map(|field| format!("challenge field `{}` is missing - {}", field, schema_hint))
+        .collect();
+    let challenge = match serde_json::from_value::<ChallengeResponse>(raw) {
+        Ok(parsed) => parsed.challenge,
+        Err(e) => {
+            problems.push(format!("challenge response does not parse: {} - {}", e, schema_hint));
+            return problems;
+        }
+    };
+    problems.extend(challenge_value_problems(&challenge).into_iter().map(|p| format!("{} - {}", p, schema_hint)));
+
+    // 2. Submission endpoint reachable (HEAD never submits anything)
+    let url = format!("{}/solution/{}/{}/{:016x}", http::api_base(), PROBE_WALLET_ADDRESS, challenge.challenge_id, 0u64);
+    match http::send(http::head(&client, &url)) {
+        Ok(response) if response.status().is_server_error() => problems.push(format!(
+            "submission endpoint answered HTTP {} - the API may be down, try again later", response.status()
+        )),
+        Ok(_) => {}
+        Err(e) => problems.push(format!("submission endpoint unreachable: {} - {}", e, network_hint)),
+    }
+
+    problems
+}
+
+/// Send a deliberately invalid submission and check the API rejects it with a readable error
+fn probe_submission_path(challenge_id: &str, problems: &mut Vec<String>, warnings: &mut Vec<String>) {
+    let url = format!("{}/solution/{}/{}/{:016x}", http::api_base(), PROBE_WALLET_ADDRESS, challenge_id, 0u64);
+
+    let result = http::client()
+        .and_then(|client| http::send(http::post(&client, &url).json(&serde_json::json!({}))));
+
+    match result {
+        Ok(response) => {
+            let status = response.status();
+            let body = response.text().unwrap_or_default();
+            println!("\n📮 POST /solution (invalid address) -> HTTP {}", status.as_u16());
+            println!("   {}", body.chars().take(200).collect::<String>());
+
+            if status.is_success() {
+                problems.push("submission endpoint accepted an invalid address".to_string());
+            } else if serde_json::from_str::<serde_json::Value>(&body).is_err() {
+                warnings.push("submission errors are not JSON; error classification may be unreliable".to_string());
+            }
+        }
+        Err(e) => problems.push(format!("submission endpoint unreachable: {}", e)),
+    }
+}
+
+/// Seed for the benchmark ROM (fixed so runs are comparable across machines)
+const BENCH_ROM_SEED: &[u8] = b"scavenger-miner-bench";
+
+/// Format a duration in seconds as a short human-readable string
+fn format_duration_secs(secs: f64) -> String {
+    if !secs.is_finite() {
+        "never".to_string()
+    } else if secs < 60.0 {
+        format!("{:.0}s", secs)
+    } else if secs < 3600.0 {
+        format!("{}m {}s", (secs / 60.0).floor(), (secs % 60.0).floor())
+    } else if secs < 86400.0 {
+        format!("{}h {}m", (secs / 3600.0).floor(), ((secs % 3600.0) / 60.0).floor())
+    } else {
+        format!("{:.1}d", secs / 86400.0)
+    }
+}
+
+/// Hash with `num_threads` threads for `duration`, returning (total hashes, elapsed seconds)
+fn measure_hashrate(rom: &Arc<dyn HashRom>, num_threads: usize, duration: Duration) -> (u64, f64) {
+    let pool = build_mining_pool(num_threads);
+
+    // Same warmup as real mining so the measurement isn't skewed by cold caches
+    pool.broadcast(|ctx| {
+        rom.pre_touch(ctx.index(), ctx.num_threads());
+        for i in 0..WARMUP_HASHES_PER_THREAD {
+            std::hint::black_box(rom.hash(&construct_preimage_fast(i, b"warmup")));
+        }
+    });
+
+    let total_hashes = AtomicU64::new(0);
+    let start = Instant::now();
+    pool.broadcast(|ctx| {
+        let suffix = BENCH_ROM_SEED;
+        let mut nonce = ctx.index() as u64;
+        while start.elapsed() < duration && !shutdown_requested() {
+            std::hint::black_box(rom.hash(&construct_preimage_fast(nonce, suffix)));
+            total_hashes.fetch_add(1, Ordering::Relaxed);
+            nonce += ctx.num_threads() as u64;
+        }
+    });
+    (total_hashes.load(Ordering::Relaxed), start.elapsed().as_secs_f64())
+}
+
+/// CPU usage levels tried by `--auto-threads`
+const CALIBRATION_CPU_LEVELS: &[f64] = &[25.0, 50.0, 75.0, 100.0];
+/// Default measurement time per level
+const DEFAULT_CALIBRATION_SECS: f64 = 20.0;
+/// A level within this fraction of the best hashrate is good enough (fewer threads = less power and heat)
+const CALIBRATION_GOOD_ENOUGH: f64 = 0.95;
+
+/// Measure each CPU usage level against a temporary ROM and pick a thread count
+/// Hashrate often stops scaling before 100% (memory bandwidth, hyper-threads), so this takes the
+/// fewest threads that get within 5% of the best hashrate instead of the absolute maximum
+fn calibrate_threads(backend: &dyn HashBackend, secs_per_level: f64) -> usize {
+    tracing::info!(
+        "🎛️  Calibrating thread count ({}s per level, temporary ROM)...",
+        secs_per_level,
+    );
+    let rom = backend.build_rom(BENCH_ROM_SEED);
+
+    let mut levels: Vec<usize> = CALIBRATION_CPU_LEVELS.iter().map(|&cpu| threads_for_cpu_usage(cpu)).collect();
+    levels.dedup();
+
+    let mut results: Vec<(usize, f64)> = Vec::new();
+    for threads in levels {
+        if shutdown_requested() {
+            break;
+        }
+        let (total, elapsed) = measure_hashrate(&rom, threads, Duration::from_secs_f64(secs_per_level));
+        let rate = if elapsed > 0.0 { total as f64 / elapsed } else { 0.0 };
+        tracing::info!(
+            "   {:>3} thread(s): {:.2} H/s ({:.2} H/s per thread)",
+            threads, rate, rate / threads as f64,
+        );
+        results.push((threads, rate));
+    }
+
+    let best = results.iter().map(|(_, rate)| *rate).fold(0.0, f64::max);
+    let (threads, rate) = results
+        .iter()
+        .find(|(_, rate)| *rate >= best * CALIBRATION_GOOD_ENOUGH)
+        .copied()
+        .unwrap_or((threads_for_cpu_usage(50.0), 0.0));
+    tracing::info!(
+        "🎛️  Using {} thread(s): {:.2} H/s, {:.0}% of the best measured ({:.2} H/s)",
+        threads, rate, if best > 0.0 { rate / best * 100.0 } else { 0.0 }, best,
+    );
+    threads
+}
+
+/// Offline hashrate benchmark: no network, no wallets file
+/// Usage: bench [--duration <secs>] [--cpu <percent>] [--threads <n>] [--hash-backend <name>] [--nice]
+fn run_bench(args: &[String]) -> bool {
+    let cli = CliArgs::parse(args);
+
+    let duration_secs = cli.option("--duration")
+        .and_then(|s| s.parse::<f64>().ok())
+        .unwrap_or(30.0)
+        .max(1.0);
+    let num_threads = match cli.option("--threads").and_then(|s| s.parse::<usize>().ok()) {
+        Some(threads) => threads.max(1),
+        None => {
+            let cpu_usage = cli.option("--cpu")
+                .and_then(|s| s.parse::<f64>().ok())
+                .unwrap_or(50.0)
+                .clamp(1.0, 100.0);
+            threads_for_cpu_usage(cpu_usage)
+        }
+    };
+    let backend_name = cli.option("--hash-backend").unwrap_or(backend::DEFAULT_BACKEND);
+    let Some(hash_backend) = backend::backend_by_name(backend_name) else {
+        println!("❌ Unknown hash backend '{}'", backend_name);
+        return false;
+    };
+
+    LOW_PRIORITY_THREADS.store(cli.switch("--nice"), Ordering::Relaxed);
+    qos::set_background_only(cli.switch("--background-only"));
+
+    println!("🏁 Benchmark: {} thread(s) for {}s", num_threads, duration_secs);
+    println!("   Backend: {}", hash_backend.describe());
+
+    println!("\n🔄 Building benchmark ROM...");
+    let rom_start = Instant::now();
+    let rom = hash_backend.build_rom(BENCH_ROM_SEED);
+    println!("   ✓ ROM initialized in {:.2?}", rom_start.elapsed());
+
+    println!("\n⛏️  Hashing...");
+    let (total, elapsed) = measure_hashrate(&rom, num_threads, Duration::from_secs_f64(duration_secs));
+    let hash_rate = total as f64 / elapsed;
+
+    println!("\n📊 Results:");
+    println!("   Hashes:      {}", total);
+    println!("   Elapsed:     {:.2}s", elapsed);
+    println!("   Hashrate:    {:.2} H/s ({:.2} H/s per thread)", hash_rate, hash_rate / num_threads as f64);
+
+    // Each zero bit in the difficulty mask halves the chance a hash qualifies
+    println!("\n⏱️  Estimated time to solution:");
+    println!("   {:>10}  {:>14}  {:>16}", "zero bits", "expected", "expected time");
+    for zero_bits in [8u32, 12, 16, 20, 24] {
+        let expected_hashes = 2f64.powi(zero_bits as i32);
+        let expected_secs = if hash_rate > 0.0 { expected_hashes / hash_rate } else { f64::INFINITY };
+        println!("   {:>10}  {:>14.0}  {:>16}", zero_bits, expected_hashes, format_duration_secs(expected_secs));
+    }
+
+    true
+}
+
+/// Wallet address used by the self-test
+const SELFTEST_WALLET: &str = "addr1selftest";
+
+/// Fixed challenges for the self-test: a harder one (8 zero bits) and an easy one (6 zero bits)
+fn selftest_challenges() -> Vec<Challenge> {
+    [("**SELFTEST01", "00FFFFFF"), ("**SELFTEST02", "03FFFFFF")]
+        .iter()
+        .map(|(id, difficulty)| Challenge {
+            challenge_id: id.to_string(),
+            challenge_number: None,
+            day: None,
+            issued_at: None,
+            difficulty: difficulty.to_string(),
+            no_pre_mine: "5e1f7e57".repeat(8),
+            latest_submission: "2099-12-31T23:59:59Z".to_string(),
+            no_pre_mine_hour: "0".to_string(),
+            version: None,
+        })
+        .collect()
+}
+
+/// Deterministic end-to-end run with a tiny ROM and fixed challenges, no network
+/// Exercises selection -> mining -> difficulty check -> solution export in a scratch directory
+/// Usage: selftest [--dir <path>] [--threads <n>]
+fn run_selftest(args: &[String]) -> bool {
+    let cli = CliArgs::parse(args);
+    let dir = cli.option("--dir")
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(|| env::temp_dir().join("scavenger-miner-selftest"));
+    // One thread by default so the nonce found is the same on every machine
+    let num_threads = cli.option("--threads")
+        .and_then(|s| s.parse::<usize>().ok())
+        .unwrap_or(1)
+        .max(1);
+
+    // Start from a clean scratch directory so earlier runs don't count as solved
+    paths::use_data_dir(&dir);
+    let _ = fs::remove_dir_all(paths::solutions_dir());
+    if let Some(db) = store::database_path() {
+        let _ = fs::remove_file(dir.join(db));
+    }
+    if let Err(e) = fs::create_dir_all(&dir).and_then(|_| env::set_current_dir(&dir)) {
+        println!("❌ Cannot use self-test directory {}: {}", dir.display(), e);
+        return false;
+    }
+    if let Err(e) = setup_directories() {
+        println!("❌ Failed to create output directories: {}", e);
+        return false;
+    }
+    println!("🧪 Self-test in {} ({} thread(s))", dir.display(), num_threads);
+
+    // Selection: the easier challenge must come first
+    let mut challenges = Vec::new();
+    apply_active_challenges(&mut challenges, selftest_challenges(), num_threads);
+    let Some(challenge) = select_challenge_for_wallet(SELFTEST_WALLET, &challenges, &challenge_filter::ChallengeFilter::default(), None) else {
+        println!("❌ Selection returned no challenge");
+        return false;
+    };
+    if challenge.challenge_id != "**SELFTEST02" {
+        println!("❌ Selection picked {} instead of the easiest challenge", challenge.challenge_id);
+        return false;
+    }
+
+    // Mining
+    let backend = backend::test_backend();
+    let rom = backend.build_rom(challenge.no_pre_mine.as_bytes());
+    let limits = MiningLimits {
+        max_hashes: Some(1_000_000),
+        max_duration: None,
+        check_interval: DEFAULT_LIMIT_CHECK_INTERVAL,
+        nonce_count: None,
+        share_bits: None,
+    };
+    let nonce = match mine_single_solution(Arc::clone(&rom), SELFTEST_WALLET, &challenge, num_threads, &limits, 0) {
+        MiningResult::Found(nonce, _) => nonce,
+        other => {
+            println!("❌ Mining did not find a solution: {:?}", other);
+            return false;
+        }
+    };
+
+    // Difficulty check, independent of the mining loop
+    let suffix = build_preimage_suffix(SELFTEST_WALLET, &challenge);
+    let hash = rom.hash(&construct_preimage_fast(nonce, &suffix));
+    let diff_bytes = hex::decode(&challenge.difficulty).unwrap_or_default();
+    if !check_difficulty(&hash, &diff_bytes) {
+        println!("❌ Nonce {:016x} does not meet difficulty {}", nonce, challenge.difficulty);
+        return false;
+    }
+
+    // Export, then the solved challenge must no longer be selected
+    let record = SolutionRecord {
+        wallet_address: SELFTEST_WALLET.to_string(),
+        challenge_id: challenge.challenge_id.clone(),
+        nonce: format!("{:016x}", nonce),
+        found_at: get_timestamp(),
+        submitted_at: None,
+        crypto_receipt: None,
+        status: "selftest".to_string(),
+        error_message: None,
+        retry_count: 0,
+        last_retry_at: None,
+        nonce_start: Some(format!("{:016x}", 0)),
+        next_retry_at: None,
+        verified: None,
+    };
+    if let Err(e) = export_solution(&record) {
+        println!("❌ Failed to export solution: {}", e);
+        return false;
+    }
+    match select_challenge_for_wallet(SELFTEST_WALLET, &challenges, &challenge_filter::ChallengeFilter::default(), None) {
+        Some(next) if next.challenge_id == "**SELFTEST01" => {}
+        other => {
+            println!("❌ Expected the remaining challenge after export, got {:?}", other.map(|c| c.challenge_id));
+            return false;
+        }
+    }
+
+    println!("✅ Self-test passed (nonce {:016x})", nonce);
+    true
+}
+
+/// Run a subcommand if one was given, returning its exit code
+fn run_subcommand(args: &[String]) -> Option<i32> {
+    let run: fn(&[String]) -> bool = match args.get(1).map(|s| s.as_str()) {
+        Some("probe-api") => run_probe_api,
+        Some("bench") => run_bench,
+        Some("analyze") => difficulty::run_analyze,
+        Some("simulate") => simulate::run_simulate,
+        Some("selftest") => run_selftest,
+        Some("verify") => verify::run_verify,
+        Some("mine") => mine::run_mine,
+        Some("coordinator") => coordinator::run_coordinator,
+        Some("worker") => worker::run_worker,
+        Some("pool") => coordinator::run_pool,
+        Some("pool-report") => pool::run_pool_report,
+        Some("status") => status::run_status,
+        Some("ctl") => ctl::run_ctl,
+        Some("reconcile") => reconcile::run_reconcile,
+        Some("archive") => archive::run_archive,
+        Some("export") => export::run_export,
+        Some("attempts") => attempts::run_attempts,
+        Some("export-hashrate") => hashrate_history::run_export_hashrate,
+        Some("export-state") => state::run_export_state,
+        Some("import-state") => state::run_import_state,
+        Some("migrate") => migrate::run_migrate,
+        Some("backup") => backup::run_backup,
+        Some("restore") => backup::run_restore,
+        #[cfg(feature = "sqlite")]
+        Some("migrate-store") => sqlite_store::run_migrate_store,
+        #[cfg(feature = "mock-api")]
+        Some("mock-api") => mock_api::run_mock_api,
+        _ => return None,
+    };
+
+    // Network settings (proxy) from the command line or config file apply to subcommands too
+    load_cli_args(&args[2..]);
+    Some(if run(&args[2..]) { 0 } else { 1 })
+}
+
+/// How often the background poller fetches the current challenge
+const CHALLENGE_POLL_INTERVAL_SECS: u64 = 300;
+/// Poll delay after a failed fetch (API down or flapping)
+const CHALLENGE_POLL_ERROR_RETRY_SECS: u64 = 30;
+/// Minimum time between forced refreshes (cache empty / all challenges solved)
+const FORCED_REFRESH_MIN_INTERVAL_SECS: u64 = 60;
+
+/// Server-sent events endpoint pushing each new challenge as it is published
+const CHALLENGE_STREAM_PATH: &str = "/challenge/stream";
+/// Reconnect delay after the stream drops (doubles up to the poll interval)
+const CHALLENGE_STREAM_RECONNECT_SECS: u64 = 5;
+
+/// Challenge carried by one stream event: a full challenge response or a bare challenge
+fn parse_stream_event(data: &str) -> Option<Challenge> {
+    serde_json::from_str::<ChallengeResponse>(data)
+        .map(|r| r.challenge)
+        .or_else(|_| serde_json::from_str::<Challenge>(data))
+        .ok()
+}
+
+/// Subscribe to the challenge stream and forward every pushed challenge
+/// Returns once the API turns out not to offer a stream (polling covers it) or the receiver is gone
+fn run_challenge_stream(fetched_tx: std::sync::mpsc::Sender<Challenge>) {
+    use std::io::BufRead;
+
+    let url = format!("{}{}", http::api_base(), CHALLENGE_STREAM_PATH);
+    // The connection stays open indefinitely, so no overall request timeout
+    let client = match http::client_builder().timeout(None).build() {
+        Ok(client) => client,
+        Err(_) => return,
+    };
+
+    let mut reconnect = Duration::from_secs(CHALLENGE_STREAM_RECONNECT_SECS);
+    let mut connected_once = false;
+    while !shutdown_requested() {
+        let response = http::send(http::get(&client, &url).header(reqwest::header::ACCEPT, "text/event-stream"));
+        let is_stream = |r: &reqwest::blocking::Response| {
+            r.status().is_success() && r.headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|v| v.starts_with("text/event-stream"))
+        };
+
+        match response {
+            Ok(response) if is_stream(&response) => {
+                if !connected_once {
+                    tracing::info!("📡 Subscribed to challenge stream, new challenges arrive without polling delay");
+                    connected_once = true;
+                }
+                reconnect = Duration::from_secs(CHALLENGE_STREAM_RECONNECT_SECS);
+
+                // Events are "data:" lines terminated by a blank line
+                let mut data = String::new();
+                for line in std::io::BufReader::new(response).lines() {
+                    let Ok(line) = line else { break };
+                    if let Some(payload) = line.strip_prefix("data:") {
+                        data.push_str(payload.trim_start());
+                    } else if line.is_empty() && !data.is_empty() {
+                        if let Some(challenge) = parse_stream_event(&data) {
+                            health::api_reachable();
+                            if fetched_tx.send(challenge).is_err() {
+                                return;
+                            }
+                        }
+                        data.clear();
+                    }
+                }
+            }
+            // Reachable but no stream here: stick to polling
+            Ok(response) if !connected_once => {
+                tracing::info!("ℹ️  No challenge stream available ({}), using polling only", response.status());
+                return;
+            }
+            Ok(_) | Err(_) => {}
+        }
+
+        if shutdown_requested() {
+            break;
+        }
+        if connected_once {
+            tracing::warn!("⚠️  Challenge stream disconnected, reconnecting in {}s (polling continues)", reconnect.as_secs());
+        }
+        sleep_unless_shutdown(reconnect);
+        reconnect = (reconnect * 2).min(Duration::from_secs(CHALLENGE_POLL_INTERVAL_SECS));
+    }
+}
+
+/// Background challenge poller
+/// Fetches while the main loop is mining and hands results over through a channel,
+/// so they are only applied to the cache at preemption points (between tasks).
+/// Challenges pushed over the stream endpoint go through the same channel
+struct ChallengePoller {
+    fetched_rx: std::sync::mpsc::Receiver<Challenge>,
+    refresh_tx: std::sync::mpsc::Sender<()>,
+    last_forced_refresh: Option<Instant>,
+}
+
+impl ChallengePoller {
+    /// Poll the API, or re-read `challenges_file` instead when running offline
+    fn start(challenges_file: Option<String>) -> Self {
+        let (fetched_tx, fetched_rx) = std::sync::mpsc::channel::<Challenge>();
+        let (refresh_tx, refresh_rx) = std::sync::mpsc::channel::<()>();
+
+        if challenges_file.is_none() {
+            let stream_tx = fetched_tx.clone();
+            thread::Builder::new()
+                .name("challenge-stream".to_string())
+                .spawn(move || run_challenge_stream(stream_tx))
+                .expect("failed to spawn challenge stream thread");
+        }
+
+        thread::Builder::new()
+            .name("challenge-poller".to_string())
+            .spawn(move || {
+                use std::sync::mpsc::RecvTimeoutError;
+
+                let mut wait = Duration::ZERO; // Fetch immediately on startup
+                // Bulk list on startup and forced refreshes, until the API turns out not to have one
+                let mut list_supported = true;
+                let mut list_wanted = true;
+                // Unchanged challenge documents come back as an empty 304
+                let mut validators = ChallengeValidators::default();
+
+                // Wake on timeout or refresh request; stop when the main loop drops the poller
+                loop {
+                    match refresh_rx.recv_timeout(wait) {
+                        Ok(()) => {
+                            // Someone is waiting for a result, so ask for the full document
+                            list_wanted = true;
+                            validators = ChallengeValidators::default();
+                        }
+                        Err(RecvTimeoutError::Timeout) => {}
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    }
+                    // Coalesce refresh requests that piled up while waiting
+                    while refresh_rx.try_recv().is_ok() {}
+
+                    // No API polling while paused
+                    if is_paused() {
+                        wait = Duration::from_secs(5);
+                        continue;
+                    }
+
+                    // Offline: the file is the only source, re-read so edits get picked up
+                    if let Some(path) = &challenges_file {
+                        wait = match load_challenges_file(path) {
+                            Ok(challenges) => {
+                                systemd::ready();
+                                if challenges.into_iter().any(|c| fetched_tx.send(c).is_err()) {
+                                    break;
+                                }
+                                Duration::from_secs(CHALLENGE_POLL_INTERVAL_SECS)
+                            }
+                            Err(e) => {
+                                tracing::warn!(error = %e, "⚠️  {}, will retry in {}s", e, CHALLENGE_POLL_ERROR_RETRY_SECS);
+                                Duration::from_secs(CHALLENGE_POLL_ERROR_RETRY_SECS)
+                            }
+                        };
+                        continue;
+                    }
+
+                    if list_supported && list_wanted {
+                        list_wanted = false;
+                        match fetch_active_challenge_list() {
+                            Ok(Some(challenges)) => {
+                                systemd::ready();
+                                tracing::info!("📚 Challenge list: {} challenge(s) still open", challenges.len());
+                                if challenges.into_iter().any(|c| fetched_tx.send(c).is_err()) {
+                                    break;
+                                }
+                            }
+                            Ok(None) => {
+                                tracing::info!("ℹ️  No challenge list endpoint, learning challenges from polling only");
+                                list_supported = false;
+                            }
+                            Err(e) => tracing::warn!(error = %e, "⚠️  Error fetching challenge list: {}", e),
+                        }
+                    }
+
+                    let fetched = fetch_challenge_if_changed(&mut validators);
+                    if fetched.is_ok() {
+                        systemd::ready();
+                    }
+                    wait = match fetched {
+                        Ok(Some(challenge)) => {
+                            if fetched_tx.send(challenge).is_err() {
+                                break;
+                            }
+                            Duration::from_secs(CHALLENGE_POLL_INTERVAL_SECS)
+                        }
+                        Ok(None) => Duration::from_secs(CHALLENGE_POLL_INTERVAL_SECS),
+                        Err(e) => {
+                            tracing::warn!(error = %e, "⚠️  Error fetching challenge: {}, will retry in {}s", e, CHALLENGE_POLL_ERROR_RETRY_SECS);
+                            Duration::from_secs(CHALLENGE_POLL_ERROR_RETRY_SECS)
+                        }
+                    };
+                }
+            })
+            .expect("failed to spawn challenge poller thread");
+
+        ChallengePoller {
+            fetched_rx,
+            refresh_tx,
+            last_forced_refresh: Some(Instant::now()), // The startup fetch counts as one
+        }
+    }
+
+    /// Challenges fetched since the last call (non-blocking)
+    fn take_fetched(&self) -> Vec<Challenge> {
+        self.fetched_rx.try_iter().collect()
+    }
+
+    /// Ask for an immediate fetch, at most once per FORCED_REFRESH_MIN_INTERVAL_SECS,
+    /// then wait up to `timeout` for a result. Returns whatever arrived
+    fn force_refresh(&mut self, timeout: Duration) -> Vec<Challenge> {
+        let allowed = self.last_forced_refresh
+            .is_none_or(|t| t.elapsed() >= Duration::from_secs(FORCED_REFRESH_MIN_INTERVAL_SECS));
+
+        if allowed {
+            self.last_forced_refresh = Some(Instant::now());
+            let _ = self.refresh_tx.send(());
+        } else {
+            tracing::info!("⏳ Forced refresh rate-limited, waiting for the regular poll");
+        }
+
+        let mut fetched = Vec::new();
+        let deadline = Instant::now() + timeout;
+        while !shutdown_requested() && fetched.is_empty() {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            if let Ok(challenge) = self.fetched_rx.recv_timeout((deadline - now).min(Duration::from_millis(250))) {
+                fetched.push(challenge);
+            }
+        }
+        fetched.extend(self.take_fetched());
+        fetched
+    }
+}
+
+/// Apply fetched challenges to the active list
+/// Adds new challenges if not present, removes expired challenges, and sorts by difficulty
+/// Returns whether the list changed
+fn apply_active_challenges(
+    challenges_cache: &mut Vec<Challenge>,
+    fetched: Vec<Challenge>,
+    num_threads: usize,
+) -> bool {
+    let mut added = false;
+    for current_challenge in fetched {
+        // Add to cache if not already present (check by challenge_id)
+        let already_exists = challenges_cache.iter().any(|c| c.challenge_id == current_challenge.challenge_id);
+        if !already_exists {
+            tracing::info!(challenge_id = %current_challenge.challenge_id, "📥 New challenge discovered: {}", current_challenge.challenge_id);
+            challenges_cache.push(current_challenge);
+            added = true;
+        }
+    }
+
+    // Filter out inactive challenges (where deadline is within 1 hour or already passed)
+    let initial_count = challenges_cache.len();
+    challenges_cache.retain(|c| {
+        let is_active = c.is_active();
+        if !is_active {
+            tracing::info!(challenge_id = %c.challenge_id, "⏰ Challenge {} expires soon (< 1 hour), removing from active list", c.challenge_id);
+        }
+        is_active
+    });
+    let removed_count = initial_count - challenges_cache.len();
+    if removed_count > 0 {
+        tracing::info!("🗑️  Removed {} challenge(s) expiring within 1 hour", removed_count);
+    }
+
+    // Sort using comprehensive comparison:
+    // 1. Expected hashes to a solution (fewer = easier, see difficulty.rs)
+    // 2. Latest submission (thread-count dependent):
+    //    - < 6 threads: newer first (faster refresh strategy)
+    //    - >= 6 threads: older first (less competition strategy)
+    // 3. Challenge ID (deterministic tiebreaker)
+    challenges_cache.sort_by(|a, b| a.compare_for_selection(b, num_threads));
+    added || removed_count > 0
+}
+
+/// Active challenges as of the last change, so challenges discovered in earlier runs that are
+/// still open aren't forgotten on restart (the API only serves the current one)
+const CHALLENGE_CACHE_FILE: &str = "challenges_cache.json";
+
+/// Challenges saved by an earlier run (empty if there is no readable cache)
+fn load_challenge_cache() -> Vec<Challenge> {
+    let path = paths::data_dir().join(CHALLENGE_CACHE_FILE);
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "⚠️  Ignoring unreadable challenge cache {}: {}", path.display(), e);
+        Vec::new()
+    })
+}
+
+/// Write the active challenges for the next run, and add new ones to the challenge history
+fn save_challenge_cache(challenges: &[Challenge]) {
+    let path = paths::data_dir().join(CHALLENGE_CACHE_FILE);
+    if let Err(e) = write_challenges(&path, challenges) {
+        tracing::warn!(error = %e, "⚠️  Failed to save challenge cache {}: {}", path.display(), e);
+    }
+
+    let history_path = paths::data_dir().join(CHALLENGE_HISTORY_FILE);
+    let mut history = load_challenges_file(&history_path.to_string_lossy()).unwrap_or_default();
+    let known = history.len();
+    for challenge in challenges {
+        if !history.iter().any(|c| c.challenge_id == challenge.challenge_id) {
+            history.push(challenge.clone());
+        }
+    }
+    if history.len() > known {
+        if let Err(e) = write_challenges(&history_path, &history) {
+            tracing::warn!(error = %e, "⚠️  Failed to save challenge history {}: {}", history_path.display(), e);
+        }
+    }
+}
+
+/// Every challenge seen while mining, expired ones included, for `simulate`
+const CHALLENGE_HISTORY_FILE: &str = "challenge_history.json";
+
+/// Replace a challenges file (temp file + rename, so a crash can't leave half of it)
+fn write_challenges(path: &Path, challenges: &[Challenge]) -> Result<(), MinerError> {
+    let tmp = path.with_extension("json.tmp");
+    let json = serde_json::to_string_pretty(challenges)?;
+    fs::write(&tmp, json)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// What a resubmission pass knows about the challenges its records belong to
+struct KnownChallenges {
+    /// The API's active list, if it offers one (a challenge missing from it is closed)
+    active: Option<Vec<Challenge>>,
+    /// Challenges saved by earlier runs (active and history) and the current one
+    saved: Vec<Challenge>,
+}
+
+impl KnownChallenges {
+    fn load() -> Self {
+        let active = fetch_active_challenge_list().ok().flatten();
+        let mut saved = load_challenge_cache();
+        let history_path = paths::data_dir().join(CHALLENGE_HISTORY_FILE);
+        saved.extend(load_challenges_file(&history_path.to_string_lossy()).unwrap_or_default());
+        if active.is_none() {
+            saved.extend(fetch_current_challenge().ok());
+        }
+        Self { active, saved }
+    }
+}
+
+/// Check if a solution's own challenge still accepts submissions (current time < latest_submission)
+/// Uses the API's active list when there is one, otherwise the saved challenges; a challenge
+/// nothing is known about is assumed open, and the API rejects the submission if it isn't
+fn is_challenge_still_open(solution: &SolutionRecord, known: &KnownChallenges) -> bool {
+    let find = |challenges: &[Challenge]| challenges.iter().find(|c| c.challenge_id == solution.challenge_id).cloned();
+    match &known.active {
+        Some(active) => find(active).is_some_and(|c| c.accepts_submissions()),
+        None => find(&known.saved).is_none_or(|c| c.accepts_submissions()),
+    }
+}
+
+/// Key of a wallet-challenge pair in the in-flight list (its JSON record path)
+fn solution_file_name(wallet_address: &str, challenge_id: &str) -> String {
+    store::JsonStore::file_name(wallet_address, challenge_id)
+}
+
+/// Check if a solution already exists (or is waiting to be submitted) for a wallet-challenge pair
+fn solution_exists(wallet_address: &str, challenge_id: &str) -> bool {
+    submission_in_flight(wallet_address, challenge_id)
+        || store::get().exists(wallet_address, challenge_id)
+        || credited::is_credited(wallet_address, challenge_id)
+}
+
+/// Select the best challenge for a wallet (easiest unsolved challenge its filter allows)
+fn select_challenge_for_wallet(
+    wallet_address: &str,
+    challenges: &[Challenge],
+    filter: &challenge_filter::ChallengeFilter,
+    spreader: Option<&mut spread::Spreader>,
+) -> Option<Challenge> {
+    let mut candidates = challenges
+        .iter()
+        .filter(|c| {
+            filter.allows(&c.challenge_id)
+                && !solution_exists(wallet_address, &c.challenge_id)
+                && !dashboard::was_skipped(wallet_address, &c.challenge_id)
+                && !redis_claims::taken(wallet_address, &c.challenge_id)
+        });
+
+    match spreader {
+        // Spread mode: weighted round-robin over everything this wallet can still mine
+        Some(spreader) => spreader.pick(&candidates.collect::<Vec<_>>(), challenges),
+        // Challenges are already sorted (most likely to be solved in time, then easiest first)
+        // This maximizes solutions/hour by solving easy challenges quickly
+        None => candidates.next().cloned(),
+    }
 }
 
 /// Result of Scavenger Mine submission
+/// Rejections and network failures come back as `Err`, see `MinerError`
 #[derive(Debug)]
 enum SubmitResult {
     Success(CryptoReceipt),
-    Failed(String), // Error message
+    RateLimited(Duration), // API asked us to come back later (429 / 503 + Retry-After)
+}
+
+/// Wait used for a 429 without a Retry-After header
+const DEFAULT_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+/// Longest Retry-After we sleep through in place; longer ones are handed back to the caller
+const MAX_INLINE_RATE_LIMIT_WAIT: Duration = Duration::from_secs(120);
+
+/// Timestamp a queued submission may be retried at
+fn retry_at_timestamp(retry_after: Duration) -> String {
+    let datetime = chrono::DateTime::from_timestamp((now_unix_secs() + retry_after.as_secs()) as i64, 0)
+        .unwrap_or_default();
+    datetime.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+/// Delay requested by a rate-limited response: any 429, or a 503 that carries Retry-After
+fn rate_limit_delay(response: &reqwest::blocking::Response) -> Option<Duration> {
+    let retry_after = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| {
+            // Either delay-seconds or an HTTP date
+            v.trim().parse::<u64>().ok().map(Duration::from_secs).or_else(|| {
+                let at = chrono::DateTime::parse_from_rfc2822(v.trim()).ok()?;
+                Some(Duration::from_secs((at.timestamp() - now_utc().timestamp()).max(0) as u64))
+            })
+        });
+
+    match response.status().as_u16() {
+        429 => Some(retry_after.unwrap_or(DEFAULT_RATE_LIMIT_WAIT)),
+        503 => retry_after,
+        _ => None,
+    }
 }
 
 /// Submit nonce to Scavenger Mine API
@@ -625,23 +2193,33 @@ fn submit_to_scavenger(
     wallet_address: &str,
     challenge_id: &str,
     nonce: u64,
-) -> Result<SubmitResult, Box<dyn std::error::Error>> {
+) -> Result<SubmitResult, MinerError> {
     let url = format!("{}/solution/{}/{}/{:016x}",
-                     SCAVENGER_API_BASE, wallet_address, challenge_id, nonce);
-
-    let client = reqwest::blocking::Client::builder()
-        .gzip(true)
-        .build()?;
-
-    let response = client.post(&url)
-        .header("Content-Type", "application/json")
-        .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
-        .header("Accept", "application/json, text/plain, */*")
-        .header("Accept-Language", "en-US,en;q=0.9")
-        .header("Accept-Encoding", "gzip, deflate, br")
-        .header("Connection", "keep-alive")
-        .json(&serde_json::json!({}))
-        .send()?;
+                     http::api_base(), wallet_address, challenge_id, nonce);
+
+    let client = http::client()?;
+
+    let response = with_api_retry("submit solution", || {
+        let request = http::post(&client, &url)
+            .header("Content-Type", "application/json")
+            .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
+            .header("Accept", "application/json, text/plain, */*")
+            .header("Accept-Language", "en-US,en;q=0.9")
+            .header("Accept-Encoding", "gzip, deflate, br")
+            .header("Connection", "keep-alive")
+            .json(&serde_json::json!({}));
+        let response = http::send(request)?;
+        match rate_limit_delay(&response) {
+            Some(retry_after) => Err(MinerError::RateLimited { retry_after }),
+            None => Ok(response),
+        }
+    });
+    let response = match response {
+        Ok(response) => response,
+        // Still rate limited after waiting: queue it instead of counting it as a failure
+        Err(MinerError::RateLimited { retry_after }) => return Ok(SubmitResult::RateLimited(retry_after)),
+        Err(e) => return Err(e),
+    };
 
     let status = response.status();
 
@@ -654,230 +2232,782 @@ fn submit_to_scavenger(
                     Ok(SubmitResult::Success(receipt))
                 } else {
                     let error_msg = "API returned success but no crypto_receipt".to_string();
-                    log_mining_progress(&format!("⚠️  {}", error_msg));
-                    Ok(SubmitResult::Failed(error_msg))
+                    tracing::warn!("⚠️  {}", error_msg);
+                    Err(MinerError::Protocol(error_msg))
                 }
             }
             Err(e) => {
                 let error_msg = format!("Failed to parse response: {}", e);
-                log_mining_progress(&format!("⚠️  {}", error_msg));
-                Ok(SubmitResult::Failed(error_msg))
+                tracing::warn!("⚠️  {}", error_msg);
+                Err(MinerError::Parse(error_msg))
             }
         }
     } else {
         // Get response text for error logging
         let error_text = response.text().unwrap_or_else(|_| "Unable to read response".to_string());
-        let error_msg = format!("HTTP {}: {}", status.as_u16(), error_text);
-        log_mining_progress(&format!("❌ Scavenger API error: {}", error_msg));
-        Ok(SubmitResult::Failed(error_msg))
+        let error = MinerError::api(status.as_u16(), error_text);
+        tracing::error!(error = %error, "❌ Scavenger API error: {}", error);
+        Err(error)
     }
 }
 
-/// Load user wallets from file
-fn load_user_wallets(path: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
-    if !Path::new(path).exists() {
-        return Err(format!("Wallets file not found: {}", path).into());
+/// A found solution waiting for the submitter thread
+struct FoundSolution {
+    wallet_address: String,
+    challenge_id: String,
+    nonce: u64,
+    nonce_start: u64,
+    found_at: String,
+}
+
+/// Solution files of submissions still in the queue, so the same task isn't mined again meanwhile
+static SUBMISSIONS_IN_FLIGHT: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Whether the submitter thread still has this pair (its record is saved as `queued` meanwhile)
+fn submission_in_flight(wallet_address: &str, challenge_id: &str) -> bool {
+    SUBMISSIONS_IN_FLIGHT.lock().unwrap().contains(&solution_file_name(wallet_address, challenge_id))
+}
+
+/// The record saved while a found solution waits in the queue, due at once if the miner stops first
+fn queued_record(job: &FoundSolution) -> SolutionRecord {
+    SolutionRecord {
+        wallet_address: job.wallet_address.clone(),
+        challenge_id: job.challenge_id.clone(),
+        nonce: format!("{:016x}", job.nonce),
+        found_at: job.found_at.clone(),
+        submitted_at: None,
+        crypto_receipt: None,
+        status: "queued".to_string(),
+        error_message: None,
+        retry_count: 0,
+        last_retry_at: None,
+        nonce_start: Some(format!("{:016x}", job.nonce_start)),
+        next_retry_at: Some(job.found_at.clone()),
+        verified: None,
     }
+}
 
-    let content = fs::read_to_string(path)?;
-    let wallets: Vec<String> = content
-        .lines()
-        .map(|line| line.trim())
-        .filter(|line| !line.is_empty() && !line.starts_with('#'))
-        .map(|line| line.to_string())
-        .collect();
+/// Submit a found solution and record the outcome in `solutions/`
+/// Receipts and failures are reported through `events` for the main loop's notifier and statistics
+fn submit_found_solution(
+    job: &FoundSolution,
+    retry_policies: &retry_policy::RetryPolicies,
+    events: &std::sync::mpsc::Sender<notify::Event>,
+) {
+    let _submission = tracing::info_span!(
+        "submission",
+        challenge_id = %job.challenge_id,
+        wallet = %job.wallet_address,
+        nonce = %format!("{:016x}", job.nonce)
+    )
+    .entered();
+    match submit_to_scavenger(&job.wallet_address, &job.challenge_id, job.nonce) {
+        Ok(SubmitResult::Success(crypto_receipt)) => {
+            tracing::info!(target: logging::SOLUTIONS, "✅ Submitted to Scavenger Mine");
+            let verified = receipt::verify(&crypto_receipt);
+            let _ = events.send(notify::Event::Receipt {
+                wallet_address: job.wallet_address.clone(),
+                challenge_id: job.challenge_id.clone(),
+            });
+            wallet_stats::record_accepted(&job.wallet_address);
+
+            // Export solution with crypto receipt
+            let record = SolutionRecord {
+                wallet_address: job.wallet_address.clone(),
+                challenge_id: job.challenge_id.clone(),
+                nonce: format!("{:016x}", job.nonce),
+                found_at: job.found_at.clone(),
+                submitted_at: Some(get_timestamp()),
+                crypto_receipt: Some(crypto_receipt),
+                status: "submitted".to_string(),
+                error_message: None,
+                retry_count: 0,
+                last_retry_at: None,
+                nonce_start: Some(format!("{:016x}", job.nonce_start)),
+                next_retry_at: None,
+                verified,
+            };
+
+            if let Err(e) = export_solution(&record) {
+                tracing::warn!(error = %e, "⚠️  Failed to export solution: {}", e);
+            }
+        }
+        Err(e) if !e.is_network() => {
+            let error_msg = e.to_string();
+            tracing::error!(error = %error_msg, "❌ Scavenger submission failed: {}", error_msg);
+            let _ = events.send(notify::Event::SubmitFailed {
+                wallet_address: job.wallet_address.clone(),
+                challenge_id: job.challenge_id.clone(),
+                error: error_msg.clone(),
+            });
+
+            // Check if this is a non-retriable error
+            let status = match e.rejection() {
+                Some(Rejection::Duplicate) => {
+                    tracing::info!("   ℹ️  Solution already submitted elsewhere (won't retry)");
+                    wallet_stats::record_duplicate(&job.wallet_address);
+                    "duplicate".to_string()
+                }
+                Some(Rejection::InvalidNonce) => {
+                    tracing::info!("   ℹ️  Invalid nonce (won't retry)");
+                    "invalid_nonce".to_string()
+                }
+                Some(Rejection::WindowClosed) => {
+                    tracing::info!("   ℹ️  Submission window closed (won't retry)");
+                    "challenge_closed".to_string()
+                }
+                _ => {
+                    tracing::info!(
+                        "   🔄 Will retry after {}",
+                        format_duration_secs(retry_policies.for_wallet(&job.wallet_address).interval_secs as f64),
+                    );
+                    "failed".to_string()
+                }
+            };
+
+            // Export solution with error
+            let record = SolutionRecord {
+                wallet_address: job.wallet_address.clone(),
+                challenge_id: job.challenge_id.clone(),
+                nonce: format!("{:016x}", job.nonce),
+                found_at: job.found_at.clone(),
+                submitted_at: Some(get_timestamp()),
+                crypto_receipt: None,
+                status,
+                error_message: Some(error_msg),
+                retry_count: 0,
+                last_retry_at: None,
+                nonce_start: Some(format!("{:016x}", job.nonce_start)),
+                next_retry_at: None,
+                verified: None,
+            };
+
+            if let Err(e) = export_solution(&record) {
+                tracing::warn!(error = %e, "⚠️  Failed to export solution: {}", e);
+            }
+        }
+        Ok(SubmitResult::RateLimited(retry_after)) => {
+            tracing::info!(
+                "⏳ Rate limited by Scavenger Mine, submission queued for {}",
+                format_duration_secs(retry_after.as_secs_f64()),
+            );
+
+            // Queue it - retried once the server's delay has passed, not counted as a failure
+            let record = SolutionRecord {
+                wallet_address: job.wallet_address.clone(),
+                challenge_id: job.challenge_id.clone(),
+                nonce: format!("{:016x}", job.nonce),
+                found_at: job.found_at.clone(),
+                submitted_at: None,
+                crypto_receipt: None,
+                status: "queued".to_string(),
+                error_message: Some("Rate limited by the API".to_string()),
+                retry_count: 0,
+                last_retry_at: None,
+                nonce_start: Some(format!("{:016x}", job.nonce_start)),
+                next_retry_at: Some(retry_at_timestamp(retry_after)),
+                verified: None,
+            };
+
+            if let Err(e) = export_solution(&record) {
+                tracing::warn!(error = %e, "⚠️  Failed to export solution: {}", e);
+            }
+        }
+        Err(e) => {
+            tracing::error!(error = %e, "❌ Network error submitting to Scavenger: {}", e);
+            let _ = events.send(notify::Event::SubmitFailed {
+                wallet_address: job.wallet_address.clone(),
+                challenge_id: job.challenge_id.clone(),
+                error: format!("network error: {}", e),
+            });
+            tracing::info!(
+                "   🔄 Will retry after {}",
+                format_duration_secs(retry_policies.for_wallet(&job.wallet_address).interval_secs as f64),
+            );
 
-    if wallets.is_empty() {
-        return Err("No valid wallet addresses found in file".into());
+            // Export solution with error - will be retried
+            let record = SolutionRecord {
+                wallet_address: job.wallet_address.clone(),
+                challenge_id: job.challenge_id.clone(),
+                nonce: format!("{:016x}", job.nonce),
+                found_at: job.found_at.clone(),
+                submitted_at: None,
+                crypto_receipt: None,
+                status: "error: network".to_string(),
+                error_message: Some(format!("Network error: {}", e)),
+                retry_count: 0,
+                last_retry_at: None,
+                nonce_start: Some(format!("{:016x}", job.nonce_start)),
+                next_retry_at: None,
+                verified: None,
+            };
+
+            if let Err(e) = export_solution(&record) {
+                tracing::warn!(error = %e, "⚠️  Failed to export solution: {}", e);
+            }
+        }
     }
 
-    Ok(wallets)
+    // Whatever the answer, this rig has the nonce and resubmits it if needed
+    redis_claims::record_done(&job.wallet_address, &job.challenge_id);
+}
+
+/// Pass submission outcomes to the notifier and telemetry; returns the number of receipts among them
+fn record_submit_events(
+    events: Vec<notify::Event>,
+    notifier: &mut notify::Notifier,
+    telemetry: &telemetry::Reporter,
+) -> u64 {
+    let mut receipts = 0;
+    for event in events {
+        if matches!(event, notify::Event::Receipt { .. }) {
+            receipts += 1;
+        }
+        telemetry.observe(&event);
+        notifier.event(event);
+    }
+    receipts
+}
+
+/// Background submitter: found solutions are submitted one at a time on a dedicated
+/// thread, so mining the next task doesn't wait for the API
+struct SubmitQueue {
+    jobs_tx: Option<std::sync::mpsc::Sender<FoundSolution>>,
+    events_rx: std::sync::mpsc::Receiver<notify::Event>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl SubmitQueue {
+    fn start(retry_policies: Arc<retry_policy::RetryPolicies>) -> Self {
+        let (jobs_tx, jobs_rx) = std::sync::mpsc::channel::<FoundSolution>();
+        let (events_tx, events_rx) = std::sync::mpsc::channel::<notify::Event>();
+
+        let handle = thread::Builder::new()
+            .name("submitter".to_string())
+            .spawn(move || {
+                // Runs until the queue is closed and drained
+                for job in jobs_rx {
+                    submit_found_solution(&job, &retry_policies, &events_tx);
+                    store::get().release(&queued_record(&job));
+                    let file = solution_file_name(&job.wallet_address, &job.challenge_id);
+                    SUBMISSIONS_IN_FLIGHT.lock().unwrap().retain(|f| *f != file);
+                }
+            })
+            .expect("failed to spawn submitter thread");
+
+        SubmitQueue {
+            jobs_tx: Some(jobs_tx),
+            events_rx,
+            handle: Some(handle),
+        }
+    }
+
+    /// Save the solution as `queued`, then queue it for submission (the outcome replaces the record)
+    /// A crash or forced quit before the submitter gets to it leaves the record for the next retry pass;
+    /// meanwhile retry passes, here or on miners sharing the store, leave it alone
+    fn push(&self, job: FoundSolution) {
+        SUBMISSIONS_IN_FLIGHT.lock().unwrap().push(solution_file_name(&job.wallet_address, &job.challenge_id));
+        let record = queued_record(&job);
+        match store::get().save(&record) {
+            Ok(_) => {
+                let _ = store::get().claim(&record);
+            }
+            Err(e) => tracing::warn!(error = %e, "⚠️  Failed to save the found solution before submitting it: {}", e),
+        }
+        if let Some(tx) = &self.jobs_tx {
+            let _ = tx.send(job);
+        }
+    }
+
+    /// Submission outcomes since the last call (non-blocking)
+    fn take_events(&self) -> Vec<notify::Event> {
+        self.events_rx.try_iter().collect()
+    }
+
+    /// Stop accepting work and wait for queued submissions to finish; returns their outcomes
+    fn finish(&mut self) -> Vec<notify::Event> {
+        let pending = SUBMISSIONS_IN_FLIGHT.lock().unwrap().len();
+        if pending > 0 {
+            tracing::info!("📮 Waiting for {} queued submission(s)...", pending);
+        }
+        self.jobs_tx = None;
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+        self.take_events()
+    }
+}
+
+/// Every row of a wallets source for the miner: exits if it can't be parsed, with help on
+/// creating the file if there is none
+fn read_wallet_rows_or_exit(source: &str) -> Vec<wallets::WalletEntry> {
+    let content = match wallets::read(source) {
+        Ok(content) => content,
+        Err(e) if !wallets::is_file(source) => {
+            tracing::error!(error = %e, "❌ Error loading {}: {}", wallets::source_name(source), e);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            tracing::error!(error = %e, "❌ Error loading wallets: {}", e);
+            eprintln!("\n❌ ERROR: Could not load wallets file '{}'", source);
+            eprintln!("\n📝 Please create this file with one wallet address per line");
+            eprintln!("   Example content:");
+            eprintln!("   addr1q8upjxynn626c772r5nzym...");
+            eprintln!("   addr1qpxvug56xgecxhuzv3c60u4...");
+            eprintln!("\n💡 Tip: The file should be in the same folder as this executable");
+            eprintln!("   Current folder: {}", env::current_dir().unwrap().display());
+            eprintln!("\nPress Enter to exit...");
+
+            // Wait for user to acknowledge in interactive mode
+            let args: Vec<String> = env::args().collect();
+            if args.len() == 1 {
+                let mut input = String::new();
+                std::io::stdin().read_line(&mut input).unwrap();
+            }
+
+            std::process::exit(1);
+        }
+    };
+    match wallets::parse(&content, wallets::Format::detect(source, &content)) {
+        Ok(rows) => rows,
+        Err(e) => {
+            tracing::error!(error = %e, "❌ {}, {}", wallets::source_name(source), e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Load user wallets from a wallets source (see wallets.rs)
+fn load_user_wallets(source: &str) -> Result<Vec<String>, MinerError> {
+    Ok(wallets::load(source)?.into_iter().map(|wallet| wallet.address).collect())
+}
+
+/// Default number of hashes between per-thread limit checks
+const DEFAULT_LIMIT_CHECK_INTERVAL: u64 = 1000;
+
+/// Give-up criteria for a single mining task
+struct MiningLimits {
+    max_hashes: Option<u64>,
+    max_duration: Option<Duration>,
+    check_interval: u64,      // Hashes between per-thread limit checks
+    nonce_count: Option<u64>, // Nonces from the start offset to try, for assigned ranges (worker.rs)
+    share_bits: Option<u32>,  // Also report hashes meeting this many constrained bits as pool shares (worker.rs)
+}
+
+/// Throwaway hashes each thread runs before the measured mining starts
+const WARMUP_HASHES_PER_THREAD: u64 = 8;
+
+/// Nonces handed to a mining thread at a time
+const NONCE_CHUNK_SIZE: u64 = 1 << 20;
+
+/// `pause_started` value while mining isn't paused
+const NOT_PAUSED: u64 = u64::MAX;
+
+/// Stop mining a challenge this long before its `latest_submission`, leaving time to submit
+const DEADLINE_ABORT_MARGIN_SECS: i64 = 60;
+
+/// Instant at which mining a challenge should stop, or None if the deadline can't be parsed
+fn mining_deadline(challenge: &Challenge) -> Option<Instant> {
+    let deadline = chrono::DateTime::parse_from_rfc3339(&challenge.latest_submission).ok()?;
+    let remaining = (deadline.with_timezone(&chrono::Utc) - now_utc()).num_seconds() - DEADLINE_ABORT_MARGIN_SECS;
+    Some(Instant::now() + Duration::from_secs(remaining.max(0) as u64))
 }
 
 /// Result of mining operation
+#[derive(Debug)]
 enum MiningResult {
-    Found(u64),              // Solution found with nonce
-    TooHard(u64, u64),       // Exceeded threshold: (total_hashes, duration_secs)
+    Found(u64, u64),         // Solution found: (nonce, total_hashes)
+    TooHard(u64, u64),       // Exceeded hash or time threshold: (total_hashes, duration_secs)
+    Interrupted(u64),        // Shutdown requested: total_hashes
+    Skipped(u64),            // Skipped from the dashboard: total_hashes
+    DeadlinePassed(u64),     // Challenge deadline too close to submit: total_hashes
     NotFound,                // No solution found
 }
 
+impl MiningResult {
+    /// Hashes computed for this task
+    fn hashes(&self) -> u64 {
+        match *self {
+            MiningResult::Found(_, hashes)
+            | MiningResult::TooHard(hashes, _)
+            | MiningResult::Interrupted(hashes)
+            | MiningResult::Skipped(hashes)
+            | MiningResult::DeadlinePassed(hashes) => hashes,
+            MiningResult::NotFound => 0,
+        }
+    }
+
+    /// How the task ended, as recorded in the attempt history
+    fn outcome(&self) -> &'static str {
+        match self {
+            MiningResult::Found(..) => "found",
+            MiningResult::TooHard(..) => "too_hard",
+            MiningResult::Interrupted(_) => "interrupted",
+            MiningResult::Skipped(_) => "skipped",
+            MiningResult::DeadlinePassed(_) => "deadline",
+            MiningResult::NotFound => "not_found",
+        }
+    }
+}
+
+/// Build a rayon thread pool with exactly `num_threads` workers and processor group affinity
+fn build_mining_pool(num_threads: usize) -> rayon::ThreadPool {
+    // Use atomic counter to track thread indices reliably (thread name parsing may fail)
+    let thread_counter = Arc::new(AtomicU64::new(0));
+
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .spawn_handler({
+            let counter = thread_counter.clone();
+            move |thread| {
+                // Atomically get the next thread index
+                let thread_idx = counter.fetch_add(1, Ordering::SeqCst) as usize;
+
+                let mut b = std::thread::Builder::new();
+                if let Some(name) = thread.name() {
+                    b = b.name(name.to_owned());
+                }
+                if let Some(stack_size) = thread.stack_size() {
+                    b = b.stack_size(stack_size);
+                }
+                b.spawn(move || {
+                    // Set processor group affinity on Windows for >64 logical processors
+                    #[cfg(windows)]
+                    {
+                        set_thread_processor_group_affinity(thread_idx);
+                    }
+                    if LOW_PRIORITY_THREADS.load(Ordering::Relaxed) {
+                        if !lower_current_thread_priority() && thread_idx == 0 {
+                            tracing::warn!("⚠️  Could not lower mining thread priority (--nice)");
+                        }
+                    } else if !qos::set_mining_thread_qos() && thread_idx == 0 {
+                        tracing::warn!("⚠️  Could not set the QoS class of the mining threads");
+                    }
+                    thread.run()
+                })?;
+                Ok(())
+            }
+        })
+        .build()
+        .unwrap()
+}
+
+/// Number of mining threads for a CPU usage percentage
+fn threads_for_cpu_usage(cpu_usage: f64) -> usize {
+    ((get_total_logical_processors() as f64 * cpu_usage / 100.0).ceil() as usize).max(1)
+}
+
 /// Mine a single solution using Rayon for optimal CPU utilization
 fn mine_single_solution(
-    rom: Arc<Rom>,
+    rom: Arc<dyn HashRom>,
     address: &str,
     challenge: &Challenge,
     num_threads: usize,
-    max_hashes: Option<u64>,
+    limits: &MiningLimits,
+    start_offset: u64,
 ) -> MiningResult {
-    // Use atomic counter to track thread indices reliably (thread name parsing may fail)
-    let thread_counter = Arc::new(AtomicU64::new(0));
+    let max_hashes = limits.max_hashes;
+    let max_duration = limits.max_duration;
+
+    // Every thread checks the shared hash count against the limit, independent of how often
+    // progress is logged; fast threads keep hashing while a throttled one lags behind
+    let check_interval = max_hashes
+        .map_or(limits.check_interval, |max_h| limits.check_interval.min(max_h))
+        .max(1);
 
     // Decode difficulty once before mining (optimization - avoids repeated hex decoding in hot loop)
     let diff_bytes = match hex::decode(&challenge.difficulty) {
         Ok(bytes) => bytes,
         Err(_) => {
-            log_mining_progress(&format!("❌ Invalid difficulty hex string: {}", challenge.difficulty));
+            tracing::error!("❌ Invalid difficulty hex string: {}", challenge.difficulty);
             return MiningResult::NotFound;
         }
     };
 
-    // Build preimage suffix once (optimization - avoids 6 extend_from_slice calls per nonce)
-    let preimage_suffix = build_preimage_suffix(address, challenge);
-    let preimage_suffix = Arc::new(preimage_suffix);
+    // Hashes meeting the easier share mask are collected for a pool (worker.rs)
+    let share_mask = limits.share_bits.map(|bits| difficulty::relaxed_mask(&diff_bytes, bits));
 
-    // Configure rayon thread pool to use exact number of threads with processor group affinity
-    let pool = rayon::ThreadPoolBuilder::new()
-        .num_threads(num_threads)
-        .spawn_handler({
-            let counter = thread_counter.clone();
-            move |thread| {
-                // Atomically get the next thread index
-                #[allow(unused_variables)]  // Used on Windows for thread affinity
-                let thread_idx = counter.fetch_add(1, Ordering::SeqCst) as usize;
+    // Build preimage suffix once (optimization - avoids 6 extend_from_slice calls per nonce)
+    let preimage_suffix = build_preimage_suffix(address, challenge);
+    let preimage_suffix = Arc::new(preimage_suffix);
 
-                let mut b = std::thread::Builder::new();
-                if let Some(name) = thread.name() {
-                    b = b.name(name.to_owned());
-                }
-                if let Some(stack_size) = thread.stack_size() {
-                    b = b.stack_size(stack_size);
-                }
-                b.spawn(move || {
-                    // Set processor group affinity on Windows for >64 logical processors
-                    #[cfg(windows)]
-                    {
-                        set_thread_processor_group_affinity(thread_idx);
-                    }
-                    thread.run()
-                })?;
-                Ok(())
-            }
-        })
-        .build()
-        .unwrap();
+    // Configure rayon thread pool to use exact number of threads with processor group affinity
+    let pool = build_mining_pool(num_threads);
+
+    // Wall-clock cutoff: pauses don't move the challenge deadline
+    let deadline = mining_deadline(challenge);
+    if deadline.is_some_and(|d| d <= Instant::now()) {
+        tracing::info!("⌛ Challenge deadline is too close, not starting");
+        return MiningResult::DeadlinePassed(0);
+    }
+    // No App Nap while this task is mined (macOS)
+    let _app_nap = qos::MiningActivity::begin();
 
     let found = Arc::new(AtomicBool::new(false));
+    let deadline_hit = Arc::new(AtomicBool::new(false));
+    let paused_millis = Arc::new(AtomicU64::new(0)); // Time spent paused, excluded from limits and hashrate
+    let pause_started = AtomicU64::new(NOT_PAUSED); // Millis since start_time when the current pause began
+    let hash_limit_hit = Arc::new(AtomicBool::new(false));
+    let time_limit_hit = Arc::new(AtomicBool::new(false));
     let hash_count = Arc::new(AtomicU64::new(0));
+    // Per-thread counters so slow cores (throttling, bad affinity) show up in the logs
+    let thread_hashes: Arc<Vec<AtomicU64>> = Arc::new((0..num_threads).map(|_| AtomicU64::new(0)).collect());
     let result: Arc<Mutex<Option<u64>>> = Arc::new(Mutex::new(None));
 
-    // Strided approach: each thread gets start_nonce = thread_id, stride = num_threads
-    // Thread 0: 0, 4, 8, 12, ...
-    // Thread 1: 1, 5, 9, 13, ...
-    // Thread 2: 2, 6, 10, 14, ...
-    // Thread 3: 3, 7, 11, 15, ...
-    // This provides better load balancing and lower variance than range partitioning
-    let stride = num_threads as u64;
-    let work_assignments: Vec<(u64, usize)> = (0..num_threads)
-        .map(|thread_id| {
-            let start_nonce = thread_id as u64;
-            (start_nonce, thread_id)
-        })
-        .collect();
+    // Shared nonce dispenser: threads pull the next NONCE_CHUNK_SIZE range whenever they finish one,
+    // so a throttled core simply takes fewer chunks instead of lagging on a fixed lane
+    // A non-zero start_offset shifts every range so separate machines don't overlap
+    let next_chunk = AtomicU64::new(0);
+    let thread_ids: Vec<usize> = (0..num_threads).collect();
+
+    // Warmup: every pool thread reads its slice of the ROM and runs a few throwaway hashes,
+    // so cold caches and TLBs don't skew the hashrate measured below
+    let warmup_start = Instant::now();
+    pool.broadcast(|ctx| {
+        rom.pre_touch(ctx.index(), ctx.num_threads());
+        for i in 0..WARMUP_HASHES_PER_THREAD {
+            let preimage = construct_preimage_fast(i, b"warmup");
+            std::hint::black_box(rom.hash(&preimage));
+        }
+    });
+    tracing::info!("🔥 Warmup complete in {:.2?} ({} hashes/thread)", warmup_start.elapsed(), WARMUP_HASHES_PER_THREAD);
 
+    // Measurement clock starts after warmup
     let start_time = Instant::now();
     let last_log_time = Arc::new(Mutex::new(Instant::now()));
 
+    // Pool threads don't inherit the caller's span, so each one opens its own under it
+    let task_span = tracing::Span::current();
+    dashboard::task_started(&challenge.challenge_id, address, &challenge.difficulty, deadline, Arc::clone(&thread_hashes));
+
     // Use rayon's parallel iterator for better CPU saturation
     pool.install(|| {
-        work_assignments.par_iter().for_each(|(start_nonce, thread_id)| {
-            let mut nonce = *start_nonce;
+        thread_ids.par_iter().for_each(|thread_id| {
+            let _thread = tracing::info_span!(parent: &task_span, "thread", thread = *thread_id).entered();
+            let mut nonce = 0u64;
+            let mut chunk_remaining = 0u64;
             let mut local_count = 0u64;
             let suffix = Arc::clone(&preimage_suffix);
 
-            // Each thread increments by stride for interleaved nonce testing
             loop {
-                if found.load(Ordering::Relaxed) {
+                if chunk_remaining == 0 {
+                    let chunk_start = next_chunk.fetch_add(NONCE_CHUNK_SIZE, Ordering::Relaxed);
+                    nonce = start_offset.wrapping_add(chunk_start);
+                    chunk_remaining = match limits.nonce_count {
+                        // An assigned range is done once every nonce in it was tried
+                        Some(count) if chunk_start >= count => break,
+                        Some(count) => (count - chunk_start).min(NONCE_CHUNK_SIZE),
+                        None => NONCE_CHUNK_SIZE,
+                    };
+                }
+
+                if found.load(Ordering::Relaxed) || shutdown_requested() || dashboard::skip_requested() {
                     break;
                 }
 
                 let preimage = construct_preimage_fast(nonce, &suffix);
-                let result_hash = hash(&preimage, &rom, NB_LOOPS, NB_INSTRS);
+                let result_hash = rom.hash(&preimage);
 
                 hash_count.fetch_add(1, Ordering::Relaxed);
                 local_count += 1;
+                thread_hashes[*thread_id].store(local_count, Ordering::Relaxed);
 
+                if share_mask.as_ref().is_some_and(|mask| check_difficulty(&result_hash, mask)) {
+                    worker::record_share(nonce);
+                }
                 if check_difficulty(&result_hash, &diff_bytes) {
                     found.store(true, Ordering::Relaxed);
-                    log_mining_progress(&format!("🎉 [Thread {}] Found solution! Nonce: {:016x}", thread_id, nonce));
+                    tracing::info!(nonce = %format!("{:016x}", nonce), "🎉 [Thread {}] Found solution! Nonce: {:016x}", thread_id, nonce);
 
                     let mut res = result.lock().unwrap();
                     *res = Some(nonce);
                     return;
                 }
 
-                // Strided increment (wraps on overflow, but impossible in practice)
-                nonce += stride;
+                // Next nonce in this chunk (wraps on overflow, possible with a random start offset)
+                nonce = nonce.wrapping_add(1);
+                chunk_remaining -= 1;
+
+                if local_count.is_multiple_of(check_interval) {
+                    // Suspend hashing while paused (ROM stays in memory); the first thread to notice
+                    // the pause marks when it began and the first one to wake accounts the pause time,
+                    // whichever threads are still running
+                    if is_paused() {
+                        let now = start_time.elapsed().as_millis() as u64;
+                        if pause_started.compare_exchange(NOT_PAUSED, now, Ordering::AcqRel, Ordering::Relaxed).is_ok() {
+                            tracing::info!("⏸️  Mining paused ({})", pause_reason().unwrap_or("resumed"));
+                            qos::allow_app_nap();
+                        }
+                        wait_while_paused();
+                        let began = pause_started.swap(NOT_PAUSED, Ordering::AcqRel);
+                        if began != NOT_PAUSED {
+                            let waited = Duration::from_millis((start_time.elapsed().as_millis() as u64).saturating_sub(began));
+                            qos::prevent_app_nap();
+                            paused_millis.fetch_add(waited.as_millis() as u64, Ordering::Relaxed);
+                            tracing::info!("▶️  Mining resumed after {:.0?}", waited);
+                        }
+                    }
+
+                    // Check the hash limit (if set) against the hashes of all threads together
+                    if let Some(max_h) = max_hashes {
+                        if hash_count.load(Ordering::Relaxed) >= max_h {
+                            if !hash_limit_hit.swap(true, Ordering::Relaxed) {
+                                tracing::info!("⏱️  Hash limit reached: {} hashes", max_h);
+                            }
+                            found.store(true, Ordering::Relaxed);
+                            return;
+                        }
+                    }
+
+                    // Stop before the challenge closes; a late solution can't be submitted
+                    if deadline.is_some_and(|d| Instant::now() >= d) {
+                        if !deadline_hit.swap(true, Ordering::Relaxed) {
+                            tracing::info!(
+                                "⌛ Challenge deadline is less than {}s away, stopping",
+                                DEADLINE_ABORT_MARGIN_SECS,
+                            );
+                        }
+                        found.store(true, Ordering::Relaxed);
+                        return;
+                    }
+
+                    // Check wall-clock limit (if set)
+                    if let Some(limit) = max_duration {
+                        let active = start_time.elapsed().saturating_sub(Duration::from_millis(paused_millis.load(Ordering::Relaxed)));
+                        if active >= limit {
+                            if !time_limit_hit.swap(true, Ordering::Relaxed) {
+                                tracing::info!("⏱️  Time limit reached: {:.1} minutes", limit.as_secs_f64() / 60.0);
+                            }
+                            found.store(true, Ordering::Relaxed);
+                            return;
+                        }
+                    }
+                }
 
                 if local_count % 5000 == 0 {
-                    // Log progress and check hash limit every 30 seconds
+                    // Log progress every 30 seconds
                     let mut last_log = last_log_time.lock().unwrap();
                     if last_log.elapsed() >= Duration::from_secs(30) {
                         // Load total hash count once and reuse
                         let total = hash_count.load(Ordering::Relaxed);
-                        let elapsed = start_time.elapsed().as_secs_f64();
+                        let elapsed = start_time.elapsed()
+                            .saturating_sub(Duration::from_millis(paused_millis.load(Ordering::Relaxed)))
+                            .as_secs_f64();
                         let hash_rate = if elapsed > 0.0 { total as f64 / elapsed } else { 0.0 };
-                        log_mining_progress(&format!(
-                            "⛏️  Mining... {} total hashes ({:.2} H/s overall)",
-                            total, hash_rate
-                        ));
+                        tracing::info!(target: logging::PROGRESS, hashes = total, hash_rate, "⛏️  Mining... {} total hashes ({:.2} H/s overall)", total, hash_rate);
+                        log_thread_hashrates(&thread_hashes, elapsed);
                         *last_log = Instant::now();
-
-                        // Check hash limit (if set) - this is a soft limit
-                        if let Some(max_h) = max_hashes {
-                            if total >= max_h {
-                                found.store(true, Ordering::Relaxed);
-                                log_mining_progress(&format!("⏱️  Hash limit reached: {} hashes", total));
-                                return;
-                            }
-                        }
                     }
                 }
             }
         });
     });
 
+    dashboard::task_finished();
+    progress::clear();
     let res = result.lock().unwrap();
     let total_hashes = hash_count.load(Ordering::Relaxed);
     let duration_secs = start_time.elapsed().as_secs();
+    let active = start_time.elapsed().saturating_sub(Duration::from_millis(paused_millis.load(Ordering::Relaxed)));
+    ranking::record_hashrate(total_hashes, active.as_secs_f64());
 
     match *res {
-        Some(nonce) => MiningResult::Found(nonce),
+        Some(nonce) => MiningResult::Found(nonce, total_hashes),
+        None if shutdown_requested() => MiningResult::Interrupted(total_hashes),
+        None if dashboard::skip_requested() => MiningResult::Skipped(total_hashes),
+        None if deadline_hit.load(Ordering::Relaxed) => MiningResult::DeadlinePassed(total_hashes),
         None => {
-            // Check if we hit the hash limit (soft limit, may be slightly exceeded)
-            if let Some(max_h) = max_hashes {
-                if total_hashes >= max_h {
-                    return MiningResult::TooHard(total_hashes, duration_secs);
-                }
+            // Check if we hit the hash limit (checked every check_interval hashes, so it may be slightly exceeded)
+            if hash_limit_hit.load(Ordering::Relaxed) {
+                return MiningResult::TooHard(total_hashes, duration_secs);
+            }
+            // Time limit gives up through the same path as the hash limit
+            if time_limit_hit.load(Ordering::Relaxed) {
+                return MiningResult::TooHard(total_hashes, duration_secs);
             }
             MiningResult::NotFound
         }
     }
 }
 
+/// Log the per-thread hashrate distribution (min/median/max) and the slowest thread
+fn log_thread_hashrates(thread_hashes: &[AtomicU64], elapsed_secs: f64) {
+    if thread_hashes.len() < 2 || elapsed_secs <= 0.0 {
+        return;
+    }
+
+    let mut rates: Vec<(usize, f64)> = thread_hashes
+        .iter()
+        .enumerate()
+        .map(|(i, count)| (i, count.load(Ordering::Relaxed) as f64 / elapsed_secs))
+        .collect();
+    rates.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    let (slowest_thread, min) = rates[0];
+    let median = rates[rates.len() / 2].1;
+    let max = rates[rates.len() - 1].1;
+
+    tracing::info!(
+        target: logging::PROGRESS,
+        "   🧵 Per-thread H/s: min {:.2} / median {:.2} / max {:.2} (slowest: thread {})",
+        min, median, max, slowest_thread
+    );
+
+    // Flag threads running well below the median (likely throttled or sharing a core)
+    let laggards: Vec<String> = rates
+        .iter()
+        .filter(|(_, rate)| median > 0.0 && *rate < median * 0.75)
+        .map(|(i, rate)| format!("#{} ({:.2})", i, rate))
+        .collect();
+    if !laggards.is_empty() {
+        tracing::warn!("   ⚠️  Threads below 75% of median: {}", laggards.join(", "));
+    }
+}
+
+/// Concurrent requests per resubmission pass
+const RESUBMIT_WORKERS: usize = 4;
+/// Minimum spacing between resubmission requests, across all workers
+const RESUBMIT_REQUEST_INTERVAL: Duration = Duration::from_millis(250);
+/// A resubmission's outcome, with the record's position in the queue
+type Resubmission = (usize, SolutionRecord, Result<SubmitResult, MinerError>);
+
 /// Check and retry failed submissions (called in main mining loop)
-/// Only retries if at least 1 hour has passed since last retry
-fn check_and_retry_failed_submissions() {
+/// Only retries once the wallet's retry interval (default 1 hour) has passed since the last attempt.
+/// Pending records are read once, due ones are resubmitted concurrently by a small worker pool
+/// (spaced out by RESUBMIT_REQUEST_INTERVAL), and every changed record is written back at the end
+/// `force` retries everything now, due or not (dashboard `r`)
+fn check_and_retry_failed_submissions(retry_policies: &retry_policy::RetryPolicies, force: bool) {
     let failed_solutions = get_failed_solutions();
 
     if failed_solutions.is_empty() {
         return;
     }
 
-    let current_time = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
+    let current_time = now_unix_secs();
 
-    let mut retried_count = 0;
+    // Loaded at most once per pass, only if something is due
+    let mut known_challenges: Option<KnownChallenges> = None;
 
-    for mut solution in failed_solutions {
-        // Check if at least 1 hour has passed since last retry
-        let should_retry = if let Some(ref last_retry) = solution.last_retry_at {
+    let mut updated: Vec<SolutionRecord> = Vec::new();
+    let mut due: Vec<(SolutionRecord, u64)> = Vec::new();
+    // Held until written back, so miners sharing the store don't resubmit the same records
+    let mut claimed: Vec<SolutionRecord> = Vec::new();
+
+    for solution in failed_solutions {
+        let policy = retry_policies.for_wallet(&solution.wallet_address);
+
+        // Check if the retry interval has passed since last retry (rate-limited ones wait for the server's time instead)
+        let should_retry = if force {
+            true
+        } else if let Some(ref next_retry) = solution.next_retry_at {
+            chrono::DateTime::parse_from_rfc3339(next_retry)
+                .map_or(true, |next_time| current_time >= next_time.timestamp() as u64)
+        } else if let Some(ref last_retry) = solution.last_retry_at {
             // Parse last retry timestamp
             if let Ok(last_time) = chrono::DateTime::parse_from_rfc3339(last_retry) {
                 let last_timestamp = last_time.timestamp() as u64;
                 let elapsed = current_time.saturating_sub(last_timestamp);
-                elapsed >= 3600 // 1 hour in seconds
+                elapsed >= policy.interval_secs
             } else {
                 true // If can't parse, retry
             }
@@ -886,125 +3016,273 @@ fn check_and_retry_failed_submissions() {
             if let Ok(found_time) = chrono::DateTime::parse_from_rfc3339(&solution.found_at) {
                 let found_timestamp = found_time.timestamp() as u64;
                 let elapsed = current_time.saturating_sub(found_timestamp);
-                elapsed >= 3600 // 1 hour since found
+                elapsed >= policy.interval_secs
             } else {
                 true // If can't parse, retry
             }
         };
 
-        if !should_retry {
+        if !should_retry || submission_in_flight(&solution.wallet_address, &solution.challenge_id) {
             continue;
         }
+        let Some(mut solution) = store::get().claim(&solution) else {
+            continue;
+        };
+        claimed.push(solution.clone());
 
         // Check if challenge is still open
-        if !is_challenge_still_open(&solution) {
-            log_mining_progress(&format!("⏭️  Challenge {} no longer active", solution.challenge_id));
+        let known = known_challenges.get_or_insert_with(KnownChallenges::load);
+        if !is_challenge_still_open(&solution, known) {
+            tracing::info!(challenge_id = %solution.challenge_id, "⏭️  Challenge {} no longer accepts submissions", solution.challenge_id);
             solution.status = "challenge_closed".to_string();
-            solution.error_message = Some("Challenge no longer in active list".to_string());
-            if let Err(e) = update_solution_record(&solution) {
-                log_mining_progress(&format!("⚠️  Failed to update solution record: {}", e));
-            }
+            solution.error_message = Some("Challenge closed for submissions".to_string());
+            updated.push(solution);
             continue;
         }
 
-        // Check if already too many retries
-        if solution.retry_count >= 10 {
+        // Check if already too many retries (held solutions keep going until the challenge closes)
+        if solution.retry_count >= policy.max_retries && !policy.hold_until_deadline {
             if solution.status != "abandoned" {
                 solution.status = "abandoned".to_string();
-                if let Err(e) = update_solution_record(&solution) {
-                    log_mining_progress(&format!("⚠️  Failed to update solution record: {}", e));
-                }
+                updated.push(solution);
             }
             continue;
         }
 
-        log_mining_progress(&format!("🔁 Retrying solution: {}... (attempt #{})",
-            &solution.challenge_id[..16.min(solution.challenge_id.len())],
-            solution.retry_count + 1));
-
         // Parse nonce from hex string
         let nonce = match u64::from_str_radix(&solution.nonce, 16) {
             Ok(n) => n,
             Err(e) => {
-                log_mining_progress(&format!("❌ Invalid nonce format: {}", e));
+                tracing::error!(error = %e, "❌ Invalid nonce format: {}", e);
                 continue;
             }
         };
 
-        // Attempt resubmission
-        match submit_to_scavenger(&solution.wallet_address, &solution.challenge_id, nonce) {
-            Ok(SubmitResult::Success(crypto_receipt)) => {
-                log_mining_progress("   ✅ Retry successful!");
+        due.push((solution, nonce));
+    }
 
-                solution.status = "submitted".to_string();
-                solution.crypto_receipt = Some(crypto_receipt);
-                solution.submitted_at = Some(get_timestamp());
-                solution.error_message = None;
-                solution.retry_count += 1;
-                solution.last_retry_at = Some(get_timestamp());
+    if !due.is_empty() {
+        tracing::info!("🔁 Resubmitting {} solution(s)...", due.len());
+    }
+    let retried_count = due.len();
+
+    // Submit concurrently; outcomes are applied below in submission order so the log stays readable
+    let workers = RESUBMIT_WORKERS.min(due.len());
+    let queue = Mutex::new(due.into_iter().enumerate());
+    let next_slot = Mutex::new(Instant::now());
+    let rate_limited = AtomicBool::new(false);
+    let results: Mutex<Vec<Resubmission>> = Mutex::new(Vec::new());
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                // A rate limit applies to everything else too, so stop sending
+                if shutdown_requested() || rate_limited.load(Ordering::Relaxed) {
+                    break;
+                }
+                let Some((index, (solution, nonce))) = queue.lock().unwrap().next() else {
+                    break;
+                };
 
-                if let Err(e) = update_solution_record(&solution) {
-                    log_mining_progress(&format!("⚠️  Failed to update solution record: {}", e));
+                // Reserve the next send slot
+                let wait = {
+                    let mut slot = next_slot.lock().unwrap();
+                    let now = Instant::now();
+                    let at = (*slot).max(now);
+                    *slot = at + RESUBMIT_REQUEST_INTERVAL;
+                    at - now
+                };
+                thread::sleep(wait);
+
+                tracing::info!(
+                    "🔁 Retrying solution: {}... (attempt #{})",
+                    &solution.challenge_id[..16.min(solution.challenge_id.len())],
+                    solution.retry_count + 1,
+                );
+                let result = submit_to_scavenger(&solution.wallet_address, &solution.challenge_id, nonce);
+                if matches!(result, Ok(SubmitResult::RateLimited(_))) {
+                    rate_limited.store(true, Ordering::Relaxed);
                 }
+                results.lock().unwrap().push((index, solution, result));
+            });
+        }
+    });
 
-                retried_count += 1;
-            }
-            Ok(SubmitResult::Failed(error_msg)) => {
-                log_mining_progress(&format!("   ❌ Retry failed: {}", error_msg));
+    // Workers finish in any order
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|(index, _, _)| *index);
+    for (_, mut solution, result) in results {
+        let policy = retry_policies.for_wallet(&solution.wallet_address);
+        apply_retry_result(&mut solution, result, policy);
+        updated.push(solution);
+    }
+
+    // Write back everything that changed in one go
+    for record in &updated {
+        if let Err(e) = update_solution_record(record) {
+            tracing::warn!(error = %e, "⚠️  Failed to update solution record: {}", e);
+        }
+    }
+    for record in &claimed {
+        store::get().release(record);
+    }
+
+    if retried_count > 0 {
+        tracing::info!("✓ Processed {} resubmission(s)", retried_count);
+    }
+}
 
-                // Check if this is a non-retriable error
-                let error_lower = error_msg.to_lowercase();
-                if error_lower.contains("solution already exists") ||
-                   error_lower.contains("already exists") {
+/// Update a pending solution record with the outcome of one resubmission
+fn apply_retry_result(
+    solution: &mut SolutionRecord,
+    result: Result<SubmitResult, MinerError>,
+    policy: retry_policy::RetryPolicy,
+) {
+    let _resubmission = tracing::info_span!(
+        "resubmission",
+        challenge_id = %solution.challenge_id,
+        wallet = %solution.wallet_address,
+        nonce = %solution.nonce,
+        attempt = solution.retry_count + 1
+    )
+    .entered();
+    match result {
+        Ok(SubmitResult::Success(crypto_receipt)) => {
+            tracing::info!(target: logging::SOLUTIONS, "   ✅ Retry successful!");
+            wallet_stats::record_accepted(&solution.wallet_address);
+
+            solution.verified = receipt::verify(&crypto_receipt);
+            solution.status = "submitted".to_string();
+            solution.crypto_receipt = Some(crypto_receipt);
+            solution.submitted_at = Some(get_timestamp());
+            solution.error_message = None;
+            solution.retry_count += 1;
+            solution.last_retry_at = Some(get_timestamp());
+            solution.next_retry_at = None;
+        }
+        Err(e) if !e.is_network() => {
+            let error_msg = e.to_string();
+            tracing::error!(error = %error_msg, "   ❌ Retry failed: {}", error_msg);
+            solution.next_retry_at = None;
+
+            // Check if this is a non-retriable error
+            match e.rejection() {
+                Some(Rejection::Duplicate) => {
                     solution.status = "duplicate".to_string();
                     solution.error_message = Some(error_msg);
-                    log_mining_progress("   ⏭️  Marked as duplicate (won't retry)");
-                } else if error_lower.contains("does not meet difficulty") ||
-                          (error_lower.contains("difficulty") && error_lower.contains("not meet")) {
+                    tracing::info!("   ⏭️  Marked as duplicate (won't retry)");
+                    wallet_stats::record_duplicate(&solution.wallet_address);
+                }
+                Some(Rejection::InvalidNonce) => {
                     solution.status = "invalid_nonce".to_string();
                     solution.error_message = Some(error_msg);
-                    log_mining_progress("   ⏭️  Marked as invalid (won't retry)");
-                } else {
+                    tracing::info!("   ⏭️  Marked as invalid (won't retry)");
+                }
+                Some(Rejection::WindowClosed) => {
+                    solution.status = "challenge_closed".to_string();
+                    solution.error_message = Some(error_msg);
+                    tracing::info!("   ⏭️  Submission window closed (won't retry)");
+                }
+                _ => {
+                    if solution.status == "queued" || solution.status == "offline" {
+                        solution.status = "failed".to_string();
+                    }
                     solution.retry_count += 1;
                     solution.last_retry_at = Some(get_timestamp());
                     solution.error_message = Some(error_msg);
 
-                    if solution.retry_count >= 10 {
+                    if solution.retry_count >= policy.max_retries && !policy.hold_until_deadline {
                         solution.status = "abandoned".to_string();
-                        log_mining_progress(&format!("   ⚠️  Giving up after {} attempts", solution.retry_count));
+                        tracing::warn!("   ⚠️  Giving up after {} attempts", solution.retry_count);
                     }
                 }
-
-                if let Err(e) = update_solution_record(&solution) {
-                    log_mining_progress(&format!("⚠️  Failed to update solution record: {}", e));
-                }
-
-                retried_count += 1;
             }
-            Err(e) => {
-                log_mining_progress(&format!("   ❌ Network error: {}", e));
+        }
+        Ok(SubmitResult::RateLimited(retry_after)) => {
+            // Not the solution's fault, so it doesn't count as an attempt
+            tracing::info!(
+                "   ⏳ Rate limited, retrying in {}",
+                format_duration_secs(retry_after.as_secs_f64()),
+            );
+            solution.status = "queued".to_string();
+            solution.next_retry_at = Some(retry_at_timestamp(retry_after));
+            solution.error_message = Some("Rate limited by the API".to_string());
+        }
+        Err(e) => {
+            tracing::error!(error = %e, "   ❌ Network error: {}", e);
+
+            solution.next_retry_at = None;
+            solution.retry_count += 1;
+            solution.last_retry_at = Some(get_timestamp());
+            solution.error_message = Some(format!("Network error: {}", e));
+        }
+    }
+}
 
-                solution.retry_count += 1;
-                solution.last_retry_at = Some(get_timestamp());
-                solution.error_message = Some(format!("Network error: {}", e));
+/// Tell the control endpoint which wallets are being mined and which groups exist
+fn publish_wallets(control: &control::ControlServer, groups: &[groups::Group]) {
+    let wallets = groups
+        .iter()
+        .flat_map(|g| g.wallets.iter().map(|(address, weight)| control::WalletInfo {
+            address: address.clone(),
+            group: g.name.clone(),
+            weight: *weight,
+        }))
+        .collect();
+    control.publish(wallets, groups.iter().map(|g| g.name.clone()).collect());
+}
 
-                if let Err(e) = update_solution_record(&solution) {
-                    log_mining_progress(&format!("⚠️  Failed to update solution record: {}", e));
+/// Add and drain wallets as asked over the control endpoint
+fn apply_wallet_commands(commands: Vec<control::WalletCommand>, groups: &mut [groups::Group], all_wallets: &mut Vec<String>) {
+    for command in commands {
+        match command {
+            control::WalletCommand::Add { address, weight, group } => {
+                if groups.iter().any(|g| g.wallets.iter().any(|(w, _)| *w == address)) {
+                    continue;
+                }
+                let index = groups.iter().position(|g| Some(&g.name) == group.as_ref()).unwrap_or(0);
+                let Some(group) = groups.get_mut(index) else {
+                    continue;
+                };
+                group.add_wallet(&address, weight);
+                tracing::info!("👛 Added wallet {} to group {} (weight {})", wallets::label(&address), group.name, weight);
+                if !all_wallets.contains(&address) {
+                    all_wallets.push(address);
+                }
+            }
+            control::WalletCommand::Remove { address } => {
+                if groups.iter_mut().any(|g| g.remove_wallet(&address)) {
+                    tracing::info!("👛 Drained wallet {}, it is no longer mined", wallets::label(&address));
                 }
-
-                retried_count += 1;
             }
         }
+    }
+}
 
-        // Small delay between retries
-        if retried_count < get_failed_solutions().len() {
-            thread::sleep(Duration::from_millis(500));
-        }
+/// Print the session statistics block
+fn print_session_statistics(total_solutions: u64, session_start: Instant, wallets: &[String]) {
+    println!("\n📊 Session Statistics:");
+    println!("   Total solutions: {} (100% for your wallets)", total_solutions);
+    println!("   Runtime: {:.2?}", session_start.elapsed());
+
+    // Calculate and display average time per solution
+    if total_solutions > 0 {
+        let avg_time_secs = session_start.elapsed().as_secs_f64() / total_solutions as f64;
+        let avg_minutes = (avg_time_secs / 60.0).floor() as u64;
+        let avg_seconds = (avg_time_secs % 60.0).floor() as u64;
+        println!("   Average time per solution: {}m {}s", avg_minutes, avg_seconds);
     }
 
-    if retried_count > 0 {
-        log_mining_progress(&format!("✓ Processed {} resubmission(s)", retried_count));
+    // Totals including earlier sessions
+    session_stats::print_summary();
+
+    // Per-wallet totals, this session and earlier ones
+    let stats = wallet_stats::snapshot();
+    if !wallets.is_empty() {
+        println!("   Per wallet (all sessions):");
+        wallet_stats::print_table(wallets.iter().map(|w| (w.as_str(), stats.get(w).copied().unwrap_or_default())));
     }
+    println!();
 }
 
 /// Get user input from stdin
@@ -1023,33 +3301,526 @@ fn get_user_input(prompt: &str, default: &str) -> String {
     }
 }
 
+/// Options that are plain switches and never take a value
+const CLI_SWITCHES: &[&str] = &[
+    "--submit-probe", "--nice", "--background-only", "--include-logs", "--force", "--force-lock", "--clock-correct", "--auto-threads",
+    "--no-system-proxy", "--skip-health-check", "--dry-run", "--tui",
+    "--telegram-commands", "--desktop-notify", "--quiet", "--verbose",
+    "--no-progress", "--trust-shares", "--shared-solutions",
+    service::RUN_SWITCH, service::INSTALL_SWITCH, service::UNINSTALL_SWITCH,
+    daemon::DAEMON_SWITCH, daemon::STOP_SWITCH,
+];
+
+/// Short forms of switches
+const CLI_SHORT_SWITCHES: &[(&str, &str)] = &[("-q", "--quiet"), ("-v", "--verbose")];
+
+/// Config file read if present; `--config <file>` selects another one
+const DEFAULT_CONFIG_FILE: &str = "config.json";
+
+/// Parse arguments, fill in the config file and apply network settings (exits on invalid config)
+fn load_cli_args(args: &[String]) -> CliArgs {
+    let mut cli = CliArgs::parse(args);
+    if let Err(e) = cli.apply_config_file() {
+        eprintln!("❌ {}", e);
+        std::process::exit(1);
+    }
+    if let Err(e) = http::configure(&cli)
+        .and_then(|_| receipt::configure(&cli))
+        .and_then(|_| paths::configure(&cli))
+        .and_then(|_| store::configure(&cli))
+    {
+        eprintln!("❌ {}", e);
+        std::process::exit(1);
+    }
+    cli
+}
+
+/// Command-line arguments split into positionals, `--name value` options and switches
+struct CliArgs {
+    positional: Vec<String>,
+    options: std::collections::HashMap<String, String>,
+    switches: Vec<String>,
+    nested: std::collections::HashMap<String, serde_json::Value>, // Arrays and objects from the config file
+}
+
+impl CliArgs {
+    /// Parse arguments (excluding the program name)
+    /// Accepts both `--name value` and `--name=value`
+    fn parse(args: &[String]) -> Self {
+        let mut cli = CliArgs {
+            positional: Vec::new(),
+            options: std::collections::HashMap::new(),
+            switches: Vec::new(),
+            nested: std::collections::HashMap::new(),
+        };
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if let Some((_, long)) = CLI_SHORT_SWITCHES.iter().find(|(short, _)| short == arg) {
+                cli.switches.push(long.to_string());
+            } else if !arg.starts_with("--") {
+                cli.positional.push(arg.clone());
+            } else if let Some((name, value)) = arg.split_once('=') {
+                cli.options.insert(name.to_string(), value.to_string());
+            } else if CLI_SWITCHES.contains(&arg.as_str()) {
+                cli.switches.push(arg.clone());
+            } else if let Some(value) = iter.next() {
+                cli.options.insert(arg.clone(), value.clone());
+            } else {
+                cli.switches.push(arg.clone());
+            }
+        }
+
+        cli
+    }
+
+    /// Fill in options missing from the command line with values from the JSON config file
+    /// Keys are option names without the dashes (`"proxy"`, `"max_minutes_per_task"`);
+    /// `true` turns on a switch. Nested values are kept for the features that read them (`nested`).
+    fn apply_config_file(&mut self) -> Result<(), String> {
+        let (path, explicit) = match self.option("--config") {
+            Some(path) => (path.to_string(), true),
+            None => (DEFAULT_CONFIG_FILE.to_string(), false),
+        };
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(_) if !explicit => return Ok(()),
+            Err(e) => return Err(format!("Cannot read config file {}: {}", path, e)),
+        };
+        let values: serde_json::Map<String, serde_json::Value> = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid config file {}: {}", path, e))?;
+
+        for (key, value) in values {
+            let name = format!("--{}", key.replace('_', "-"));
+            match value {
+                serde_json::Value::Bool(true) if !self.switch(&name) => self.switches.push(name),
+                serde_json::Value::String(s) => {
+                    self.options.entry(name).or_insert(s);
+                }
+                serde_json::Value::Number(n) => {
+                    self.options.entry(name).or_insert(n.to_string());
+                }
+                serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
+                    self.nested.insert(key, value);
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(|s| s.as_str())
+    }
+
+    fn switch(&self, name: &str) -> bool {
+        self.switches.iter().any(|s| s == name)
+    }
+
+    /// Array or object value of a config file key
+    fn nested(&self, key: &str) -> Option<&serde_json::Value> {
+        self.nested.get(key)
+    }
+}
+
+/// Miner configuration gathered from CLI args or interactive prompts
+struct MinerConfig {
+    wallets_file: String,
+    cpu_usage: f64,
+    max_hashes_millions: Option<f64>,
+    max_minutes_per_task: Option<f64>,
+    limit_check_interval: u64,
+    idle_only_minutes: Option<f64>,
+    low_priority: bool,
+    background_only: bool,           // Utility QoS for mining threads on macOS (qos.rs)
+    cgroup: Option<String>,          // cgroup v2 to enforce the CPU share in (cgroup.rs)
+    process_priority: Option<ProcessPriority>, // Left as inherited when None
+    max_clock_skew_secs: u64,
+    clock_correct: bool,
+    rom_cache_mb: u64,
+    auto_threads: Option<f64>, // Calibration seconds per level
+    api_retries: u32,
+    api_backoff_ms: u64,
+    notify_mode: notify::NotifyMode,
+    notify_webhook: Option<String>,
+    telegram_token: Option<String>,  // Telegram bot for notifications and commands (telegram.rs)
+    telegram_chat: Option<String>,
+    telegram_commands: bool,         // Answer /status, /pause, /resume from that chat
+    telegram_api: String,
+    desktop_notify: bool,            // Desktop toasts for solutions and receipts (desktop.rs)
+    control_port: Option<u16>,       // Localhost wallet management endpoint (control.rs)
+    control_token: Option<String>,   // Its bearer token (default: created in the data folder)
+    health_port: Option<u16>,        // Liveness endpoint for orchestrators (health.rs)
+    health_bind: String,
+    health_stale_minutes: u64,
+    grpc_port: Option<u16>,          // gRPC control and status API (grpc.rs)
+    grpc_bind: String,
+    grpc_token: Option<String>,
+    log_format: logging::ConsoleFormat, // Console output: pretty lines or JSON (logging.rs)
+    verbosity: logging::Verbosity,   // -q: solutions and errors only, -v: API requests too
+    log_json: Option<String>,        // Also write JSON-lines logs to this file
+    log_rotation: logging::Rotation, // When mining.log and the JSON log rotate, how long old ones are kept
+    tui: bool,                       // Full-screen dashboard instead of the scrolling log (dashboard.rs)
+    daemon: Option<std::path::PathBuf>, // Pidfile when detaching from the terminal (--daemon, daemon.rs)
+    progress: bool,                  // Progress line while hashing on a terminal (progress.rs)
+    hash_backend: String,
+    backend_schedule: Option<String>,
+    nonce_start: NonceStart,
+    challenges_file: Option<String>, // Offline mode: challenges from this file, no API calls
+    health_check: bool,              // Check the API before loading wallets (off with --skip-health-check)
+    telemetry_url: Option<String>,   // Opt-in fleet stats collector
+    telemetry_interval_secs: u64,
+    hashrate_interval_secs: u64,     // Hashrate history sampling, 0 = off (hashrate_history.rs)
+    miner_id: Option<String>,        // Name in telemetry reports (default: random ID) and Redis claims (default: hostname)
+    archive_after_days: u64,         // Move older solution records to solutions/archive/ (0 = never)
+    min_solve_percent: f64,          // Skip challenges less likely to be solved before their deadline (0 = never)
+    selection: spread::SelectionMode,
+    rotation: rotation::RotationPolicy,
+    difficult_retry_days: u64,       // Retry difficult tasks marked this long ago (0 = only when limits or hashrate improve)
+    max_hashes_factor: Option<f64>,  // Per-challenge hash limit as a multiple of its expected cost (replaces max_hashes)
+    force: bool,                     // Start even with invalid wallet addresses
+    force_lock: bool,                // Start even if another miner holds the data directory lock
+    credited_refresh_minutes: f64,   // Refresh of the solutions the API credits to each wallet (0 = don't ask)
+    redis_url: Option<String>,       // Fleet-wide task claims (redis_claims.rs)
+    redis_prefix: String,
+    redis_claim_ttl_secs: u64,
+    groups: Vec<groups::GroupConfig>, // Wallet groups from the config file (empty = one group from wallets_file)
+    schedule: Option<schedule::Schedule>, // Mining windows from the config file (schedule.rs)
+}
+
+/// Named bundle of defaults for a machine class, selected with `--profile`
+/// Anything given explicitly on the command line overrides the profile
+struct Profile {
+    name: &'static str,
+    description: &'static str,
+    cpu_usage: f64,
+    idle_only_minutes: Option<f64>,
+    low_priority: bool,
+    notify_mode: notify::NotifyMode,
+}
+
+const PROFILES: &[Profile] = &[
+    Profile {
+        name: "workstation",
+        description: "shared desktop: half the threads at low priority, hourly digest",
+        cpu_usage: 50.0,
+        idle_only_minutes: None,
+        low_priority: true,
+        notify_mode: notify::NotifyMode::Digest(Duration::from_secs(3600)),
+    },
+    Profile {
+        name: "rig",
+        description: "dedicated mining rig: all threads, a notification per event",
+        cpu_usage: 100.0,
+        idle_only_minutes: None,
+        low_priority: false,
+        notify_mode: notify::NotifyMode::Events,
+    },
+    Profile {
+        name: "server",
+        description: "server with other workloads: 75% of threads at low priority, daily digest",
+        cpu_usage: 75.0,
+        idle_only_minutes: None,
+        low_priority: true,
+        notify_mode: notify::NotifyMode::Digest(Duration::from_secs(86_400)),
+    },
+    Profile {
+        name: "laptop",
+        description: "laptop: 25% of threads at low priority, only after 5 idle minutes, daily digest",
+        cpu_usage: 25.0,
+        idle_only_minutes: Some(5.0),
+        low_priority: true,
+        notify_mode: notify::NotifyMode::Digest(Duration::from_secs(86_400)),
+    },
+];
+
+fn profile_by_name(name: &str) -> Option<&'static Profile> {
+    let name = name.trim().to_ascii_lowercase();
+    let name = if name == "dedicated-rig" { "rig" } else { name.as_str() };
+    PROFILES.iter().find(|p| p.name == name)
+}
+
+/// Where each task's nonce search begins
+#[derive(Debug, Clone, Copy)]
+enum NonceStart {
+    Zero,            // 0..num_threads (original behavior)
+    Random,          // Fresh random 64-bit offset per task
+    Fixed(u64),      // Operator-chosen offset (e.g. one per machine)
+}
+
+impl NonceStart {
+    /// Parse `random`, `zero`, a decimal number or a `0x` hex number
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "random" => Some(NonceStart::Random),
+            "zero" | "0" => Some(NonceStart::Zero),
+            _ => match value.strip_prefix("0x") {
+                Some(hex_value) => u64::from_str_radix(hex_value, 16).ok().map(NonceStart::Fixed),
+                None => value.parse::<u64>().ok().map(NonceStart::Fixed),
+            },
+        }
+    }
+
+    /// Pick the starting nonce for the next task
+    fn pick(&self) -> u64 {
+        match self {
+            NonceStart::Zero => 0,
+            NonceStart::Random => rand::random::<u64>(),
+            NonceStart::Fixed(offset) => *offset,
+        }
+    }
+}
+
+impl MinerConfig {
+    /// A wallet group with its settings resolved: what it leaves out comes from the command line,
+    /// and a group that sets either hash limit replaces both command-line ones
+    fn group(&self, index: usize, group: groups::GroupConfig, wallets: Vec<(String, f64)>, num_threads: usize, cpu_usage: f64) -> groups::Group {
+        let (max_hashes_millions, max_hashes_factor) = if group.max_hashes_millions.is_some() || group.max_hashes_factor.is_some() {
+            (group.max_hashes_millions, group.max_hashes_factor.filter(|f| *f > 0.0))
+        } else {
+            (self.max_hashes_millions, self.max_hashes_factor)
+        };
+        let max_minutes_per_task = group.max_minutes_per_task.or(self.max_minutes_per_task);
+        let selection = group.selection.as_deref().and_then(spread::SelectionMode::parse).unwrap_or(self.selection);
+        let policy = group.rotation.as_deref().and_then(rotation::RotationPolicy::parse).unwrap_or(self.rotation);
+        let weights = wallets.iter().map(|(_, weight)| *weight).collect();
+
+        groups::Group {
+            name: group.name.unwrap_or_else(|| format!("#{}", index + 1)),
+            share: group.share.unwrap_or(1.0),
+            num_threads: group.cpu_usage.map_or(num_threads, threads_for_cpu_usage),
+            cpu_usage: group.cpu_usage.unwrap_or(cpu_usage),
+            limits: MiningLimits {
+                // With a factor each challenge gets its own hash limit instead
+                max_hashes: max_hashes_millions.filter(|_| max_hashes_factor.is_none()).map(|m| (m * 1_000_000.0) as u64),
+                max_duration: max_minutes_per_task.filter(|m| *m > 0.0).map(|m| Duration::from_secs_f64(m * 60.0)),
+                check_interval: self.limit_check_interval,
+                nonce_count: None,
+                share_bits: None,
+            },
+            max_hashes_factor,
+            rotation: rotation::WalletRotation::new(weights, policy),
+            spreader: (selection == spread::SelectionMode::Spread).then(spread::Spreader::default),
+            wallets,
+        }
+    }
+}
+
 /// Parse configuration from either CLI args or interactive prompts
-fn get_configuration() -> (String, f64, Option<f64>) {
+fn get_configuration() -> MinerConfig {
     let args: Vec<String> = env::args().collect();
 
     // Check if running in CLI mode (has arguments)
     if args.len() > 1 {
-        // CLI mode - parse arguments
-        let wallets_file = args.get(1)
+        // CLI mode - parse arguments (config file fills in anything not given)
+        let cli = load_cli_args(&args[1..]);
+
+        let profile = cli.option("--profile").map(|name| {
+            profile_by_name(name).unwrap_or_else(|| {
+                let known: Vec<&str> = PROFILES.iter().map(|p| p.name).collect();
+                eprintln!("❌ Unknown profile '{}' (available: {})", name, known.join(", "));
+                std::process::exit(1);
+            })
+        });
+        if let Some(profile) = profile {
+            println!("🧩 Profile: {} ({})", profile.name, profile.description);
+        }
+
+        let wallets_file = cli.positional.first()
             .map(|s| s.as_str())
-            .unwrap_or("wallets.txt");
+            .or(cli.option("--wallets"))
+            .unwrap_or(wallets::default_source());
 
-        let cpu_usage = args.get(2)
+        let cpu_usage = cli.positional.get(1)
             .and_then(|s| s.parse::<f64>().ok())
+            .or(profile.map(|p| p.cpu_usage))
             .unwrap_or(50.0)  // Default to 50% CPU usage for maximum performance
             .min(100.0)
             .max(1.0);
 
-        let max_hashes_millions = args.get(3)
+        let max_hashes_millions = cli.positional.get(2)
+            .and_then(|s| s.parse::<f64>().ok());
+
+        let max_minutes_per_task = cli.option("--max-minutes-per-task")
             .and_then(|s| s.parse::<f64>().ok());
 
-        (wallets_file.to_string(), cpu_usage, max_hashes_millions)
+        let hash_backend = cli.option("--hash-backend")
+            .unwrap_or(backend::DEFAULT_BACKEND)
+            .to_string();
+
+        let nonce_start = match cli.option("--nonce-start") {
+            Some(value) => NonceStart::parse(value).unwrap_or_else(|| {
+                eprintln!("⚠️  Invalid --nonce-start '{}', starting from 0", value);
+                NonceStart::Zero
+            }),
+            None => NonceStart::Zero,
+        };
+
+        let selection = match cli.option("--selection") {
+            Some(value) => spread::SelectionMode::parse(value).unwrap_or_else(|| {
+                eprintln!("⚠️  Invalid --selection '{}', expected easiest or spread; using easiest", value);
+                spread::SelectionMode::Easiest
+            }),
+            None => spread::SelectionMode::Easiest,
+        };
+
+        let rotation = match cli.option("--rotation") {
+            Some(value) => rotation::RotationPolicy::parse(value).unwrap_or_else(|| {
+                eprintln!("⚠️  Invalid --rotation '{}', expected round-robin, least-recent or least-hashes; using round-robin", value);
+                rotation::RotationPolicy::RoundRobin
+            }),
+            None => rotation::RotationPolicy::RoundRobin,
+        };
+
+        let notify_mode = match cli.option("--notify") {
+            Some(value) => notify::NotifyMode::parse(value).unwrap_or_else(|| {
+                eprintln!("⚠️  Invalid --notify '{}', expected events, hourly or daily; using events", value);
+                notify::NotifyMode::Events
+            }),
+            None => profile.map_or(notify::NotifyMode::Events, |p| p.notify_mode),
+        };
+
+        let log_format = match cli.option("--log-format") {
+            Some(value) => logging::ConsoleFormat::parse(value).unwrap_or_else(|| {
+                eprintln!("⚠️  Invalid --log-format '{}', expected pretty or json; using pretty", value);
+                logging::ConsoleFormat::Pretty
+            }),
+            None => logging::ConsoleFormat::Pretty,
+        };
+
+        let limit_check_interval = cli.option("--limit-check-interval")
+            .and_then(|s| s.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_LIMIT_CHECK_INTERVAL)
+            .max(1);
+
+        MinerConfig {
+            wallets_file: wallets_file.to_string(),
+            cpu_usage,
+            max_hashes_millions,
+            max_minutes_per_task,
+            limit_check_interval,
+            idle_only_minutes: cli.option("--idle-only")
+                .and_then(|s| s.parse::<f64>().ok())
+                .or(profile.and_then(|p| p.idle_only_minutes)),
+            low_priority: cli.switch("--nice") || profile.is_some_and(|p| p.low_priority),
+            background_only: cli.switch("--background-only"),
+            cgroup: cli.option("--cgroup").map(|s| s.to_string()),
+            process_priority: cli.option("--process-priority").map(|name| {
+                ProcessPriority::parse(name).unwrap_or_else(|| {
+                    eprintln!("❌ Unknown process priority '{}' (use idle, below-normal or normal)", name);
+                    std::process::exit(1);
+                })
+            }),
+            max_clock_skew_secs: cli.option("--max-clock-skew")
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or(DEFAULT_MAX_CLOCK_SKEW_SECS),
+            clock_correct: cli.switch("--clock-correct"),
+            rom_cache_mb: cli.option("--rom-cache-mb")
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or(DEFAULT_ROM_CACHE_MB),
+            auto_threads: cli.switch("--auto-threads").then(|| {
+                cli.option("--calibration-secs")
+                    .and_then(|s| s.parse::<f64>().ok())
+                    .unwrap_or(DEFAULT_CALIBRATION_SECS)
+                    .max(1.0)
+            }),
+            api_retries: cli.option("--api-retries")
+                .and_then(|s| s.parse::<u32>().ok())
+                .unwrap_or(DEFAULT_API_RETRIES)
+                .max(1),
+            api_backoff_ms: cli.option("--api-backoff-ms")
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or(DEFAULT_API_BACKOFF_MS),
+            notify_mode,
+            notify_webhook: cli.option("--notify-webhook").map(|s| s.to_string()),
+            telegram_token: cli.option("--telegram-token").map(|s| s.to_string()),
+            telegram_chat: cli.option("--telegram-chat").map(|s| s.to_string()),
+            telegram_commands: cli.switch("--telegram-commands"),
+            telegram_api: cli.option("--telegram-api").unwrap_or(telegram::DEFAULT_API_BASE).to_string(),
+            desktop_notify: cli.switch("--desktop-notify"),
+            control_port: cli.option("--control-port").and_then(|s| s.parse::<u16>().ok()),
+            control_token: cli.option("--control-token").map(|s| s.to_string()),
+            health_port: cli.option("--health-port").and_then(|s| s.parse::<u16>().ok()),
+            health_bind: cli.option("--health-bind").unwrap_or(health::DEFAULT_BIND).to_string(),
+            health_stale_minutes: cli.option("--health-stale-minutes")
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or(health::DEFAULT_STALE_MINUTES),
+            grpc_port: cli.option("--grpc-port").and_then(|s| s.parse::<u16>().ok()),
+            grpc_bind: cli.option("--grpc-bind").unwrap_or(grpc::DEFAULT_BIND).to_string(),
+            grpc_token: cli.option("--grpc-token").map(|s| s.to_string()),
+            log_format,
+            verbosity: logging::Verbosity::from_switches(cli.switch("--quiet"), cli.switch("--verbose")),
+            log_json: cli.option("--log-json").map(|s| s.to_string()),
+            tui: cli.switch("--tui") && !cli.switch(service::RUN_SWITCH) && !cli.switch(daemon::DAEMON_SWITCH), // No screen to draw on
+            daemon: cli.switch(daemon::DAEMON_SWITCH).then(|| daemon::pid_file_path(cli.option("--pid-file"))),
+            progress: !cli.switch("--no-progress"),
+            log_rotation: logging::Rotation::new(
+                cli.option("--log-max-size-mb").and_then(|s| s.parse::<u64>().ok()).unwrap_or(logging::DEFAULT_MAX_SIZE_MB),
+                cli.option("--log-retention-days").and_then(|s| s.parse::<u64>().ok()).unwrap_or(logging::DEFAULT_RETENTION_DAYS),
+            ),
+            hash_backend,
+            backend_schedule: cli.option("--backend-schedule").map(|s| s.to_string()),
+            nonce_start,
+            challenges_file: cli.option("--challenges-file").map(|s| s.to_string()),
+            health_check: !cli.switch("--skip-health-check"),
+            telemetry_url: cli.option("--telemetry-url").map(|s| s.to_string()),
+            telemetry_interval_secs: cli.option("--telemetry-interval")
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or(telemetry::DEFAULT_TELEMETRY_INTERVAL_SECS)
+                .max(10),
+            hashrate_interval_secs: cli.option("--hashrate-interval")
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or(hashrate_history::DEFAULT_INTERVAL_SECS),
+            miner_id: cli.option("--miner-id").map(|s| s.to_string()),
+            archive_after_days: cli.option("--archive-after-days")
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or(archive::DEFAULT_ARCHIVE_AFTER_DAYS),
+            min_solve_percent: cli.option("--min-solve-probability")
+                .and_then(|s| s.trim_end_matches('%').parse::<f64>().ok())
+                .unwrap_or(ranking::DEFAULT_MIN_SOLVE_PERCENT),
+            selection,
+            rotation,
+            difficult_retry_days: cli.option("--difficult-retry-days")
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or(DEFAULT_DIFFICULT_RETRY_DAYS),
+            max_hashes_factor: cli.option("--max-hashes-factor")
+                .and_then(|s| s.trim_end_matches(['x', '×']).parse::<f64>().ok())
+                .filter(|f| *f > 0.0),
+            force: cli.switch("--force"),
+            force_lock: cli.switch("--force-lock"),
+            credited_refresh_minutes: cli.option("--credited-refresh-minutes")
+                .and_then(|s| s.parse::<f64>().ok())
+                .unwrap_or(credited::DEFAULT_REFRESH_MINUTES)
+                .max(0.0),
+            redis_url: cli.option("--redis-url").map(|s| s.to_string()),
+            redis_prefix: cli.option("--redis-prefix").unwrap_or(redis_claims::DEFAULT_PREFIX).to_string(),
+            redis_claim_ttl_secs: cli.option("--redis-claim-ttl")
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or(redis_claims::DEFAULT_CLAIM_TTL_SECS),
+            groups: match cli.nested("groups").map(groups::parse) {
+                Some(Ok(groups)) => groups,
+                Some(Err(e)) => {
+                    eprintln!("❌ Config file: {}", e);
+                    std::process::exit(1);
+                }
+                None => Vec::new(),
+            },
+            schedule: match cli.nested("schedule").map(schedule::parse) {
+                Some(Ok(schedule)) => Some(schedule),
+                Some(Err(e)) => {
+                    eprintln!("❌ Config file: {}", e);
+                    std::process::exit(1);
+                }
+                None => None,
+            },
+        }
     } else {
-        // Interactive mode - prompt user
+        // Interactive mode - prompt user (network settings still come from the config file)
+        load_cli_args(&[]);
         println!("\n📝 Configuration Setup (press Enter to use defaults)\n");
 
         // Get wallets file location
-        let wallets_file = get_user_input("📂 Wallets file location", "wallets.txt");
+        let wallets_file = get_user_input("📂 Wallets file location", wallets::default_source());
 
         // Get CPU usage percentage
         let cpu_input = get_user_input("💻 Maximum CPU usage (25/50/75/100)", "50");
@@ -1058,95 +3829,427 @@ fn get_configuration() -> (String, f64, Option<f64>) {
             .min(100.0)
             .max(1.0);
 
-        // Get max hashes threshold (optional)
-        println!("\n⏱️  Maximum hashes per task (auto-skip if exceeded)?");
-        println!("   Default: mine until solution found (no limit)");
-        println!("   Examples: 100 = 100M hashes, 0.5 = 500K hashes");
-        let max_hashes_input = get_user_input("🔢 Max hashes in millions (press Enter for no limit)", "none");
-        let max_hashes_millions = if max_hashes_input.is_empty() || max_hashes_input == "none" {
-            None
-        } else {
-            max_hashes_input.parse::<f64>().ok()
-        };
+        // Get max hashes threshold (optional)
+        println!("\n⏱️  Maximum hashes per task (auto-skip if exceeded)?");
+        println!("   Default: mine until solution found (no limit)");
+        println!("   Examples: 100 = 100M hashes, 0.5 = 500K hashes");
+        let max_hashes_input = get_user_input("🔢 Max hashes in millions (press Enter for no limit)", "none");
+        let max_hashes_millions = if max_hashes_input.is_empty() || max_hashes_input == "none" {
+            None
+        } else {
+            max_hashes_input.parse::<f64>().ok()
+        };
+
+        // Get max minutes per task (optional)
+        let max_minutes_input = get_user_input("⏳ Max minutes per task (press Enter for no limit)", "none");
+        let max_minutes_per_task = if max_minutes_input.is_empty() || max_minutes_input == "none" {
+            None
+        } else {
+            max_minutes_input.parse::<f64>().ok()
+        };
+
+        println!();
+
+        MinerConfig {
+            wallets_file,
+            cpu_usage,
+            max_hashes_millions,
+            max_minutes_per_task,
+            limit_check_interval: DEFAULT_LIMIT_CHECK_INTERVAL,
+            idle_only_minutes: None,
+            low_priority: false,
+            background_only: false,
+            cgroup: None,
+            process_priority: None,
+            max_clock_skew_secs: DEFAULT_MAX_CLOCK_SKEW_SECS,
+            clock_correct: false,
+            rom_cache_mb: DEFAULT_ROM_CACHE_MB,
+            auto_threads: None,
+            api_retries: DEFAULT_API_RETRIES,
+            api_backoff_ms: DEFAULT_API_BACKOFF_MS,
+            notify_mode: notify::NotifyMode::Events,
+            notify_webhook: None,
+            telegram_token: None,
+            telegram_chat: None,
+            telegram_commands: false,
+            telegram_api: telegram::DEFAULT_API_BASE.to_string(),
+            desktop_notify: false,
+            control_port: None,
+            control_token: None,
+            health_port: None,
+            health_bind: health::DEFAULT_BIND.to_string(),
+            health_stale_minutes: health::DEFAULT_STALE_MINUTES,
+            grpc_port: None,
+            grpc_bind: grpc::DEFAULT_BIND.to_string(),
+            grpc_token: None,
+            log_format: logging::ConsoleFormat::Pretty,
+            verbosity: logging::Verbosity::Normal,
+            log_json: None,
+            log_rotation: logging::Rotation::default(),
+            tui: false,
+            daemon: None,
+            progress: true,
+            hash_backend: backend::DEFAULT_BACKEND.to_string(),
+            backend_schedule: None,
+            nonce_start: NonceStart::Zero,
+            challenges_file: None,
+            health_check: true,
+            telemetry_url: None,
+            telemetry_interval_secs: telemetry::DEFAULT_TELEMETRY_INTERVAL_SECS,
+            hashrate_interval_secs: hashrate_history::DEFAULT_INTERVAL_SECS,
+            miner_id: None,
+            archive_after_days: archive::DEFAULT_ARCHIVE_AFTER_DAYS,
+            min_solve_percent: ranking::DEFAULT_MIN_SOLVE_PERCENT,
+            selection: spread::SelectionMode::Easiest,
+            rotation: rotation::RotationPolicy::RoundRobin,
+            difficult_retry_days: DEFAULT_DIFFICULT_RETRY_DAYS,
+            max_hashes_factor: None,
+            force: false,
+            force_lock: false,
+            credited_refresh_minutes: credited::DEFAULT_REFRESH_MINUTES,
+            redis_url: None,
+            redis_prefix: redis_claims::DEFAULT_PREFIX.to_string(),
+            redis_claim_ttl_secs: redis_claims::DEFAULT_CLAIM_TTL_SECS,
+            groups: Vec::new(),
+            schedule: None,
+        }
+    }
+}
+
+fn main() {
+    logging::init();
+    let args: Vec<String> = env::args().collect();
+    if let Some(exit_code) = run_subcommand(&args)
+        .or_else(|| service::handle_install_args(&args))
+        .or_else(|| daemon::handle_stop_args(&args))
+    {
+        std::process::exit(exit_code);
+    }
+    if args.iter().any(|a| a == service::RUN_SWITCH) {
+        std::process::exit(service::run(run_miner));
+    }
+    run_miner();
+}
+
+/// The miner itself: configuration, then mining until stopped
+fn run_miner() {
+    let args: Vec<String> = env::args().collect();
+
+    // The config file can't be read yet, so only the command line silences the banner
+    if !args.iter().any(|a| a == "-q" || a == "--quiet") {
+        println!("╔═══════════════════════════════════════════════════╗");
+        println!("║   Scavenger Mine USER-ONLY Miner v4.0             ║");
+        println!("║   - No profit sharing (100% for your wallets)    ║");
+        println!("║   - Dual core support                            ║");
+        println!("║   - Optimize hash rate                           ║");
+        println!("║   - Auto skip difficult challenges               ║");
+        println!("║   - Auto select easiest challenge to solve       ║");
+        println!("╚═══════════════════════════════════════════════════╝\n");
+    }
+
+    // Get configuration (either from CLI args or interactive prompts)
+    let config = get_configuration();
+
+    // Setup directories (configurable, so only once the configuration is known)
+    if let Err(e) = setup_directories() {
+        eprintln!("Failed to create output directories: {}", e);
+        std::process::exit(1);
+    }
+    // Detach from the terminal now that the configuration checked out, before any thread starts
+    let _pid_file = config.daemon.as_deref().map(daemon::daemonize);
+    configure_logging(&config, false);
+
+    // One miner per data directory; held until the process ends
+    let _instance_lock = instance::acquire_or_exit(config.force_lock);
+
+    // Before any worker thread starts, so they all inherit it
+    if let Some(priority) = config.process_priority {
+        if set_process_priority(priority) {
+            tracing::info!("🪶 Process priority: {}", priority.name());
+        } else {
+            tracing::warn!("⚠️  Could not set the process priority to {}: {}", priority.name(), std::io::Error::last_os_error());
+        }
+    }
+
+    tracing::info!("🚀 Starting USER-ONLY Miner (No Profit Sharing)");
+    tracing::info!("📁 Solutions will be saved to: {}/", paths::solutions_dir().display());
+    tracing::info!("📋 Logs will be saved to: {}/", paths::logs_dir().display());
+    let wallets_file = config.wallets_file.clone();
+    let cpu_usage = config.cpu_usage;
+
+    let hash_limit_msg = match (config.max_hashes_factor, config.max_hashes_millions) {
+        (Some(factor), _) => format!("Max hashes: {}× each challenge's expected cost", factor),
+        (None, Some(hashes)) => format!("Max hashes: {}M", hashes),
+        (None, None) => "No hash limit".to_string(),
+    };
+    let time_limit_msg = match config.max_minutes_per_task {
+        Some(minutes) => format!("Max time: {} min/task", minutes),
+        None => "No time limit".to_string(),
+    };
+    let wallets_msg = if config.groups.is_empty() {
+        format!("Wallets: {}", wallets::source_name(&wallets_file))
+    } else {
+        format!("Wallet groups: {}", config.groups.len())
+    };
+    tracing::info!(
+        "⚙️  Configuration: {}, CPU usage: {}%, {}, {}",
+        wallets_msg, cpu_usage, hash_limit_msg, time_limit_msg,
+    );
+    if let Some(db) = store::database_path() {
+        tracing::info!("🗄️  Solution records are kept in the SQLite database {}", db.display());
+    }
+
+    let _ = API_RETRY_POLICY.set(ApiRetryPolicy {
+        max_attempts: config.api_retries,
+        base_delay: Duration::from_millis(config.api_backoff_ms),
+        max_delay: API_BACKOFF_MAX,
+    });
+
+    if http::api_base() != SCAVENGER_API_BASE {
+        tracing::info!("🌐 API: {}", http::api_base());
+    }
+    if let Some(proxy) = http::proxy_description() {
+        tracing::info!("🌐 Using proxy {}", proxy);
+    }
+    if let Some(tls) = http::tls_description() {
+        tracing::info!("🔐 TLS: {}", tls);
+    }
+    if http::has_api_credentials() {
+        tracing::info!("🔑 Sending API credentials with every API request");
+    }
+    if receipt::enabled() {
+        tracing::info!("🔏 Crypto receipts are verified against the configured public key");
+    }
+
+    if let Some(path) = &config.challenges_file {
+        // Validate up front so a bad file fails before any work is done
+        match load_challenges_file(path) {
+            Ok(challenges) => tracing::info!(
+                "📴 Offline mode: {} challenge(s) from {}, solutions are saved for later submission", challenges.len(), path,
+            ),
+            Err(e) => {
+                tracing::error!(error = %e, "❌ {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
 
-        println!();
+    // Deadlines and record timestamps are meaningless with a wildly wrong clock (needs the API, so not offline)
+    if config.max_clock_skew_secs > 0 && config.challenges_file.is_none() {
+        if let Err(e) = check_clock_skew(config.max_clock_skew_secs, config.clock_correct) {
+            tracing::error!(error = %e, "❌ {}", e);
+            std::process::exit(1);
+        }
+    }
 
-        (wallets_file, cpu_usage, max_hashes_millions)
+    // Fail fast on an unreachable or changed API instead of after generating a ROM
+    if config.health_check && config.challenges_file.is_none() {
+        tracing::info!("🩺 Checking the API...");
+        let problems = startup_health_check();
+        if !problems.is_empty() {
+            tracing::error!("❌ API health check failed:");
+            for problem in &problems {
+                tracing::info!("   - {}", problem);
+            }
+            tracing::info!("   (use --skip-health-check to start anyway)");
+            std::process::exit(1);
+        }
+        tracing::info!("   ✅ Challenge endpoint and submission endpoint OK");
     }
-}
 
-fn main() {
-    println!("╔═══════════════════════════════════════════════════╗");
-    println!("║   Scavenger Mine USER-ONLY Miner v4.0             ║");
-    println!("║   - No profit sharing (100% for your wallets)    ║");
-    println!("║   - Dual core support                            ║");
-    println!("║   - Optimize hash rate                           ║");
-    println!("║   - Auto skip difficult challenges               ║");
-    println!("║   - Auto select easiest challenge to solve       ║");
-    println!("╚═══════════════════════════════════════════════════╝\n");
-
-    // Setup directories
-    if let Err(e) = setup_directories() {
-        eprintln!("Failed to create output directories: {}", e);
-        std::process::exit(1);
+    // Another rig may have solved challenges for the same wallets already
+    if config.credited_refresh_minutes > 0.0 && config.challenges_file.is_none() {
+        credited::enable(Duration::from_secs_f64(config.credited_refresh_minutes * 60.0));
     }
 
-    log_mining_progress("🚀 Starting USER-ONLY Miner (No Profit Sharing)");
-    log_mining_progress(&format!("📁 Solutions will be saved to: {}/", SOLUTIONS_DIR));
-    log_mining_progress(&format!("📋 Logs will be saved to: {}/", LOGS_DIR));
+    if config.low_priority {
+        LOW_PRIORITY_THREADS.store(true, Ordering::Relaxed);
+        tracing::info!("🐢 Mining threads run at low OS priority (--nice)");
+    } else if config.background_only {
+        qos::set_background_only(true);
+        if cfg!(target_os = "macos") {
+            tracing::info!("🌙 Mining threads run at utility QoS, yielding to foreground apps (--background-only)");
+        } else {
+            tracing::warn!("⚠️  --background-only only applies on macOS; use --nice elsewhere");
+        }
+    }
 
-    // Get configuration (either from CLI args or interactive prompts)
-    let (wallets_file, cpu_usage, max_hashes_millions) = get_configuration();
-
-    // Calculate hash threshold (if provided, convert millions to actual count)
-    let max_hashes = max_hashes_millions.map(|m| (m * 1_000_000.0) as u64);
-
-    let config_msg = match max_hashes_millions {
-        Some(hashes) => format!(
-            "⚙️  Configuration: Wallets file: {}, CPU usage: {}%, Max hashes: {}M",
-            wallets_file, cpu_usage, hashes
-        ),
-        None => format!(
-            "⚙️  Configuration: Wallets file: {}, CPU usage: {}%, No limit",
-            wallets_file, cpu_usage
-        ),
+    // Resolve the hash backend up front so a typo fails before any work is done
+    let hash_backend = match backend::backend_by_name(&config.hash_backend) {
+        Some(b) => b,
+        None => {
+            let known: Vec<String> = backend::available_backends().iter().map(|b| b.name().to_string()).collect();
+            tracing::error!("❌ Unknown hash backend '{}' (available: {})", config.hash_backend, known.join(", "));
+            std::process::exit(1);
+        }
+    };
+    tracing::info!("🧮 Default hash backend: {} - {}", hash_backend.name(), hash_backend.describe());
+
+    // Per-challenge routing: payload version first, then the operator's date schedule, then the default
+    let backend_router = match config.backend_schedule.as_deref() {
+        Some(spec) => match backend::BackendRouter::new(hash_backend).with_schedule(spec) {
+            Ok(router) => {
+                tracing::info!("🗓️  Backend schedule: {}", spec);
+                router
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "❌ Invalid --backend-schedule: {}", e);
+                std::process::exit(1);
+            }
+        },
+        None => backend::BackendRouter::new(hash_backend),
     };
-    log_mining_progress(&config_msg);
+
+    // Hashes per solution so far, for per-challenge hash thresholds
+    let mut solve_costs = solve_costs::SolveCosts::load();
+    if (config.max_hashes_factor.is_some() || config.groups.iter().any(|g| g.max_hashes_factor.is_some())) && solve_costs.solutions() > 0 {
+        tracing::info!("📚 Solve history: {} solution(s) to learn hash limits from", solve_costs.solutions());
+    }
 
     // Load difficult tasks
-    let difficult_tasks = load_difficult_tasks();
+    let mut difficult_tasks = load_difficult_tasks();
     if !difficult_tasks.is_empty() {
-        log_mining_progress(&format!("📋 Loaded {} difficult task(s) to skip", difficult_tasks.len()));
+        tracing::info!("📋 Loaded {} difficult task(s) to skip", difficult_tasks.len());
     }
 
-    // Load user wallets
-    let user_wallets = match load_user_wallets(&wallets_file) {
-        Ok(wallets) => {
-            log_mining_progress(&format!("✅ Loaded {} user wallet(s)", wallets.len()));
-            wallets
+    // Per-wallet retry overrides for failed submissions
+    let retry_policies = match retry_policy::RetryPolicies::load() {
+        Ok(policies) => {
+            if policies.override_count() > 0 {
+                tracing::info!("🔁 Loaded {} retry policy override(s)", policies.override_count());
+            }
+            Arc::new(policies)
         }
         Err(e) => {
-            log_mining_progress(&format!("❌ Error loading wallets: {}", e));
-            eprintln!("\n❌ ERROR: Could not load wallets file '{}'", wallets_file);
-            eprintln!("\n📝 Please create this file with one wallet address per line");
-            eprintln!("   Example content:");
-            eprintln!("   addr1q8upjxynn626c772r5nzym...");
-            eprintln!("   addr1qpxvug56xgecxhuzv3c60u4...");
-            eprintln!("\n💡 Tip: The file should be in the same folder as this executable");
-            eprintln!("   Current folder: {}", env::current_dir().unwrap().display());
-            eprintln!("\nPress Enter to exit...");
+            tracing::error!(error = %e, "❌ Invalid {}: {}", retry_policy::RETRY_POLICY_FILE, e);
+            std::process::exit(1);
+        }
+    };
 
-            // Wait for user to acknowledge in interactive mode
-            let args: Vec<String> = env::args().collect();
-            if args.len() == 1 {
-                let mut input = String::new();
-                std::io::stdin().read_line(&mut input).unwrap();
+    // Per-wallet allow/deny lists of challenge IDs
+    let challenge_filters = match challenge_filter::ChallengeFilters::load() {
+        Ok(filters) => {
+            if !filters.is_empty() {
+                tracing::info!(
+                    "🧭 Loaded challenge filters from {} ({} wallet override(s))",
+                    challenge_filter::CHALLENGE_FILTER_FILE, filters.override_count(),
+                );
             }
+            filters
+        }
+        Err(e) => {
+            tracing::error!(error = %e, "❌ Invalid {}: {}", challenge_filter::CHALLENGE_FILTER_FILE, e);
+            std::process::exit(1);
+        }
+    };
 
+    // Per-wallet solution quotas (the wallets file can set more), counted against the solutions
+    // already on record once the wallets are loaded
+    let mut quotas = match quota::WalletQuotas::load() {
+        Ok(quotas) => {
+            if !quotas.is_empty() {
+                tracing::info!(
+                    "🎯 Loaded solution quotas from {} ({} wallet override(s))",
+                    quota::WALLET_QUOTA_FILE, quotas.override_count(),
+                );
+            }
+            quotas
+        }
+        Err(e) => {
+            tracing::error!(error = %e, "❌ Invalid {}: {}", quota::WALLET_QUOTA_FILE, e);
             std::process::exit(1);
         }
     };
 
+    // Wallet groups: the config file's groups, or the wallets file with the command-line settings
+    // (split by its group column if it has one)
+    let group_configs = if !config.groups.is_empty() {
+        config.groups.clone()
+    } else {
+        let mut names: Vec<Option<String>> = Vec::new();
+        for row in read_wallet_rows_or_exit(&wallets_file) {
+            if !names.contains(&row.group) {
+                names.push(row.group);
+            }
+        }
+        if names.iter().all(Option::is_none) {
+            vec![groups::GroupConfig::from_wallets_file(&wallets_file)]
+        } else {
+            names.into_iter().map(groups::GroupConfig::tagged).collect()
+        }
+    };
+
+    // Load user wallets, checking every row's address
+    let mut group_wallets: Vec<Vec<(String, f64)>> = Vec::new();
+    let mut invalid_addresses = 0;
+    for (index, group_config) in group_configs.iter().enumerate() {
+        let name = group_config.name.clone().unwrap_or_else(|| format!("#{}", index + 1));
+        let (source, rows) = match &group_config.wallets {
+            Some(groups::GroupWallets::File(path)) => (wallets::source_name(path), read_wallet_rows_or_exit(path)),
+            Some(groups::GroupWallets::List(list)) => match wallets::parse(&list.join("\n"), wallets::Format::Text) {
+                Ok(rows) => (format!("group {}", name), rows),
+                Err(e) => {
+                    tracing::error!(error = %e, "❌ Error loading group {}: {}", name, e);
+                    std::process::exit(1);
+                }
+            },
+            None => {
+                let rows = read_wallet_rows_or_exit(&wallets_file);
+                if let Some(row) = rows.iter().find(|r| r.group.is_some() && !group_configs.iter().any(|g| g.name == r.group)) {
+                    tracing::error!(
+                        "❌ {}, line {}: group '{}' is not one of the config file's groups",
+                        wallets::source_name(&wallets_file), row.line, row.group.as_deref().unwrap_or_default(),
+                    );
+                    std::process::exit(1);
+                }
+                let source = format!("{} (group {})", wallets::source_name(&wallets_file), name);
+                (source, rows.into_iter().filter(|r| r.group == group_config.name).collect())
+            }
+        };
+
+        // A typo'd address mines solutions nobody can claim
+        for issue in address::check_addresses(rows.iter().map(|r| (Some(r.line), r.address.as_str()))) {
+            let icon = if issue.severity == address::Severity::Error { "❌" } else { "⚠️ " };
+            tracing::info!("{} {}, {}", icon, source, issue);
+            if issue.severity == address::Severity::Error {
+                invalid_addresses += 1;
+            }
+        }
+
+        let rows = wallets::unique(rows);
+        if rows.is_empty() {
+            tracing::error!("❌ No wallets in {}", source);
+            std::process::exit(1);
+        }
+        for row in rows.iter().filter(|r| r.quota_per_day.is_some() || r.quota_total.is_some()) {
+            quotas.set(&row.address, row.quota_per_day, row.quota_total);
+        }
+        let wallets: Vec<(String, f64)> = rows.into_iter().map(|r| (r.address, r.weight)).collect();
+
+        tracing::info!("✅ Loaded {} user wallet(s) from {}", wallets.len(), source);
+        if wallets.iter().any(|(_, weight)| *weight != 1.0) {
+            let total: f64 = wallets.iter().map(|(_, weight)| weight).sum();
+            for (address, weight) in wallets.iter().filter(|(_, weight)| *weight != 1.0) {
+                tracing::info!(
+                    "⚖️  Wallet {} has weight {} ({:.0}% of mining cycles)",
+                    wallets::label(address), weight, 100.0 * weight / total,
+                );
+            }
+        }
+        group_wallets.push(wallets);
+    }
+    quotas.count_recorded();
+    if invalid_addresses > 0 {
+        if !config.force {
+            tracing::error!(
+                "❌ {} invalid wallet address(es); fix them, or pass --force to mine anyway",
+                invalid_addresses,
+            );
+            std::process::exit(1);
+        }
+        tracing::warn!("⚠️  Mining anyway with {} invalid wallet address(es) (--force)", invalid_addresses);
+    }
+
     // Generate miner ID
     let hostname = hostname::get()
         .ok()
@@ -1155,248 +4258,654 @@ fn main() {
     let miner_id = format!("user-only-miner-{}-{}", hostname,
         SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs());
 
-    log_mining_progress(&format!("🆔 Miner ID: {}", miner_id));
+    tracing::info!("🆔 Miner ID: {}", miner_id);
 
     // Calculate number of threads - use Windows processor group aware detection for systems with >64 logical processors
     let total_cpus = get_total_logical_processors();
     let physical_cores = num_cpus::get_physical();
-    let num_threads = ((total_cpus as f64 * cpu_usage / 100.0).ceil() as usize).max(1);
+    let num_threads = match config.auto_threads {
+        Some(secs_per_level) => {
+            let backend = backend::backend_by_name(&config.hash_backend).expect("backend validated above");
+            calibrate_threads(backend.as_ref(), secs_per_level)
+        }
+        None => threads_for_cpu_usage(cpu_usage),
+    };
+    let cpu_usage = if config.auto_threads.is_some() {
+        (num_threads as f64 * 100.0 / total_cpus as f64).round()
+    } else {
+        cpu_usage
+    };
 
     // Log detailed CPU information
     if physical_cores < total_cpus {
-        log_mining_progress(&format!(
+        tracing::info!(
             "💻 System: {} logical processors ({} physical cores with hyper-threading), using {} threads ({}%)",
-            total_cpus, physical_cores, num_threads, cpu_usage
-        ));
-        log_mining_progress(&format!(
+            total_cpus, physical_cores, num_threads, cpu_usage,
+        );
+        tracing::info!(
             "   ℹ️  Hyper-threading detected: {} threads per core",
-            total_cpus / physical_cores
-        ));
+            total_cpus / physical_cores,
+        );
     } else {
-        log_mining_progress(&format!(
+        tracing::info!(
             "💻 System: {} CPU cores, using {} threads ({}%)",
-            total_cpus, num_threads, cpu_usage
-        ));
+            total_cpus, num_threads, cpu_usage,
+        );
     }
 
     // Additional tip for users with hyper-threading
     if num_threads >= total_cpus && physical_cores < total_cpus {
-        log_mining_progress("   ✅ Using all logical processors including hyper-threads for maximum performance");
+        tracing::info!("   ✅ Using all logical processors including hyper-threads for maximum performance");
+    }
+
+    // Kernel-enforced CPU share instead of only the rounded thread count
+    if let Some(path) = &config.cgroup {
+        match cgroup::join(path, cpu_usage) {
+            Ok(dir) => tracing::info!("📏 Running in cgroup {} with a CPU quota of {}%", dir.display(), cpu_usage),
+            Err(e) => tracing::warn!(error = %e, "⚠️  {}; limiting CPU by thread count only", e),
+        }
     }
 
     // ROM cache
-    let mut rom_cache = RomCache::new();
+    let mut rom_cache = RomCache::new(config.rom_cache_mb);
+
+    // Groups ready to mine, taking turns by their shares (a single group without `groups`)
+    let mut groups: Vec<groups::Group> = group_configs
+        .into_iter()
+        .zip(group_wallets)
+        .enumerate()
+        .map(|(index, (group_config, wallets))| config.group(index, group_config, wallets, num_threads, cpu_usage))
+        .collect();
+    if groups.len() > 1 {
+        let total_share: f64 = groups.iter().map(|g| g.share).sum();
+        for group in &groups {
+            let limit = match (group.max_hashes_factor, group.limits.max_hashes) {
+                (Some(factor), _) => format!("max {}× expected hashes", factor),
+                (None, Some(hashes)) => format!("max {} hashes", difficulty::format_count(hashes as f64)),
+                (None, None) => "no hash limit".to_string(),
+            };
+            tracing::info!(
+                "👥 Group {}: {} wallet(s), {:.0}% of mining cycles, {} thread(s), {} selection, {} rotation, {}",
+                group.name, group.wallets.len(), 100.0 * group.share / total_share, group.num_threads,
+                if group.spreader.is_some() { "spread" } else { "easiest" }, group.rotation.policy().name(), limit,
+            );
+        }
+    }
+    let mut group_rotation = rotation::WalletRotation::new(groups.iter().map(|g| g.share).collect(), rotation::RotationPolicy::RoundRobin);
+    for (address, _) in groups.iter().flat_map(|g| &g.wallets) {
+        if let Some(reached) = quotas.reached(address) {
+            let (today, total) = quotas.counts(address);
+            tracing::info!(
+                "🎯 Wallet {} is at its {} ({} today, {} in total), sitting out",
+                wallets::label(address),
+                match reached {
+                    quota::Reached::Daily(limit) => format!("daily quota of {}", limit),
+                    quota::Reached::Total(limit) => format!("total quota of {}", limit),
+                },
+                today, total,
+            );
+        }
+    }
+    let mut waiting_for_quotas = false;
+    let mut all_wallets: Vec<String> = groups.iter().flat_map(|g| g.wallets.iter().map(|(w, _)| w.clone())).collect();
 
     // Statistics
     let mut total_solutions = 0u64;
-    let mut current_wallet_index = 0usize;
     let session_start = Instant::now();
+    session_stats::start();
+    hashrate_history::start(config.hashrate_interval_secs);
 
-    // Challenges cache (fetch once per cycle or when needed)
+    // Challenges cache (fetch once per cycle or when needed), seeded with the ones earlier runs
+    // found that are still open; offline runs only use the challenges file
     let mut challenges_cache: Vec<Challenge> = vec![];
-    let mut last_challenges_fetch = Instant::now();
+    if config.challenges_file.is_none() {
+        apply_active_challenges(&mut challenges_cache, load_challenge_cache(), num_threads);
+        if !challenges_cache.is_empty() {
+            tracing::info!("📥 Restored {} active challenge(s) from earlier runs", challenges_cache.len());
+        }
+    }
+    let persist_challenges = config.challenges_file.is_none();
+    // Reorders the cache by the chance of solving each challenge in time, once the hashrate is known
+    let mut ranker = ranking::Ranker::new(config.min_solve_percent);
+    // Spread mode: consecutive wallets go to different challenges instead of all to the easiest
+    if groups.len() == 1 && groups[0].spreader.is_some() {
+        tracing::info!("🔀 Spreading wallets across the active challenges, weighted by difficulty");
+    }
+    if groups.len() == 1 && groups[0].rotation.policy() != rotation::RotationPolicy::RoundRobin {
+        tracing::info!("🔄 Wallet rotation: {}", groups[0].rotation.policy().name());
+    }
+    let mut challenge_poller = ChallengePoller::start(config.challenges_file.clone());
+
+    // Ctrl-C / SIGTERM stop the loop at the next safe point instead of killing mid-write
+    install_shutdown_handler();
+    install_pause_handler();
+
+    // Telegram: notifications, and commands from the same chat if asked for
+    let telegram = match (&config.telegram_token, &config.telegram_chat) {
+        (Some(token), Some(chat)) => match telegram::Telegram::new(token, chat, &config.telegram_api) {
+            Ok(bot) => Some(Arc::new(bot)),
+            Err(e) => {
+                tracing::warn!(error = %e, "⚠️  Telegram disabled (HTTP client: {})", e);
+                None
+            }
+        },
+        (None, None) => None,
+        _ => {
+            tracing::warn!("⚠️  Telegram needs both --telegram-token and --telegram-chat, not sending notifications there");
+            None
+        }
+    };
+    if let Some(bot) = &telegram {
+        tracing::info!("🤖 Telegram notifications to chat {}", config.telegram_chat.as_deref().unwrap_or_default());
+        if config.telegram_commands {
+            bot.start_commands();
+        }
+    }
+
+    // Receipts/failures go out per event or as a periodic digest
+    let mut notifier = notify::Notifier::new(config.notify_mode, config.notify_webhook.clone(), telegram, config.desktop_notify);
+    let mut submit_queue = SubmitQueue::start(Arc::clone(&retry_policies));
+    if let notify::NotifyMode::Digest(period) = config.notify_mode {
+        tracing::info!("📬 Notification digest every {}", format_duration_secs(period.as_secs_f64()));
+    }
+
+    // The miner ID defaults to the hostname so rigs sharing Redis are told apart
+    let miner_id = config.miner_id.clone().unwrap_or_else(|| {
+        hostname::get()
+            .ok()
+            .and_then(|h| h.into_string().ok())
+            .unwrap_or_else(|| "unknown".to_string())
+    });
+
+    // Opt-in fleet stats; anonymous unless --miner-id names the rig, so the hostname never leaves it
+    let telemetry_id = match (&config.telemetry_url, &config.miner_id) {
+        (_, Some(id)) => id.clone(),
+        (Some(_), None) => telemetry::anonymous_id(),
+        (None, None) => String::new(),
+    };
+    if let Some(url) = &config.telemetry_url {
+        tracing::info!(
+            "📡 Telemetry: reporting as '{}' to {} every {}",
+            telemetry_id, url, format_duration_secs(config.telemetry_interval_secs as f64),
+        );
+    }
+    let mut telemetry = telemetry::Reporter::new(
+        config.telemetry_url.clone(),
+        Duration::from_secs(config.telemetry_interval_secs),
+        telemetry_id,
+        num_threads,
+    );
+
+    // Rigs sharing a Redis server don't start the same task twice
+    if let (Some(url), None) = (&config.redis_url, &config.challenges_file) {
+        if let Err(e) = redis_claims::enable(url, &config.redis_prefix, config.redis_claim_ttl_secs, &miner_id) {
+            tracing::error!(error = %e, "❌ {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    // Keep solutions/ small: old records move to solutions/archive/ now and once a day
+    let mut archiver = archive::Archiver::new(config.archive_after_days);
+    archiver.tick();
+
+    // Idle-only mode: mine only after the user has been away for N minutes
+    if let Some(minutes) = config.idle_only_minutes {
+        tracing::info!("😴 Idle-only mode: mining after {} minute(s) without user input", minutes);
+        idle::start_monitor(Duration::from_secs_f64(minutes.max(0.0) * 60.0));
+    }
+
+    // Mining schedule: idle outside its windows, each window's CPU budget inside them
+    if let Some(schedule) = &config.schedule {
+        tracing::info!("🗓️  Mining schedule (local time): {}", schedule.describe());
+        schedule::enable(schedule.clone());
+    }
+    let configured_threads: Vec<(usize, f64)> = groups.iter().map(|g| (g.num_threads, g.cpu_usage)).collect();
+    let mut schedule_budget = None;
+
+    // Wallets can be added and drained over a localhost endpoint while mining
+    let control = config.control_port.map(|port| match control::ControlServer::start(port, config.control_token.clone()) {
+        Ok(control) => {
+            publish_wallets(&control, &groups);
+            control
+        }
+        Err(e) => {
+            tracing::error!(error = %e, "❌ Cannot start the wallet control endpoint on port {}: {}", port, e);
+            std::process::exit(1);
+        }
+    });
+    let mut waiting_for_wallets = false;
+
+    // Liveness probe for Docker/Kubernetes
+    if let Some(port) = config.health_port {
+        if let Err(e) = health::start(&config.health_bind, port, config.health_stale_minutes, config.challenges_file.is_some()) {
+            tracing::error!(error = %e, "❌ Cannot start the health endpoint on {}:{}: {}", config.health_bind, port, e);
+            std::process::exit(1);
+        }
+    }
+
+    // Typed control and status API for fleet tooling
+    let grpc = config.grpc_port.map(|port| {
+        grpc::GrpcServer::start(&config.grpc_bind, port, config.grpc_token.clone(), &miner_id).unwrap_or_else(|e| {
+            tracing::error!(error = %e, "❌ Cannot start the gRPC control API on {}:{}: {}", config.grpc_bind, port, e);
+            std::process::exit(1);
+        })
+    });
+
+    // Keep a `WatchdogSec=` systemd unit from restarting a miner that is making progress
+    systemd::start_watchdog();
+
+    // Full-screen dashboard instead of the scrolling log, from here until shutdown
+    dashboard::set_wallets(&all_wallets);
+    let dashboard = if config.tui { dashboard::Dashboard::start() } else { None };
+    if dashboard.is_some() {
+        configure_logging(&config, true);
+    }
+    // Otherwise a progress line on the terminal instead of periodic hashing lines
+    let show_progress = dashboard.is_none()
+        && config.progress
+        && config.log_format == logging::ConsoleFormat::Pretty
+        && config.verbosity != logging::Verbosity::Quiet;
+    let progress_line = if show_progress { progress::ProgressLine::start() } else { None };
 
     // Main mining loop - USER ONLY MODE
     loop {
-        // Update active challenges periodically (every cycle or every 5 minutes)
-        // This fetches the current challenge, adds it to cache, and removes expired ones
-        if challenges_cache.is_empty() || last_challenges_fetch.elapsed() > Duration::from_secs(300) {
-            match update_active_challenges(&mut challenges_cache, num_threads) {
-                Ok(()) => {
-                    last_challenges_fetch = Instant::now();
-                    log_mining_progress(&format!("📥 Active challenges: {} (sorted by difficulty, easiest first)", challenges_cache.len()));
-                }
-                Err(e) => {
-                    log_mining_progress(&format!("⚠️  Error updating challenges: {}, will retry later", e));
-                    if challenges_cache.is_empty() {
-                        thread::sleep(Duration::from_secs(30));
-                        continue;
+        if shutdown_requested() {
+            break;
+        }
+        health::loop_tick();
+        systemd::loop_tick();
+
+        // Stay idle while paused; the ROM cache is kept so resuming is instant
+        if let Some(reason) = pause_reason() {
+            tracing::info!("⏸️  Paused ({}), waiting for resume...", reason);
+            // Nothing is fetched while paused, so startup is as complete as it gets
+            systemd::ready();
+            systemd::status(&format!("Paused ({})", reason));
+            let waited = wait_while_paused();
+            if shutdown_requested() {
+                break;
+            }
+            tracing::info!("▶️  Resumed after {:.0?}", waited);
+            systemd::status("Mining");
+        }
+
+        // Tasks started in a schedule window with a CPU budget get its thread count
+        let window = schedule::current_window();
+        let budget = window.and_then(|w| w.cpu_usage);
+        if budget != schedule_budget {
+            for (group, &(threads, cpu_usage)) in groups.iter_mut().zip(&configured_threads) {
+                group.num_threads = budget.map_or(threads, threads_for_cpu_usage);
+                group.cpu_usage = budget.unwrap_or(cpu_usage);
+            }
+            if let Some(window) = window {
+                tracing::info!("🗓️  Schedule window {}: {} thread(s)", window.describe(), groups.first().map_or(num_threads, |g| g.num_threads));
+            }
+            schedule_budget = budget;
+        }
+
+        let receipts = record_submit_events(submit_queue.take_events(), &mut notifier, &telemetry);
+        total_solutions += receipts;
+        session_stats::record_solutions(receipts);
+
+        // Preemption point: apply whatever the background poller fetched while we were mining
+        // (also drops challenges that are now within 1 hour of their deadline)
+        let fetched = challenge_poller.take_fetched();
+        let fetched_any = !fetched.is_empty();
+        if apply_active_challenges(&mut challenges_cache, fetched, num_threads) && persist_challenges {
+            save_challenge_cache(&challenges_cache);
+        }
+        if fetched_any {
+            tracing::info!("📥 Active challenges: {} (sorted by difficulty, easiest first)", challenges_cache.len());
+        }
+
+        // Same for wallets added or drained over the control endpoint
+        if let Some(control) = &control {
+            let commands = control.take_commands();
+            if !commands.is_empty() {
+                apply_wallet_commands(commands, &mut groups, &mut all_wallets);
+                publish_wallets(control, &groups);
+                dashboard::set_wallets(&all_wallets);
+            }
+        }
+
+        // And whatever was asked for over gRPC
+        if let Some(grpc) = &grpc {
+            for command in grpc.take_commands() {
+                match command {
+                    grpc::Command::SetCpuUsage(percent) => {
+                        let threads = threads_for_cpu_usage(percent);
+                        for group in &mut groups {
+                            group.num_threads = threads;
+                            group.cpu_usage = percent;
+                        }
+                        tracing::info!("🧵 CPU usage set to {}% over gRPC: {} thread(s)", percent, threads);
+                    }
+                    grpc::Command::RefreshChallenges => {
+                        let fetched = challenge_poller.force_refresh(Duration::from_secs(30));
+                        if apply_active_challenges(&mut challenges_cache, fetched, num_threads) && persist_challenges {
+                            save_challenge_cache(&challenges_cache);
+                        }
+                        tracing::info!("📥 Active challenges: {} (refreshed over gRPC)", challenges_cache.len());
+                    }
+                    grpc::Command::RetrySubmissions => {
+                        if config.challenges_file.is_none() {
+                            check_and_retry_failed_submissions(&retry_policies, true);
+                        }
                     }
                 }
             }
+            grpc.publish(groups.first().map_or(num_threads, |g| g.num_threads), challenges_cache.len());
         }
 
-        // Mine for user - cycle through user wallets
-        let user_wallet = &user_wallets[current_wallet_index];
-        current_wallet_index = (current_wallet_index + 1) % user_wallets.len();
+        if challenges_cache.is_empty() {
+            // Nothing to mine yet: ask for a (rate-limited) refresh and wait for it
+            let fetched = challenge_poller.force_refresh(Duration::from_secs(30));
+            if fetched.is_empty() {
+                tracing::warn!("⚠️  No active challenges yet, will retry later");
+            }
+            if apply_active_challenges(&mut challenges_cache, fetched, num_threads) && persist_challenges {
+                save_challenge_cache(&challenges_cache);
+            }
+            continue;
+        }
 
-        log_mining_progress(&format!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━"));
-        log_mining_progress(&format!("👤 Mining for USER (Solution #{})", total_solutions + 1));
-        log_mining_progress(&format!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━"));
+        // Mine for user - cycle through the groups, and the wallets within each
+        // (wallets that reached their solution quota sit out)
+        let multiple_groups = groups.len() > 1;
+        let picked = group_rotation
+            .next(|index| groups[index].wallets.iter().any(|(w, _)| quotas.reached(w).is_none()))
+            .and_then(|index| groups[index].next_wallet(|w| quotas.reached(w).is_none()).map(|w| (index, w)));
+        let Some((group_index, user_wallet)) = picked else {
+            let wallets = || groups.iter().flat_map(|g| &g.wallets);
+            if wallets().next().is_none() {
+                // Every wallet was drained over the control endpoint
+                if !waiting_for_wallets {
+                    tracing::info!("👛 No wallets left to mine, waiting for POST /wallets...");
+                    waiting_for_wallets = true;
+                }
+                main_loop_sleep(Duration::from_secs(5));
+                continue;
+            }
+            if wallets().all(|(w, _)| matches!(quotas.reached(w), Some(quota::Reached::Total(_)))) {
+                tracing::info!("🎯 Every wallet has reached its total solution quota, nothing left to mine");
+                break;
+            }
+            if !waiting_for_quotas {
+                tracing::info!("🎯 Every wallet has reached its solution quota for today, waiting for the next UTC day...");
+                waiting_for_quotas = true;
+            }
+            let tomorrow = (now_utc().date_naive() + chrono::Days::new(1)).and_time(chrono::NaiveTime::MIN).and_utc();
+            let until_tomorrow = (tomorrow - now_utc()).to_std().unwrap_or_default() + Duration::from_secs(1);
+            main_loop_sleep(until_tomorrow.min(Duration::from_secs(300)));
+            continue;
+        };
+        waiting_for_quotas = false;
+        waiting_for_wallets = false;
+        let group = &mut groups[group_index];
+        let user_wallet = &user_wallet;
+
+        tracing::info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+        if multiple_groups {
+            tracing::info!("👤 Mining for USER, group {} (Solution #{})", group.name, total_solutions + 1);
+        } else {
+            tracing::info!("👤 Mining for USER (Solution #{})", total_solutions + 1);
+        }
+        tracing::info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
 
-        // Select best challenge for this wallet (easiest unsolved challenge)
-        let challenge = match select_challenge_for_wallet(user_wallet, &challenges_cache) {
+        // Select best challenge for this wallet (most likely to be solved in time, then easiest)
+        let wallet_filter = challenge_filters.for_wallet(user_wallet);
+        let challenge = match select_challenge_for_wallet(user_wallet, &ranker.rank(&challenges_cache, &group.limits), wallet_filter, group.spreader.as_mut()) {
             Some(challenge) => challenge,
             None => {
-                log_mining_progress(&format!("✅ All active challenges solved for wallet: {}...", &user_wallet[..20.min(user_wallet.len())]));
-                log_mining_progress("📥 Updating challenges list...");
-
-                // Force refresh challenges
-                match update_active_challenges(&mut challenges_cache, num_threads) {
-                    Ok(()) => {
-                        last_challenges_fetch = Instant::now();
-                        log_mining_progress(&format!("📥 Active challenges updated: {}", challenges_cache.len()));
-                    }
-                    Err(e) => {
-                        log_mining_progress(&format!("❌ Error updating challenges: {}", e));
-                        thread::sleep(Duration::from_secs(30));
-                        continue;
-                    }
+                tracing::info!(
+                    "✅ All{} active challenges{} solved for wallet: {}",
+                    if wallet_filter.is_restrictive() { " allowed" } else { "" },
+                    if ranker.skipping(&challenges_cache) { " within reach" } else { "" },
+                    wallets::label(user_wallet),
+                );
+                tracing::info!("📥 Updating challenges list...");
+
+                // Force refresh challenges (rate-limited so a flapping API can't make us spin)
+                let fetched = challenge_poller.force_refresh(Duration::from_secs(30));
+                if apply_active_challenges(&mut challenges_cache, fetched, num_threads) && persist_challenges {
+                    save_challenge_cache(&challenges_cache);
                 }
+                tracing::info!("📥 Active challenges updated: {}", challenges_cache.len());
 
                 // Try again with updated challenges
-                match select_challenge_for_wallet(user_wallet, &challenges_cache) {
+                match select_challenge_for_wallet(user_wallet, &ranker.rank(&challenges_cache, &group.limits), wallet_filter, group.spreader.as_mut()) {
                     Some(challenge) => challenge,
                     None => {
-                        log_mining_progress("⚠️  No available challenges to mine, waiting...");
-                        thread::sleep(Duration::from_secs(60));
+                        tracing::warn!("⚠️  No available challenges to mine, waiting...");
+                        group.pass(user_wallet);
+                        main_loop_sleep(Duration::from_secs(60));
                         continue;
                     }
                 }
             }
         };
 
-        log_mining_progress(&format!("📋 Challenge: {}", challenge.challenge_id));
-        log_mining_progress(&format!("👛 Wallet: {}...", &user_wallet[..20.min(user_wallet.len())]));
-        log_mining_progress(&format!("🎯 Difficulty: {}", challenge.difficulty));
-
-        // Check if this task is marked as too difficult
-        if is_difficult_task(user_wallet, &challenge.challenge_id, &difficult_tasks) {
-            log_mining_progress("⏭️  Skipping: Task marked as too difficult");
-            continue;
+        // Everything logged until the next task carries the challenge and wallet (logging.rs)
+        let _task = tracing::info_span!("task", challenge_id = %challenge.challenge_id, wallet = %user_wallet).entered();
+        tracing::info!(challenge_id = %challenge.challenge_id, "📋 Challenge: {}", challenge.challenge_id);
+        tracing::info!("👛 Wallet: {}", wallets::label(user_wallet));
+        tracing::info!("🎯 Difficulty: {}", challenge.difficulty);
+        let model = challenge.difficulty_model();
+        if let Some(model) = &model {
+            tracing::info!("   📐 {}", model.describe(ranking::measured_hashrate()));
         }
 
-        let rom = rom_cache.get_or_create(&challenge.no_pre_mine);
+        // Hash threshold for this challenge from the solve history (--max-hashes-factor)
+        let task_limits = match (group.max_hashes_factor, &model) {
+            (Some(factor), Some(model)) => {
+                let (limit, how) = solve_costs.hash_limit(model, factor);
+                tracing::info!("   📏 Hash limit: {} = {}", difficulty::format_count(limit as f64), how);
+                group.task_limits(Some(limit))
+            }
+            _ => group.task_limits(None),
+        };
 
-        log_mining_progress("⛏️  Starting mining threads...");
-        let start_time = Instant::now();
-        match mine_single_solution(rom, user_wallet, &challenge, num_threads, max_hashes) {
-            MiningResult::Found(nonce) => {
-                let elapsed = start_time.elapsed();
-                log_mining_progress(&format!("✅ Solution found in {:.2?}", elapsed));
+        // Check if this task is marked as too difficult, and whether it's worth another try by now
+        if let Some(task) = difficult_tasks.iter().find(|t| t.wallet_address == *user_wallet && t.challenge_id == challenge.challenge_id) {
+            match task.retry_reason(&task_limits, ranking::measured_hashrate(), config.difficult_retry_days) {
+                Some(reason) => {
+                    tracing::info!("🔁 Retrying task marked as too difficult: {}", reason);
+                    if let Err(e) = remove_difficult_task(user_wallet, &challenge.challenge_id) {
+                        tracing::warn!(error = %e, "⚠️  Failed to update difficult tasks: {}", e);
+                    }
+                    difficult_tasks.retain(|t| t.wallet_address != *user_wallet || t.challenge_id != challenge.challenge_id);
+                }
+                None => {
+                    tracing::info!("⏭️  Skipping: Task marked as too difficult");
+                    group.pass(user_wallet);
+                    continue;
+                }
+            }
+        }
 
-                let found_timestamp = get_timestamp();
+        // Route to the hash parameters this challenge was issued with
+        let schedule_time = challenge.issued_at.as_deref().unwrap_or(&challenge.latest_submission);
+        let hash_backend = match backend_router.route(challenge.version_indicator().as_deref(), Some(schedule_time)) {
+            Ok(b) => b,
+            Err(e) => {
+                tracing::info!(challenge_id = %challenge.challenge_id, error = %e, "⏭️  Skipping challenge {}: {}", challenge.challenge_id, e);
+                challenges_cache.retain(|c| c.challenge_id != challenge.challenge_id);
+                group.pass(user_wallet);
+                continue;
+            }
+        };
 
-                match submit_to_scavenger(user_wallet, &challenge.challenge_id, nonce) {
-                    Ok(SubmitResult::Success(crypto_receipt)) => {
-                        log_mining_progress("✅ Submitted to Scavenger Mine");
-
-                        // Export solution with crypto receipt
-                        let record = SolutionRecord {
-                            wallet_address: user_wallet.clone(),
-                            challenge_id: challenge.challenge_id.clone(),
-                            nonce: format!("{:016x}", nonce),
-                            found_at: found_timestamp,
-                            submitted_at: Some(get_timestamp()),
-                            crypto_receipt: Some(crypto_receipt),
-                            status: "submitted".to_string(),
-                            error_message: None,
-                            retry_count: 0,
-                            last_retry_at: None,
-                        };
+        // Another rig may have started this task since the challenge was picked
+        let claim = match redis_claims::claim(user_wallet, &challenge.challenge_id) {
+            Ok(claim) => claim,
+            Err(holder) => {
+                tracing::info!("🔐 Task already claimed by {}, picking another", holder);
+                group.pass(user_wallet);
+                continue;
+            }
+        };
 
-                        if let Err(e) = export_solution(&record) {
-                            log_mining_progress(&format!("⚠️  Failed to export solution: {}", e));
-                        }
+        let rom = rom_cache.get_or_create(hash_backend.as_ref(), &challenge.no_pre_mine);
 
-                        total_solutions += 1;
-                    }
-                    Ok(SubmitResult::Failed(error_msg)) => {
-                        log_mining_progress(&format!("❌ Scavenger submission failed: {}", error_msg));
-
-                        // Check if this is a non-retriable error
-                        let error_lower = error_msg.to_lowercase();
-                        let status = if error_lower.contains("solution already exists") ||
-                                        error_lower.contains("already exists") {
-                            log_mining_progress("   ℹ️  Solution already submitted elsewhere (won't retry)");
-                            "duplicate".to_string()
-                        } else if error_lower.contains("does not meet difficulty") ||
-                                  (error_lower.contains("difficulty") && error_lower.contains("not meet")) {
-                            log_mining_progress("   ℹ️  Invalid nonce (won't retry)");
-                            "invalid_nonce".to_string()
-                        } else {
-                            log_mining_progress("   🔄 Will retry after 1 hour");
-                            "failed".to_string()
-                        };
+        let nonce_start = config.nonce_start.pick();
+        if nonce_start != 0 {
+            tracing::info!("🎲 Nonce search starts at {:016x}", nonce_start);
+        }
 
-                        // Export solution with error
-                        let record = SolutionRecord {
-                            wallet_address: user_wallet.clone(),
-                            challenge_id: challenge.challenge_id.clone(),
-                            nonce: format!("{:016x}", nonce),
-                            found_at: found_timestamp,
-                            submitted_at: Some(get_timestamp()),
-                            crypto_receipt: None,
-                            status,
-                            error_message: Some(error_msg),
-                            retry_count: 0,
-                            last_retry_at: None,
-                        };
+        tracing::info!("⛏️  Starting mining threads...");
+        telemetry.set_active_challenge(&challenge.challenge_id, challenges_cache.len());
+        let start_time = Instant::now();
+        cgroup::set_cpu_usage(group.cpu_usage);
+        let result = mine_single_solution(rom, user_wallet, &challenge, group.num_threads, &task_limits, nonce_start);
+        if let Some(claim) = claim {
+            claim.finish(matches!(result, MiningResult::Found(..)));
+        }
+        wallet_stats::record_task(user_wallet, result.hashes(), start_time.elapsed().as_secs_f64(), matches!(result, MiningResult::Found(..)));
+        session_stats::record_hashes(result.hashes());
+        attempts::record(
+            user_wallet,
+            &challenge.challenge_id,
+            &challenge.difficulty,
+            result.hashes(),
+            start_time.elapsed().as_secs_f64(),
+            result.outcome(),
+        );
+        group.served(user_wallet, result.hashes());
+        if let Some(model) = &model {
+            solve_costs.record(model, result.hashes(), matches!(result, MiningResult::Found(..)));
+        }
+        if matches!(result, MiningResult::Found(..)) {
+            telemetry.record_solution();
+            quotas.record_found(user_wallet);
+            match quotas.reached(user_wallet) {
+                Some(quota::Reached::Daily(limit)) => tracing::info!(
+                    "🎯 Wallet {} reached its quota of {} solution(s) today, sitting out until tomorrow (UTC)",
+                    wallets::label(user_wallet), limit,
+                ),
+                Some(quota::Reached::Total(limit)) => tracing::info!(
+                    "🎯 Wallet {} reached its total quota of {} solution(s), sitting out from now on",
+                    wallets::label(user_wallet), limit,
+                ),
+                None => {}
+            }
+        }
+        match result {
+            MiningResult::Found(nonce, hashes) if config.challenges_file.is_some() => {
+                let elapsed = start_time.elapsed();
+                tracing::info!(target: logging::SOLUTIONS, nonce = %format!("{:016x}", nonce), hashes, elapsed_secs = elapsed.as_secs_f64(), "✅ Solution found in {:.2?} (offline, saved for later submission)", elapsed);
+                notifier.record_hashes(hashes, elapsed.as_secs_f64());
+                notifier.solution_found(user_wallet, &challenge.challenge_id);
 
-                        if let Err(e) = export_solution(&record) {
-                            log_mining_progress(&format!("⚠️  Failed to export solution: {}", e));
-                        }
-                    }
-                    Err(e) => {
-                        log_mining_progress(&format!("❌ Network error submitting to Scavenger: {}", e));
-                        log_mining_progress("   🔄 Will retry after 1 hour");
-
-                        // Export solution with error - will be retried
-                        let record = SolutionRecord {
-                            wallet_address: user_wallet.clone(),
-                            challenge_id: challenge.challenge_id.clone(),
-                            nonce: format!("{:016x}", nonce),
-                            found_at: found_timestamp,
-                            submitted_at: None,
-                            crypto_receipt: None,
-                            status: "error: network".to_string(),
-                            error_message: Some(format!("Network error: {}", e)),
-                            retry_count: 0,
-                            last_retry_at: None,
-                        };
+                // Picked up by the retry logic on the next online run, right away
+                let found_timestamp = get_timestamp();
+                let record = SolutionRecord {
+                    wallet_address: user_wallet.clone(),
+                    challenge_id: challenge.challenge_id.clone(),
+                    nonce: format!("{:016x}", nonce),
+                    found_at: found_timestamp.clone(),
+                    submitted_at: None,
+                    crypto_receipt: None,
+                    status: "offline".to_string(),
+                    error_message: None,
+                    retry_count: 0,
+                    last_retry_at: None,
+                    nonce_start: Some(format!("{:016x}", nonce_start)),
+                    next_retry_at: Some(found_timestamp),
+                    verified: None,
+                };
 
-                        if let Err(e) = export_solution(&record) {
-                            log_mining_progress(&format!("⚠️  Failed to export solution: {}", e));
-                        }
-                    }
+                if let Err(e) = export_solution(&record) {
+                    tracing::warn!(error = %e, "⚠️  Failed to export solution: {}", e);
                 }
+
+                total_solutions += 1;
+                session_stats::record_solutions(1);
+            }
+            MiningResult::Found(nonce, hashes) => {
+                let elapsed = start_time.elapsed();
+                tracing::info!(target: logging::SOLUTIONS, nonce = %format!("{:016x}", nonce), hashes, elapsed_secs = elapsed.as_secs_f64(), "✅ Solution found in {:.2?}", elapsed);
+                notifier.record_hashes(hashes, elapsed.as_secs_f64());
+                notifier.solution_found(user_wallet, &challenge.challenge_id);
+
+                // Submitted in the background so the next task starts right away
+                submit_queue.push(FoundSolution {
+                    wallet_address: user_wallet.clone(),
+                    challenge_id: challenge.challenge_id.clone(),
+                    nonce,
+                    nonce_start,
+                    found_at: get_timestamp(),
+                });
             }
             MiningResult::TooHard(hashes, duration) => {
-                log_mining_progress(&format!("⏭️  Task too difficult: {} hashes in {}s", hashes, duration));
+                notifier.record_hashes(hashes, start_time.elapsed().as_secs_f64());
+                tracing::info!(hashes, duration_secs = duration, "⏭️  Task too difficult: {} hashes in {}s", hashes, duration);
                 let difficult = DifficultTask {
                     wallet_address: user_wallet.clone(),
                     challenge_id: challenge.challenge_id.clone(),
                     marked_at: get_timestamp(),
                     total_hashes: hashes,
                     mining_duration_secs: duration,
+                    max_hashes: task_limits.max_hashes,
+                    max_duration_secs: task_limits.max_duration.map(|d| d.as_secs()),
+                    hashrate: ranking::measured_hashrate(),
                 };
-                if let Err(e) = save_difficult_task(difficult) {
-                    log_mining_progress(&format!("⚠️  Failed to save difficult task: {}", e));
+                if let Err(e) = save_difficult_task(difficult.clone()) {
+                    tracing::warn!(error = %e, "⚠️  Failed to save difficult task: {}", e);
                 }
+                difficult_tasks.retain(|t| t.wallet_address != difficult.wallet_address || t.challenge_id != difficult.challenge_id);
+                difficult_tasks.push(difficult);
+            }
+            MiningResult::Interrupted(hashes) => {
+                notifier.record_hashes(hashes, start_time.elapsed().as_secs_f64());
+                tracing::info!("🛑 Mining interrupted after {} hashes", hashes);
+            }
+            MiningResult::Skipped(hashes) => {
+                notifier.record_hashes(hashes, start_time.elapsed().as_secs_f64());
+                tracing::info!("⏭️  Skipped after {} hashes, not picking this task again this session", hashes);
+                dashboard::mark_skipped(user_wallet, &challenge.challenge_id);
+            }
+            MiningResult::DeadlinePassed(hashes) => {
+                notifier.record_hashes(hashes, start_time.elapsed().as_secs_f64());
+                tracing::info!(challenge_id = %challenge.challenge_id, "⌛ Gave up on challenge {} at its deadline after {} hashes", challenge.challenge_id, hashes);
+                challenges_cache.retain(|c| c.challenge_id != challenge.challenge_id);
             }
             MiningResult::NotFound => {
-                log_mining_progress("❌ No solution found");
+                tracing::error!("❌ No solution found");
             }
         }
 
         // Check and retry any failed submissions (only if at least 1 hour has passed)
-        check_and_retry_failed_submissions();
-
-        // Print statistics
-        println!("\n📊 Session Statistics:");
-        println!("   Total solutions: {} (100% for your wallets)", total_solutions);
-        println!("   Runtime: {:.2?}", session_start.elapsed());
-
-        // Calculate and display average time per solution
-        if total_solutions > 0 {
-            let avg_time_secs = session_start.elapsed().as_secs_f64() / total_solutions as f64;
-            let avg_minutes = (avg_time_secs / 60.0).floor() as u64;
-            let avg_seconds = (avg_time_secs % 60.0).floor() as u64;
-            println!("   Average time per solution: {}m {}s\n", avg_minutes, avg_seconds);
-        } else {
-            println!();
+        if config.challenges_file.is_none() {
+            check_and_retry_failed_submissions(&retry_policies, dashboard::take_retry());
+        }
+
+        // Print statistics (the dashboard shows them itself, quiet mode leaves them out)
+        if !dashboard::active() && logging::verbosity() != logging::Verbosity::Quiet {
+            print_session_statistics(total_solutions, session_start, &all_wallets);
         }
+        notifier.tick();
+        archiver.tick();
+
+        main_loop_sleep(Duration::from_secs(2));
+    }
+
+    drop(progress_line);
+    if let Some(dashboard) = dashboard {
+        drop(dashboard);
+        configure_logging(&config, false);
+    }
 
-        thread::sleep(Duration::from_secs(2));
+    // Everything found so far has been submitted or written to solutions/ by now
+    tracing::info!("🛑 Shutting down gracefully");
+    systemd::stopping();
+    let receipts = record_submit_events(submit_queue.finish(), &mut notifier, &telemetry);
+    total_solutions += receipts;
+    session_stats::record_solutions(receipts);
+    session_stats::flush();
+    if logging::verbosity() != logging::Verbosity::Quiet {
+        print_session_statistics(total_solutions, session_start, &all_wallets);
     }
-}
\ No newline at end of file
+    notifier.flush();
+    telemetry.finish();
+    tracing::info!("👋 Miner stopped");
+}
@@ -0,0 +1,137 @@
+//! `migrate`: bring solution files from older versions to the current naming scheme
+//!
+//! Early versions named record files after a shortened wallet address, so the existence check
+//! (keyed on the full address) missed them and solved challenges were mined again. `migrate`
+//! reads every record file in `solutions/`, rewrites the ones whose name doesn't match their
+//! wallet and challenge under the current name, and reconciles pairs that ended up with more
+//! than one file: the record with a receipt wins, then one with a final status, then the most
+//! recent. The others are removed, except ones holding a different receipt, which are left
+//! in place and reported.
+
+use crate::store::{self, JsonStore, SolutionStore};
+use crate::{load_user_wallets, wallets, CliArgs, SolutionRecord};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+/// Shortest address prefix matched against the wallets file
+const MIN_PREFIX_LEN: usize = 12;
+
+/// Full address for a record whose wallet was stored shortened ("addr1qxy..." or a bare prefix)
+fn full_wallet(address: &str, wallets: &[String]) -> Option<String> {
+    if wallets.iter().any(|w| w == address) {
+        return None;
+    }
+    let prefix = address.trim_end_matches("...");
+    if prefix.len() < MIN_PREFIX_LEN {
+        return None;
+    }
+    let mut matches = wallets.iter().filter(|w| w.starts_with(prefix));
+    match (matches.next(), matches.next()) {
+        (Some(wallet), None) => Some(wallet.clone()),
+        _ => None,
+    }
+}
+
+/// Ordering for picking which of several records for a pair is kept
+fn rank(record: &SolutionRecord) -> (bool, bool, &str) {
+    let last_activity = [Some(&record.found_at), record.submitted_at.as_ref(), record.last_retry_at.as_ref()]
+        .into_iter()
+        .flatten()
+        .max()
+        .map_or("", |s| s.as_str());
+    (record.crypto_receipt.is_some(), !store::is_pending_status(&record.status), last_activity)
+}
+
+fn name(path: &std::path::Path) -> String {
+    path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+/// Usage: migrate [--wallets <file>] [--dry-run]
+pub fn run_migrate(args: &[String]) -> bool {
+    let cli = CliArgs::parse(args);
+    let dry_run = cli.switch("--dry-run");
+    let wallets_file = cli.option("--wallets").unwrap_or(wallets::default_source());
+
+    if let Some(db) = store::database_path() {
+        println!("🗄️  Records are kept in the SQLite database {}, where file names don't matter", db.display());
+        return true;
+    }
+
+    // Shortened addresses inside records can only be restored from the wallets file
+    let wallets = load_user_wallets(wallets_file).unwrap_or_default();
+
+    let mut pairs: BTreeMap<(String, String), Vec<(PathBuf, SolutionRecord)>> = BTreeMap::new();
+    let mut expanded: HashSet<PathBuf> = HashSet::new();
+    for (path, mut record) in JsonStore::records_on_disk() {
+        if let Some(wallet) = full_wallet(&record.wallet_address, &wallets) {
+            record.wallet_address = wallet;
+            expanded.insert(path.clone());
+        }
+        pairs.entry((record.wallet_address.clone(), record.challenge_id.clone())).or_default().push((path, record));
+    }
+
+    println!(
+        "🔧 {} solution file(s) in {}{}",
+        pairs.values().map(|files| files.len()).sum::<usize>(),
+        store::description(),
+        if dry_run { " (dry run, nothing is changed)" } else { "" }
+    );
+
+    let store = JsonStore::default();
+    let (mut renamed, mut merged, mut conflicts, mut failed) = (0, 0, 0, 0);
+    for ((wallet, challenge_id), mut files) in pairs {
+        let current_name = JsonStore::file_name(&wallet, &challenge_id);
+        if files.len() == 1 && name(&files[0].0) == current_name && !expanded.contains(&files[0].0) {
+            continue;
+        }
+
+        files.sort_by(|(_, a), (_, b)| rank(b).cmp(&rank(a)));
+        let (kept_path, kept) = files.remove(0);
+        let old_names: Vec<String> = std::iter::once(&kept_path).chain(files.iter().map(|(p, _)| p)).map(|p| name(p)).collect();
+        println!("   {} -> {}", old_names.join(" + "), current_name);
+
+        // Extra files are only dropped if the kept record covers them
+        let (drop, keep): (Vec<_>, Vec<_>) = files.into_iter().partition(|(_, record)| match (&record.crypto_receipt, &kept.crypto_receipt) {
+            (None, _) => true,
+            (Some(a), Some(b)) => a.signature == b.signature,
+            (Some(_), None) => false,
+        });
+        for (path, _) in &keep {
+            println!("      ⚠️  {} holds a different receipt; left in place, check it by hand", path.display());
+        }
+        conflicts += keep.len();
+        merged += drop.len();
+        if name(&kept_path) != current_name {
+            renamed += 1;
+        }
+        if dry_run {
+            continue;
+        }
+
+        let saved = match store.save(&kept) {
+            Ok(location) => PathBuf::from(location),
+            Err(e) => {
+                println!("      ❌ Failed to write {}: {}", current_name, e);
+                failed += 1;
+                continue;
+            }
+        };
+        for path in std::iter::once(&kept_path).chain(drop.iter().map(|(p, _)| p)) {
+            if *path != saved {
+                if let Err(e) = fs::remove_file(path) {
+                    println!("      ⚠️  Could not remove {}: {}", path.display(), e);
+                }
+            }
+        }
+    }
+
+    if !dry_run && renamed + merged > 0 {
+        store.rebuild_index();
+    }
+    println!(
+        "✅ {} file(s) renamed, {} duplicate(s) merged, {} shortened address(es) completed from {}, {} conflict(s), {} failed",
+        renamed, merged, expanded.len(), wallets_file, conflicts, failed
+    );
+    failed == 0
+}
@@ -0,0 +1,225 @@
+//! `mine`: mine one chosen challenge now, bypassing challenge selection
+//!
+//! Meant for a challenge about to expire that the ranking would leave for later. The challenge
+//! is looked up like `verify` does; every listed wallet (or every wallet in the wallets file)
+//! that hasn't solved it yet mines it in turn. Solutions are submitted right away, or with
+//! `--challenges-file` saved as "offline" records for the next online run. Difficult-task
+//! marks, challenge filters and the safety buffer before the deadline are ignored.
+
+use crate::address::{self, Severity};
+use crate::attempts;
+use crate::credited;
+use crate::retry_policy::RetryPolicies;
+use crate::wallet_stats;
+use crate::wallets;
+use crate::{
+    backend_for_challenge, export_solution, find_known_challenge, get_timestamp,
+    install_shutdown_handler, load_user_wallets, mine_single_solution, setup_directories, solution_exists,
+    submit_found_solution, threads_for_cpu_usage, CliArgs, FoundSolution, MiningLimits, MiningResult, NonceStart, SolutionRecord,
+    DEFAULT_LIMIT_CHECK_INTERVAL, SHUTDOWN_REQUESTED,
+};
+use std::sync::atomic::Ordering;
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+
+/// Usage: mine --challenge <id> [--wallet <addr>[,<addr>...] | --wallets <file>] [--threads <n> | --cpu <percent>]
+///        [--max-minutes-per-task <minutes>] [--nonce-start <n>] [--challenges-file <file>] [--force]
+pub fn run_mine(args: &[String]) -> bool {
+    let cli = CliArgs::parse(args);
+    let Some(challenge_id) = cli.option("--challenge") else {
+        println!("Usage: scavenger-miner mine --challenge <id> [--wallet <addr>[,<addr>...] | --wallets <file>] [--threads <n>]");
+        return false;
+    };
+
+    let Some(wallets) = selected_wallets(&cli) else {
+        return false;
+    };
+    let num_threads = selected_threads(&cli);
+    let nonce_start = match cli.option("--nonce-start") {
+        Some(value) => match NonceStart::parse(value) {
+            Some(start) => start,
+            None => {
+                println!("❌ Invalid --nonce-start '{}'", value);
+                return false;
+            }
+        },
+        None => NonceStart::Zero,
+    };
+    let limits = MiningLimits {
+        max_hashes: None,
+        max_duration: cli.option("--max-minutes-per-task")
+            .and_then(|s| s.parse::<f64>().ok())
+            .filter(|m| *m > 0.0)
+            .map(|m| Duration::from_secs_f64(m * 60.0)),
+        check_interval: DEFAULT_LIMIT_CHECK_INTERVAL,
+        nonce_count: None,
+        share_bits: None,
+    };
+    let offline = cli.option("--challenges-file").is_some();
+    if !offline {
+        credited::enable(Duration::from_secs_f64(credited::DEFAULT_REFRESH_MINUTES * 60.0));
+    }
+
+    let challenge = match find_known_challenge(&cli, challenge_id) {
+        Ok(challenge) => challenge,
+        Err(e) => {
+            println!("❌ {}", e);
+            return false;
+        }
+    };
+    let hash_backend = match backend_for_challenge(&cli, &challenge) {
+        Ok(backend) => backend,
+        Err(e) => {
+            println!("❌ {}", e);
+            return false;
+        }
+    };
+    let retry_policies = match RetryPolicies::load() {
+        Ok(policies) => policies,
+        Err(e) => {
+            println!("❌ Invalid retry policy: {}", e);
+            return false;
+        }
+    };
+    if let Err(e) = setup_directories() {
+        println!("❌ Failed to create output directories: {}", e);
+        return false;
+    }
+
+    let pending: Vec<&String> = wallets.iter().filter(|w| !solution_exists(w, challenge_id)).collect();
+    println!(
+        "⛏️  Challenge {} (deadline {}, difficulty {}): {} of {} wallet(s) still to solve it, {} thread(s)",
+        challenge.challenge_id, challenge.latest_submission, challenge.difficulty, pending.len(), wallets.len(), num_threads
+    );
+    if pending.is_empty() {
+        println!("✅ Nothing to do");
+        return true;
+    }
+    if !challenge.is_active() {
+        println!("⚠️  Less than an hour left before the deadline; mining anyway");
+    }
+
+    install_shutdown_handler();
+    let rom = {
+        let start = Instant::now();
+        println!("🔄 Initializing ROM ({})...", hash_backend.describe());
+        let rom = hash_backend.build_rom(challenge.no_pre_mine.as_bytes());
+        println!("   ✓ ROM initialized in {:.2?}", start.elapsed());
+        rom
+    };
+
+    // No notifier runs here, so receipt and failure events go nowhere
+    let (events, _event_receiver) = mpsc::channel();
+    let (mut solved, mut failed) = (0, 0);
+    for wallet in pending {
+        if SHUTDOWN_REQUESTED.load(Ordering::SeqCst) {
+            break;
+        }
+        println!("\n👛 Wallet {}", wallets::label(wallet));
+        let start = nonce_start.pick();
+        let started = Instant::now();
+        let result = mine_single_solution(Arc::clone(&rom), wallet, &challenge, num_threads, &limits, start);
+        wallet_stats::record_task(wallet, result.hashes(), started.elapsed().as_secs_f64(), matches!(result, MiningResult::Found(..)));
+        attempts::record(wallet, &challenge.challenge_id, &challenge.difficulty, result.hashes(), started.elapsed().as_secs_f64(), result.outcome());
+        match result {
+            MiningResult::Found(nonce, _) if offline => {
+                println!("✅ Solution {:016x} found in {:.2?} (offline, saved for later submission)", nonce, started.elapsed());
+                let found_timestamp = get_timestamp();
+                let record = SolutionRecord {
+                    wallet_address: wallet.clone(),
+                    challenge_id: challenge.challenge_id.clone(),
+                    nonce: format!("{:016x}", nonce),
+                    found_at: found_timestamp.clone(),
+                    submitted_at: None,
+                    crypto_receipt: None,
+                    status: "offline".to_string(),
+                    error_message: None,
+                    retry_count: 0,
+                    last_retry_at: None,
+                    nonce_start: Some(format!("{:016x}", start)),
+                    next_retry_at: Some(found_timestamp),
+                    verified: None,
+                };
+                match export_solution(&record) {
+                    Ok(_) => solved += 1,
+                    Err(e) => {
+                        println!("❌ Failed to export solution: {}", e);
+                        failed += 1;
+                    }
+                }
+            }
+            MiningResult::Found(nonce, _) => {
+                println!("✅ Solution {:016x} found in {:.2?}", nonce, started.elapsed());
+                submit_found_solution(
+                    &FoundSolution {
+                        wallet_address: wallet.clone(),
+                        challenge_id: challenge.challenge_id.clone(),
+                        nonce,
+                        nonce_start: start,
+                        found_at: get_timestamp(),
+                    },
+                    &retry_policies,
+                    &events,
+                );
+                solved += 1;
+            }
+            MiningResult::DeadlinePassed(_) => {
+                println!("⌛ Challenge deadline reached");
+                failed += 1;
+                break;
+            }
+            other => {
+                println!("❌ No solution: {:?}", other);
+                failed += 1;
+            }
+        }
+    }
+
+    println!("\n📊 {} solved, {} without a solution (results in solutions/)", solved, failed);
+    failed == 0 && !SHUTDOWN_REQUESTED.load(Ordering::SeqCst)
+}
+
+/// The wallets from `--wallet` or the wallets file (`--wallets`), checked like at miner startup and
+/// each once in its normalized form; problems are printed
+pub fn selected_wallets(cli: &CliArgs) -> Option<Vec<String>> {
+    let wallets: Vec<String> = match cli.option("--wallet") {
+        Some(list) => list.split(',').map(|w| w.trim().to_string()).filter(|w| !w.is_empty()).collect(),
+        None => {
+            let wallets_file = cli.option("--wallets").unwrap_or(wallets::default_source());
+            match load_user_wallets(wallets_file) {
+                Ok(wallets) => wallets,
+                Err(e) => {
+                    println!("❌ Could not load wallets from {}: {}", wallets_file, e);
+                    return None;
+                }
+            }
+        }
+    };
+    // Same address checks as at miner startup, then each wallet once in its normalized form
+    let issues = address::check_addresses(wallets.iter().map(|w| (None, w.as_str())));
+    for issue in &issues {
+        println!("{} {}", if issue.severity == Severity::Error { "❌" } else { "⚠️ " }, issue);
+    }
+    if issues.iter().any(|i| i.severity == Severity::Error) && !cli.switch("--force") {
+        println!("❌ Invalid wallet address(es); fix them, or pass --force to mine anyway");
+        return None;
+    }
+    let mut wallets: Vec<String> = wallets.iter().map(|w| address::normalize(w)).collect();
+    let mut seen = std::collections::HashSet::new();
+    wallets.retain(|w| seen.insert(w.clone()));
+    Some(wallets)
+}
+
+/// Threads from `--threads`, or from `--cpu` (percent, default 50)
+pub fn selected_threads(cli: &CliArgs) -> usize {
+    match cli.option("--threads").and_then(|s| s.parse::<usize>().ok()) {
+        Some(threads) => threads.max(1),
+        None => {
+            let cpu_usage = cli.option("--cpu")
+                .and_then(|s| s.parse::<f64>().ok())
+                .unwrap_or(50.0)
+                .clamp(1.0, 100.0);
+            threads_for_cpu_usage(cpu_usage)
+        }
+    }
+}
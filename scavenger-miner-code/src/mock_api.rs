@@ -0,0 +1,180 @@
+//! `mock-api`: a local stand-in for the Scavenger API (built with `--features mock-api`)
+//!
+//! Serves `/challenge`, `/challenges`, `/solution/...`, `/solutions/...` and `/statistics/...` over plain
+//! HTTP on localhost, so the miner's submit / retry / duplicate / invalid-nonce handling
+//! can be exercised end to end with `--api-base`. Challenges carry `version: "test"`,
+//! which routes them to the tiny test ROM, and the default difficulty accepts any nonce.
+//!
+//! Submissions are answered from a script (`--respond ok,429,duplicate,...`), then `ok`.
+
+use crate::http_server::{self, reply, Reply, Settings};
+use crate::CliArgs;
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::net::TcpListener;
+use std::sync::Mutex;
+
+/// Largest request body read
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// Scripted answer to one submission
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MockResponse {
+    Ok,
+    Duplicate,
+    InvalidNonce,
+    RateLimited,
+    ServerError,
+}
+
+impl MockResponse {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "ok" => Some(MockResponse::Ok),
+            "duplicate" => Some(MockResponse::Duplicate),
+            "invalid" => Some(MockResponse::InvalidNonce),
+            "429" => Some(MockResponse::RateLimited),
+            "500" => Some(MockResponse::ServerError),
+            _ => None,
+        }
+    }
+}
+
+struct MockState {
+    challenges: Vec<serde_json::Value>,
+    script: VecDeque<MockResponse>,
+    retry_after_secs: u64,
+    receipts: HashMap<String, Vec<String>>, // Challenges with an accepted submission, per wallet
+}
+
+/// Error body in the API's own shape
+fn api_error(status: u16, message: &str) -> Reply {
+    reply(status, serde_json::json!({ "statusCode": status, "message": message }))
+}
+
+/// Challenges `**MOCK01`..`**MOCKnn`, open for another day
+fn mock_challenges(count: u32, difficulty: &str) -> Vec<serde_json::Value> {
+    let now = chrono::Utc::now();
+    (1..=count)
+        .map(|n| {
+            serde_json::json!({
+                "challenge_id": format!("**MOCK{:02}", n),
+                "challenge_number": n,
+                "day": 1,
+                "issued_at": now.to_rfc3339(),
+                "difficulty": difficulty,
+                "no_pre_mine": format!("{:064x}", n),
+                "latest_submission": (now + chrono::Duration::hours(24)).to_rfc3339(),
+                "no_pre_mine_hour": "0",
+                "version": "test",
+            })
+        })
+        .collect()
+}
+
+fn handle_request(state: &Mutex<MockState>, method: &str, path: &str) -> Reply {
+    let mut state = state.lock().unwrap();
+    let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+
+    match (method, segments.as_slice()) {
+        ("GET", ["challenge"]) => {
+            let latest = state.challenges.last().cloned().unwrap_or_default();
+            reply(200, serde_json::json!({
+                "challenge": latest,
+                "total_challenges": state.challenges.len(),
+                "starts_at": latest["issued_at"],
+                "next_challenge_starts_at": latest["latest_submission"],
+            }))
+        }
+        ("GET", ["challenges"]) => reply(200, serde_json::json!({ "challenges": state.challenges })),
+        ("HEAD", ["solution", ..]) => Reply { status: 200, headers: Vec::new(), body: String::new() },
+        ("POST", ["solution", wallet, challenge_id, nonce]) => {
+            let response = state.script.pop_front().unwrap_or(MockResponse::Ok);
+            println!("   POST /solution {} {} {} -> {:?}", wallet, challenge_id, nonce, response);
+            match response {
+                MockResponse::Ok => {
+                    state.receipts.entry(wallet.to_string()).or_default().push(challenge_id.to_string());
+                    reply(201, serde_json::json!({
+                        "crypto_receipt": {
+                            "preimage": format!("{}{}{}", nonce, wallet, challenge_id),
+                            "timestamp": chrono::Utc::now().to_rfc3339(),
+                            "signature": "00".repeat(64),
+                        }
+                    }))
+                }
+                MockResponse::Duplicate => api_error(400, "Solution already exists"),
+                MockResponse::InvalidNonce => api_error(400, "Solution does not meet difficulty"),
+                MockResponse::RateLimited => Reply {
+                    headers: vec![("Retry-After", state.retry_after_secs.to_string())],
+                    ..api_error(429, "Too many requests")
+                },
+                MockResponse::ServerError => api_error(500, "Internal server error"),
+            }
+        }
+        ("GET", ["statistics", wallet]) => {
+            let receipts = state.receipts.get(*wallet).map_or(0, |r| r.len());
+            reply(200, serde_json::json!({ "local": { "crypto_receipts": receipts } }))
+        }
+        ("GET", ["solutions", wallet]) => {
+            let solutions: Vec<serde_json::Value> = state.receipts.get(*wallet).into_iter().flatten()
+                .map(|challenge_id| serde_json::json!({ "challenge_id": challenge_id }))
+                .collect();
+            reply(200, serde_json::json!({ "solutions": solutions }))
+        }
+        _ => api_error(404, "Not found"),
+    }
+}
+
+/// Usage: mock-api [--port <n>] [--challenges <n>] [--difficulty <hex>] [--respond <list>] [--retry-after <secs>]
+/// Runs until killed; the first line printed is the base URL to pass to `--api-base`
+pub fn run_mock_api(args: &[String]) -> bool {
+    let cli = CliArgs::parse(args);
+    let port = cli.option("--port").and_then(|s| s.parse::<u16>().ok()).unwrap_or(0);
+    let count = cli.option("--challenges").and_then(|s| s.parse::<u32>().ok()).unwrap_or(1).max(1);
+    let difficulty = cli.option("--difficulty").unwrap_or("FFFFFFFF");
+
+    let mut script = VecDeque::new();
+    for entry in cli.option("--respond").unwrap_or("").split(',').filter(|e| !e.trim().is_empty()) {
+        match MockResponse::parse(entry) {
+            Some(response) => script.push_back(response),
+            None => {
+                println!("❌ Unknown response '{}' (expected ok, duplicate, invalid, 429 or 500)", entry);
+                return false;
+            }
+        }
+    }
+
+    let state = Mutex::new(MockState {
+        challenges: mock_challenges(count, difficulty),
+        script,
+        retry_after_secs: cli.option("--retry-after").and_then(|s| s.parse::<u64>().ok()).unwrap_or(1),
+        receipts: HashMap::new(),
+    });
+
+    let listener = match TcpListener::bind(("127.0.0.1", port)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            println!("❌ Cannot listen on port {}: {}", port, e);
+            return false;
+        }
+    };
+    let addr = match listener.local_addr() {
+        Ok(addr) => addr,
+        Err(e) => {
+            println!("❌ {}", e);
+            return false;
+        }
+    };
+    println!("http://{}", addr);
+    println!("🧪 Mock Scavenger API serving {} challenge(s), run the miner with --api-base http://{}", count, addr);
+    let _ = std::io::stdout().flush();
+
+    let settings = Settings { name: "mock-api", max_body: MAX_BODY_BYTES, token: None };
+    if let Err(e) = http_server::serve(listener, settings, move |request| handle_request(&state, &request.method, &request.path)) {
+        println!("❌ {}", e);
+        return false;
+    }
+    loop {
+        std::thread::park();
+    }
+}
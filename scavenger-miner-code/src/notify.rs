@@ -0,0 +1,161 @@
+//! Notifications about mining events
+//!
+//! Events go to the configured channels (the miner log, plus an optional webhook and Telegram
+//! chat), either one message per event or batched into an hourly/daily digest. Desktop toasts
+//! (`--desktop-notify`) are local and immediate: one per solution found and receipt obtained,
+//! whatever the mode.
+
+use crate::{desktop, format_duration_secs, telegram, wallets};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How notifications are delivered
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NotifyMode {
+    Events,           // One notification per event
+    Digest(Duration), // One summary per period
+}
+
+impl NotifyMode {
+    /// Parse `events`, `hourly` or `daily`
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "events" | "event" => Some(NotifyMode::Events),
+            "hourly" => Some(NotifyMode::Digest(Duration::from_secs(3600))),
+            "daily" => Some(NotifyMode::Digest(Duration::from_secs(86_400))),
+            _ => None,
+        }
+    }
+}
+
+/// Something worth telling the user about
+pub enum Event {
+    Receipt { wallet_address: String, challenge_id: String },
+    SubmitFailed { wallet_address: String, challenge_id: String, error: String },
+}
+
+/// Counters collected between digests
+#[derive(Default)]
+struct DigestStats {
+    receipts: u64,
+    failures: u64,
+    hashes: u64,
+    mining_secs: f64,
+}
+
+pub struct Notifier {
+    mode: NotifyMode,
+    webhook: Option<String>,
+    telegram: Option<Arc<telegram::Telegram>>,
+    desktop: bool,
+    stats: DigestStats,
+    period_start: Instant,
+}
+
+impl Notifier {
+    pub fn new(mode: NotifyMode, webhook: Option<String>, telegram: Option<Arc<telegram::Telegram>>, desktop: bool) -> Self {
+        Notifier {
+            mode,
+            webhook,
+            telegram,
+            desktop,
+            stats: DigestStats::default(),
+            period_start: Instant::now(),
+        }
+    }
+
+    /// Record an event: sent right away in events mode, counted for the next digest otherwise
+    pub fn event(&mut self, event: Event) {
+        let message = match &event {
+            Event::Receipt { wallet_address, challenge_id } => {
+                self.stats.receipts += 1;
+                if self.desktop {
+                    desktop::notify("Receipt obtained", &format!("{}: challenge {}", wallets::label(wallet_address), challenge_id));
+                }
+                format!("✅ Receipt obtained for {}, challenge {}", wallets::label(wallet_address), challenge_id)
+            }
+            Event::SubmitFailed { wallet_address, challenge_id, error } => {
+                self.stats.failures += 1;
+                format!("❌ Submission failed for {}, challenge {}: {}", wallets::label(wallet_address), challenge_id, error)
+            }
+        };
+
+        // The log already records every event, so only the webhook and chat need the per-event message
+        if self.mode == NotifyMode::Events {
+            self.deliver(&message);
+        }
+    }
+
+    /// A solution was found (it is submitted separately); only worth a desktop toast
+    pub fn solution_found(&self, wallet_address: &str, challenge_id: &str) {
+        if self.desktop {
+            desktop::notify("Solution found", &format!("{}: challenge {}", wallets::label(wallet_address), challenge_id));
+        }
+    }
+
+    /// Record hashing work for the digest's average hashrate
+    pub fn record_hashes(&mut self, hashes: u64, mining_secs: f64) {
+        self.stats.hashes += hashes;
+        self.stats.mining_secs += mining_secs;
+    }
+
+    /// Send the digest if its period has elapsed
+    pub fn tick(&mut self) {
+        if let NotifyMode::Digest(period) = self.mode {
+            if self.period_start.elapsed() >= period {
+                self.flush();
+            }
+        }
+    }
+
+    /// Send the digest for the current period now (e.g. on shutdown), if digests are enabled
+    pub fn flush(&mut self) {
+        if self.mode == NotifyMode::Events {
+            return;
+        }
+
+        let stats = std::mem::take(&mut self.stats);
+        let hashrate = if stats.mining_secs > 0.0 { stats.hashes as f64 / stats.mining_secs } else { 0.0 };
+        let message = format!(
+            "📬 Digest for the last {}: {} receipt(s), {} failed submission(s), average {:.2} H/s",
+            format_duration_secs(self.period_start.elapsed().as_secs_f64()),
+            stats.receipts,
+            stats.failures,
+            hashrate
+        );
+        self.period_start = Instant::now();
+
+        tracing::info!("{}", message);
+        self.deliver(&message);
+    }
+
+    /// Send a message to the webhook and the Telegram chat, whichever are configured
+    fn deliver(&self, message: &str) {
+        if let Some(telegram) = &self.telegram {
+            telegram.send(message);
+        }
+        self.send_webhook(message);
+    }
+
+    /// POST a message to the webhook (Slack and Discord compatible payload)
+    fn send_webhook(&self, message: &str) {
+        let Some(url) = &self.webhook else {
+            return;
+        };
+
+        let result = crate::http::client().and_then(|client| {
+            client
+                .post(url)
+                .json(&serde_json::json!({ "text": message, "content": message }))
+                .send()
+        });
+
+        match result {
+            Ok(response) if !response.status().is_success() => {
+                tracing::warn!("⚠️  Notification webhook returned {}", response.status());
+            }
+            Err(e) => tracing::warn!(error = %e, "⚠️  Notification webhook failed: {}", e),
+            Ok(_) => {}
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! Where the miner keeps its files
+//!
+//! Solution records, logs, `difficult_tasks.json` and the SQLite database live in a data
+//! directory: `--data-dir` if given, otherwise the working directory if it already has a
+//! `solutions/` folder (setups from before this option existed), otherwise a per-user folder:
+//! `$XDG_DATA_HOME/scavenger-miner` (`~/.local/share/scavenger-miner`) on Linux,
+//! `~/Library/Application Support/scavenger-miner` on macOS and `%APPDATA%\scavenger-miner`
+//! on Windows. `--solutions-dir`, `--logs-dir` and `--difficult-tasks-file` move single items.
+//!
+//! Path options expand a leading `~` and `$VAR`, `${VAR}` or `%VAR%` environment references.
+//! Relative paths are relative to the working directory.
+
+use crate::CliArgs;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+const APP_DIR: &str = "scavenger-miner";
+
+#[derive(Debug, Clone)]
+struct Paths {
+    data_dir: PathBuf,
+    solutions_dir: PathBuf,
+    logs_dir: PathBuf,
+    difficult_tasks_file: PathBuf,
+}
+
+impl Paths {
+    fn in_dir(data_dir: PathBuf) -> Self {
+        Paths {
+            solutions_dir: data_dir.join("solutions"),
+            logs_dir: data_dir.join("logs"),
+            difficult_tasks_file: data_dir.join("difficult_tasks.json"),
+            data_dir,
+        }
+    }
+}
+
+static PATHS: RwLock<Option<Paths>> = RwLock::new(None);
+
+fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")).filter(|h| !h.is_empty()).map(PathBuf::from)
+}
+
+/// Per-user data folder for this platform
+fn user_data_dir() -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        env::var_os("APPDATA").filter(|d| !d.is_empty()).map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home_dir().map(|h| h.join("Library").join("Application Support"))
+    } else {
+        env::var_os("XDG_DATA_HOME")
+            .filter(|d| !d.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| home_dir().map(|h| h.join(".local").join("share")))
+    };
+    base.map(|b| b.join(APP_DIR))
+}
+
+/// Data directory when none is configured
+fn default_data_dir() -> PathBuf {
+    if Path::new("solutions").is_dir() {
+        return PathBuf::from(".");
+    }
+    user_data_dir().unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Expand a leading `~` and `$VAR` / `${VAR}` / `%VAR%` references
+pub fn expand(value: &str) -> Result<PathBuf, String> {
+    let lookup = |name: &str| env::var(name).map_err(|_| format!("environment variable {} in '{}' is not set", name, value));
+
+    let mut out = String::new();
+    let mut rest = value;
+    if rest == "~" || rest.starts_with("~/") || rest.starts_with("~\\") {
+        let home = home_dir().ok_or_else(|| format!("cannot expand ~ in '{}': no home directory", value))?;
+        out.push_str(&home.to_string_lossy());
+        rest = &rest[1..];
+    }
+
+    while let Some(pos) = rest.find(['$', '%']) {
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+        let (name, remainder) = if rest[pos..].starts_with("${") {
+            match after[1..].find('}') {
+                Some(end) => (&after[1..end + 1], &after[end + 2..]),
+                None => return Err(format!("unterminated ${{ in '{}'", value)),
+            }
+        } else if rest[pos..].starts_with('$') {
+            let end = after.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(after.len());
+            (&after[..end], &after[end..])
+        } else {
+            match after.find('%') {
+                Some(end) if end > 0 => (&after[..end], &after[end + 1..]),
+                _ => ("", after), // A lone % is just a character
+            }
+        };
+        if name.is_empty() {
+            out.push_str(&rest[pos..pos + 1]);
+        } else {
+            out.push_str(&lookup(name)?);
+        }
+        rest = remainder;
+    }
+    out.push_str(rest);
+    Ok(PathBuf::from(out))
+}
+
+/// Read the storage paths from the command line / config file
+pub fn configure(cli: &CliArgs) -> Result<(), String> {
+    let option = |name: &str| cli.option(name).map(expand).transpose().map_err(|e| format!("Invalid {}: {}", name, e));
+
+    let mut paths = Paths::in_dir(option("--data-dir")?.unwrap_or_else(default_data_dir));
+    if let Some(dir) = option("--solutions-dir")? {
+        paths.solutions_dir = dir;
+    }
+    if let Some(dir) = option("--logs-dir")? {
+        paths.logs_dir = dir;
+    }
+    if let Some(file) = option("--difficult-tasks-file")? {
+        paths.difficult_tasks_file = file;
+    }
+    *PATHS.write().unwrap() = Some(paths);
+    Ok(())
+}
+
+/// Keep everything in `dir`, whatever was configured (used by `selftest`)
+pub fn use_data_dir(dir: &Path) {
+    *PATHS.write().unwrap() = Some(Paths::in_dir(dir.to_path_buf()));
+}
+
+fn current() -> Paths {
+    if let Some(paths) = PATHS.read().unwrap().as_ref() {
+        return paths.clone();
+    }
+    PATHS.write().unwrap().get_or_insert_with(|| Paths::in_dir(default_data_dir())).clone()
+}
+
+pub fn data_dir() -> PathBuf {
+    current().data_dir
+}
+
+pub fn solutions_dir() -> PathBuf {
+    current().solutions_dir
+}
+
+pub fn logs_dir() -> PathBuf {
+    current().logs_dir
+}
+
+pub fn difficult_tasks_file() -> PathBuf {
+    current().difficult_tasks_file
+}
@@ -0,0 +1,204 @@
+//! Pool share ledger (`pool_shares.jsonl`) and the `pool-report` contribution report
+//!
+//! In pool mode (`pool`, see coordinator.rs) workers also report "shares": hashes that meet only
+//! the first `--share-bits` constrained bits of the challenge mask. A share turns up every 2^bits
+//! hashes on average whatever the challenge, so shares count the work each worker did, not just
+//! the luck of finding a solution. Every accepted share and every solution is appended to the
+//! ledger with the worker that found it; `pool-report` adds the work up per worker (a share counts
+//! 2^bits hashes, so changing `--share-bits` between runs keeps the figures comparable) to split
+//! the rewards of the pool's wallets.
+
+use crate::difficulty::format_count;
+use crate::export::{csv_field, parse_date};
+use crate::{get_timestamp, paths, wallets, CliArgs};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+pub const POOL_SHARES_FILE: &str = "pool_shares.jsonl";
+/// One share per 65536 hashes on average
+pub const DEFAULT_SHARE_BITS: u32 = 16;
+
+const CSV_COLUMNS: &[&str] = &["worker", "shares", "solutions", "work_hashes", "contribution_percent"];
+
+/// A share or a solution found by a pool worker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareRecord {
+    pub at: String,
+    pub worker: String,
+    pub wallet_address: String,
+    pub challenge_id: String,
+    pub nonce: String,
+    pub share_bits: u32,
+    pub kind: String, // share or solution
+}
+
+impl ShareRecord {
+    pub fn new(kind: &str, worker: &str, wallet_address: &str, challenge_id: &str, nonce: u64, share_bits: u32) -> Self {
+        ShareRecord {
+            at: get_timestamp(),
+            worker: worker.to_string(),
+            wallet_address: wallet_address.to_string(),
+            challenge_id: challenge_id.to_string(),
+            nonce: format!("{:016x}", nonce),
+            share_bits,
+            kind: kind.to_string(),
+        }
+    }
+}
+
+fn path() -> PathBuf {
+    paths::data_dir().join(POOL_SHARES_FILE)
+}
+
+/// Append a record to the ledger
+pub fn record(entry: &ShareRecord) {
+    let result = serde_json::to_string(entry).map_err(std::io::Error::other).and_then(|mut line| {
+        line.push('\n');
+        fs::OpenOptions::new().create(true).append(true).open(path())?.write_all(line.as_bytes())
+    });
+    if let Err(e) = result {
+        tracing::warn!(error = %e, "⚠️  Failed to record a pool share in {}: {}", POOL_SHARES_FILE, e);
+    }
+}
+
+/// Every record in the ledger, oldest first (unreadable lines are skipped)
+pub fn load() -> Vec<ShareRecord> {
+    let Ok(file) = fs::File::open(path()) else {
+        return Vec::new();
+    };
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+/// One worker's part of the pool's work
+#[derive(Default, Serialize)]
+struct Contribution {
+    worker: String,
+    shares: u64,
+    solutions: u64,
+    work_hashes: f64, // Shares weighted by 2^bits
+    contribution_percent: f64,
+}
+
+/// Usage: pool-report [--from <date>] [--to <date>] [--wallet <addr>[,<addr>...]] [--csv <file> | --json <file>]
+pub fn run_pool_report(args: &[String]) -> bool {
+    let cli = CliArgs::parse(args);
+    let wallet_filter: Vec<String> = cli
+        .option("--wallet")
+        .map(|v| v.split(',').map(|s| crate::address::normalize(s.trim())).filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default();
+    let (mut from, mut to) = (None, None);
+    for (name, end_of_day) in [("--from", false), ("--to", true)] {
+        let Some(value) = cli.option(name) else {
+            continue;
+        };
+        let Some(date) = parse_date(value, end_of_day) else {
+            println!("❌ Invalid {} '{}' (expected YYYY-MM-DD or an RFC 3339 timestamp)", name, value);
+            return false;
+        };
+        if end_of_day {
+            to = Some(date);
+        } else {
+            from = Some(date);
+        }
+    }
+
+    let records: Vec<ShareRecord> = load()
+        .into_iter()
+        .filter(|r| wallet_filter.is_empty() || wallet_filter.contains(&r.wallet_address))
+        .filter(|r| {
+            let at = DateTime::parse_from_rfc3339(&r.at).map(|t| t.with_timezone(&Utc)).ok();
+            from.is_none_or(|from| at.is_some_and(|at| at >= from)) && to.is_none_or(|to| at.is_some_and(|at| at < to))
+        })
+        .collect();
+    if records.is_empty() {
+        println!("No pool shares recorded in {} (matching the filters)", path().display());
+        return true;
+    }
+
+    let mut by_worker: BTreeMap<&str, Contribution> = BTreeMap::new();
+    let mut solved: BTreeMap<&str, u64> = BTreeMap::new();
+    for r in &records {
+        let contribution = by_worker.entry(&r.worker).or_insert_with(|| Contribution { worker: r.worker.clone(), ..Contribution::default() });
+        if r.kind == "solution" {
+            contribution.solutions += 1;
+            *solved.entry(&r.wallet_address).or_default() += 1;
+        } else {
+            contribution.shares += 1;
+            contribution.work_hashes += 2f64.powi(r.share_bits as i32);
+        }
+    }
+    let total_work: f64 = by_worker.values().map(|c| c.work_hashes).sum();
+    let mut contributions: Vec<Contribution> = by_worker.into_values().collect();
+    for c in &mut contributions {
+        c.contribution_percent = if total_work > 0.0 { c.work_hashes * 100.0 / total_work } else { 0.0 };
+    }
+    contributions.sort_by(|a, b| b.work_hashes.total_cmp(&a.work_hashes));
+
+    if let Some(output) = cli.option("--csv") {
+        let mut csv = String::from("\u{feff}");
+        csv.push_str(&CSV_COLUMNS.join(","));
+        csv.push_str("\r\n");
+        for c in &contributions {
+            let row = [
+                c.worker.clone(),
+                c.shares.to_string(),
+                c.solutions.to_string(),
+                format!("{:.0}", c.work_hashes),
+                format!("{:.2}", c.contribution_percent),
+            ];
+            csv.push_str(&row.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(","));
+            csv.push_str("\r\n");
+        }
+        if let Err(e) = fs::write(output, csv) {
+            println!("❌ Failed to write {}: {}", output, e);
+            return false;
+        }
+        println!("📄 Wrote the contributions of {} worker(s) to {}", contributions.len(), output);
+    }
+    if let Some(output) = cli.option("--json") {
+        let report = serde_json::json!({
+            "from": records.first().map(|r| r.at.clone()),
+            "to": records.last().map(|r| r.at.clone()),
+            "solutions_by_wallet": solved,
+            "workers": contributions,
+        });
+        let written = serde_json::to_string_pretty(&report).map_err(|e| e.to_string()).and_then(|json| fs::write(output, json).map_err(|e| e.to_string()));
+        if let Err(e) = written {
+            println!("❌ Failed to write {}: {}", output, e);
+            return false;
+        }
+        println!("📄 Wrote the contributions of {} worker(s) to {}", contributions.len(), output);
+    }
+
+    println!(
+        "🤝 {} share(s) and {} solution(s) from {} to {} ({})",
+        records.iter().filter(|r| r.kind != "solution").count(),
+        solved.values().sum::<u64>(),
+        records.first().map_or("", |r| r.at.as_str()),
+        records.last().map_or("", |r| r.at.as_str()),
+        path().display()
+    );
+    for (wallet, count) in &solved {
+        println!("   {} solution(s) for {}", count, wallets::label(wallet));
+    }
+    println!("\n   {:<24}  {:>8}  {:>9}  {:>10}  {:>12}", "worker", "shares", "solutions", "work", "contribution");
+    for c in &contributions {
+        println!(
+            "   {:<24}  {:>8}  {:>9}  {:>10}  {:>11.2}%",
+            c.worker,
+            c.shares,
+            c.solutions,
+            format_count(c.work_hashes),
+            c.contribution_percent
+        );
+    }
+    true
+}
@@ -0,0 +1,184 @@
+//! Progress line while hashing (on a terminal by default, `--no-progress` turns it off)
+//!
+//! Instead of a console line every 30 seconds, an indicatif progress bar at the bottom of the
+//! console is updated twice a second:
+//!
+//! ```text
+//! [#######-------------]  35%  1.4M / 4.3M hashes | 15.2K H/s | ETA ~4m 43s | deadline 5h 12m
+//! ```
+//!
+//! Hashes done for the task against the hashes a solution takes on average at its difficulty
+//! (2^k, see difficulty.rs), the hashrate over the last few seconds, the expected time to a
+//! solution at that rate and the time left before the challenge closes. Hashing has no memory,
+//! so the ETA stays put as the bar fills; past 100% the task is simply unlucky so far.
+//!
+//! Log lines are printed above the bar (`MultiProgress::suspend`). The periodic hashing lines
+//! (the `PROGRESS` log target) still go to `mining.log` and the JSON log.
+
+use crate::dashboard;
+use crate::difficulty::{format_count, DifficultyModel};
+use crate::format_duration_secs;
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::collections::VecDeque;
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const UPDATE_INTERVAL: Duration = Duration::from_millis(500);
+/// Hashrate window
+const RATE_WINDOW: Duration = Duration::from_secs(5);
+/// Bar length in characters, and the bar position standing for 100%
+const BAR_WIDTH: u64 = 20;
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+/// Owns the console while active; log lines go through it
+static MULTI: Mutex<Option<MultiProgress>> = Mutex::new(None);
+/// The bar of the task being mined
+static BAR: Mutex<Option<ProgressBar>> = Mutex::new(None);
+
+/// A progress line is being shown
+pub fn active() -> bool {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+/// Print `text` (whole lines) above the progress line
+pub fn print_above(text: &str) {
+    let print = || {
+        let mut stdout = std::io::stdout().lock();
+        let _ = stdout.write_all(text.as_bytes());
+        let _ = stdout.flush();
+    };
+    match MULTI.lock().unwrap().as_ref() {
+        Some(multi) => multi.suspend(print),
+        None => print(),
+    }
+}
+
+/// Remove the progress line (it comes back with the next task)
+pub fn clear() {
+    if let Some(bar) = BAR.lock().unwrap().take() {
+        bar.finish_and_clear();
+        if let Some(multi) = MULTI.lock().unwrap().as_ref() {
+            multi.remove(&bar);
+        }
+    }
+}
+
+/// Shown while mining; dropping it removes the line
+pub struct ProgressLine {
+    updater: Option<thread::JoinHandle<()>>,
+}
+
+impl ProgressLine {
+    /// Start showing progress, unless stdout is not a terminal
+    pub fn start() -> Option<Self> {
+        if !std::io::stdout().is_terminal() {
+            return None;
+        }
+        *MULTI.lock().unwrap() = Some(MultiProgress::with_draw_target(ProgressDrawTarget::stdout()));
+        ACTIVE.store(true, Ordering::SeqCst);
+        let updater = thread::Builder::new().name("progress".to_string()).spawn(update).ok();
+        if updater.is_none() {
+            ACTIVE.store(false, Ordering::SeqCst);
+            *MULTI.lock().unwrap() = None;
+            return None;
+        }
+        Some(ProgressLine { updater })
+    }
+}
+
+impl Drop for ProgressLine {
+    fn drop(&mut self) {
+        ACTIVE.store(false, Ordering::SeqCst);
+        if let Some(updater) = self.updater.take() {
+            let _ = updater.join();
+        }
+        clear();
+        *MULTI.lock().unwrap() = None;
+    }
+}
+
+/// Feed the bar from the task's hash counters until the line is closed
+fn update() {
+    let mut samples: VecDeque<(Instant, u64)> = VecDeque::new();
+    let mut task_started: Option<Instant> = None;
+    while active() {
+        match dashboard::task_progress() {
+            Some((hashes, difficulty, started, deadline)) => {
+                if task_started != Some(started) {
+                    task_started = Some(started);
+                    samples.clear();
+                }
+                let now = Instant::now();
+                samples.push_back((now, hashes));
+                while samples.len() > 2 && samples.front().is_some_and(|(at, _)| now.duration_since(*at) > RATE_WINDOW) {
+                    samples.pop_front();
+                }
+                show(hashes, &difficulty, deadline, rate(&samples));
+            }
+            None => {
+                task_started = None;
+                clear();
+            }
+        }
+        thread::sleep(UPDATE_INTERVAL);
+    }
+}
+
+/// Hashes per second over the samples
+fn rate(samples: &VecDeque<(Instant, u64)>) -> Option<f64> {
+    let (first, last) = (samples.front()?, samples.back()?);
+    let secs = last.0.duration_since(first.0).as_secs_f64();
+    (secs > 0.0).then(|| last.1.saturating_sub(first.1) as f64 / secs)
+}
+
+/// Update (or add) the task's bar
+fn show(hashes: u64, difficulty: &str, deadline: Option<Instant>, rate: Option<f64>) {
+    let expected = DifficultyModel::parse(difficulty).ok().map(|m| 2f64.powi(m.constrained_bits as i32));
+
+    let mut parts = Vec::new();
+    let mut position = None;
+    match expected {
+        Some(expected) => {
+            let fraction = hashes as f64 / expected;
+            position = Some(((fraction.min(1.0) * BAR_WIDTH as f64).round() as u64).min(BAR_WIDTH));
+            parts.push(format!(
+                "{:>4.0}%  {} / {} hashes",
+                fraction * 100.0,
+                format_count(hashes as f64),
+                format_count(expected)
+            ));
+        }
+        None => parts.push(format!("{} hashes", format_count(hashes as f64))),
+    }
+    if let Some(reason) = crate::pause_reason() {
+        parts.push(format!("paused ({})", reason));
+    } else if let Some(rate) = rate {
+        parts.push(format!("{} H/s", format_count(rate)));
+        if let Some(expected) = expected.filter(|_| rate > 0.0) {
+            parts.push(format!("ETA ~{}", format_duration_secs(expected / rate)));
+        }
+    }
+    if let Some(deadline) = deadline {
+        parts.push(format!("deadline {}", format_duration_secs(deadline.saturating_duration_since(Instant::now()).as_secs_f64())));
+    }
+
+    let mut bar = BAR.lock().unwrap();
+    let bar = bar.get_or_insert_with(|| {
+        let multi = MULTI.lock().unwrap();
+        let bar = ProgressBar::new(BAR_WIDTH);
+        // Without a parseable difficulty there is nothing to fill the bar against
+        let template = if position.is_some() { "[{bar:20}] {wide_msg}" } else { "{wide_msg}" };
+        if let Ok(style) = ProgressStyle::with_template(template) {
+            bar.set_style(style.progress_chars("#-"));
+        }
+        match multi.as_ref() {
+            Some(multi) => multi.add(bar),
+            None => bar,
+        }
+    });
+    bar.set_position(position.unwrap_or(0));
+    bar.set_message(parts.join(" | "));
+}
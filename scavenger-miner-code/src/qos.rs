@@ -0,0 +1,161 @@
+//! macOS scheduling: QoS classes for mining threads and App Nap
+//!
+//! macOS schedules by quality-of-service class rather than by priority. Mining threads run at
+//! user-initiated QoS: full speed on the performance cores, but below the user-interactive
+//! threads that draw the apps in front, so the machine stays responsive. `--background-only`
+//! drops them to utility QoS, which yields to anything the user is doing and saves power;
+//! `--nice` goes further, to background QoS (efficiency cores only, see main.rs).
+//!
+//! A miner in a hidden Terminal window is also a candidate for App Nap, which throttles its
+//! timers and CPU time. While a task is being mined the miner holds an NSProcessInfo activity
+//! that keeps App Nap off; it is released between tasks and while paused. Idle system sleep
+//! stays allowed. On other platforms everything here does nothing.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// `--background-only`: utility instead of user-initiated QoS
+static BACKGROUND_ONLY: AtomicBool = AtomicBool::new(false);
+
+pub fn set_background_only(enabled: bool) {
+    BACKGROUND_ONLY.store(enabled, Ordering::Relaxed);
+}
+
+pub fn background_only() -> bool {
+    BACKGROUND_ONLY.load(Ordering::Relaxed)
+}
+
+/// Keeps App Nap off while held (one task's worth of mining)
+pub struct MiningActivity;
+
+impl MiningActivity {
+    pub fn begin() -> Self {
+        prevent_app_nap();
+        MiningActivity
+    }
+}
+
+impl Drop for MiningActivity {
+    fn drop(&mut self) {
+        allow_app_nap();
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod imp {
+    use std::ffi::{c_char, c_void, CStr};
+    use std::ptr::null_mut;
+    use std::sync::atomic::{AtomicPtr, Ordering};
+
+    const QOS_CLASS_USER_INITIATED: u32 = 0x19;
+    const QOS_CLASS_UTILITY: u32 = 0x11;
+    /// NSActivityUserInitiatedAllowingIdleSystemSleep
+    const ACTIVITY_OPTIONS: u64 = 0x00FF_FFFF & !(1 << 20);
+    const ACTIVITY_REASON: &CStr = c"Mining Scavenger Mine challenges";
+
+    type Id = *mut c_void;
+    type Sel = *mut c_void;
+
+    extern "C" {
+        fn pthread_set_qos_class_self_np(qos_class: u32, relative_priority: i32) -> i32;
+    }
+
+    #[link(name = "Foundation", kind = "framework")]
+    extern "C" {}
+
+    #[link(name = "objc")]
+    extern "C" {
+        fn objc_getClass(name: *const c_char) -> Id;
+        fn sel_registerName(name: *const c_char) -> Sel;
+        fn objc_msgSend();
+        fn objc_autoreleasePoolPush() -> *mut c_void;
+        fn objc_autoreleasePoolPop(pool: *mut c_void);
+    }
+
+    /// The activity token from beginActivityWithOptions:reason: (retained), null when not held
+    static ACTIVITY: AtomicPtr<c_void> = AtomicPtr::new(null_mut());
+
+    pub fn set_mining_thread_qos(background_only: bool) -> bool {
+        let class = if background_only { QOS_CLASS_UTILITY } else { QOS_CLASS_USER_INITIATED };
+        unsafe { pthread_set_qos_class_self_np(class, 0) == 0 }
+    }
+
+    fn sel(name: &CStr) -> Sel {
+        unsafe { sel_registerName(name.as_ptr()) }
+    }
+
+    fn class(name: &CStr) -> Id {
+        unsafe { objc_getClass(name.as_ptr()) }
+    }
+
+    // objc_msgSend has to be called through a pointer of the method's exact signature
+    unsafe fn send(receiver: Id, selector: &CStr) -> Id {
+        let f: unsafe extern "C" fn(Id, Sel) -> Id = std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        f(receiver, sel(selector))
+    }
+
+    pub fn prevent_app_nap() {
+        if !ACTIVITY.load(Ordering::SeqCst).is_null() {
+            return;
+        }
+        unsafe {
+            let pool = objc_autoreleasePoolPush();
+            let process_info = send(class(c"NSProcessInfo"), c"processInfo");
+            let with_utf8: unsafe extern "C" fn(Id, Sel, *const c_char) -> Id = std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+            let reason = with_utf8(class(c"NSString"), sel(c"stringWithUTF8String:"), ACTIVITY_REASON.as_ptr());
+            let begin: unsafe extern "C" fn(Id, Sel, u64, Id) -> Id = std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+            let activity = begin(process_info, sel(c"beginActivityWithOptions:reason:"), ACTIVITY_OPTIONS, reason);
+            if !activity.is_null() {
+                send(activity, c"retain");
+                if ACTIVITY.compare_exchange(null_mut(), activity, Ordering::SeqCst, Ordering::SeqCst).is_err() {
+                    end(process_info, activity); // Another thread got there first
+                }
+            }
+            objc_autoreleasePoolPop(pool);
+        }
+    }
+
+    pub fn allow_app_nap() {
+        let activity = ACTIVITY.swap(null_mut(), Ordering::SeqCst);
+        if activity.is_null() {
+            return;
+        }
+        unsafe {
+            let pool = objc_autoreleasePoolPush();
+            let process_info = send(class(c"NSProcessInfo"), c"processInfo");
+            end(process_info, activity);
+            objc_autoreleasePoolPop(pool);
+        }
+    }
+
+    unsafe fn end(process_info: Id, activity: Id) {
+        let end_activity: unsafe extern "C" fn(Id, Sel, Id) = std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        end_activity(process_info, sel(c"endActivity:"), activity);
+        send(activity, c"release");
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod imp {
+    pub fn set_mining_thread_qos(_background_only: bool) -> bool {
+        true
+    }
+
+    pub fn prevent_app_nap() {}
+
+    pub fn allow_app_nap() {}
+}
+
+/// Give the calling mining thread its QoS class (false if the OS refused)
+pub fn set_mining_thread_qos() -> bool {
+    imp::set_mining_thread_qos(background_only())
+}
+
+/// Keep App Nap off until `allow_app_nap` (idempotent)
+pub fn prevent_app_nap() {
+    imp::prevent_app_nap();
+}
+
+/// Let App Nap apply again, e.g. while paused (idempotent)
+pub fn allow_app_nap() {
+    imp::allow_app_nap();
+}
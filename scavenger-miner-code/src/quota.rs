@@ -0,0 +1,181 @@
+//! Per-wallet solution quotas
+//!
+//! Loaded from `wallet_quotas.json` if present:
+//!
+//! ```json
+//! {
+//!   "default": { "per_day": 5 },
+//!   "overrides": [
+//!     { "wallets": ["addr1q8up..."], "per_day": 20, "total": 500 }
+//!   ]
+//! }
+//! ```
+//!
+//! A wallet that has reached a quota sits out of the rotation: until the next UTC day for
+//! `per_day`, for the rest of the run for `total`. A solution counts from the moment it is
+//! found, so submissions still in flight can't push a wallet over, on the day it was submitted
+//! (or found, while it waits). Ones the API refused for good (duplicates, closed windows) don't
+//! count. Each override applies to a group of wallets; limits it leaves out come from `default`.
+
+use crate::{store, SolutionRecord};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+pub const WALLET_QUOTA_FILE: &str = "wallet_quotas.json";
+
+/// How many solutions one wallet should get
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Quota {
+    pub per_day: Option<u64>, // Per UTC day
+    pub total: Option<u64>,
+}
+
+impl Quota {
+    fn is_limited(&self) -> bool {
+        self.per_day.is_some() || self.total.is_some()
+    }
+}
+
+/// Which quota a wallet has reached
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reached {
+    Daily(u64),
+    Total(u64),
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct QuotaFields {
+    per_day: Option<u64>,
+    total: Option<u64>,
+}
+
+impl QuotaFields {
+    fn apply(&self, base: Quota) -> Quota {
+        Quota {
+            per_day: self.per_day.or(base.per_day),
+            total: self.total.or(base.total),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct QuotaOverride {
+    wallets: Vec<String>,
+    #[serde(flatten)]
+    fields: QuotaFields,
+}
+
+#[derive(Deserialize, Default)]
+struct QuotaFile {
+    #[serde(default)]
+    default: QuotaFields,
+    #[serde(default)]
+    overrides: Vec<QuotaOverride>,
+}
+
+/// Quotas for all wallets and the solutions counted against them
+#[derive(Default)]
+pub struct WalletQuotas {
+    default: Quota,
+    overrides: Vec<(Vec<String>, Quota)>,
+    solutions: HashMap<String, Vec<NaiveDate>>, // UTC day of each counted solution, per wallet
+}
+
+/// Day a record counts on, if it counts at all
+fn counted_day(record: &SolutionRecord) -> Option<NaiveDate> {
+    let timestamp = if record.crypto_receipt.is_some() {
+        record.submitted_at.as_deref().unwrap_or(&record.found_at)
+    } else if store::is_pending_status(&record.status) {
+        &record.found_at
+    } else {
+        return None;
+    };
+    DateTime::parse_from_rfc3339(timestamp).ok().map(|t| t.with_timezone(&Utc).date_naive())
+}
+
+impl WalletQuotas {
+    /// Load from `WALLET_QUOTA_FILE`; without it no wallet has a quota
+    pub fn load() -> Result<Self, String> {
+        if !Path::new(WALLET_QUOTA_FILE).exists() {
+            return Ok(WalletQuotas::default());
+        }
+
+        let content = fs::read_to_string(WALLET_QUOTA_FILE).map_err(|e| e.to_string())?;
+        let file: QuotaFile = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+
+        let default = file.default.apply(Quota::default());
+        let overrides = file
+            .overrides
+            .into_iter()
+            .map(|o| {
+                let quota = o.fields.apply(default);
+                (o.wallets, quota)
+            })
+            .collect();
+        Ok(WalletQuotas { default, overrides, solutions: HashMap::new() })
+    }
+
+    /// Number of wallet groups with their own quota
+    pub fn override_count(&self) -> usize {
+        self.overrides.len()
+    }
+
+    /// Whether any wallet has a quota
+    pub fn is_empty(&self) -> bool {
+        !self.default.is_limited() && self.overrides.iter().all(|(_, q)| !q.is_limited())
+    }
+
+    /// Quota for a wallet: the first override listing it, otherwise the default
+    pub fn for_wallet(&self, wallet_address: &str) -> Quota {
+        self.overrides
+            .iter()
+            .find(|(wallets, _)| wallets.iter().any(|w| w == wallet_address))
+            .map_or(self.default, |(_, quota)| *quota)
+    }
+
+    /// Give one wallet its own quota; limits left out stay as they were
+    pub fn set(&mut self, wallet_address: &str, per_day: Option<u64>, total: Option<u64>) {
+        let base = self.for_wallet(wallet_address);
+        let quota = Quota { per_day: per_day.or(base.per_day), total: total.or(base.total) };
+        self.overrides.insert(0, (vec![wallet_address.to_string()], quota));
+    }
+
+    /// Count the solutions already in the store
+    pub fn count_recorded(&mut self) {
+        if self.is_empty() {
+            return;
+        }
+        for record in store::get().all() {
+            if let Some(day) = counted_day(&record) {
+                self.solutions.entry(record.wallet_address).or_default().push(day);
+            }
+        }
+    }
+
+    /// Count a solution found just now
+    pub fn record_found(&mut self, wallet_address: &str) {
+        self.solutions.entry(wallet_address.to_string()).or_default().push(Utc::now().date_naive());
+    }
+
+    /// Solutions counted for a wallet: (today, total)
+    pub fn counts(&self, wallet_address: &str) -> (u64, u64) {
+        let today = Utc::now().date_naive();
+        let days = self.solutions.get(wallet_address).map_or(&[][..], |d| d.as_slice());
+        (days.iter().filter(|d| **d == today).count() as u64, days.len() as u64)
+    }
+
+    /// The quota a wallet has reached, if any (the total one first, since it lasts)
+    pub fn reached(&self, wallet_address: &str) -> Option<Reached> {
+        let quota = self.for_wallet(wallet_address);
+        let (today, total) = self.counts(wallet_address);
+        match (quota.total, quota.per_day) {
+            (Some(limit), _) if total >= limit => Some(Reached::Total(limit)),
+            (_, Some(limit)) if today >= limit => Some(Reached::Daily(limit)),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! Ranking challenges by the chance of solving them before their deadline
+//!
+//! At a measured hashrate `r`, a challenge with `t` seconds of mining left gets `r * t` hashes
+//! (capped by the per-task hash and time limits), which solve it with the probability given
+//! by its difficulty model (see difficulty.rs).
+//! Challenges are ranked by that probability in whole percent, the difficulty heuristic
+//! (`Challenge::compare_for_selection`) breaking ties, and ones below
+//! `--min-solve-probability` are skipped until they become reachable. The hashrate is averaged
+//! over the tasks mined so far; before the first one the heuristic decides alone.
+
+use crate::{now_utc, Challenge, MiningLimits, DEADLINE_ABORT_MARGIN_SECS};
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+/// Challenges less likely than this (percent) to be solved in time are skipped
+pub const DEFAULT_MIN_SOLVE_PERCENT: f64 = 1.0;
+
+/// Weight of the latest task in the hashrate average
+const HASHRATE_SMOOTHING: f64 = 0.3;
+
+/// Tasks shorter than this are mostly startup and say little about the hashrate
+const MIN_MEASURED_SECS: f64 = 10.0;
+
+static HASHRATE: Mutex<Option<f64>> = Mutex::new(None);
+
+/// Fold a finished task's hashes and active mining time (pauses excluded) into the average
+pub fn record_hashrate(hashes: u64, active_secs: f64) {
+    if active_secs < MIN_MEASURED_SECS || hashes == 0 {
+        return;
+    }
+    let rate = hashes as f64 / active_secs;
+    let mut hashrate = HASHRATE.lock().unwrap();
+    *hashrate = Some(match *hashrate {
+        Some(average) => average + HASHRATE_SMOOTHING * (rate - average),
+        None => rate,
+    });
+}
+
+/// Average hashrate of the tasks mined so far
+pub fn measured_hashrate() -> Option<f64> {
+    *HASHRATE.lock().unwrap()
+}
+
+/// Chance of solving a challenge before its deadline at `hashrate`, within the task limits
+/// None if the deadline can't be parsed
+pub fn solve_probability(challenge: &Challenge, hashrate: f64, limits: &MiningLimits) -> Option<f64> {
+    let deadline = chrono::DateTime::parse_from_rfc3339(&challenge.latest_submission).ok()?;
+    let mut secs = ((deadline.with_timezone(&chrono::Utc) - now_utc()).num_seconds() - DEADLINE_ABORT_MARGIN_SECS).max(0) as f64;
+    if let Some(limit) = limits.max_duration {
+        secs = secs.min(limit.as_secs_f64());
+    }
+    let mut hashes = hashrate * secs;
+    if let Some(limit) = limits.max_hashes {
+        hashes = hashes.min(limit as f64);
+    }
+    Some(challenge.difficulty_model().map_or(0.0, |model| model.solve_probability(hashes)))
+}
+
+/// Orders the active challenges for selection
+pub struct Ranker {
+    min_probability: f64,     // 0 = never skip
+    skipped: HashSet<String>, // Challenges already reported as out of reach
+}
+
+impl Ranker {
+    pub fn new(min_percent: f64) -> Self {
+        Ranker { min_probability: (min_percent / 100.0).clamp(0.0, 1.0), skipped: HashSet::new() }
+    }
+
+    /// Challenges worth mining, most likely to be solved first
+    /// `challenges` is expected in heuristic order (see `apply_active_challenges`)
+    pub fn rank(&mut self, challenges: &[Challenge], limits: &MiningLimits) -> Vec<Challenge> {
+        let Some(hashrate) = measured_hashrate() else {
+            return challenges.to_vec();
+        };
+
+        let mut ranked: Vec<(u32, Challenge)> = Vec::new();
+        for challenge in challenges {
+            let Some(probability) = solve_probability(challenge, hashrate, limits) else {
+                ranked.push((100, challenge.clone()));
+                continue;
+            };
+            if probability < self.min_probability {
+                if self.skipped.insert(challenge.challenge_id.clone()) {
+                    tracing::info!(
+                        challenge_id = %challenge.challenge_id,
+                        "⏭️  Skipping challenge {} for now: {:.2}% chance to solve it before its deadline at {:.0} H/s",
+                        challenge.challenge_id, probability * 100.0, hashrate,
+                    );
+                }
+                continue;
+            }
+            self.skipped.remove(&challenge.challenge_id);
+            ranked.push(((probability * 100.0).floor() as u32, challenge.clone()));
+        }
+
+        // Stable, so challenges with the same chance keep the heuristic order
+        ranked.sort_by_key(|(percent, _)| std::cmp::Reverse(*percent));
+        ranked.into_iter().map(|(_, challenge)| challenge).collect()
+    }
+
+    /// Whether any active challenge is currently skipped as out of reach
+    pub fn skipping(&self, challenges: &[Challenge]) -> bool {
+        challenges.iter().any(|c| self.skipped.contains(&c.challenge_id))
+    }
+}
@@ -0,0 +1,56 @@
+//! Verification of crypto receipts returned by the API
+//!
+//! A receipt's `signature` is checked as an Ed25519 signature over its `preimage`
+//! string, using the public key given with `--receipt-pubkey` (hex or base64).
+//! Without a key, receipts are stored unverified as before.
+
+use crate::{CliArgs, CryptoReceipt};
+use base64::Engine;
+use ring::signature::{UnparsedPublicKey, ED25519};
+use std::sync::OnceLock;
+
+static PUBLIC_KEY: OnceLock<Option<Vec<u8>>> = OnceLock::new();
+
+/// Decode a hex or base64 value
+fn decode(value: &str) -> Option<Vec<u8>> {
+    let value = value.trim();
+    hex::decode(value.strip_prefix("0x").unwrap_or(value))
+        .ok()
+        .or_else(|| base64::engine::general_purpose::STANDARD.decode(value).ok())
+}
+
+/// Read the verification key from the command line / config file
+/// Must run before the first receipt is checked; later calls are ignored
+pub fn configure(cli: &CliArgs) -> Result<(), String> {
+    let key = match cli.option("--receipt-pubkey") {
+        Some(value) => match decode(value) {
+            Some(key) if key.len() == 32 => Some(key),
+            _ => return Err(format!("Invalid --receipt-pubkey '{}' (expected a 32-byte Ed25519 key in hex or base64)", value)),
+        },
+        None => None,
+    };
+    let _ = PUBLIC_KEY.set(key);
+    Ok(())
+}
+
+/// Whether receipts are being verified at all
+pub fn enabled() -> bool {
+    PUBLIC_KEY.get().is_some_and(|key| key.is_some())
+}
+
+/// Verify a receipt's signature; `None` if no key is configured
+/// A receipt that fails verification is logged loudly, since the API may not have recorded the solution
+pub fn verify(receipt: &CryptoReceipt) -> Option<bool> {
+    let key = PUBLIC_KEY.get()?.as_ref()?;
+    let valid = decode(&receipt.signature).is_some_and(|signature| {
+        UnparsedPublicKey::new(&ED25519, key)
+            .verify(receipt.preimage.as_bytes(), &signature)
+            .is_ok()
+    });
+
+    if !valid {
+        tracing::info!("🚨🚨 Crypto receipt signature did NOT verify against the configured public key!");
+        tracing::info!("   The receipt may be forged or corrupted; keep the solution file and check with reconcile");
+    }
+    Some(valid)
+}
@@ -0,0 +1,131 @@
+//! `reconcile`: cross-check local receipts against the API, per wallet
+//!
+//! Counts the crypto receipts saved locally (`solutions/` or the SQLite store) and compares them with the receipt
+//! count the API reports for each wallet, then lists what needs attention before the
+//! claim window closes.
+
+use crate::{load_user_wallets, store, wallets, CliArgs};
+use std::collections::BTreeMap;
+
+/// Local view of one wallet
+#[derive(Default)]
+struct WalletSummary {
+    receipts: u64,
+    pending: Vec<String>, // Challenges still waiting for a successful (re)submission
+    lost: Vec<String>,    // Challenges given up on (abandoned or closed before submitting)
+    unverified: Vec<String>, // Receipts whose signature failed verification
+}
+
+/// Receipt count the API reports for a wallet, if the statistics endpoint answers
+fn fetch_server_receipts(client: &reqwest::blocking::Client, wallet: &str) -> Result<u64, String> {
+    let url = format!("{}/statistics/{}", crate::http::api_base(), wallet);
+    let response = crate::http::send(crate::http::get(client, &url)).map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+    let body: serde_json::Value = response.json().map_err(|e| e.to_string())?;
+
+    // Per-wallet numbers live under "local"; accept a flat layout too
+    ["/local/crypto_receipts", "/crypto_receipts", "/local/solutions", "/solutions"]
+        .iter()
+        .find_map(|pointer| body.pointer(pointer).and_then(|v| v.as_u64()))
+        .ok_or_else(|| "no receipt count in statistics response".to_string())
+}
+
+/// Usage: reconcile [--wallets <file>]
+/// Returns false if any wallet needs attention
+pub fn run_reconcile(args: &[String]) -> bool {
+    let cli = CliArgs::parse(args);
+    let wallets_file = cli.option("--wallets").unwrap_or(wallets::default_source());
+
+    let mut wallets: BTreeMap<String, WalletSummary> = BTreeMap::new();
+    if let Ok(addresses) = load_user_wallets(wallets_file) {
+        for address in addresses {
+            wallets.entry(address).or_default();
+        }
+    }
+
+    for record in store::get().all() {
+        let summary = wallets.entry(record.wallet_address.clone()).or_default();
+        if record.crypto_receipt.is_some() {
+            summary.receipts += 1;
+            if record.verified == Some(false) {
+                summary.unverified.push(record.challenge_id);
+            }
+        } else if matches!(record.status.as_str(), "abandoned" | "challenge_closed") {
+            summary.lost.push(record.challenge_id);
+        } else if matches!(record.status.as_str(), "failed" | "rejected" | "queued" | "offline") || record.status.starts_with("error:") {
+            summary.pending.push(record.challenge_id);
+        }
+    }
+
+    if wallets.is_empty() {
+        println!("No wallets in {} and no records in {}", wallets_file, store::description());
+        return true;
+    }
+
+    let client = match crate::http::client() {
+        Ok(client) => client,
+        Err(e) => {
+            println!("❌ Failed to create HTTP client: {}", e);
+            return false;
+        }
+    };
+
+    println!("🧾 Receipt reconciliation ({} wallet(s))\n", wallets.len());
+    println!("   {:<24}  {:>6}  {:>6}  status", "wallet", "local", "server");
+
+    let mut actions: Vec<String> = Vec::new();
+    for (wallet, summary) in &wallets {
+        let short = wallets::label(wallet);
+        let (server, status) = match fetch_server_receipts(&client, wallet) {
+            Ok(server) if server == summary.receipts => (server.to_string(), "✅ match".to_string()),
+            Ok(server) if server < summary.receipts => {
+                actions.push(format!(
+                    "{}: {} local receipt(s) not counted by the server - keep the receipts in {} as proof and contact support",
+                    short, summary.receipts - server, store::description()
+                ));
+                (server.to_string(), "❌ missing server-side".to_string())
+            }
+            Ok(server) => {
+                actions.push(format!(
+                    "{}: server shows {} solution(s) with no local receipt - mined on another machine? import its state with import-state",
+                    short, server - summary.receipts
+                ));
+                (server.to_string(), "⚠️  missing locally".to_string())
+            }
+            Err(e) => ("n/a".to_string(), format!("⚠️  not queryable ({})", e)),
+        };
+        println!("   {:<24}  {:>6}  {:>6}  {}", short, summary.receipts, server, status);
+
+        if !summary.pending.is_empty() {
+            actions.push(format!(
+                "{}: {} solution(s) awaiting resubmission ({}) - keep the miner running so retries go through",
+                short, summary.pending.len(), summary.pending.join(", ")
+            ));
+        }
+        if !summary.unverified.is_empty() {
+            actions.push(format!(
+                "{}: {} receipt(s) failed signature verification ({}) - the server may not have recorded them",
+                short, summary.unverified.len(), summary.unverified.join(", ")
+            ));
+        }
+        if !summary.lost.is_empty() {
+            actions.push(format!(
+                "{}: {} solution(s) never accepted before giving up ({})",
+                short, summary.lost.len(), summary.lost.join(", ")
+            ));
+        }
+    }
+
+    if actions.is_empty() {
+        println!("\n✅ Nothing to do");
+        return true;
+    }
+
+    println!("\n📋 Action items:");
+    for action in &actions {
+        println!("   - {}", action);
+    }
+    false
+}
@@ -0,0 +1,325 @@
+//! Fleet-wide task claims in Redis (`--redis-url`)
+//!
+//! Rigs mining the same wallets without shared storage (see store.rs) can still start the same
+//! wallet-challenge pair at the same moment. With a Redis server they coordinate there instead:
+//!
+//! - before mining, a rig claims the pair with `SET <prefix>claim:<wallet>:<challenge> <rig> NX EX <ttl>`
+//!   and keeps extending the claim while it hashes; a pair claimed by another rig is passed over
+//! - once the solution went through submission (whatever the API answered), the pair is marked
+//!   `<prefix>done:<wallet>:<challenge>` for `DONE_TTL`, and every rig treats it as solved
+//! - a task that ends without a solution gives its claim back right away
+//!
+//! A rig that dies mid-task leaves its claim to expire after `--redis-claim-ttl`. When Redis is
+//! unreachable, mining goes on as if every pair were free (connecting again every
+//! `RECONNECT_INTERVAL`). Only plain `redis://[user:password@]host[:port][/db]` URLs are supported.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_CLAIM_TTL_SECS: u64 = 600;
+pub const DEFAULT_PREFIX: &str = "scavenger:";
+/// How long a solved pair stays marked; challenges close well before that
+const DONE_TTL_SECS: u64 = 7 * 24 * 3600;
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(30);
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Delete or extend a claim only if this rig still holds it
+const RELEASE_SCRIPT: &str = "if redis.call('GET', KEYS[1]) == ARGV[1] then return redis.call('DEL', KEYS[1]) else return 0 end";
+const RENEW_SCRIPT: &str = "if redis.call('GET', KEYS[1]) == ARGV[1] then return redis.call('EXPIRE', KEYS[1], ARGV[2]) else return 0 end";
+
+/// Where the server is, from `--redis-url`
+struct Endpoint {
+    host: String,
+    port: u16,
+    username: Option<String>,
+    password: Option<String>,
+    db: u32,
+}
+
+impl Endpoint {
+    fn parse(url: &str) -> Result<Self, String> {
+        let parsed = reqwest::Url::parse(url).map_err(|e| format!("Invalid --redis-url '{}': {}", url, e))?;
+        if parsed.scheme() != "redis" {
+            return Err(format!("Invalid --redis-url '{}' (expected redis://[user:password@]host[:port][/db]; TLS is not supported)", url));
+        }
+        let Some(host) = parsed.host_str() else {
+            return Err(format!("Invalid --redis-url '{}': no host", url));
+        };
+        let db = match parsed.path().trim_matches('/') {
+            "" => 0,
+            db => db.parse().map_err(|_| format!("Invalid --redis-url '{}': database '{}' is not a number", url, db))?,
+        };
+        Ok(Endpoint {
+            host: host.trim_matches(['[', ']']).to_string(),
+            port: parsed.port().unwrap_or(6379),
+            username: Some(parsed.username().to_string()).filter(|u| !u.is_empty()),
+            password: parsed.password().map(|p| p.to_string()),
+            db,
+        })
+    }
+}
+
+/// A RESP reply
+#[derive(Debug, PartialEq)]
+enum Value {
+    Nil,
+    Int(i64),
+    Text(String),
+    Array(Vec<Value>),
+}
+
+/// One connection speaking RESP2
+struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    fn open(endpoint: &Endpoint) -> io::Result<Self> {
+        let addr = (endpoint.host.as_str(), endpoint.port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::other(format!("cannot resolve {}", endpoint.host)))?;
+        let stream = TcpStream::connect_timeout(&addr, IO_TIMEOUT)?;
+        stream.set_read_timeout(Some(IO_TIMEOUT))?;
+        stream.set_write_timeout(Some(IO_TIMEOUT))?;
+        let mut connection = Connection { reader: BufReader::new(stream.try_clone()?), writer: stream };
+        match (&endpoint.username, &endpoint.password) {
+            (Some(username), Some(password)) => connection.command(&["AUTH", username, password])?,
+            (None, Some(password)) => connection.command(&["AUTH", password])?,
+            _ => Value::Nil,
+        };
+        if endpoint.db != 0 {
+            connection.command(&["SELECT", &endpoint.db.to_string()])?;
+        }
+        Ok(connection)
+    }
+
+    fn command(&mut self, args: &[&str]) -> io::Result<Value> {
+        let mut request = format!("*{}\r\n", args.len());
+        for arg in args {
+            request.push_str(&format!("${}\r\n{}\r\n", arg.len(), arg));
+        }
+        self.writer.write_all(request.as_bytes())?;
+        self.read_value()
+    }
+
+    fn read_value(&mut self) -> io::Result<Value> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "connection closed by the server"));
+        }
+        let line = line.trim_end_matches(['\r', '\n']);
+        let (kind, rest) = line.split_at(line.len().min(1));
+        let number = || rest.parse::<i64>().map_err(|_| io::Error::other(format!("unexpected reply '{}'", line)));
+        match kind {
+            "+" => Ok(Value::Text(rest.to_string())),
+            "-" => Err(io::Error::other(rest.to_string())),
+            ":" => Ok(Value::Int(number()?)),
+            "$" => match number()? {
+                n if n < 0 => Ok(Value::Nil),
+                n => {
+                    let mut data = vec![0u8; n as usize + 2];
+                    self.reader.read_exact(&mut data)?;
+                    data.truncate(n as usize);
+                    Ok(Value::Text(String::from_utf8_lossy(&data).into_owned()))
+                }
+            },
+            "*" => match number()? {
+                n if n < 0 => Ok(Value::Nil),
+                n => (0..n).map(|_| self.read_value()).collect::<io::Result<_>>().map(Value::Array),
+            },
+            _ => Err(io::Error::other(format!("unexpected reply '{}'", line))),
+        }
+    }
+}
+
+struct Redis {
+    endpoint: Endpoint,
+    prefix: String,
+    holder: String, // Value of this rig's claims
+    ttl_secs: u64,
+    connection: Option<Connection>,
+    failed_at: Option<Instant>,
+}
+
+/// Set by `enable`; `None` means no claims are taken
+static REDIS: Mutex<Option<Redis>> = Mutex::new(None);
+
+/// Coordinate through the server at `url`; claims carry `holder` (rig name and process ID)
+pub fn enable(url: &str, prefix: &str, ttl_secs: u64, holder: &str) -> Result<(), String> {
+    let endpoint = Endpoint::parse(url)?;
+    let described = format!("{}:{}/{}", endpoint.host, endpoint.port, endpoint.db);
+    let mut redis = Redis {
+        endpoint,
+        prefix: prefix.to_string(),
+        holder: format!("{}:{}", holder, std::process::id()),
+        ttl_secs: ttl_secs.max(30),
+        connection: None,
+        failed_at: None,
+    };
+    match Connection::open(&redis.endpoint) {
+        Ok(connection) => {
+            redis.connection = Some(connection);
+            tracing::info!(
+                "🔐 Task claims in Redis at {} as '{}' (claims expire after {}s)",
+                described, redis.holder, redis.ttl_secs,
+            );
+        }
+        Err(e) => {
+            redis.failed_at = Some(Instant::now());
+            tracing::warn!(error = %e, "⚠️  Redis at {} unreachable ({}), mining without claims until it answers", described, e);
+        }
+    }
+    *REDIS.lock().unwrap() = Some(redis);
+    Ok(())
+}
+
+fn key(redis: &Redis, kind: &str, wallet_address: &str, challenge_id: &str) -> String {
+    format!("{}{}:{}:{}", redis.prefix, kind, wallet_address, challenge_id)
+}
+
+/// Run commands on the connection, reconnecting if needed; `None` if Redis is off or unreachable
+fn with_redis<T>(f: impl FnOnce(&mut Connection, &Redis) -> io::Result<T>) -> Option<T> {
+    let mut guard = REDIS.lock().unwrap();
+    let redis = guard.as_mut()?;
+    if redis.connection.is_none() {
+        if redis.failed_at.is_some_and(|at| at.elapsed() < RECONNECT_INTERVAL) {
+            return None;
+        }
+        match Connection::open(&redis.endpoint) {
+            Ok(connection) => {
+                if redis.failed_at.take().is_some() {
+                    tracing::info!("🔐 Redis reachable again, claiming tasks");
+                }
+                redis.connection = Some(connection);
+            }
+            Err(e) => {
+                if redis.failed_at.is_none() {
+                    tracing::warn!(error = %e, "⚠️  Redis unreachable ({}), mining without claims until it answers", e);
+                }
+                redis.failed_at = Some(Instant::now());
+                return None;
+            }
+        }
+    }
+    let mut connection = redis.connection.take()?;
+    match f(&mut connection, redis) {
+        Ok(value) => {
+            redis.connection = Some(connection);
+            Some(value)
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "⚠️  Redis request failed: {}", e);
+            redis.failed_at = Some(Instant::now());
+            None
+        }
+    }
+}
+
+/// Whether another rig has solved the pair or is mining it
+pub fn taken(wallet_address: &str, challenge_id: &str) -> bool {
+    with_redis(|connection, redis| {
+        let done = key(redis, "done", wallet_address, challenge_id);
+        let claim = key(redis, "claim", wallet_address, challenge_id);
+        Ok(match connection.command(&["MGET", &done, &claim])? {
+            Value::Array(values) => match values.as_slice() {
+                [done, claim] => *done != Value::Nil || matches!(claim, Value::Text(holder) if *holder != redis.holder),
+                _ => false,
+            },
+            _ => false,
+        })
+    })
+    .unwrap_or(false)
+}
+
+/// A pair this rig is mining; extended in the background until `finish`
+pub struct Claim {
+    key: String,
+    stop: Arc<AtomicBool>,
+    renewer: Option<thread::JoinHandle<()>>,
+}
+
+/// Claim the pair before mining it. `Ok(None)`: Redis is off or unreachable, mine anyway;
+/// `Err(holder)`: another rig has it
+pub fn claim(wallet_address: &str, challenge_id: &str) -> Result<Option<Claim>, String> {
+    let claimed = with_redis(|connection, redis| {
+        let claim = key(redis, "claim", wallet_address, challenge_id);
+        match connection.command(&["SET", &claim, &redis.holder, "NX", "EX", &redis.ttl_secs.to_string()])? {
+            Value::Nil => match connection.command(&["GET", &claim])? {
+                // Still ours from before a restart of the task
+                Value::Text(holder) if holder == redis.holder => Ok(Ok((claim, redis.ttl_secs))),
+                Value::Text(holder) => Ok(Err(holder)),
+                _ => Ok(Err("another rig".to_string())),
+            },
+            _ => Ok(Ok((claim, redis.ttl_secs))),
+        }
+    });
+    let (key, ttl_secs) = match claimed {
+        None => return Ok(None),
+        Some(Err(holder)) => return Err(holder),
+        Some(Ok(claim)) => claim,
+    };
+
+    // Extend the claim every third of its lifetime while hashing
+    let stop = Arc::new(AtomicBool::new(false));
+    let renewer = {
+        let (key, stop, ttl) = (key.clone(), Arc::clone(&stop), ttl_secs.to_string());
+        let interval = Duration::from_secs(ttl_secs / 3);
+        thread::Builder::new()
+            .name("redis-claim".to_string())
+            .spawn(move || {
+                let mut last = Instant::now();
+                while !stop.load(Ordering::SeqCst) {
+                    thread::sleep(Duration::from_millis(250));
+                    if last.elapsed() < interval {
+                        continue;
+                    }
+                    last = Instant::now();
+                    with_redis(|connection, redis| connection.command(&["EVAL", RENEW_SCRIPT, "1", &key, &redis.holder, &ttl]));
+                }
+            })
+            .ok()
+    };
+    Ok(Some(Claim { key, stop, renewer }))
+}
+
+impl Claim {
+    /// The task is over: a found solution keeps the claim until `record_done` replaces it,
+    /// anything else frees the pair for the other rigs
+    pub fn finish(mut self, found: bool) {
+        self.stop_renewing();
+        if !found {
+            let key = self.key.clone();
+            with_redis(|connection, redis| connection.command(&["EVAL", RELEASE_SCRIPT, "1", &key, &redis.holder]));
+        }
+    }
+
+    fn stop_renewing(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.renewer.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for Claim {
+    fn drop(&mut self) {
+        self.stop_renewing();
+    }
+}
+
+/// A solution to the pair went through submission: no rig needs to mine it again
+pub fn record_done(wallet_address: &str, challenge_id: &str) {
+    with_redis(|connection, redis| {
+        let done = key(redis, "done", wallet_address, challenge_id);
+        let claim = key(redis, "claim", wallet_address, challenge_id);
+        let value = format!("{} {}", redis.holder, crate::get_timestamp());
+        connection.command(&["SET", &done, &value, "EX", &DONE_TTL_SECS.to_string()])?;
+        connection.command(&["EVAL", RELEASE_SCRIPT, "1", &claim, &redis.holder])
+    });
+}
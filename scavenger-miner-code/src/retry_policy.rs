@@ -0,0 +1,111 @@
+//! Per-wallet overrides for failed submission retries
+//!
+//! Loaded from `retry_policies.json` if present:
+//!
+//! ```json
+//! {
+//!   "default": { "interval_minutes": 60, "max_retries": 10 },
+//!   "overrides": [
+//!     { "wallets": ["addr1q8up..."], "interval_minutes": 10, "hold_until_deadline": true }
+//!   ]
+//! }
+//! ```
+//!
+//! Each override applies to a group of wallets; fields it leaves out come from `default`.
+
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+pub const RETRY_POLICY_FILE: &str = "retry_policies.json";
+
+/// How failed submissions for a wallet are retried
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    pub interval_secs: u64,
+    pub max_retries: u32,
+    /// Keep retrying past `max_retries` until the challenge closes
+    pub hold_until_deadline: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            interval_secs: 3600,
+            max_retries: 10,
+            hold_until_deadline: false,
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+struct PolicyFields {
+    interval_minutes: Option<f64>,
+    max_retries: Option<u32>,
+    hold_until_deadline: Option<bool>,
+}
+
+impl PolicyFields {
+    fn apply(&self, base: RetryPolicy) -> RetryPolicy {
+        RetryPolicy {
+            interval_secs: self.interval_minutes.map_or(base.interval_secs, |m| (m.max(0.0) * 60.0) as u64),
+            max_retries: self.max_retries.unwrap_or(base.max_retries),
+            hold_until_deadline: self.hold_until_deadline.unwrap_or(base.hold_until_deadline),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct PolicyOverride {
+    wallets: Vec<String>,
+    #[serde(flatten)]
+    fields: PolicyFields,
+}
+
+#[derive(Deserialize, Default)]
+struct PolicyFile {
+    #[serde(default)]
+    default: PolicyFields,
+    #[serde(default)]
+    overrides: Vec<PolicyOverride>,
+}
+
+/// Retry policies for all wallets
+#[derive(Default)]
+pub struct RetryPolicies {
+    default: RetryPolicy,
+    overrides: Vec<(Vec<String>, RetryPolicy)>,
+}
+
+impl RetryPolicies {
+    /// Load from `RETRY_POLICY_FILE`, falling back to the built-in policy if it's missing
+    pub fn load() -> Result<Self, String> {
+        if !Path::new(RETRY_POLICY_FILE).exists() {
+            return Ok(RetryPolicies::default());
+        }
+
+        let content = fs::read_to_string(RETRY_POLICY_FILE).map_err(|e| e.to_string())?;
+        let file: PolicyFile = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+
+        let default = file.default.apply(RetryPolicy::default());
+        let overrides = file
+            .overrides
+            .into_iter()
+            .map(|o| (o.wallets, o.fields.apply(default)))
+            .collect();
+        Ok(RetryPolicies { default, overrides })
+    }
+
+    /// Number of wallet groups with their own policy
+    pub fn override_count(&self) -> usize {
+        self.overrides.len()
+    }
+
+    /// Policy for a wallet: the first override listing it, otherwise the default
+    pub fn for_wallet(&self, wallet_address: &str) -> RetryPolicy {
+        self.overrides
+            .iter()
+            .find(|(wallets, _)| wallets.iter().any(|w| w == wallet_address))
+            .map_or(self.default, |(_, policy)| *policy)
+    }
+}
@@ -0,0 +1,139 @@
+//! Which wallet mines next (`--rotation`)
+//!
+//! By default wallets take turns by smooth weighted round-robin: each turn every wallet's credit
+//! grows by its weight, the wallet with the most credit mines and gives back the total of all
+//! weights. A wallet with weight 3 gets three turns for every one of a wallet with weight 1,
+//! spread out rather than back to back, and equal weights give the plain round-robin in file
+//! order. Turns are not work, though: a wallet whose tasks keep hitting easy challenges gets as
+//! many as one stuck on hard ones. The other policies look at what each wallet actually got
+//! this session: `least-recent` picks the wallet whose last task ended longest ago (times its
+//! weight), `least-hashes` the one with the fewest hashes spent on it (per unit of weight).
+//!
+//! Wallets sitting out (see quota.rs) take no part in a turn, so the others share their cycles.
+//! A wallet whose turn ended without mining (nothing left to mine, or a skipped task) is passed
+//! over until another wallet has mined, so the policies can't keep picking it. Wallets can join
+//! and leave between turns (see control.rs).
+
+use std::time::Instant;
+
+/// How the next wallet is chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RotationPolicy {
+    RoundRobin,  // Weighted turns in file order (default)
+    LeastRecent, // Longest since its last task ended
+    LeastHashes, // Fewest hashes this session
+}
+
+impl RotationPolicy {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "round-robin" | "roundrobin" => Some(RotationPolicy::RoundRobin),
+            "least-recent" | "lru" => Some(RotationPolicy::LeastRecent),
+            "least-hashes" => Some(RotationPolicy::LeastHashes),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RotationPolicy::RoundRobin => "round-robin",
+            RotationPolicy::LeastRecent => "least-recent",
+            RotationPolicy::LeastHashes => "least-hashes",
+        }
+    }
+}
+
+pub struct WalletRotation {
+    policy: RotationPolicy,
+    weights: Vec<f64>,
+    credits: Vec<f64>,                // Round-robin
+    last_served: Vec<Option<Instant>>, // End of each wallet's last task
+    hashes: Vec<u64>,                 // Hashes this session
+    passed: Vec<bool>,                // Turn ended without mining, since the last task anywhere
+}
+
+impl WalletRotation {
+    pub fn new(weights: Vec<f64>, policy: RotationPolicy) -> Self {
+        let count = weights.len();
+        WalletRotation {
+            policy,
+            weights,
+            credits: vec![0.0; count],
+            last_served: vec![None; count],
+            hashes: vec![0; count],
+            passed: vec![false; count],
+        }
+    }
+
+    pub fn policy(&self) -> RotationPolicy {
+        self.policy
+    }
+
+    /// Index of the wallet whose turn it is among the ones `eligible` accepts (None if none is)
+    pub fn next(&mut self, eligible: impl Fn(usize) -> bool) -> Option<usize> {
+        let eligible: Vec<usize> = (0..self.weights.len()).filter(|&i| eligible(i)).collect();
+        let mut candidates: Vec<usize> = eligible.iter().copied().filter(|&i| !self.passed[i]).collect();
+        if candidates.is_empty() {
+            // Everyone had their turn without mining: start over
+            candidates = eligible;
+            self.passed.iter_mut().for_each(|p| *p = false);
+        }
+
+        match self.policy {
+            RotationPolicy::RoundRobin => {
+                let mut best: Option<usize> = None;
+                let mut total = 0.0;
+                for &index in &candidates {
+                    self.credits[index] += self.weights[index];
+                    total += self.weights[index];
+                    if best.is_none_or(|b| self.credits[index] > self.credits[b]) {
+                        best = Some(index);
+                    }
+                }
+                let best = best?;
+                self.credits[best] -= total;
+                Some(best)
+            }
+            RotationPolicy::LeastRecent => {
+                // Never served first, in file order; then the longest wait scaled by weight
+                let now = Instant::now();
+                let waited = |i: usize| self.last_served[i].map_or(f64::INFINITY, |t| (now - t).as_secs_f64() * self.weights[i]);
+                candidates.iter().copied().reduce(|best, i| if waited(i) > waited(best) { i } else { best })
+            }
+            RotationPolicy::LeastHashes => {
+                let spent = |i: usize| self.hashes[i] as f64 / self.weights[i];
+                candidates.iter().copied().reduce(|best, i| if spent(i) < spent(best) { i } else { best })
+            }
+        }
+    }
+
+    /// A wallet's task ended after `hashes` hashes
+    pub fn served(&mut self, index: usize, hashes: u64) {
+        self.last_served[index] = Some(Instant::now());
+        self.hashes[index] += hashes;
+        self.passed.iter_mut().for_each(|p| *p = false);
+    }
+
+    /// Add a wallet at the end; it starts with no credit and counts as never served
+    pub fn add(&mut self, weight: f64) {
+        self.weights.push(weight);
+        self.credits.push(0.0);
+        self.last_served.push(None);
+        self.hashes.push(0);
+        self.passed.push(false);
+    }
+
+    /// Drop a wallet; the ones after it move down an index
+    pub fn remove(&mut self, index: usize) {
+        self.weights.remove(index);
+        self.credits.remove(index);
+        self.last_served.remove(index);
+        self.hashes.remove(index);
+        self.passed.remove(index);
+    }
+
+    /// A wallet's turn ended without mining
+    pub fn pass(&mut self, index: usize) {
+        self.passed[index] = true;
+    }
+}
@@ -0,0 +1,398 @@
+//! S3 solution store (`--store s3`): records in a bucket shared by a fleet of miners
+//!
+//! Works with AWS S3 and S3-compatible servers (MinIO, Ceph, R2, ...), addressed path-style
+//! (`<endpoint>/<bucket>/<key>`) and signed with AWS Signature Version 4. Credentials come from
+//! `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and, for temporary ones, `AWS_SESSION_TOKEN`.
+//!
+//! Layout under `--s3-prefix`:
+//!
+//! - `<file>`: one JSON record per wallet-challenge pair, named like the JSON store's files
+//! - `pending/<file>`: an empty marker while the record waits for a (re)submission
+//! - `claims/<file>`: a record being resubmitted by one miner, written with `If-None-Match: *`
+//!   so only one miner gets it
+//! - `archive/<date>/<file>`: archived records
+//!
+//! Which pairs have a record is listed from the bucket at most every `SHARED_REFRESH_INTERVAL`,
+//! so a pair solved by another miner is skipped from the next listing on.
+
+use crate::error::MinerError;
+use crate::store::{archive_folder, is_pending_status, JsonStore, SolutionStore, CLAIM_TTL, SHARED_REFRESH_INTERVAL};
+use crate::{http, CliArgs, SolutionRecord};
+use chrono::{DateTime, Utc};
+use reqwest::blocking::{Client, Response};
+use reqwest::Method;
+use ring::{digest, hmac};
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Instant;
+
+const DEFAULT_REGION: &str = "us-east-1";
+const PENDING_PREFIX: &str = "pending/";
+const CLAIMS_PREFIX: &str = "claims/";
+const ARCHIVE_PREFIX: &str = "archive/";
+
+/// Bucket and credentials, from the command line and environment
+#[derive(Clone)]
+pub struct S3Settings {
+    bucket: String,
+    endpoint: String, // Without a trailing slash
+    region: String,
+    prefix: String, // Empty, or ending with `/`
+    access_key: String,
+    secret_key: String,
+    session_token: Option<String>,
+}
+
+impl std::fmt::Debug for S3Settings {
+    // The secret stays out of debug output
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.describe())
+    }
+}
+
+impl S3Settings {
+    /// `--s3-bucket`, `--s3-endpoint`, `--s3-region` and `--s3-prefix`, plus the AWS_* variables
+    pub fn from_cli(cli: &CliArgs) -> Result<Self, String> {
+        let bucket = cli.option("--s3-bucket").map(|b| b.trim().to_string()).filter(|b| !b.is_empty());
+        let Some(bucket) = bucket else {
+            return Err("--store s3 needs --s3-bucket <name>".to_string());
+        };
+        let region = cli.option("--s3-region").map(|r| r.trim().to_string()).unwrap_or_else(|| DEFAULT_REGION.to_string());
+        let endpoint = match cli.option("--s3-endpoint") {
+            Some(endpoint) => {
+                let url = reqwest::Url::parse(endpoint.trim()).map_err(|e| format!("Invalid --s3-endpoint '{}': {}", endpoint, e))?;
+                if url.host_str().is_none() || !matches!(url.scheme(), "http" | "https") {
+                    return Err(format!("Invalid --s3-endpoint '{}' (expected http(s)://host[:port])", endpoint));
+                }
+                endpoint.trim().trim_end_matches('/').to_string()
+            }
+            None => format!("https://s3.{}.amazonaws.com", region),
+        };
+        let mut prefix = cli.option("--s3-prefix").unwrap_or("").trim().trim_matches('/').to_string();
+        if !prefix.is_empty() {
+            prefix.push('/');
+        }
+        let env = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let (Some(access_key), Some(secret_key)) = (env("AWS_ACCESS_KEY_ID"), env("AWS_SECRET_ACCESS_KEY")) else {
+            return Err("--store s3 needs AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY in the environment".to_string());
+        };
+        Ok(S3Settings { bucket, endpoint, region, prefix, access_key, secret_key, session_token: env("AWS_SESSION_TOKEN") })
+    }
+
+    /// Short description for the startup log
+    pub fn describe(&self) -> String {
+        format!("s3://{}/{} ({})", self.bucket, self.prefix, self.endpoint)
+    }
+}
+
+pub struct S3Store {
+    settings: S3Settings,
+    client: Client,
+    listing: Mutex<Option<(Instant, HashSet<String>)>>, // Record file names, and when they were listed
+}
+
+impl S3Store {
+    /// Check the bucket can be listed with these credentials
+    pub fn open(settings: S3Settings) -> Result<Self, MinerError> {
+        let store = S3Store { settings, client: http::client()?, listing: Mutex::new(None) };
+        store.list("", true)?;
+        Ok(store)
+    }
+
+    /// Send a signed request for `key` (under the prefix; empty for the bucket itself)
+    fn request(&self, method: Method, key: &str, query: &[(&str, &str)], headers: &[(&str, &str)], body: Vec<u8>) -> Result<Response, MinerError> {
+        let s = &self.settings;
+        let mut path = format!("/{}", uri_encode(&s.bucket, false));
+        if !key.is_empty() {
+            path.push('/');
+            path.push_str(&uri_encode(&format!("{}{}", s.prefix, key), true));
+        }
+        let mut query: Vec<(String, String)> = query.iter().map(|(k, v)| (uri_encode(k, false), uri_encode(v, false))).collect();
+        query.sort();
+        let query = query.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join("&");
+
+        let url = reqwest::Url::parse(&s.endpoint).map_err(|e| MinerError::Protocol(e.to_string()))?;
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+        let now = crate::now_utc();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let day = now.format("%Y%m%d").to_string();
+        let payload_hash = hex::encode(digest::digest(&digest::SHA256, &body));
+
+        let mut signed: Vec<(String, String)> = vec![
+            ("host".to_string(), host),
+            ("x-amz-content-sha256".to_string(), payload_hash.clone()),
+            ("x-amz-date".to_string(), amz_date.clone()),
+        ];
+        if let Some(token) = &s.session_token {
+            signed.push(("x-amz-security-token".to_string(), token.clone()));
+        }
+        for (name, value) in headers {
+            signed.push((name.to_ascii_lowercase(), value.trim().to_string()));
+        }
+        signed.sort();
+        let signed_names = signed.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>().join(";");
+        let canonical_headers: String = signed.iter().map(|(name, value)| format!("{}:{}\n", name, value)).collect();
+        let canonical_request = format!("{}\n{}\n{}\n{}\n{}\n{}", method, path, query, canonical_headers, signed_names, payload_hash);
+
+        let scope = format!("{}/{}/s3/aws4_request", day, s.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(digest::digest(&digest::SHA256, canonical_request.as_bytes()))
+        );
+        let signing_key = [day.as_str(), s.region.as_str(), "s3", "aws4_request"]
+            .iter()
+            .fold(format!("AWS4{}", s.secret_key).into_bytes(), |key, part| sign(&key, part.as_bytes()));
+        let signature = hex::encode(sign(&signing_key, string_to_sign.as_bytes()));
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            s.access_key, scope, signed_names, signature
+        );
+
+        let url = if query.is_empty() { format!("{}{}", s.endpoint, path) } else { format!("{}{}?{}", s.endpoint, path, query) };
+        let mut request = self.client.request(method, url).header("Authorization", authorization).body(body);
+        for (name, value) in signed.iter().filter(|(name, _)| name != "host") {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        Ok(http::send(request)?)
+    }
+
+    /// Fail on an error status, with the S3 error code in the message
+    fn check(response: Response, what: &str) -> Result<Response, MinerError> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let body = response.text().unwrap_or_default();
+        let code = tag_values(&body, "Code").first().copied().unwrap_or("").to_string();
+        Err(MinerError::Protocol(format!("S3 {} failed: HTTP {} {}", what, status.as_u16(), code).trim_end().to_string()))
+    }
+
+    /// Keys under `prefix` (relative to the store prefix); `top_level` skips subfolders
+    fn list(&self, prefix: &str, top_level: bool) -> Result<Vec<String>, MinerError> {
+        let full_prefix = format!("{}{}", self.settings.prefix, prefix);
+        let mut keys = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+            let mut query = vec![("list-type", "2"), ("prefix", full_prefix.as_str())];
+            if top_level {
+                query.push(("delimiter", "/"));
+            }
+            if let Some(token) = &token {
+                query.push(("continuation-token", token.as_str()));
+            }
+            let response = Self::check(self.request(Method::GET, "", &query, &[], Vec::new())?, "listing")?;
+            let body = response.text()?;
+            keys.extend(
+                tag_values(&body, "Key")
+                    .into_iter()
+                    .filter_map(|key| unescape(key).strip_prefix(&self.settings.prefix).map(|k| k.to_string())),
+            );
+            token = tag_values(&body, "NextContinuationToken").first().map(|t| unescape(t));
+            if tag_values(&body, "IsTruncated").first() != Some(&"true") || token.is_none() {
+                return Ok(keys);
+            }
+        }
+    }
+
+    fn get_record(&self, key: &str) -> Option<SolutionRecord> {
+        let response = self.request(Method::GET, key, &[], &[], Vec::new()).ok()?;
+        if !response.status().is_success() {
+            return None;
+        }
+        let record = serde_json::from_slice(&response.bytes().ok()?);
+        if let Err(e) = &record {
+            tracing::warn!(error = %e, "⚠️  s3://{}/{}{} is not a valid solution record ({})", self.settings.bucket, self.settings.prefix, key, e);
+        }
+        record.ok()
+    }
+
+    fn put(&self, key: &str, headers: &[(&str, &str)], body: Vec<u8>) -> Result<Response, MinerError> {
+        self.request(Method::PUT, key, &[], headers, body)
+    }
+
+    fn delete(&self, key: &str) -> Result<(), MinerError> {
+        Self::check(self.request(Method::DELETE, key, &[], &[], Vec::new())?, &format!("delete of {}", key)).map(|_| ())
+    }
+
+    /// Record file names, listed again once `SHARED_REFRESH_INTERVAL` has passed
+    fn with_listing<T>(&self, f: impl FnOnce(&mut HashSet<String>) -> T) -> T {
+        let mut listing = self.listing.lock().unwrap();
+        if listing.as_ref().is_none_or(|(at, _)| at.elapsed() >= SHARED_REFRESH_INTERVAL) {
+            match self.list("", true) {
+                Ok(keys) => *listing = Some((Instant::now(), keys.into_iter().filter(|k| k.ends_with(".json")).collect())),
+                Err(e) => {
+                    tracing::warn!(error = %e, "⚠️  Cannot list the S3 solution store: {}", e);
+                    // Keep what we had; try again after another interval
+                    let names = listing.take().map(|(_, names)| names).unwrap_or_default();
+                    *listing = Some((Instant::now(), names));
+                }
+            }
+        }
+        f(&mut listing.as_mut().expect("listed above").1)
+    }
+
+    /// When a claim was written (from HEAD), if it still exists
+    fn claimed_at(&self, key: &str) -> Option<DateTime<Utc>> {
+        let response = self.request(Method::HEAD, key, &[], &[], Vec::new()).ok()?;
+        let modified = response.headers().get("last-modified")?.to_str().ok()?;
+        DateTime::parse_from_rfc2822(modified).ok().map(|t| t.with_timezone(&Utc))
+    }
+
+    /// Write the claim unless another miner has one; true if it is ours now
+    fn try_claim(&self, key: &str) -> Result<bool, MinerError> {
+        let body = format!("{} {}", crate::get_timestamp(), hostname::get().map(|h| h.to_string_lossy().into_owned()).unwrap_or_default());
+        let response = self.put(key, &[("If-None-Match", "*")], body.into_bytes())?;
+        match response.status().as_u16() {
+            412 | 409 => Ok(false),
+            _ => Self::check(response, "claim").map(|_| true),
+        }
+    }
+}
+
+impl SolutionStore for S3Store {
+    fn save(&self, record: &SolutionRecord) -> Result<String, MinerError> {
+        let file = JsonStore::file_name(&record.wallet_address, &record.challenge_id);
+        let json = serde_json::to_vec_pretty(record)?;
+        Self::check(self.put(&file, &[("Content-Type", "application/json")], json)?, &format!("upload of {}", file))?;
+        let marker = format!("{}{}", PENDING_PREFIX, file);
+        if record.crypto_receipt.is_none() && is_pending_status(&record.status) {
+            Self::check(self.put(&marker, &[], Vec::new())?, &format!("upload of {}", marker))?;
+        } else {
+            self.delete(&marker)?;
+        }
+        self.with_listing(|names| names.insert(file.clone()));
+        Ok(format!("s3://{}/{}{}", self.settings.bucket, self.settings.prefix, file))
+    }
+
+    fn exists(&self, wallet_address: &str, challenge_id: &str) -> bool {
+        let file = JsonStore::file_name(wallet_address, challenge_id);
+        self.with_listing(|names| names.contains(&file))
+    }
+
+    fn pending(&self) -> Vec<SolutionRecord> {
+        let markers = match self.list(PENDING_PREFIX, true) {
+            Ok(markers) => markers,
+            Err(e) => {
+                tracing::warn!(error = %e, "⚠️  Cannot list pending solutions in S3: {}", e);
+                return Vec::new();
+            }
+        };
+        let mut records: Vec<SolutionRecord> = markers
+            .iter()
+            .filter_map(|marker| marker.strip_prefix(PENDING_PREFIX))
+            .filter_map(|file| self.get_record(file))
+            .collect();
+        records.retain(|r| r.crypto_receipt.is_none() && is_pending_status(&r.status));
+        records
+    }
+
+    fn all(&self) -> Vec<SolutionRecord> {
+        match self.list("", false) {
+            Ok(keys) => keys
+                .iter()
+                .filter(|key| key.ends_with(".json") && !key.starts_with(PENDING_PREFIX) && !key.starts_with(CLAIMS_PREFIX))
+                .filter_map(|key| self.get_record(key))
+                .collect(),
+            Err(e) => {
+                tracing::warn!(error = %e, "⚠️  Cannot list the S3 solution store: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    fn archive(&self, cutoff: DateTime<Utc>) -> Result<usize, MinerError> {
+        let mut moved = 0;
+        for file in self.list("", true)?.into_iter().filter(|k| k.ends_with(".json")) {
+            let Some(record) = self.get_record(&file) else {
+                continue;
+            };
+            let Some(day) = archive_folder(&record, cutoff) else {
+                continue;
+            };
+            let target = format!("{}{}/{}", ARCHIVE_PREFIX, day, file);
+            Self::check(self.put(&target, &[("Content-Type", "application/json")], serde_json::to_vec_pretty(&record)?)?, &format!("upload of {}", target))?;
+            self.delete(&file)?;
+            self.with_listing(|names| names.remove(&file));
+            moved += 1;
+        }
+        Ok(moved)
+    }
+
+    fn claim(&self, record: &SolutionRecord) -> Option<SolutionRecord> {
+        let file = JsonStore::file_name(&record.wallet_address, &record.challenge_id);
+        let key = format!("{}{}", CLAIMS_PREFIX, file);
+        let claimed = match self.try_claim(&key) {
+            Ok(true) => true,
+            // Someone else's claim, unless it was left behind
+            Ok(false) => {
+                let stale = self.claimed_at(&key).is_some_and(|at| crate::now_utc() - at > chrono::Duration::from_std(CLAIM_TTL).unwrap_or_default());
+                stale && self.delete(&key).is_ok() && self.try_claim(&key).unwrap_or(false)
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "⚠️  Cannot claim {} in S3: {}", file, e);
+                false
+            }
+        };
+        if !claimed {
+            return None;
+        }
+
+        // Another miner may have resubmitted it since the pending records were listed
+        match self.get_record(&file) {
+            Some(current) if current.crypto_receipt.is_none() && is_pending_status(&current.status) => Some(current),
+            _ => {
+                self.release(record);
+                None
+            }
+        }
+    }
+
+    fn release(&self, record: &SolutionRecord) {
+        let key = format!("{}{}", CLAIMS_PREFIX, JsonStore::file_name(&record.wallet_address, &record.challenge_id));
+        if let Err(e) = self.delete(&key) {
+            tracing::warn!(error = %e, "⚠️  Cannot release {} in S3: {}", key, e);
+        }
+    }
+}
+
+fn sign(key: &[u8], data: &[u8]) -> Vec<u8> {
+    hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, key), data).as_ref().to_vec()
+}
+
+/// Percent-encode everything but unreserved characters (and `/` in object keys), as SigV4 expects
+fn uri_encode(s: &str, keep_slash: bool) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            b'/' if keep_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Text of every `<tag>...</tag>` in an XML response (S3 answers are flat enough for this)
+fn tag_values<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let (open, close) = (format!("<{}>", tag), format!("</{}>", tag));
+    let mut values = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        rest = &rest[start + open.len()..];
+        let Some(end) = rest.find(&close) else {
+            break;
+        };
+        values.push(&rest[..end]);
+        rest = &rest[end + close.len()..];
+    }
+    values
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&apos;", "'").replace("&amp;", "&")
+}
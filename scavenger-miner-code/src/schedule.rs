@@ -0,0 +1,181 @@
+//! Mining schedule: the hours to mine in, each window with its own CPU budget
+//!
+//! The `schedule` key of the config file lists the windows, in the machine's local time:
+//!
+//! ```json
+//! {
+//!   "schedule": [
+//!     { "days": "mon-fri", "from": "22:00", "to": "07:00", "cpu_usage": 100 },
+//!     { "days": "sat,sun", "cpu_usage": 75 }
+//!   ]
+//! }
+//! ```
+//!
+//! `days` takes day names and ranges (every day if left out); `from` and `to` default to the
+//! start and end of the day. A window ending before it starts runs past midnight, the early
+//! hours belonging to the day it started on (above, Friday 22:00 to Saturday 07:00). Outside
+//! every window the miner is paused like by the other pause controls, so the ROM cache is kept.
+//! A window's `cpu_usage` sets the threads of the tasks started in it; the first window listed
+//! wins where two overlap.
+
+use chrono::{Datelike, Local, NaiveDateTime, Timelike};
+use serde::Deserialize;
+use std::sync::OnceLock;
+
+const DAY_NAMES: [&str; 7] = ["monday", "tuesday", "wednesday", "thursday", "friday", "saturday", "sunday"];
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+/// The schedule in force, if the config file has one
+static SCHEDULE: OnceLock<Schedule> = OnceLock::new();
+
+/// One entry of the `schedule` config key
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WindowConfig {
+    days: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    cpu_usage: Option<f64>,
+}
+
+#[derive(Clone)]
+pub struct Window {
+    days: [bool; 7], // Monday first
+    from: u32,       // Minutes after midnight
+    to: u32,         // Up to MINUTES_PER_DAY; before `from` when running past midnight
+    pub cpu_usage: Option<f64>,
+    label: String,
+}
+
+impl Window {
+    fn contains(&self, now: NaiveDateTime) -> bool {
+        let day = now.weekday().num_days_from_monday() as usize;
+        let minute = now.hour() * 60 + now.minute();
+        if self.from < self.to {
+            self.days[day] && (self.from..self.to).contains(&minute)
+        } else {
+            (self.days[day] && minute >= self.from) || (self.days[(day + 6) % 7] && minute < self.to)
+        }
+    }
+
+    /// Label as written in the config file, e.g. `mon-fri 22:00-07:00 at 100% CPU`
+    pub fn describe(&self) -> &str {
+        &self.label
+    }
+}
+
+#[derive(Clone)]
+pub struct Schedule {
+    windows: Vec<Window>,
+}
+
+impl Schedule {
+    /// The window `now` falls in
+    pub fn window_at(&self, now: NaiveDateTime) -> Option<&Window> {
+        self.windows.iter().find(|w| w.contains(now))
+    }
+
+    pub fn describe(&self) -> String {
+        self.windows.iter().map(Window::describe).collect::<Vec<_>>().join("; ")
+    }
+}
+
+/// Parse the `schedule` config value
+pub fn parse(value: &serde_json::Value) -> Result<Schedule, String> {
+    let entries: Vec<WindowConfig> = serde_json::from_value(value.clone()).map_err(|e| format!("invalid schedule: {}", e))?;
+    if entries.is_empty() {
+        return Err("schedule is empty (leave it out to mine around the clock)".to_string());
+    }
+    let mut windows = Vec::new();
+    for (index, entry) in entries.into_iter().enumerate() {
+        let context = |e: String| format!("schedule window #{}: {}", index + 1, e);
+        let days = match &entry.days {
+            Some(days) => parse_days(days).map_err(context)?,
+            None => [true; 7],
+        };
+        let from = entry.from.as_deref().map_or(Ok(0), parse_time).map_err(context)?;
+        let to = entry.to.as_deref().map_or(Ok(MINUTES_PER_DAY), parse_time).map_err(context)?;
+        if from == MINUTES_PER_DAY {
+            return Err(context("from can't be 24:00".to_string()));
+        }
+        if from == to {
+            return Err(context("from and to are the same time".to_string()));
+        }
+        if entry.cpu_usage.is_some_and(|c| !(c > 0.0 && c <= 100.0)) {
+            return Err(context("cpu_usage must be between 1 and 100".to_string()));
+        }
+
+        let mut label = entry.days.clone().unwrap_or_else(|| "every day".to_string());
+        match (&entry.from, &entry.to) {
+            (None, None) => label.push_str(" all day"),
+            _ => label.push_str(&format!(" {}-{}", format_time(from), format_time(to))),
+        }
+        if let Some(cpu_usage) = entry.cpu_usage {
+            label.push_str(&format!(" at {}% CPU", cpu_usage));
+        }
+        windows.push(Window { days, from, to: if to == 0 { MINUTES_PER_DAY } else { to }, cpu_usage: entry.cpu_usage, label });
+    }
+    Ok(Schedule { windows })
+}
+
+/// `mon-fri`, `sat,sun`, `fri-mon`, `*`
+fn parse_days(spec: &str) -> Result<[bool; 7], String> {
+    let day = |name: &str| {
+        let name = name.trim().to_lowercase();
+        DAY_NAMES
+            .iter()
+            .position(|d| name.len() >= 3 && d.starts_with(&name))
+            .ok_or_else(|| format!("unknown day '{}' (use mon, tue, ..., sun)", name))
+    };
+    if spec.trim() == "*" {
+        return Ok([true; 7]);
+    }
+    let mut days = [false; 7];
+    for part in spec.split(',') {
+        match part.split_once('-') {
+            Some((first, last)) => {
+                let (first, last) = (day(first)?, day(last)?);
+                let mut d = first;
+                loop {
+                    days[d] = true;
+                    if d == last {
+                        break;
+                    }
+                    d = (d + 1) % 7;
+                }
+            }
+            None => days[day(part)?] = true,
+        }
+    }
+    Ok(days)
+}
+
+/// `HH:MM` as minutes after midnight (`24:00` allowed as the end of the day)
+fn parse_time(time: &str) -> Result<u32, String> {
+    let invalid = || format!("invalid time '{}' (use HH:MM)", time);
+    let (hours, minutes) = time.trim().split_once(':').ok_or_else(invalid)?;
+    let (hours, minutes): (u32, u32) = (hours.parse().map_err(|_| invalid())?, minutes.parse().map_err(|_| invalid())?);
+    if minutes >= 60 || hours > 24 || (hours == 24 && minutes > 0) {
+        return Err(invalid());
+    }
+    Ok(hours * 60 + minutes)
+}
+
+fn format_time(minutes: u32) -> String {
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+/// Put `schedule` in force for the rest of the run
+pub fn enable(schedule: Schedule) {
+    let _ = SCHEDULE.set(schedule);
+}
+
+/// The window mining is in right now (None without a schedule, too)
+pub fn current_window() -> Option<&'static Window> {
+    SCHEDULE.get()?.window_at(Local::now().naive_local())
+}
+
+/// Whether the schedule wants mining paused now
+pub fn outside_windows() -> bool {
+    SCHEDULE.get().is_some() && current_window().is_none()
+}
@@ -0,0 +1,339 @@
+//! Windows service mode (`--install-service`, `--run-as-service`)
+//!
+//! A miner in a console window dies when the user logs off. `--install-service` registers the
+//! miner with the service control manager instead: it starts at boot, runs as the virtual account
+//! `NT SERVICE\ScavengerMiner` with no console, and writes its log to `logs/` as usual. The service
+//! command line is the install command line plus `--run-as-service`, the working directory at
+//! install time (`--service-dir`) and the data directory resolved then, so relative paths and the
+//! per-user data folder keep working under another account; install grants the account read
+//! access to the former and write access to the latter. Stop and shutdown end the miner like
+//! Ctrl-C does; pause and continue act like the other pause controls. `--uninstall-service` stops
+//! and removes it.
+//!
+//! Any user can read a service's command line (`sc qc`), so install refuses secrets given as
+//! options; they go in the config file instead.
+//!
+//! The service control manager is driven through the windows-service crate.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(windows)]
+const SERVICE_NAME: &str = "ScavengerMiner";
+pub const RUN_SWITCH: &str = "--run-as-service";
+pub const INSTALL_SWITCH: &str = "--install-service";
+pub const UNINSTALL_SWITCH: &str = "--uninstall-service";
+
+/// Set while running under the service control manager
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+/// Set by the Pause control, cleared by Continue
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// Running as a Windows service
+#[cfg(windows)]
+pub fn active() -> bool {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+/// Paused from the service manager
+pub fn paused() -> bool {
+    PAUSED.load(Ordering::Relaxed)
+}
+
+/// Install or remove the service if asked to, returning the exit code
+pub fn handle_install_args(args: &[String]) -> Option<i32> {
+    let result = if args.iter().any(|a| a == INSTALL_SWITCH) {
+        imp::install(args)
+    } else if args.iter().any(|a| a == UNINSTALL_SWITCH) {
+        imp::uninstall()
+    } else {
+        return None;
+    };
+    Some(match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            1
+        }
+    })
+}
+
+/// Hand the process to the service control manager, which runs `miner` until it is stopped
+pub fn run(miner: fn()) -> i32 {
+    ACTIVE.store(true, Ordering::Relaxed);
+    match imp::run(miner) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            1
+        }
+    }
+}
+
+#[cfg(not(windows))]
+mod imp {
+    const UNSUPPORTED: &str = "Service mode is Windows-only; on Linux run the miner under the init system (e.g. a systemd unit)";
+
+    pub fn install(_args: &[String]) -> Result<(), String> {
+        Err(UNSUPPORTED.to_string())
+    }
+
+    pub fn uninstall() -> Result<(), String> {
+        Err(UNSUPPORTED.to_string())
+    }
+
+    pub fn run(_miner: fn()) -> Result<(), String> {
+        Err(UNSUPPORTED.to_string())
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use super::{INSTALL_SWITCH, PAUSED, RUN_SWITCH, SERVICE_NAME};
+    use crate::{paths, request_shutdown};
+    use std::ffi::{OsStr, OsString};
+    use std::fs;
+    use std::path::Path;
+    use std::process::Command;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::{Mutex, OnceLock};
+    use std::time::Duration;
+    use windows_service::service::{
+        ServiceAccess, ServiceAction, ServiceActionType, ServiceControl, ServiceControlAccept, ServiceErrorControl, ServiceExitCode,
+        ServiceFailureActions, ServiceFailureResetPeriod, ServiceInfo, ServiceStartType, ServiceState, ServiceStatus, ServiceType,
+    };
+    use windows_service::service_control_handler::{self, ServiceControlHandlerResult, ServiceStatusHandle};
+    use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    use windows_service::{define_windows_service, service_dispatcher};
+
+    const DISPLAY_NAME: &str = "Scavenger Miner";
+    const DESCRIPTION: &str = "Mines Scavenger Mine challenges for the configured wallets (Free Scavenger Miner)";
+    /// Virtual account the service runs as: its own identity, no password, no administrator rights
+    const SERVICE_ACCOUNT: &str = r"NT SERVICE\ScavengerMiner";
+    /// Options holding credentials, which must not end up in the readable service command line
+    const SECRET_OPTIONS: &[&str] =
+        &["--api-key", "--api-token", "--proxy-password", "--telegram-token", "--control-token", "--grpc-token", "--token"];
+
+    const ERROR_ACCESS_DENIED: i32 = 5;
+    const ERROR_SERVICE_DOES_NOT_EXIST: i32 = 1060;
+    const ERROR_SERVICE_NOT_ACTIVE: i32 = 1062;
+    const ERROR_FAILED_SERVICE_CONTROLLER_CONNECT: i32 = 1063;
+    const ERROR_SERVICE_EXISTS: i32 = 1073;
+
+    /// How long stopping may take: the current step finishes and found solutions are submitted
+    const STOP_WAIT_HINT: Duration = Duration::from_secs(60);
+    /// Restart delay after a crash
+    const RESTART_DELAY: Duration = Duration::from_secs(60);
+
+    /// The miner to run once the service manager calls in
+    static MINER: OnceLock<fn()> = OnceLock::new();
+    static STATUS_HANDLE: OnceLock<ServiceStatusHandle> = OnceLock::new();
+    static STATE: Mutex<ServiceState> = Mutex::new(ServiceState::Stopped);
+    static CHECKPOINT: AtomicU32 = AtomicU32::new(0);
+
+    /// The Windows error code behind a failed service call
+    fn error_code(e: &windows_service::Error) -> Option<i32> {
+        match e {
+            windows_service::Error::Winapi(e) => e.raw_os_error(),
+            _ => None,
+        }
+    }
+
+    /// The crate only says "IO error in winapi call"; show the Windows message instead
+    fn describe(e: &windows_service::Error) -> String {
+        match e {
+            windows_service::Error::Winapi(e) => e.to_string(),
+            e => e.to_string(),
+        }
+    }
+
+    fn open_manager(access: ServiceManagerAccess) -> Result<ServiceManager, String> {
+        ServiceManager::local_computer(None::<&str>, access).map_err(|e| match error_code(&e) {
+            Some(ERROR_ACCESS_DENIED) => "Managing services needs an elevated prompt (Run as administrator)".to_string(),
+            _ => format!("Cannot open the service manager: {}", describe(&e)),
+        })
+    }
+
+    pub fn install(args: &[String]) -> Result<(), String> {
+        let exe = std::env::current_exe().map_err(|e| format!("Cannot locate the miner executable: {}", e))?;
+        let cwd = std::env::current_dir().map_err(|e| format!("Cannot read the working directory: {}", e))?;
+        let miner_args: Vec<String> = args[1..].iter().filter(|a| *a != INSTALL_SWITCH).cloned().collect();
+        if let Some(option) = SECRET_OPTIONS.iter().find(|o| miner_args.iter().any(|a| a == *o || a.starts_with(&format!("{}=", o)))) {
+            return Err(format!(
+                "{} would be stored in the service command line, which any user can read with `sc qc`; put it in config.json as \"{}\" instead",
+                option,
+                option.trim_start_matches("--").replace('-', "_")
+            ));
+        }
+
+        // Paths as this user sees them now; the service account has another %APPDATA%
+        let cli = crate::load_cli_args(&miner_args);
+        let data_dir = cwd.join(paths::data_dir());
+        let mut launch_arguments: Vec<OsString> = miner_args.iter().map(OsString::from).collect();
+        launch_arguments.extend([RUN_SWITCH.into(), "--service-dir".into(), cwd.clone().into_os_string()]);
+        if cli.option("--data-dir").is_none() {
+            launch_arguments.extend(["--data-dir".into(), data_dir.clone().into_os_string()]);
+        }
+
+        let manager = open_manager(ServiceManagerAccess::CONNECT | ServiceManagerAccess::CREATE_SERVICE)?;
+        let info = ServiceInfo {
+            name: SERVICE_NAME.into(),
+            display_name: DISPLAY_NAME.into(),
+            service_type: ServiceType::OWN_PROCESS,
+            start_type: ServiceStartType::AutoStart,
+            error_control: ServiceErrorControl::Normal,
+            executable_path: exe,
+            launch_arguments,
+            dependencies: Vec::new(),
+            account_name: Some(SERVICE_ACCOUNT.into()),
+            account_password: None,
+        };
+        let service = manager.create_service(&info, ServiceAccess::ALL_ACCESS).map_err(|e| match error_code(&e) {
+            Some(ERROR_SERVICE_EXISTS) => format!("Service {} is already installed (remove it first with {})", SERVICE_NAME, super::UNINSTALL_SWITCH),
+            _ => format!("Cannot create service {}: {}", SERVICE_NAME, describe(&e)),
+        })?;
+
+        // Description for services.msc, and a restart one minute after a crash (twice a day at most)
+        let _ = service.set_description(DESCRIPTION);
+        let restart = ServiceAction { action_type: ServiceActionType::Restart, delay: RESTART_DELAY };
+        let _ = service.update_failure_actions(ServiceFailureActions {
+            reset_period: ServiceFailureResetPeriod::After(Duration::from_secs(24 * 3600)),
+            reboot_msg: None,
+            command: None,
+            actions: Some(vec![restart.clone(), restart, ServiceAction { action_type: ServiceActionType::None, delay: Duration::ZERO }]),
+        });
+
+        // The account exists once the service does: wallets and config to read, the data directory to write
+        let _ = fs::create_dir_all(&data_dir);
+        for (dir, rights) in [(cwd.as_path(), "(OI)(CI)RX"), (data_dir.as_path(), "(OI)(CI)M")] {
+            if let Err(e) = grant_access(dir, rights) {
+                println!("⚠️  Could not give {} access to {}: {}", SERVICE_ACCOUNT, dir.display(), e);
+                println!("   Run: icacls \"{}\" /grant \"{}:{}\"", dir.display(), SERVICE_ACCOUNT, rights);
+            }
+        }
+
+        println!("✅ Installed service {} (starts at boot, runs as {})", SERVICE_NAME, SERVICE_ACCOUNT);
+        if let Ok(config) = service.query_config() {
+            println!("   Command line: {}", config.executable_path.display());
+        }
+        println!("   Log:          {}", cwd.join(paths::logs_dir()).join("mining.log").display());
+        match service.start::<&OsStr>(&[]) {
+            Ok(()) => println!("▶️  Service started; stop it with `sc stop {}`, pause it with `sc pause {}`", SERVICE_NAME, SERVICE_NAME),
+            Err(e) => println!("⚠️  Installed but not started: {} (start it with `sc start {}`)", describe(&e), SERVICE_NAME),
+        }
+        Ok(())
+    }
+
+    /// Add an inheritable `rights` entry for the service account to `dir`
+    fn grant_access(dir: &Path, rights: &str) -> Result<(), String> {
+        let output = Command::new("icacls")
+            .arg(dir)
+            .arg("/grant")
+            .arg(format!("{}:{}", SERVICE_ACCOUNT, rights))
+            .arg("/Q")
+            .output()
+            .map_err(|e| e.to_string())?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        }
+    }
+
+    pub fn uninstall() -> Result<(), String> {
+        let manager = open_manager(ServiceManagerAccess::CONNECT)?;
+        let service = manager
+            .open_service(SERVICE_NAME, ServiceAccess::STOP | ServiceAccess::QUERY_STATUS | ServiceAccess::DELETE)
+            .map_err(|e| match error_code(&e) {
+                Some(ERROR_SERVICE_DOES_NOT_EXIST) => format!("Service {} is not installed", SERVICE_NAME),
+                _ => format!("Cannot open service {}: {}", SERVICE_NAME, describe(&e)),
+            })?;
+
+        match service.stop() {
+            Ok(_) => println!("🛑 Stop requested; the miner finishes its current step before exiting"),
+            Err(e) if error_code(&e) == Some(ERROR_SERVICE_NOT_ACTIVE) => {}
+            Err(e) => println!("⚠️  Could not stop the service: {}", describe(&e)),
+        }
+        service.delete().map_err(|e| format!("Cannot remove service {}: {}", SERVICE_NAME, describe(&e)))?;
+        println!("✅ Removed service {} (gone once it has stopped)", SERVICE_NAME);
+        Ok(())
+    }
+
+    fn report(state: ServiceState, exit_code: ServiceExitCode) {
+        let Some(handle) = STATUS_HANDLE.get() else {
+            return;
+        };
+        *STATE.lock().unwrap() = state;
+        let pending = state == ServiceState::StopPending;
+        let _ = handle.set_service_status(ServiceStatus {
+            service_type: ServiceType::OWN_PROCESS,
+            current_state: state,
+            controls_accepted: if pending || state == ServiceState::Stopped {
+                ServiceControlAccept::empty()
+            } else {
+                ServiceControlAccept::STOP | ServiceControlAccept::PAUSE_CONTINUE | ServiceControlAccept::SHUTDOWN
+            },
+            exit_code,
+            checkpoint: if pending { CHECKPOINT.fetch_add(1, Ordering::SeqCst) + 1 } else { 0 },
+            wait_hint: if pending { STOP_WAIT_HINT } else { Duration::ZERO },
+            process_id: None,
+        });
+    }
+
+    // Runs on the dispatcher thread; only flips flags the miner polls
+    fn control_handler(control: ServiceControl) -> ServiceControlHandlerResult {
+        match control {
+            ServiceControl::Stop | ServiceControl::Shutdown => {
+                request_shutdown();
+                report(ServiceState::StopPending, ServiceExitCode::NO_ERROR);
+            }
+            ServiceControl::Pause => {
+                PAUSED.store(true, Ordering::SeqCst);
+                report(ServiceState::Paused, ServiceExitCode::NO_ERROR);
+            }
+            ServiceControl::Continue => {
+                PAUSED.store(false, Ordering::SeqCst);
+                report(ServiceState::Running, ServiceExitCode::NO_ERROR);
+            }
+            ServiceControl::Interrogate => {
+                let state = *STATE.lock().unwrap();
+                report(state, ServiceExitCode::NO_ERROR);
+            }
+            _ => return ServiceControlHandlerResult::NotImplemented,
+        }
+        ServiceControlHandlerResult::NoError
+    }
+
+    define_windows_service!(ffi_service_main, service_main);
+
+    fn service_main(_arguments: Vec<OsString>) {
+        let Ok(handle) = service_control_handler::register(SERVICE_NAME, control_handler) else {
+            return;
+        };
+        let _ = STATUS_HANDLE.set(handle);
+        report(ServiceState::Running, ServiceExitCode::NO_ERROR);
+
+        let exit_code = match MINER.get().map(|miner| std::panic::catch_unwind(*miner)) {
+            Some(Ok(())) => ServiceExitCode::NO_ERROR,
+            _ => ServiceExitCode::Win32(1),
+        };
+        report(ServiceState::Stopped, exit_code);
+    }
+
+    pub fn run(miner: fn()) -> Result<(), String> {
+        // Services start in System32; relative paths are relative to where it was installed
+        let args: Vec<String> = std::env::args().collect();
+        if let Some(dir) = args.iter().position(|a| a == "--service-dir").and_then(|i| args.get(i + 1)) {
+            std::env::set_current_dir(dir).map_err(|e| format!("Cannot change to {}: {}", dir, e))?;
+        }
+        let _ = MINER.set(miner);
+
+        // Blocks until the service has stopped
+        service_dispatcher::start(SERVICE_NAME, ffi_service_main).map_err(|e| match error_code(&e) {
+            Some(ERROR_FAILED_SERVICE_CONTROLLER_CONNECT) => format!(
+                "{} is used by the Windows service manager; install the service with {} and start it with `sc start {}`",
+                RUN_SWITCH, INSTALL_SWITCH, SERVICE_NAME
+            ),
+            _ => format!("Cannot start the service dispatcher: {}", describe(&e)),
+        })
+    }
+}
@@ -0,0 +1,149 @@
+//! Miner totals across restarts, kept in `session_stats.json`
+//!
+//! The statistics block after every task covers the running session only, so a restart used to
+//! reset the solution count and runtime. Solutions, hashes and uptime are also added to running
+//! totals and to per-day counts (UTC date), written back on every update, and shown under the
+//! session's own figures.
+
+use crate::difficulty::format_count;
+use crate::{format_duration_secs, get_timestamp, paths};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Instant;
+
+pub const SESSION_STATS_FILE: &str = "session_stats.json";
+
+/// Days listed in the summary
+const SUMMARY_DAYS: usize = 7;
+
+/// Solutions, hashes and uptime over some period
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Totals {
+    pub solutions: u64,
+    pub hashes: u64,
+    pub uptime_secs: f64,
+}
+
+impl Totals {
+    /// Uptime per solution
+    pub fn average_solution_secs(&self) -> Option<f64> {
+        (self.solutions > 0).then(|| self.uptime_secs / self.solutions as f64)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionStats {
+    pub sessions: u64,
+    pub first_started_at: Option<String>,
+    pub total: Totals,
+    pub days: BTreeMap<String, Totals>, // YYYY-MM-DD (UTC)
+}
+
+/// Stats as last written and when uptime was last added to them; set by `start`
+static STATE: Mutex<Option<(SessionStats, Instant)>> = Mutex::new(None);
+
+fn path() -> PathBuf {
+    paths::data_dir().join(SESSION_STATS_FILE)
+}
+
+/// Read the stats file (empty if there is none yet or it can't be read)
+pub fn load() -> SessionStats {
+    let path = path();
+    let Ok(content) = fs::read_to_string(&path) else {
+        return SessionStats::default();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "⚠️  Ignoring unreadable session statistics {}: {}", path.display(), e);
+        SessionStats::default()
+    })
+}
+
+/// Count a new session; updates before this are ignored
+pub fn start() {
+    let mut stats = load();
+    stats.sessions += 1;
+    stats.first_started_at.get_or_insert_with(get_timestamp);
+    save(&stats);
+    *STATE.lock().unwrap() = Some((stats, Instant::now()));
+}
+
+/// Add the uptime since the last update, apply `change` to the totals and today's counts, and
+/// write the file back
+fn update(change: impl Fn(&mut Totals)) {
+    let mut guard = STATE.lock().unwrap();
+    let Some((stats, since)) = guard.as_mut() else {
+        return;
+    };
+    let uptime = since.elapsed().as_secs_f64();
+    *since = Instant::now();
+    let today = crate::now_utc().format("%Y-%m-%d").to_string();
+    for totals in [&mut stats.total, stats.days.entry(today).or_default()] {
+        totals.uptime_secs += uptime;
+        change(totals);
+    }
+    save(stats);
+}
+
+fn save(stats: &SessionStats) {
+    let path = path();
+    let tmp = path.with_extension("json.tmp");
+    let result = serde_json::to_string_pretty(stats)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(&tmp, json).and_then(|_| fs::rename(&tmp, &path)).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        tracing::warn!(error = %e, "⚠️  Failed to save session statistics {}: {}", path.display(), e);
+    }
+}
+
+/// Add the hashes of a finished task
+pub fn record_hashes(hashes: u64) {
+    update(|totals| totals.hashes += hashes);
+}
+
+/// Add solutions (receipts, or solutions saved offline)
+pub fn record_solutions(count: u64) {
+    if count > 0 {
+        update(|totals| totals.solutions += count);
+    }
+}
+
+/// Add the uptime since the last update
+pub fn flush() {
+    update(|_| {});
+}
+
+/// Print the totals over all sessions and the last few days
+pub fn print_summary() {
+    let Some(stats) = STATE.lock().unwrap().as_ref().map(|(stats, _)| stats.clone()) else {
+        return;
+    };
+    let total = stats.total;
+    println!(
+        "   All sessions: {} solution(s), {} hashes in {} over {} session(s){}",
+        total.solutions,
+        format_count(total.hashes as f64),
+        format_duration_secs(total.uptime_secs),
+        stats.sessions,
+        stats.first_started_at.map_or(String::new(), |at| format!(" since {}", at))
+    );
+    if let Some(average) = total.average_solution_secs() {
+        println!("   Average time per solution (all sessions): {}", format_duration_secs(average));
+    }
+    if !stats.days.is_empty() {
+        println!("   Per day (UTC):");
+    }
+    for (day, totals) in stats.days.iter().rev().take(SUMMARY_DAYS) {
+        println!(
+            "     {}  {:>4} solution(s)  {:>8} hashes  {:>8} uptime",
+            day,
+            totals.solutions,
+            format_count(totals.hashes as f64),
+            format_duration_secs(totals.uptime_secs)
+        );
+    }
+}
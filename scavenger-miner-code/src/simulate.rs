@@ -0,0 +1,304 @@
+//! `simulate`: replay recorded challenges through the selection strategies
+//!
+//! The miner adds every challenge it sees to `challenge_history.json` in the data folder.
+//! `simulate` steps through that history in simulated time at a given hashrate: wallets take
+//! turns as in the mining loop, each picks a challenge with the strategy under test among the
+//! ones issued by then with more than an hour left, and the hashes it needs are drawn from the
+//! geometric distribution of the challenge's difficulty (see difficulty.rs). A task gives up at
+//! the hash or time limit, or a minute before the deadline, like the miner does. Each run starts
+//! every strategy from the same seed, and the result is averaged over several runs.
+//!
+//! Other miners aren't modelled, so what spreading gains by colliding less doesn't show here.
+//! Challenges without `issued_at` are taken to have been issued a day before their deadline.
+
+use crate::difficulty::DifficultyModel;
+use crate::solve_costs::SolveCosts;
+use crate::spread::Spreader;
+use crate::{
+    load_challenges_file, load_user_wallets, paths, wallets, Challenge, CliArgs, CHALLENGE_HISTORY_FILE,
+    DEADLINE_ABORT_MARGIN_SECS,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+
+/// Challenges within this long of their deadline are no longer picked (see `Challenge::is_active`)
+const ACTIVE_BUFFER_SECS: f64 = 3600.0;
+
+/// Assumed lifetime of a challenge whose record has no `issued_at`
+const DEFAULT_LIFETIME_SECS: f64 = 86_400.0;
+
+const DEFAULT_RUNS: u32 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Strategy {
+    Easiest,  // Difficulty order alone (`--min-solve-probability 0`)
+    Ranked,   // Chance of solving in time first, skipping unlikely ones (the default)
+    Spread,   // `--selection spread`
+    Deadline, // Closest deadline first, for comparison
+}
+
+const STRATEGIES: &[(&str, Strategy)] = &[
+    ("easiest", Strategy::Easiest),
+    ("ranked", Strategy::Ranked),
+    ("spread", Strategy::Spread),
+    ("deadline", Strategy::Deadline),
+];
+
+/// A recorded challenge with its times as seconds since the epoch
+struct SimChallenge {
+    challenge: Challenge,
+    model: DifficultyModel,
+    issued: f64,
+    deadline: f64,
+}
+
+/// Task settings shared by all strategies
+struct Settings {
+    hashrate: f64,
+    wallets: usize,
+    num_threads: usize,
+    max_hashes: Option<f64>,
+    max_hashes_factor: Option<f64>,
+    max_secs: Option<f64>,
+    min_probability: f64,
+}
+
+#[derive(Default, Clone, Copy)]
+struct Outcome {
+    solutions: u64,
+    abandoned: u64,
+    hashes: f64,
+    wasted_hashes: f64, // Spent on tasks that gave up
+}
+
+fn timestamp(value: &str) -> Option<f64> {
+    chrono::DateTime::parse_from_rfc3339(value).ok().map(|t| t.timestamp_millis() as f64 / 1000.0)
+}
+
+/// Hashes the next task on `challenge` may spend starting at `now`, and whether that's a limit
+/// of the task (marks it as difficult) rather than the deadline
+fn task_budget(challenge: &SimChallenge, now: f64, settings: &Settings, costs: &SolveCosts) -> (f64, bool) {
+    let until_deadline = (challenge.deadline - DEADLINE_ABORT_MARGIN_SECS as f64 - now).max(0.0) * settings.hashrate;
+    let mut limit = f64::INFINITY;
+    if let Some(secs) = settings.max_secs {
+        limit = limit.min(secs * settings.hashrate);
+    }
+    match settings.max_hashes_factor {
+        Some(factor) => limit = limit.min(costs.hash_limit(&challenge.model, factor).0 as f64),
+        None => {
+            if let Some(max_hashes) = settings.max_hashes {
+                limit = limit.min(max_hashes);
+            }
+        }
+    }
+    if limit < until_deadline {
+        (limit, true)
+    } else {
+        (until_deadline, false)
+    }
+}
+
+/// Chance that the task solves the challenge within its budget
+fn solve_probability(challenge: &SimChallenge, now: f64, settings: &Settings, costs: &SolveCosts) -> f64 {
+    challenge.model.solve_probability(task_budget(challenge, now, settings, costs).0)
+}
+
+/// Hashes to a solution: geometric with the challenge's per-hash chance
+fn draw_hashes(model: &DifficultyModel, rng: &mut StdRng) -> f64 {
+    let u: f64 = 1.0 - rng.gen::<f64>(); // (0, 1]
+    (u.ln() / (-model.hash_probability()).ln_1p()).ceil().max(1.0)
+}
+
+/// One pass through the history with one strategy
+fn run_once(
+    history: &[SimChallenge],
+    strategy: Strategy,
+    settings: &Settings,
+    costs: &SolveCosts,
+    seed: u64,
+) -> Outcome {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut spreader = Spreader::default();
+    let mut done: HashSet<(usize, usize)> = HashSet::new(); // (wallet, challenge) solved or marked difficult
+    let mut outcome = Outcome::default();
+
+    let start = history.iter().map(|c| c.issued).fold(f64::INFINITY, f64::min);
+    let end = history.iter().map(|c| c.deadline).fold(f64::NEG_INFINITY, f64::max);
+    let mut now = start;
+    let mut wallet = 0;
+    let mut idle_wallets = 0;
+
+    while now < end {
+        // Active challenges in the miner's order
+        let mut active: Vec<usize> = (0..history.len())
+            .filter(|&i| history[i].issued <= now && now + ACTIVE_BUFFER_SECS < history[i].deadline)
+            .collect();
+        active.sort_by(|&a, &b| history[a].challenge.compare_for_selection(&history[b].challenge, settings.num_threads));
+
+        let candidates: Vec<usize> = active
+            .iter()
+            .copied()
+            .filter(|&i| !done.contains(&(wallet, i)))
+            .filter(|&i| strategy != Strategy::Ranked || solve_probability(&history[i], now, settings, costs) >= settings.min_probability)
+            .collect();
+
+        let picked = match strategy {
+            Strategy::Easiest => candidates.first().copied(),
+            Strategy::Ranked => {
+                // Whole percent, stable so ties keep the difficulty order
+                let mut ranked = candidates.clone();
+                ranked.sort_by_key(|&i| std::cmp::Reverse((solve_probability(&history[i], now, settings, costs) * 100.0).floor() as u32));
+                ranked.first().copied()
+            }
+            Strategy::Spread => {
+                let challenges: Vec<Challenge> = active.iter().map(|&i| history[i].challenge.clone()).collect();
+                let refs: Vec<&Challenge> = candidates.iter().map(|&i| &history[i].challenge).collect();
+                spreader
+                    .pick(&refs, &challenges)
+                    .and_then(|c| candidates.iter().copied().find(|&i| history[i].challenge.challenge_id == c.challenge_id))
+            }
+            Strategy::Deadline => candidates.iter().copied().min_by(|&a, &b| history[a].deadline.total_cmp(&history[b].deadline)),
+        };
+        let current_wallet = wallet;
+        wallet = (wallet + 1) % settings.wallets;
+
+        let Some(index) = picked else {
+            // Nothing left for any wallet: jump to the next challenge that comes out
+            idle_wallets += 1;
+            if idle_wallets >= settings.wallets {
+                idle_wallets = 0;
+                match history.iter().map(|c| c.issued).filter(|&t| t > now).min_by(f64::total_cmp) {
+                    Some(next) => now = next,
+                    None => break,
+                }
+            }
+            continue;
+        };
+        idle_wallets = 0;
+
+        let challenge = &history[index];
+        let (budget, limited) = task_budget(challenge, now, settings, costs);
+        let needed = draw_hashes(&challenge.model, &mut rng);
+        if needed <= budget {
+            now += needed / settings.hashrate;
+            outcome.solutions += 1;
+            outcome.hashes += needed;
+            done.insert((current_wallet, index));
+        } else {
+            now += budget / settings.hashrate;
+            outcome.abandoned += 1;
+            outcome.hashes += budget;
+            outcome.wasted_hashes += budget;
+            if limited {
+                done.insert((current_wallet, index));
+            }
+        }
+    }
+    outcome
+}
+
+/// Usage: simulate [<history file>] --hashrate <H/s> [--wallets <file> | --wallet-count <n>] [--threads <n>]
+///        [--strategies <list>] [--runs <n>] [--seed <n>] [--max-hashes <millions> | --max-hashes-factor <x>]
+///        [--max-minutes-per-task <minutes>] [--min-solve-probability <percent>]
+pub fn run_simulate(args: &[String]) -> bool {
+    let cli = CliArgs::parse(args);
+    let Some(hashrate) = cli.option("--hashrate").and_then(|s| s.parse::<f64>().ok()).filter(|r| *r > 0.0) else {
+        println!("Usage: scavenger-miner simulate [<history file>] --hashrate <H/s> [--wallet-count <n>] [--strategies easiest,ranked,spread,deadline]");
+        return false;
+    };
+
+    let history_path = cli.positional.first().cloned()
+        .unwrap_or_else(|| paths::data_dir().join(CHALLENGE_HISTORY_FILE).to_string_lossy().into_owned());
+    let recorded = match load_challenges_file(&history_path) {
+        Ok(challenges) => challenges,
+        Err(e) => {
+            println!("❌ {} (the miner records one as it runs)", e);
+            return false;
+        }
+    };
+    let history: Vec<SimChallenge> = recorded
+        .into_iter()
+        .filter_map(|challenge| {
+            let model = challenge.difficulty_model()?;
+            let deadline = timestamp(&challenge.latest_submission)?;
+            let issued = challenge.issued_at.as_deref().and_then(timestamp).unwrap_or(deadline - DEFAULT_LIFETIME_SECS);
+            Some(SimChallenge { challenge, model, issued, deadline })
+        })
+        .collect();
+    if history.is_empty() {
+        println!("❌ No usable challenges in {}", history_path);
+        return false;
+    }
+
+    let wallets = match cli.option("--wallet-count").and_then(|s| s.parse::<usize>().ok()) {
+        Some(count) => count.max(1),
+        None => load_user_wallets(cli.option("--wallets").unwrap_or(wallets::default_source())).map_or(1, |w| w.len().max(1)),
+    };
+    let strategies: Vec<(&str, Strategy)> = match cli.option("--strategies") {
+        Some(list) => {
+            let mut picked = Vec::new();
+            for name in list.split(',').map(|s| s.trim().to_ascii_lowercase()) {
+                match STRATEGIES.iter().find(|(n, _)| *n == name) {
+                    Some(&strategy) => picked.push(strategy),
+                    None => {
+                        let known: Vec<&str> = STRATEGIES.iter().map(|(n, _)| *n).collect();
+                        println!("❌ Unknown strategy '{}' (available: {})", name, known.join(", "));
+                        return false;
+                    }
+                }
+            }
+            picked
+        }
+        None => STRATEGIES.to_vec(),
+    };
+    let runs = cli.option("--runs").and_then(|s| s.parse::<u32>().ok()).unwrap_or(DEFAULT_RUNS).max(1);
+    let seed = cli.option("--seed").and_then(|s| s.parse::<u64>().ok()).unwrap_or(0);
+    let settings = Settings {
+        hashrate,
+        wallets,
+        num_threads: cli.option("--threads").and_then(|s| s.parse::<usize>().ok()).unwrap_or(1),
+        max_hashes: cli.option("--max-hashes").and_then(|s| s.parse::<f64>().ok()).map(|m| m * 1_000_000.0),
+        max_hashes_factor: cli.option("--max-hashes-factor")
+            .and_then(|s| s.trim_end_matches(['x', '×']).parse::<f64>().ok())
+            .filter(|f| *f > 0.0),
+        max_secs: cli.option("--max-minutes-per-task").and_then(|s| s.parse::<f64>().ok()).filter(|m| *m > 0.0).map(|m| m * 60.0),
+        min_probability: cli.option("--min-solve-probability")
+            .and_then(|s| s.trim_end_matches('%').parse::<f64>().ok())
+            .unwrap_or(crate::ranking::DEFAULT_MIN_SOLVE_PERCENT)
+            .clamp(0.0, 100.0) / 100.0,
+    };
+    let costs = SolveCosts::load();
+
+    let start = history.iter().map(|c| c.issued).fold(f64::INFINITY, f64::min);
+    let end = history.iter().map(|c| c.deadline).fold(f64::NEG_INFINITY, f64::max);
+    let days = ((end - start) / 86_400.0).max(1.0 / 24.0);
+    println!(
+        "🧪 Replaying {} challenge(s) over {:.1} day(s) from {}: {} wallet(s) at {:.0} H/s, {} run(s)",
+        history.len(), days, history_path, wallets, hashrate, runs
+    );
+    println!("   ℹ️  Other miners aren't simulated, so fewer collisions from spreading don't show\n");
+    println!("   {:<9} {:>13} {:>10} {:>10} {:>8}", "strategy", "solutions/day", "solved", "abandoned", "wasted");
+
+    let pairs = (history.len() * wallets) as f64;
+    for (name, strategy) in strategies {
+        let mut total = Outcome::default();
+        for run in 0..runs {
+            let outcome = run_once(&history, strategy, &settings, &costs, seed.wrapping_add(run as u64));
+            total.solutions += outcome.solutions;
+            total.abandoned += outcome.abandoned;
+            total.hashes += outcome.hashes;
+            total.wasted_hashes += outcome.wasted_hashes;
+        }
+        let solutions = total.solutions as f64 / runs as f64;
+        println!(
+            "   {:<9} {:>13.1} {:>9.1}% {:>10.1} {:>7.1}%",
+            name,
+            solutions / days,
+            100.0 * solutions / pairs,
+            total.abandoned as f64 / runs as f64,
+            if total.hashes > 0.0 { 100.0 * total.wasted_hashes / total.hashes } else { 0.0 }
+        );
+    }
+    true
+}
@@ -0,0 +1,96 @@
+//! Solve costs learned from this machine's own mining history
+//!
+//! Every finished task adds its hashes to the bucket of its challenge's constrained bits (see
+//! difficulty.rs), and every solution counts one. Hashes to a solution are geometric, so the
+//! hashes of all tasks (solved or abandoned) over the number of solutions estimates the mean
+//! cost without the bias of only counting the tasks that succeeded. With `--max-hashes-factor`
+//! each challenge's "too hard" threshold is that many times this mean, or the model's `2^k`
+//! while its bucket has too few solutions.
+
+use crate::difficulty::{format_count, DifficultyModel};
+use crate::paths;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+pub const SOLVE_COSTS_FILE: &str = "solve_costs.json";
+
+/// Solutions a bucket needs before its own mean replaces the model's
+const MIN_SOLUTIONS: u64 = 5;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+struct Bucket {
+    tasks: u64,
+    solutions: u64,
+    hashes: u64,
+}
+
+/// Hashes spent and solutions found, per number of constrained bits
+#[derive(Default, Serialize, Deserialize)]
+pub struct SolveCosts {
+    buckets: BTreeMap<u32, Bucket>,
+}
+
+fn path() -> PathBuf {
+    paths::data_dir().join(SOLVE_COSTS_FILE)
+}
+
+impl SolveCosts {
+    /// Load the history (empty if there is none yet or it can't be read)
+    pub fn load() -> Self {
+        let path = path();
+        let Ok(content) = fs::read_to_string(&path) else {
+            return SolveCosts::default();
+        };
+        serde_json::from_str(&content).unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "⚠️  Ignoring unreadable solve history {}: {}", path.display(), e);
+            SolveCosts::default()
+        })
+    }
+
+    /// Number of solutions recorded so far
+    pub fn solutions(&self) -> u64 {
+        self.buckets.values().map(|b| b.solutions).sum()
+    }
+
+    /// Add a finished task and write the history back
+    pub fn record(&mut self, model: &DifficultyModel, hashes: u64, solved: bool) {
+        if hashes == 0 {
+            return;
+        }
+        let bucket = self.buckets.entry(model.constrained_bits).or_default();
+        bucket.tasks += 1;
+        bucket.hashes += hashes;
+        if solved {
+            bucket.solutions += 1;
+        }
+
+        let path = path();
+        let tmp = path.with_extension("json.tmp");
+        let result = serde_json::to_string_pretty(self)
+            .map_err(|e| e.to_string())
+            .and_then(|json| fs::write(&tmp, json).and_then(|_| fs::rename(&tmp, &path)).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            tracing::warn!(error = %e, "⚠️  Failed to save solve history {}: {}", path.display(), e);
+        }
+    }
+
+    /// Mean hashes to a solution at this difficulty, and where the number comes from
+    pub fn expected_hashes(&self, model: &DifficultyModel) -> (f64, String) {
+        match self.buckets.get(&model.constrained_bits) {
+            Some(bucket) if bucket.solutions >= MIN_SOLUTIONS => (
+                bucket.hashes as f64 / bucket.solutions as f64,
+                format!("learned from {} solution(s) at {} bits", bucket.solutions, model.constrained_bits),
+            ),
+            _ => (model.expected_hashes(), format!("2^{} expected", model.constrained_bits)),
+        }
+    }
+
+    /// Hash threshold for a challenge: `factor` times its mean cost
+    pub fn hash_limit(&self, model: &DifficultyModel, factor: f64) -> (u64, String) {
+        let (expected, source) = self.expected_hashes(model);
+        let limit = (expected * factor).min(u64::MAX as f64).max(1.0) as u64;
+        (limit, format!("{}× {} ({})", factor, format_count(expected), source))
+    }
+}
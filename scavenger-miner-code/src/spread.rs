@@ -0,0 +1,66 @@
+//! Spreading consecutive wallets across the active challenges (`--selection spread`)
+//!
+//! Easiest-first sends every wallet to the same challenge, so all of them race the other miners
+//! on it. In spread mode each wallet instead gets the challenge picked by a smooth weighted
+//! round-robin over the ones it can still mine: every pick adds each candidate's weight to its
+//! credit, takes the candidate with the most credit and charges it the total. A challenge's
+//! weight is the inverse of its expected hashes, so each gets about the same share of mining
+//! time and easy ones still get the most wallets.
+
+use crate::Challenge;
+use std::collections::HashMap;
+
+/// How the miner picks a challenge for the next wallet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionMode {
+    Easiest, // First unsolved challenge in ranking order (default)
+    Spread,  // Weighted round-robin across the unsolved challenges
+}
+
+impl SelectionMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "easiest" => Some(SelectionMode::Easiest),
+            "spread" => Some(SelectionMode::Spread),
+            _ => None,
+        }
+    }
+}
+
+/// Round-robin state carried from one wallet to the next
+#[derive(Default)]
+pub struct Spreader {
+    credits: HashMap<String, f64>,
+}
+
+impl Spreader {
+    /// Pick among `candidates` (in ranking order, which breaks ties)
+    /// `active` is the full list, so challenges that are gone lose their credit
+    pub fn pick(&mut self, candidates: &[&Challenge], active: &[Challenge]) -> Option<Challenge> {
+        self.credits.retain(|id, _| active.iter().any(|c| &c.challenge_id == id));
+
+        // Relative to the easiest candidate, so weights stay within (0, 1]
+        let easiest = candidates.iter().map(|c| c.expected_hashes()).fold(f64::INFINITY, f64::min);
+        if !easiest.is_finite() {
+            return candidates.first().map(|c| (*c).clone());
+        }
+        let weights: Vec<f64> = candidates.iter().map(|c| easiest / c.expected_hashes()).collect();
+        let total: f64 = weights.iter().sum();
+
+        let mut best: Option<(usize, f64)> = None;
+        for (index, (challenge, weight)) in candidates.iter().zip(&weights).enumerate() {
+            let credit = self.credits.entry(challenge.challenge_id.clone()).or_insert(0.0);
+            *credit += weight;
+            if best.is_none_or(|(_, top)| *credit > top) {
+                best = Some((index, *credit));
+            }
+        }
+
+        let (index, _) = best?;
+        let chosen = candidates[index];
+        if let Some(credit) = self.credits.get_mut(&chosen.challenge_id) {
+            *credit -= total;
+        }
+        Some(chosen.clone())
+    }
+}
@@ -0,0 +1,162 @@
+//! SQLite solution store (`--store sqlite`, only with the `sqlite` feature)
+//!
+//! Goes through `rusqlite`, linking the system SQLite library (`libsqlite3`), or one compiled
+//! in with the `sqlite-bundled` feature. Each record is kept whole as JSON next to the columns
+//! it is looked up by, so the record format stays in one place.
+
+use crate::error::MinerError;
+use crate::store::{JsonStore, SolutionStore, DEFAULT_DB_FILE, PENDING_STATUSES};
+use crate::{paths, CliArgs, SolutionRecord};
+use chrono::{DateTime, Utc};
+use rusqlite::{params_from_iter, Connection};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS solutions (
+        wallet_address TEXT NOT NULL,
+        challenge_id   TEXT NOT NULL,
+        status         TEXT NOT NULL,
+        has_receipt    INTEGER NOT NULL,
+        record         TEXT NOT NULL,
+        PRIMARY KEY (wallet_address, challenge_id)
+    );
+    CREATE INDEX IF NOT EXISTS solutions_by_status ON solutions (status, has_receipt);
+    CREATE INDEX IF NOT EXISTS solutions_by_challenge ON solutions (challenge_id);
+";
+
+/// Open connection; the Mutex shares it between the miner's threads
+pub struct SqliteStore {
+    db: Mutex<Connection>,
+    path: String,
+}
+
+fn sqlite_error(e: rusqlite::Error) -> MinerError {
+    MinerError::Protocol(format!("SQLite: {}", e))
+}
+
+impl SqliteStore {
+    /// Open (or create) the database and make sure the schema exists
+    pub fn open(path: &Path) -> Result<Self, MinerError> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let db = Connection::open(path).map_err(sqlite_error)?;
+        // Another miner process (or reconcile) may hold a write lock for a moment
+        db.busy_timeout(Duration::from_secs(5)).map_err(sqlite_error)?;
+        db.execute_batch(SCHEMA).map_err(sqlite_error)?;
+        Ok(SqliteStore { db: Mutex::new(db), path: path.to_string_lossy().into_owned() })
+    }
+
+    /// Run a statement with text parameters, returning the first column of every row
+    fn query(&self, sql: &str, params: &[&str]) -> Result<Vec<String>, MinerError> {
+        let db = self.db.lock().unwrap();
+        let mut statement = db.prepare_cached(sql).map_err(sqlite_error)?;
+        let rows = statement.query_map(params_from_iter(params), |row| row.get(0)).map_err(sqlite_error)?;
+        rows.collect::<Result<_, _>>().map_err(sqlite_error)
+    }
+
+    /// Records from a query selecting the `record` column
+    fn records(&self, sql: &str, params: &[&str]) -> Vec<SolutionRecord> {
+        match self.query(sql, params) {
+            Ok(rows) => rows.iter().filter_map(|row| serde_json::from_str(row).ok()).collect(),
+            Err(e) => {
+                tracing::warn!(error = %e, "⚠️  Failed to read solutions from {}: {}", self.path, e);
+                Vec::new()
+            }
+        }
+    }
+}
+
+impl SolutionStore for SqliteStore {
+    fn save(&self, record: &SolutionRecord) -> Result<String, MinerError> {
+        let json = serde_json::to_string(record)?;
+        let db = self.db.lock().unwrap();
+        db.prepare_cached(
+            "INSERT INTO solutions (wallet_address, challenge_id, status, has_receipt, record)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT (wallet_address, challenge_id) DO UPDATE SET
+                 status = excluded.status, has_receipt = excluded.has_receipt, record = excluded.record",
+        )
+        .and_then(|mut statement| {
+            statement.execute(rusqlite::params![
+                record.wallet_address,
+                record.challenge_id,
+                record.status,
+                record.crypto_receipt.is_some(),
+                json
+            ])
+        })
+        .map_err(sqlite_error)?;
+        Ok(self.path.clone())
+    }
+
+    fn exists(&self, wallet_address: &str, challenge_id: &str) -> bool {
+        let db = self.db.lock().unwrap();
+        db.prepare_cached("SELECT 1 FROM solutions WHERE wallet_address = ?1 AND challenge_id = ?2")
+            .and_then(|mut statement| statement.exists([wallet_address, challenge_id]))
+            .unwrap_or(false)
+    }
+
+    fn pending(&self) -> Vec<SolutionRecord> {
+        let placeholders = (1..=PENDING_STATUSES.len()).map(|i| format!("?{}", i)).collect::<Vec<_>>().join(", ");
+        let sql = format!(
+            "SELECT record FROM solutions WHERE has_receipt = 0 AND (status IN ({}) OR status LIKE 'error:%')",
+            placeholders
+        );
+        self.records(&sql, PENDING_STATUSES)
+    }
+
+    fn all(&self) -> Vec<SolutionRecord> {
+        self.records("SELECT record FROM solutions ORDER BY wallet_address, challenge_id", &[])
+    }
+
+    fn archive(&self, _cutoff: DateTime<Utc>) -> Result<usize, MinerError> {
+        // Lookups are indexed, so old rows stay where they are and nothing is ever moved
+        Ok(0)
+    }
+}
+
+/// Usage: migrate-store [--store-db <file>]
+/// Copies every JSON record in `solutions/` into the database; safe to run again, the
+/// JSON files are left in place
+pub fn run_migrate_store(args: &[String]) -> bool {
+    let cli = CliArgs::parse(args);
+    let path = match cli.option("--store-db").map(paths::expand) {
+        None => paths::data_dir().join(DEFAULT_DB_FILE),
+        Some(Ok(path)) => path,
+        Some(Err(e)) => {
+            println!("❌ Invalid --store-db: {}", e);
+            return false;
+        }
+    };
+
+    let store = match SqliteStore::open(&path) {
+        Ok(store) => store,
+        Err(e) => {
+            println!("❌ Cannot open {}: {}", path.display(), e);
+            return false;
+        }
+    };
+
+    let solutions_dir = paths::solutions_dir();
+    let records = JsonStore::default().all();
+    println!("📦 Migrating {} record(s) from {}/ to {}", records.len(), solutions_dir.display(), path.display());
+    let mut failed = 0;
+    for record in &records {
+        if let Err(e) = store.save(record) {
+            println!("   ❌ {} / {}: {}", record.wallet_address, record.challenge_id, e);
+            failed += 1;
+        }
+    }
+
+    if failed > 0 {
+        println!("❌ {} record(s) could not be migrated", failed);
+        return false;
+    }
+    println!("✅ Migrated {} record(s); start the miner with --store sqlite to use them", records.len());
+    println!("   The JSON files were left in {}/ and can be archived once you're happy", solutions_dir.display());
+    true
+}
@@ -0,0 +1,253 @@
+//! `export-state` / `import-state`: move a rig's history to another machine
+//!
+//! The archive is a single JSON file mapping relative paths to file contents, so it
+//! can be inspected or edited by hand. Solutions, logs, difficult tasks and the solve history
+//! are stored under their default names (`solutions/...`, `logs/...`, `difficult_tasks.json`,
+//! `solve_costs.json`, `attempts.jsonl`) and land wherever the importing machine keeps them (see paths.rs). ROMs are only ever held in memory, so there
+//! is no ROM cache to carry over; the new machine rebuilds them on first use.
+
+use crate::store::{JsonStore, ARCHIVE_DIR, CLAIMS_DIR, INDEX_FILE, INVALID_DIR, LOCK_FILE};
+use crate::{paths, CliArgs};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Marker identifying a state archive
+const STATE_FORMAT: &str = "scavenger-miner-state";
+const STATE_VERSION: u32 = 1;
+
+/// Names of the relocatable items inside an archive
+const SOLUTIONS_NAME: &str = "solutions";
+const LOGS_NAME: &str = "logs";
+const DIFFICULT_TASKS_NAME: &str = "difficult_tasks.json";
+
+/// Files kept directly in the data folder
+const DATA_FILES: &[&str] = &[
+    crate::solve_costs::SOLVE_COSTS_FILE,
+    crate::wallet_stats::WALLET_STATS_FILE,
+    crate::session_stats::SESSION_STATS_FILE,
+    crate::attempts::ATTEMPTS_FILE,
+    crate::hashrate_history::HASHRATE_HISTORY_FILE,
+    crate::pool::POOL_SHARES_FILE,
+];
+
+#[derive(Serialize, Deserialize)]
+struct StateArchive {
+    format: String,
+    version: u32,
+    created_at: String,
+    hostname: String,
+    files: BTreeMap<String, String>,
+}
+
+/// Add a text file to the archive under `name`, if it exists
+fn add_file(files: &mut BTreeMap<String, String>, name: String, path: &Path) {
+    match fs::read_to_string(path) {
+        Ok(content) => {
+            files.insert(name, content);
+        }
+        Err(e) if path.exists() => println!("⚠️  Skipping {}: {}", path.display(), e),
+        Err(_) => {}
+    }
+}
+
+/// Add a file under the path it was given as
+fn add_local_file(files: &mut BTreeMap<String, String>, path: &str) {
+    add_file(files, path.replace('\\', "/"), Path::new(path));
+}
+
+/// Add every regular file in a directory (not recursive) under `prefix/`
+fn add_dir(files: &mut BTreeMap<String, String>, prefix: &str, dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_file()))
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        // Rotated logs are gzipped; the archive only holds text, so they stay behind
+        .filter(|name| !name.ends_with(".gz"))
+        .collect();
+    names.sort();
+    for name in names {
+        add_file(files, format!("{}/{}", prefix, name), &dir.join(&name));
+    }
+}
+
+/// Add the dated folders in a solutions folder (`solutions/` itself or its archive)
+fn add_dated_dirs(files: &mut BTreeMap<String, String>, prefix: &str, dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut dates: Vec<String> = entries
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .filter(|name| name != ARCHIVE_DIR && name != INVALID_DIR)
+        .collect();
+    dates.sort();
+    for date in dates {
+        add_dir(files, &format!("{}/{}", prefix, date), &dir.join(&date));
+    }
+}
+
+/// Where an archive entry goes on this machine
+fn target_path(name: &str) -> PathBuf {
+    if let Some(rest) = name.strip_prefix(&format!("{}/", SOLUTIONS_NAME)) {
+        paths::solutions_dir().join(rest)
+    } else if let Some(rest) = name.strip_prefix(&format!("{}/", LOGS_NAME)) {
+        paths::logs_dir().join(rest)
+    } else if name == DIFFICULT_TASKS_NAME {
+        paths::difficult_tasks_file()
+    } else if DATA_FILES.contains(&name) {
+        paths::data_dir().join(name)
+    } else {
+        PathBuf::from(name)
+    }
+}
+
+/// Usage: export-state [--output <file>] [--wallets <file>] [--include-logs]
+pub fn run_export_state(args: &[String]) -> bool {
+    let cli = CliArgs::parse(args);
+    let wallets_file = cli.option("--wallets").unwrap_or("wallets.txt");
+    let output = cli.option("--output").map(|s| s.to_string()).unwrap_or_else(|| {
+        format!("scavenger-state-{}.json", chrono::Utc::now().format("%Y%m%d-%H%M%S"))
+    });
+
+    let mut files = BTreeMap::new();
+    add_local_file(&mut files, wallets_file);
+    add_file(&mut files, DIFFICULT_TASKS_NAME.to_string(), &paths::difficult_tasks_file());
+    for name in DATA_FILES {
+        add_file(&mut files, name.to_string(), &paths::data_dir().join(name));
+    }
+    add_local_file(&mut files, crate::retry_policy::RETRY_POLICY_FILE);
+    add_local_file(&mut files, crate::challenge_filter::CHALLENGE_FILTER_FILE);
+    add_local_file(&mut files, crate::quota::WALLET_QUOTA_FILE);
+    add_local_file(&mut files, crate::DEFAULT_CONFIG_FILE);
+    if !crate::store::is_json() {
+        // Records from the SQLite or S3 store go in as JSON files, importable anywhere
+        for record in crate::store::get().all() {
+            if let Ok(json) = serde_json::to_string_pretty(&record) {
+                let name = JsonStore::file_name(&record.wallet_address, &record.challenge_id);
+                files.insert(format!("{}/{}", SOLUTIONS_NAME, name), json);
+            }
+        }
+    } else {
+        let solutions_dir = paths::solutions_dir();
+        add_dir(&mut files, SOLUTIONS_NAME, &solutions_dir);
+        add_dated_dirs(&mut files, SOLUTIONS_NAME, &solutions_dir);
+        add_dated_dirs(&mut files, &format!("{}/{}", SOLUTIONS_NAME, ARCHIVE_DIR), &solutions_dir.join(ARCHIVE_DIR));
+        // The index is rebuilt from the records on the other side
+        files.remove(&format!("{}/{}", SOLUTIONS_NAME, INDEX_FILE));
+        files.remove(&format!("{}/{}", SOLUTIONS_NAME, LOCK_FILE));
+        files.remove(&format!("{}/{}", SOLUTIONS_NAME, crate::instance::LOCK_FILE));
+        files.retain(|name, _| !name.starts_with(&format!("{}/{}/", SOLUTIONS_NAME, CLAIMS_DIR)));
+    }
+    if cli.switch("--include-logs") {
+        add_dir(&mut files, LOGS_NAME, &paths::logs_dir());
+    }
+
+    let solutions = files.keys().filter(|p| p.starts_with(&format!("{}/", SOLUTIONS_NAME))).count();
+    let archive = StateArchive {
+        format: STATE_FORMAT.to_string(),
+        version: STATE_VERSION,
+        created_at: crate::get_timestamp(),
+        hostname: hostname::get()
+            .ok()
+            .and_then(|h| h.into_string().ok())
+            .unwrap_or_else(|| "unknown".to_string()),
+        files,
+    };
+
+    let json = match serde_json::to_string_pretty(&archive) {
+        Ok(json) => json,
+        Err(e) => {
+            println!("❌ Failed to serialize state: {}", e);
+            return false;
+        }
+    };
+    if let Err(e) = fs::write(&output, json) {
+        println!("❌ Failed to write {}: {}", output, e);
+        return false;
+    }
+
+    println!("📦 Exported {} file(s) ({} solution record(s)) to {}", archive.files.len(), solutions, output);
+    true
+}
+
+/// Only allow plain relative paths so an archive can't write outside the miner folder
+fn is_safe_relative_path(path: &str) -> bool {
+    let path = Path::new(path);
+    !path.as_os_str().is_empty() && path.components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+/// Usage: import-state <file> [--force]
+/// Existing files with different content are kept unless `--force` is given
+pub fn run_import_state(args: &[String]) -> bool {
+    let cli = CliArgs::parse(args);
+    let Some(input) = cli.positional.first() else {
+        println!("Usage: scavenger-miner import-state <file> [--force]");
+        return false;
+    };
+    let force = cli.switch("--force");
+
+    let archive: StateArchive = match fs::read_to_string(input)
+        .map_err(|e| e.to_string())
+        .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()))
+    {
+        Ok(archive) => archive,
+        Err(e) => {
+            println!("❌ Could not read state archive {}: {}", input, e);
+            return false;
+        }
+    };
+    if archive.format != STATE_FORMAT || archive.version > STATE_VERSION {
+        println!("❌ {} is not a supported state archive ({} v{})", input, archive.format, archive.version);
+        return false;
+    }
+
+    println!("📦 Importing state exported from {} at {}", archive.hostname, archive.created_at);
+
+    let (mut written, mut unchanged, mut kept) = (0, 0, 0);
+    for (path, content) in &archive.files {
+        if !is_safe_relative_path(path) {
+            println!("⚠️  Skipping unsafe path in archive: {}", path);
+            continue;
+        }
+
+        let target = target_path(path);
+        let target = target.as_path();
+        if let Ok(existing) = fs::read_to_string(target) {
+            if existing == *content {
+                unchanged += 1;
+                continue;
+            }
+            if !force {
+                println!("   ⏭️  Keeping existing {} (use --force to overwrite)", path);
+                kept += 1;
+                continue;
+            }
+        }
+
+        if let Some(parent) = target.parent().filter(|p| !p.as_os_str().is_empty()) {
+            if let Err(e) = fs::create_dir_all(parent) {
+                println!("❌ Failed to create {}: {}", parent.display(), e);
+                return false;
+            }
+        }
+        if let Err(e) = fs::write(target, content) {
+            println!("❌ Failed to write {}: {}", path, e);
+            return false;
+        }
+        written += 1;
+    }
+
+    // New records bypassed the solutions index, so have it rebuilt on next start
+    if written > 0 {
+        let _ = fs::remove_file(paths::solutions_dir().join(INDEX_FILE));
+    }
+
+    println!("✅ Imported: {} written, {} already up to date, {} kept", written, unchanged, kept);
+    true
+}
@@ -0,0 +1,228 @@
+//! `status`: summary of the local state
+//!
+//! Reads the solution store, `difficult_tasks.json`, `wallet_stats.json` and `logs/mining.log`
+//! and prints what would otherwise take grepping JSON by hand: solutions per wallet, mining
+//! totals per wallet, counts by status, submissions waiting to be retried and how many wallets
+//! have solved each challenge.
+//! Nothing is sent to the API.
+
+use crate::{get_failed_solutions, load_difficult_tasks, load_user_wallets, paths, store, wallet_stats, wallets, CliArgs, SolutionRecord};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+
+/// How much of the end of the log is read for the last entries
+const LOG_TAIL_BYTES: u64 = 1024 * 1024;
+/// Warnings and errors from the log shown at the end
+const RECENT_PROBLEMS: usize = 5;
+
+/// Column a record is counted in
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Outcome {
+    Submitted, // Has a crypto receipt
+    Duplicate,
+    Failed, // Waiting to be retried
+    Abandoned,
+    Other,
+}
+
+impl Outcome {
+    fn of(record: &SolutionRecord) -> Self {
+        if record.crypto_receipt.is_some() {
+            return Outcome::Submitted;
+        }
+        match record.status.as_str() {
+            "duplicate" => Outcome::Duplicate,
+            "abandoned" | "challenge_closed" | "invalid_nonce" => Outcome::Abandoned,
+            status if store::is_pending_status(status) => Outcome::Failed,
+            _ => Outcome::Other,
+        }
+    }
+}
+
+/// Record counts per outcome
+#[derive(Default)]
+struct Counts([usize; 5]);
+
+impl Counts {
+    fn add(&mut self, outcome: Outcome) {
+        self.0[outcome as usize] += 1;
+    }
+
+    fn get(&self, outcome: Outcome) -> usize {
+        self.0[outcome as usize]
+    }
+
+    fn total(&self) -> usize {
+        self.0.iter().sum()
+    }
+}
+
+/// The last `LOG_TAIL_BYTES` of the log, starting at a full line
+fn log_tail(path: &std::path::Path) -> Option<(u64, String)> {
+    let mut file = fs::File::open(path).ok()?;
+    let size = file.metadata().ok()?.len();
+    let start = size.saturating_sub(LOG_TAIL_BYTES);
+    file.seek(SeekFrom::Start(start)).ok()?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).ok()?;
+    let text = String::from_utf8_lossy(&bytes).into_owned();
+    let text = match (start > 0, text.find('\n')) {
+        (true, Some(newline)) => text[newline + 1..].to_string(),
+        _ => text,
+    };
+    Some((size, text))
+}
+
+/// Usage: status [--wallets <file>]
+pub fn run_status(args: &[String]) -> bool {
+    let cli = CliArgs::parse(args);
+    let wallets_file = cli.option("--wallets").unwrap_or(wallets::default_source());
+
+    let records = store::get().all();
+    let difficult_tasks = load_difficult_tasks();
+
+    // Wallets from the wallets file first, then any that only appear in the records
+    let mut wallets: BTreeMap<String, Counts> = BTreeMap::new();
+    if let Ok(addresses) = load_user_wallets(wallets_file) {
+        for address in addresses {
+            wallets.entry(address).or_default();
+        }
+    }
+
+    let mut by_status: BTreeMap<String, usize> = BTreeMap::new();
+    let mut solved: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new(); // Challenge -> wallets with a receipt
+    let mut attempted: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new(); // Challenge -> wallets with any record
+    for record in &records {
+        let outcome = Outcome::of(record);
+        wallets.entry(record.wallet_address.clone()).or_default().add(outcome);
+
+        // "error: network", "error: timeout", ... are one line
+        let status = if record.status.starts_with("error:") { "error" } else { record.status.as_str() };
+        *by_status.entry(status.to_string()).or_default() += 1;
+
+        attempted.entry(&record.challenge_id).or_default().insert(&record.wallet_address);
+        if outcome == Outcome::Submitted {
+            solved.entry(&record.challenge_id).or_default().insert(&record.wallet_address);
+        }
+    }
+    let mut skipped: BTreeMap<&str, usize> = BTreeMap::new();
+    for task in &difficult_tasks {
+        *skipped.entry(&task.challenge_id).or_default() += 1;
+    }
+
+    println!("📊 Local state");
+    println!("   Solutions:       {} ({} record(s))", store::description(), records.len());
+    println!("   Difficult tasks: {} ({} skipped)", paths::difficult_tasks_file().display(), difficult_tasks.len());
+    println!("   Logs:            {}/", paths::logs_dir().display());
+
+    if wallets.is_empty() {
+        println!("\nNo wallets in {} and no solution records yet", wallets_file);
+        return true;
+    }
+
+    println!("\n👛 Solutions per wallet");
+    println!(
+        "   {:<24}  {:>9}  {:>9}  {:>6}  {:>9}  {:>5}  {:>5}",
+        "wallet", "submitted", "duplicate", "failed", "abandoned", "other", "total"
+    );
+    let mut totals = Counts::default();
+    for (wallet, counts) in &wallets {
+        println!(
+            "   {:<24}  {:>9}  {:>9}  {:>6}  {:>9}  {:>5}  {:>5}",
+            wallets::label(wallet),
+            counts.get(Outcome::Submitted),
+            counts.get(Outcome::Duplicate),
+            counts.get(Outcome::Failed),
+            counts.get(Outcome::Abandoned),
+            counts.get(Outcome::Other),
+            counts.total()
+        );
+        for (i, n) in counts.0.iter().enumerate() {
+            totals.0[i] += n;
+        }
+    }
+    if wallets.len() > 1 {
+        println!(
+            "   {:<24}  {:>9}  {:>9}  {:>6}  {:>9}  {:>5}  {:>5}",
+            "all wallets",
+            totals.get(Outcome::Submitted),
+            totals.get(Outcome::Duplicate),
+            totals.get(Outcome::Failed),
+            totals.get(Outcome::Abandoned),
+            totals.get(Outcome::Other),
+            totals.total()
+        );
+    }
+
+    // Hashes and solve times only the miner knows, from every session so far
+    let stats = wallet_stats::load();
+    if !stats.is_empty() {
+        println!("\n⛏️  Mining per wallet ({})", paths::data_dir().join(wallet_stats::WALLET_STATS_FILE).display());
+        wallet_stats::print_table(
+            wallets
+                .keys()
+                .chain(stats.keys().filter(|w| !wallets.contains_key(*w)))
+                .map(|w| (w.as_str(), stats.get(w).copied().unwrap_or_default())),
+        );
+    }
+
+    if !by_status.is_empty() {
+        println!("\n🏷️  Records by status");
+        for (status, count) in &by_status {
+            println!("   {:<18} {:>6}", status, count);
+        }
+    }
+
+    let pending = get_failed_solutions();
+    println!("\n🔁 Pending retries: {}", pending.len());
+    for record in &pending {
+        let next = record.next_retry_at.as_deref().or(record.last_retry_at.as_deref()).unwrap_or("next loop");
+        println!(
+            "   {}  {:<14}  {:<16}  {} retr{}  ({})",
+            wallets::label(&record.wallet_address),
+            record.challenge_id,
+            record.status,
+            record.retry_count,
+            if record.retry_count == 1 { "y" } else { "ies" },
+            if record.next_retry_at.is_some() { format!("not before {}", next) } else { format!("last tried {}", next) }
+        );
+    }
+
+    let challenges: BTreeSet<&str> = attempted.keys().chain(skipped.keys()).copied().collect();
+    if !challenges.is_empty() {
+        println!("\n🎯 Challenge coverage ({} wallet(s))", wallets.len());
+        println!("   {:<16}  {:>7}  {:>8}  {:>7}", "challenge", "solved", "no proof", "skipped");
+        for challenge in &challenges {
+            let solved = solved.get(challenge).map_or(0, |w| w.len());
+            let attempted = attempted.get(challenge).map_or(0, |w| w.len());
+            println!(
+                "   {:<16}  {:>3}/{:<3}  {:>8}  {:>7}",
+                challenge,
+                solved,
+                wallets.len(),
+                attempted - solved,
+                skipped.get(challenge).copied().unwrap_or(0)
+            );
+        }
+    }
+
+    let log_path = paths::logs_dir().join("mining.log");
+    match log_tail(&log_path) {
+        Some((size, tail)) => {
+            println!("\n📋 {} ({:.1} MB)", log_path.display(), size as f64 / (1024.0 * 1024.0));
+            if let Some(last) = tail.lines().rev().find(|l| !l.trim().is_empty()) {
+                println!("   Last entry: {}", last);
+            }
+            let problems: Vec<&str> = tail.lines().filter(|l| l.contains('❌') || l.contains("⚠️")).collect();
+            if !problems.is_empty() {
+                println!("   Recent warnings and errors:");
+                for line in &problems[problems.len().saturating_sub(RECENT_PROBLEMS)..] {
+                    println!("   {}", line);
+                }
+            }
+        }
+        None => println!("\n📋 No log at {}", log_path.display()),
+    }
+    true
+}
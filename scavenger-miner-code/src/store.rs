@@ -0,0 +1,626 @@
+//! Where solution records are kept
+//!
+//! By default every record is a JSON file in `solutions/`. Builds with the `sqlite` feature
+//! can keep them in a SQLite database instead (`--store sqlite`), with indexed lookups by
+//! wallet, challenge and status; `migrate-store` copies existing JSON records into it.
+//!
+//! Several miners can share their records, so a pair solved by one rig is skipped by all of
+//! them and each pending record is resubmitted by one rig only: either point `--solutions-dir`
+//! at a network folder (SMB/NFS) and pass `--shared-solutions`, or keep the records in an
+//! S3-compatible bucket (`--store s3`, see s3_store.rs).
+
+use crate::error::MinerError;
+use crate::{paths, CliArgs, SolutionRecord};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Statuses of records still waiting for a successful (re)submission
+pub const PENDING_STATUSES: &[&str] = &["rejected", "failed", "queued", "offline"];
+
+/// Whether a record without a receipt should be picked up by the retry logic
+pub fn is_pending_status(status: &str) -> bool {
+    PENDING_STATUSES.contains(&status) || status.starts_with("error:")
+}
+
+/// Folder in `solutions/` holding archived records, one subfolder per day found
+pub const ARCHIVE_DIR: &str = "archive";
+
+/// Folder in `solutions/` where record files that don't parse are moved
+pub const INVALID_DIR: &str = "invalid";
+
+/// Folder in a shared `solutions/` holding one lock file per record being resubmitted
+pub const CLAIMS_DIR: &str = "claims";
+
+/// Lock file serializing index writes in a shared `solutions/`
+pub const LOCK_FILE: &str = "index.lock";
+
+/// A retry claim older than this was left behind by a miner that stopped mid-retry
+pub const CLAIM_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// How often a shared store looks for records saved by other miners
+pub const SHARED_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Archive folder name (date found) for a record found before `cutoff`
+/// Records still waiting to be resubmitted are never archived
+pub fn archive_folder(record: &SolutionRecord, cutoff: DateTime<Utc>) -> Option<String> {
+    if record.crypto_receipt.is_none() && is_pending_status(&record.status) {
+        return None;
+    }
+    let found_at = DateTime::parse_from_rfc3339(&record.found_at).ok()?.with_timezone(&Utc);
+    (found_at < cutoff).then(|| found_at.format("%Y-%m-%d").to_string())
+}
+
+pub trait SolutionStore: Send + Sync {
+    /// Insert or replace the record for its wallet-challenge pair; returns where it was written
+    fn save(&self, record: &SolutionRecord) -> Result<String, MinerError>;
+
+    /// Whether a record exists for a wallet-challenge pair
+    fn exists(&self, wallet_address: &str, challenge_id: &str) -> bool;
+
+    /// Records without a receipt in a pending status (see `is_pending_status`)
+    fn pending(&self) -> Vec<SolutionRecord>;
+
+    /// Every record, archived ones included
+    fn all(&self) -> Vec<SolutionRecord>;
+
+    /// Move records found before `cutoff` out of the hot path; returns how many were moved
+    fn archive(&self, cutoff: DateTime<Utc>) -> Result<usize, MinerError>;
+
+    /// Take a pending record for resubmission, so miners sharing the store don't resubmit it at
+    /// the same time. Returns the record as currently stored, or `None` if another miner holds it
+    /// or it is no longer pending; `release` once the outcome is saved
+    fn claim(&self, record: &SolutionRecord) -> Option<SolutionRecord> {
+        Some(record.clone())
+    }
+
+    /// Give a claimed record back
+    fn release(&self, _record: &SolutionRecord) {}
+}
+
+/// Which backend to open, from `--store` / `--store-db` / `--shared-solutions`
+#[derive(Debug, Clone)]
+enum StoreKind {
+    Json { shared: bool },
+    #[cfg_attr(not(feature = "sqlite"), allow(dead_code))]
+    Sqlite(Option<PathBuf>), // None: DEFAULT_DB_FILE in the data directory
+    S3(crate::s3_store::S3Settings),
+}
+
+/// Database file used by `--store sqlite` unless `--store-db` says otherwise
+pub const DEFAULT_DB_FILE: &str = "solutions.db";
+
+static KIND: OnceLock<StoreKind> = OnceLock::new();
+static STORE: OnceLock<Box<dyn SolutionStore>> = OnceLock::new();
+
+/// Read the storage settings from the command line / config file
+/// The store itself is opened on first use, in the data directory at that point
+pub fn configure(cli: &CliArgs) -> Result<(), String> {
+    let shared = cli.switch("--shared-solutions");
+    let kind = match cli.option("--store").map(|s| s.trim().to_ascii_lowercase()) {
+        None => StoreKind::Json { shared },
+        Some(name) if name == "json" => StoreKind::Json { shared },
+        Some(name) if name == "s3" => StoreKind::S3(crate::s3_store::S3Settings::from_cli(cli)?),
+        #[cfg(feature = "sqlite")]
+        Some(name) if name == "sqlite" => {
+            let path = cli.option("--store-db").map(paths::expand).transpose();
+            StoreKind::Sqlite(path.map_err(|e| format!("Invalid --store-db: {}", e))?)
+        }
+        #[cfg(not(feature = "sqlite"))]
+        Some(name) if name == "sqlite" => {
+            return Err("This build has no SQLite support (build with `cargo build --release --features sqlite`)".to_string());
+        }
+        Some(name) => return Err(format!("Invalid --store '{}' (expected json, sqlite or s3)", name)),
+    };
+    let _ = KIND.set(kind);
+    Ok(())
+}
+
+/// Database file in use, if records are kept in SQLite
+pub fn database_path() -> Option<PathBuf> {
+    match KIND.get() {
+        Some(StoreKind::Sqlite(path)) => Some(path.clone().unwrap_or_else(|| paths::data_dir().join(DEFAULT_DB_FILE))),
+        _ => None,
+    }
+}
+
+/// Whether records are JSON files in the solutions folder (not a database or bucket)
+pub fn is_json() -> bool {
+    matches!(KIND.get(), None | Some(StoreKind::Json { .. }))
+}
+
+/// Whether other rigs are meant to use the same records (a shared folder or a bucket)
+pub fn is_shared() -> bool {
+    matches!(KIND.get(), Some(StoreKind::Json { shared: true } | StoreKind::S3(_)))
+}
+
+/// Short description for the startup log
+pub fn description() -> String {
+    match (KIND.get(), database_path()) {
+        (Some(StoreKind::S3(settings)), _) => settings.describe(),
+        (_, Some(path)) => format!("SQLite database {}", path.display()),
+        (Some(StoreKind::Json { shared: true }), None) => format!("{}/ (shared)", paths::solutions_dir().display()),
+        _ => format!("{}/", paths::solutions_dir().display()),
+    }
+}
+
+/// The configured store; exits if the database can't be opened, since nothing could be saved
+pub fn get() -> &'static dyn SolutionStore {
+    STORE
+        .get_or_init(|| match (KIND.get().cloned().unwrap_or(StoreKind::Json { shared: false }), database_path()) {
+            (StoreKind::Json { shared: true }, _) => Box::new(JsonStore::shared()),
+            (StoreKind::S3(settings), _) => match crate::s3_store::S3Store::open(settings) {
+                Ok(store) => Box::new(store),
+                Err(e) => {
+                    eprintln!("❌ Cannot use the S3 solution store: {}", e);
+                    std::process::exit(1);
+                }
+            },
+            (StoreKind::Json { .. }, _) | (_, None) => Box::new(JsonStore::default()),
+            #[cfg(feature = "sqlite")]
+            (StoreKind::Sqlite(_), Some(path)) => match crate::sqlite_store::SqliteStore::open(&path) {
+                Ok(store) => Box::new(store),
+                Err(e) => {
+                    eprintln!("❌ Cannot open solution database {}: {}", path.display(), e);
+                    std::process::exit(1);
+                }
+            },
+            #[cfg(not(feature = "sqlite"))]
+            (StoreKind::Sqlite(_), Some(_)) => unreachable!("rejected by configure"),
+        })
+        .as_ref()
+}
+
+/// Index of every JSON record, one line per write (last line for a record wins)
+pub const INDEX_FILE: &str = "index.jsonl";
+
+/// One line of the index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexEntry {
+    wallet_address: String,
+    challenge_id: String,
+    status: String,
+    has_receipt: bool,
+    path: String, // Relative to the solutions folder (`solutions/<file>` in older indexes)
+}
+
+impl IndexEntry {
+    fn for_record(record: &SolutionRecord, path: String) -> Self {
+        IndexEntry {
+            wallet_address: record.wallet_address.clone(),
+            challenge_id: record.challenge_id.clone(),
+            status: record.status.clone(),
+            has_receipt: record.crypto_receipt.is_some(),
+            path,
+        }
+    }
+}
+
+/// One JSON file per record, in `solutions/<date found>/`
+///
+/// Dated folders keep any one folder from growing to tens of thousands of files. Records from
+/// before that (directly in `solutions/`) are still read, and move to their dated folder the
+/// next time they are saved. `solutions/index.jsonl` is appended on every save, so existence
+/// checks and the retry scan don't re-read every file. It is loaded once (rebuilt from the
+/// files if missing), compacted when it has grown well past one line per record, and can be
+/// deleted at any time to force a rebuild. Records are written to a temporary file and renamed into place,
+/// so a crash never leaves half a record behind; files that don't parse anyway (edited by
+/// hand, disk trouble) are moved to `solutions/invalid/` rather than silently skipped. Old
+/// records are moved to `solutions/archive/<date>/` (see archive.rs), out of the index and
+/// the retry scan. The folder itself can be moved with `--solutions-dir` (see paths.rs).
+///
+/// A shared folder (`--shared-solutions`) is written by several miners: index writes take
+/// `index.lock` (an OS file lock, which SMB and NFS pass on to the server), the index is read
+/// again once another miner changed it, and a record being resubmitted is claimed with a file
+/// in `solutions/claims/`.
+#[derive(Default)]
+pub struct JsonStore {
+    index: Mutex<Option<HashMap<String, IndexEntry>>>, // By file name (unique per record), loaded on first use
+    shared: Option<Mutex<SharedIndex>>,
+}
+
+/// What a shared store last saw of the index file
+#[derive(Default)]
+struct SharedIndex {
+    signature: Option<(u64, SystemTime)>, // Size and modification time
+    checked: Option<Instant>,
+}
+
+impl JsonStore {
+    /// Store in a solutions folder shared with other miners
+    pub fn shared() -> Self {
+        JsonStore { index: Mutex::new(None), shared: Some(Mutex::new(SharedIndex::default())) }
+    }
+
+    fn index_signature() -> Option<(u64, SystemTime)> {
+        let metadata = fs::metadata(Self::index_path()).ok()?;
+        Some((metadata.len(), metadata.modified().ok()?))
+    }
+
+    /// Run `f` holding the lock of a shared folder (directly otherwise)
+    fn locked<T>(&self, f: impl FnOnce() -> T) -> T {
+        if self.shared.is_none() {
+            return f();
+        }
+        let lock = fs::create_dir_all(paths::solutions_dir())
+            .and_then(|_| fs::OpenOptions::new().create(true).truncate(false).write(true).open(paths::solutions_dir().join(LOCK_FILE)));
+        match lock {
+            Ok(file) => {
+                if let Err(e) = file.lock() {
+                    tracing::warn!(error = %e, "⚠️  Cannot lock the shared solutions index: {}", e);
+                }
+                let result = f();
+                let _ = file.unlock();
+                result
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "⚠️  Cannot open {}: {}", LOCK_FILE, e);
+                f()
+            }
+        }
+    }
+
+    /// Shared folder: read the index again if another miner changed it (at most every
+    /// `SHARED_REFRESH_INTERVAL`)
+    fn refresh_shared(&self, index: &mut HashMap<String, IndexEntry>) {
+        let Some(shared) = &self.shared else {
+            return;
+        };
+        let mut shared = shared.lock().unwrap();
+        if shared.checked.is_some_and(|at| at.elapsed() < SHARED_REFRESH_INTERVAL) {
+            return;
+        }
+        shared.checked = Some(Instant::now());
+        let signature = Self::index_signature();
+        if signature != shared.signature {
+            *index = self.locked(Self::load_index);
+            shared.signature = signature;
+        }
+    }
+
+    /// The lock file claiming a record for resubmission
+    fn claim_path(record: &SolutionRecord) -> PathBuf {
+        let name = Self::file_name(&record.wallet_address, &record.challenge_id);
+        paths::solutions_dir().join(CLAIMS_DIR).join(format!("{}.lock", name.trim_end_matches(".json")))
+    }
+
+    /// File name of the record for a wallet-challenge pair
+    pub fn file_name(wallet_address: &str, challenge_id: &str) -> String {
+        let clean_challenge_id = challenge_id.replace("*", "").replace("/", "_");
+        format!("{}_{}.json", wallet_address, clean_challenge_id)
+    }
+
+    /// Index key of a record file: its file name
+    fn key(path: &Path) -> String {
+        path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default()
+    }
+
+    /// Every record file in `solutions/` and its dated folders with its record (archive excluded)
+    pub fn records_on_disk() -> Vec<(PathBuf, SolutionRecord)> {
+        Self::record_files()
+            .into_iter()
+            .filter_map(|path| Self::read_record(&path).map(|record| (path, record)))
+            .collect()
+    }
+
+    /// Throw the index away and rebuild it from the record files
+    pub fn rebuild_index(&self) {
+        let _ = fs::remove_file(Self::index_path());
+        *self.index.lock().unwrap() = Some(Self::load_index());
+    }
+
+    /// Path of a record file relative to the solutions folder, with `/` separators
+    fn relative(path: &Path) -> String {
+        let solutions_dir = paths::solutions_dir();
+        let relative = path.strip_prefix(&solutions_dir).unwrap_or(path).to_string_lossy().replace('\\', "/");
+        // Indexes written before `--solutions-dir` existed hold `solutions/<file>`
+        relative.strip_prefix("solutions/").map(|r| r.to_string()).unwrap_or(relative)
+    }
+
+    /// Where a record belongs: the folder for the (UTC) day it was found
+    fn relative_path_for(record: &SolutionRecord) -> String {
+        let day = DateTime::parse_from_rfc3339(&record.found_at)
+            .map(|t| t.with_timezone(&Utc))
+            .unwrap_or_else(|_| crate::now_utc());
+        format!("{}/{}", day.format("%Y-%m-%d"), Self::file_name(&record.wallet_address, &record.challenge_id))
+    }
+
+    fn index_path() -> PathBuf {
+        paths::solutions_dir().join(INDEX_FILE)
+    }
+
+    /// Every record file in `solutions/` and its dated folders
+    fn record_files() -> Vec<PathBuf> {
+        let solutions_dir = paths::solutions_dir();
+        let mut files = Self::json_files(&solutions_dir);
+        if let Ok(entries) = fs::read_dir(&solutions_dir) {
+            for entry in entries.flatten() {
+                let name = entry.file_name();
+                if entry.file_type().is_ok_and(|t| t.is_dir()) && name != ARCHIVE_DIR && name != INVALID_DIR && name != CLAIMS_DIR {
+                    files.extend(Self::json_files(&entry.path()));
+                }
+            }
+        }
+        files
+    }
+
+    /// Every record file in the dated folders of `solutions/archive/`
+    fn archived_files() -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(paths::solutions_dir().join(ARCHIVE_DIR)) else {
+            return Vec::new();
+        };
+        entries
+            .flatten()
+            .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+            .flat_map(|e| Self::json_files(&e.path()))
+            .collect()
+    }
+
+    fn json_files(dir: &Path) -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        entries
+            .flatten()
+            .filter(|e| e.file_type().is_ok_and(|t| t.is_file()))
+            .map(|e| e.path())
+            .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("json"))
+            .collect()
+    }
+
+    /// Read a record file, quarantining it if it isn't a valid record
+    fn read_record(path: &Path) -> Option<SolutionRecord> {
+        let content = fs::read_to_string(path).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(record) => Some(record),
+            Err(e) => {
+                Self::quarantine(path, &e.to_string());
+                None
+            }
+        }
+    }
+
+    /// Move an unparseable record file to `solutions/invalid/`
+    fn quarantine(path: &Path, reason: &str) {
+        let Some(file_name) = path.file_name() else {
+            return;
+        };
+        let dir = paths::solutions_dir().join(INVALID_DIR);
+        match fs::create_dir_all(&dir).and_then(|_| fs::rename(path, dir.join(file_name))) {
+            Ok(()) => tracing::warn!(
+                "⚠️  {} is not a valid solution record ({}); moved to {}/",
+                path.display(), reason, dir.display(),
+            ),
+            Err(e) => tracing::warn!(
+                error = %e,
+                "⚠️  {} is not a valid solution record ({}) and could not be moved: {}",
+                path.display(), reason, e,
+            ),
+        }
+    }
+
+    /// Replace a file's content via a temporary file in the same directory
+    fn write_atomic(path: &Path, content: &[u8]) -> std::io::Result<()> {
+        let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+        tmp_name.push(".tmp");
+        let tmp = path.with_file_name(tmp_name);
+        let result = fs::File::create(&tmp)
+            .and_then(|mut file| file.write_all(content).and_then(|_| file.sync_all()))
+            .and_then(|_| fs::rename(&tmp, path));
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+        result
+    }
+
+    /// Read the index file, or rebuild it from the record files if there is none
+    fn load_index() -> HashMap<String, IndexEntry> {
+        let mut index = HashMap::new();
+        let Ok(content) = fs::read_to_string(Self::index_path()) else {
+            for path in Self::record_files() {
+                if let Some(record) = Self::read_record(&path) {
+                    index.insert(Self::key(&path), IndexEntry::for_record(&record, Self::relative(&path)));
+                }
+            }
+            Self::write_index(&index);
+            return index;
+        };
+
+        let mut lines = 0;
+        for mut entry in content.lines().filter_map(|line| serde_json::from_str::<IndexEntry>(line).ok()) {
+            lines += 1;
+            entry.path = Self::relative(Path::new(&entry.path));
+            index.insert(Self::key(Path::new(&entry.path)), entry);
+        }
+        if lines > 2 * index.len() + 100 {
+            Self::write_index(&index);
+        }
+        index
+    }
+
+    /// Rewrite the index with one line per record
+    fn write_index(index: &HashMap<String, IndexEntry>) {
+        let mut entries: Vec<&IndexEntry> = index.values().collect();
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        let content: String = entries
+            .iter()
+            .filter_map(|entry| serde_json::to_string(entry).ok())
+            .map(|line| line + "\n")
+            .collect();
+
+        let _ = Self::write_atomic(&Self::index_path(), content.as_bytes());
+    }
+
+    /// Drop records from the index and rewrite it (a shared index is read again first, so lines
+    /// other miners appended since are kept)
+    fn remove_from_index(&self, index: &mut HashMap<String, IndexEntry>, filenames: &[String]) {
+        self.locked(|| {
+            if self.shared.is_some() {
+                *index = Self::load_index();
+            }
+            for filename in filenames {
+                index.remove(filename);
+            }
+            Self::write_index(index);
+        });
+    }
+
+    /// Run `f` on the index, loading it first if needed
+    fn with_index<T>(&self, f: impl FnOnce(&mut HashMap<String, IndexEntry>) -> T) -> T {
+        let mut guard = self.index.lock().unwrap();
+        let index = guard.get_or_insert_with(|| self.locked(Self::load_index));
+        self.refresh_shared(index);
+        f(index)
+    }
+}
+
+impl SolutionStore for JsonStore {
+    fn save(&self, record: &SolutionRecord) -> Result<String, MinerError> {
+        let filename = JsonStore::file_name(&record.wallet_address, &record.challenge_id);
+        let relative = Self::relative_path_for(record);
+        let solutions_dir = paths::solutions_dir();
+        let path = solutions_dir.join(&relative);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(record)?;
+        Self::write_atomic(&path, json.as_bytes())?;
+
+        let entry = IndexEntry::for_record(record, relative);
+        self.with_index(|index| self.locked(|| {
+            // A record saved before the dated layout (or on another day's clock) moves here
+            if let Some(old) = index.get(&filename).filter(|old| old.path != entry.path) {
+                let _ = fs::remove_file(solutions_dir.join(&old.path));
+            }
+            let _ = fs::remove_file(solutions_dir.join(&filename));
+            let line = serde_json::to_string(&entry)? + "\n";
+            fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(Self::index_path())?
+                .write_all(line.as_bytes())?;
+            index.insert(filename, entry);
+            Ok::<_, MinerError>(())
+        }))?;
+        Ok(path.display().to_string())
+    }
+
+    fn exists(&self, wallet_address: &str, challenge_id: &str) -> bool {
+        let filename = JsonStore::file_name(wallet_address, challenge_id);
+        self.with_index(|index| index.contains_key(&filename))
+    }
+
+    fn pending(&self) -> Vec<SolutionRecord> {
+        let mut pending: Vec<(String, String)> = self.with_index(|index| {
+            index
+                .iter()
+                .filter(|(_, e)| !e.has_receipt && is_pending_status(&e.status))
+                .map(|(filename, e)| (e.path.clone(), filename.clone()))
+                .collect()
+        });
+        pending.sort();
+
+        // The files stay authoritative: re-check what they actually say
+        let dir = paths::solutions_dir();
+        let mut records = Vec::new();
+        let mut gone = Vec::new();
+        for (relative, filename) in pending {
+            let path = dir.join(&relative);
+            match Self::read_record(&path) {
+                Some(record) => records.push(record),
+                None if !path.exists() => gone.push(filename),
+                None => {}
+            }
+        }
+
+        // Deleted or quarantined: the task counts as unsolved again
+        if !gone.is_empty() {
+            self.with_index(|index| self.remove_from_index(index, &gone));
+        }
+        records.retain(|r| r.crypto_receipt.is_none() && is_pending_status(&r.status));
+        records
+    }
+
+    fn all(&self) -> Vec<SolutionRecord> {
+        Self::record_files()
+            .iter()
+            .chain(Self::archived_files().iter())
+            .filter_map(|path| Self::read_record(path))
+            .collect()
+    }
+
+    fn archive(&self, cutoff: DateTime<Utc>) -> Result<usize, MinerError> {
+        let mut moved = Vec::new();
+        let mut result = Ok(());
+        for path in Self::record_files() {
+            let (Some(record), Some(file_name)) = (Self::read_record(&path), path.file_name()) else {
+                continue;
+            };
+            let Some(folder) = archive_folder(&record, cutoff) else {
+                continue;
+            };
+            let dir = paths::solutions_dir().join(ARCHIVE_DIR).join(folder);
+            if let Err(e) = fs::create_dir_all(&dir).and_then(|_| fs::rename(&path, dir.join(file_name))) {
+                result = Err(e.into());
+                break;
+            }
+            moved.push(Self::key(&path));
+            // Drop the dated folder once its last record has moved (fails while it has others)
+            if let Some(parent) = path.parent().filter(|p| *p != paths::solutions_dir()) {
+                let _ = fs::remove_dir(parent);
+            }
+        }
+
+        // Whatever was moved before a failure still has to leave the index
+        if !moved.is_empty() {
+            self.with_index(|index| self.remove_from_index(index, &moved));
+        }
+        result.map(|_| moved.len())
+    }
+
+    fn claim(&self, record: &SolutionRecord) -> Option<SolutionRecord> {
+        if self.shared.is_none() {
+            return Some(record.clone());
+        }
+        let path = Self::claim_path(record);
+        let create = || {
+            fs::create_dir_all(path.parent()?).ok()?;
+            let mut file = fs::OpenOptions::new().write(true).create_new(true).open(&path).ok()?;
+            let _ = writeln!(file, "{} {}", crate::get_timestamp(), std::process::id());
+            Some(())
+        };
+        if create().is_none() {
+            // Someone else's claim, unless it was left behind
+            let stale = fs::metadata(&path)
+                .and_then(|m| m.modified())
+                .is_ok_and(|at| at.elapsed().unwrap_or_default() > CLAIM_TTL);
+            if !stale || fs::remove_file(&path).is_err() || create().is_none() {
+                return None;
+            }
+        }
+
+        // Another miner may have resubmitted it since the pending records were read
+        let relative = self.with_index(|index| {
+            index.get(&Self::file_name(&record.wallet_address, &record.challenge_id)).map(|e| e.path.clone())
+        });
+        let current = relative.and_then(|relative| Self::read_record(&paths::solutions_dir().join(relative)));
+        match current {
+            Some(current) if current.crypto_receipt.is_none() && is_pending_status(&current.status) => Some(current),
+            _ => {
+                self.release(record);
+                None
+            }
+        }
+    }
+
+    fn release(&self, record: &SolutionRecord) {
+        if self.shared.is_some() {
+            let _ = fs::remove_file(Self::claim_path(record));
+        }
+    }
+}
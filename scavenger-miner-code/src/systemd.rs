@@ -0,0 +1,111 @@
+//! systemd integration: readiness, status and the service watchdog (sd_notify)
+//!
+//! Under a `Type=notify` unit, systemd passes `NOTIFY_SOCKET` and the miner reports READY=1 once
+//! the first challenge fetch succeeded or it found itself paused (a paused miner fetches nothing,
+//! and would otherwise hold `systemctl start` until the unit's start timeout), a STATUS= line
+//! while it runs and STOPPING=1 on shutdown.
+//! With `WatchdogSec=` set, systemd also passes `WATCHDOG_USEC` and restarts the miner when it
+//! stops sending WATCHDOG=1. Mining runs on the main thread and a task can take longer than any
+//! sensible watchdog interval, so a thread pets the watchdog on the main loop's behalf as long as
+//! the loop came around or the hash counters moved within the interval (a paused miner counts as
+//! alive, like for the health endpoint). A miner wedged in an API call or a deadlock goes quiet
+//! and gets restarted. Without `NOTIFY_SOCKET` every call here does nothing.
+
+use crate::{dashboard, is_paused};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// When the main loop last came around; `None` until the watchdog starts
+static LAST_TICK: Mutex<Option<Instant>> = Mutex::new(None);
+static READY_SENT: AtomicBool = AtomicBool::new(false);
+
+/// The first challenge fetch succeeded, or the miner is paused: tell systemd startup is complete
+pub fn ready() {
+    if !READY_SENT.swap(true, Ordering::SeqCst) {
+        notify(if is_paused() { "READY=1" } else { "READY=1\nSTATUS=Mining" });
+    }
+}
+
+/// Free-form status shown by `systemctl status`
+pub fn status(text: &str) {
+    notify(&format!("STATUS={}", text));
+}
+
+/// The miner is shutting down
+pub fn stopping() {
+    notify("STOPPING=1\nSTATUS=Shutting down");
+}
+
+/// The main loop came around
+pub fn loop_tick() {
+    if let Some(tick) = LAST_TICK.lock().unwrap().as_mut() {
+        *tick = Instant::now();
+    }
+}
+
+/// Pet the watchdog while the miner makes progress, if the unit has `WatchdogSec=`
+pub fn start_watchdog() {
+    let Some(interval) = watchdog_interval() else {
+        return;
+    };
+    *LAST_TICK.lock().unwrap() = Some(Instant::now());
+    tracing::info!("🐕 systemd watchdog: {:.0?}", interval);
+
+    // Twice per interval, as sd_watchdog_enabled(3) recommends
+    let spawned = std::thread::Builder::new().name("systemd-watchdog".to_string()).spawn(move || {
+        let mut hashes = dashboard::hashes_mined();
+        let mut last_hashing = Instant::now();
+        loop {
+            std::thread::sleep(interval / 2);
+            let now_hashes = dashboard::hashes_mined();
+            if now_hashes != hashes {
+                hashes = now_hashes;
+                last_hashing = Instant::now();
+            }
+            let last_tick = LAST_TICK.lock().unwrap().unwrap_or(last_hashing);
+            if is_paused() || last_tick.max(last_hashing).elapsed() < interval {
+                notify("WATCHDOG=1");
+            }
+        }
+    });
+    if let Err(e) = spawned {
+        tracing::warn!(error = %e, "⚠️  Could not start the systemd watchdog thread: {}", e);
+    }
+}
+
+/// `WATCHDOG_USEC`, if it is meant for this process
+fn watchdog_interval() -> Option<Duration> {
+    let usec: u64 = std::env::var("WATCHDOG_USEC").ok()?.parse().ok()?;
+    if let Ok(pid) = std::env::var("WATCHDOG_PID") {
+        if pid.parse::<u32>().ok()? != std::process::id() {
+            return None;
+        }
+    }
+    (usec > 0 && std::env::var_os("NOTIFY_SOCKET").is_some()).then(|| Duration::from_micros(usec))
+}
+
+/// Send one notification to `NOTIFY_SOCKET` (a path, or an abstract socket written `@name`)
+#[cfg(target_os = "linux")]
+fn notify(state: &str) {
+    use std::os::linux::net::SocketAddrExt;
+    use std::os::unix::net::{SocketAddr, UnixDatagram};
+
+    let Some(socket_path) = std::env::var_os("NOTIFY_SOCKET") else {
+        return;
+    };
+    let socket_path = socket_path.to_string_lossy().into_owned();
+    let result = UnixDatagram::unbound().and_then(|socket| {
+        let addr = match socket_path.strip_prefix('@') {
+            Some(name) => SocketAddr::from_abstract_name(name.as_bytes())?,
+            None => SocketAddr::from_pathname(&socket_path)?,
+        };
+        socket.send_to_addr(state.as_bytes(), &addr)
+    });
+    if let Err(e) = result {
+        tracing::debug!("sd_notify to {} failed: {}", socket_path, e);
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn notify(_state: &str) {}
@@ -0,0 +1,175 @@
+//! Telegram bot (`--telegram-token`, `--telegram-chat`)
+//!
+//! Notifications go to the chat like they go to the webhook (per event or as digests, see
+//! notify.rs). With `--telegram-commands` the bot also answers in that chat, and only there:
+//!
+//! - `/status` - what is being mined, hashrate and totals
+//! - `/pause`, `/resume` - suspend and resume mining (the ROM stays in memory)
+//!
+//! Commands are fetched by long polling `getUpdates` on a background thread. Messages from any
+//! other chat are ignored.
+
+use crate::{dashboard, format_duration_secs, ranking, wallet_stats, wallets};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+pub const DEFAULT_API_BASE: &str = "https://api.telegram.org";
+
+/// How long one `getUpdates` call waits for new messages
+const POLL_TIMEOUT_SECS: u64 = 25;
+
+/// Set by `/pause`, cleared by `/resume`
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// Paused from the chat
+pub fn paused() -> bool {
+    PAUSED.load(Ordering::Relaxed)
+}
+
+pub struct Telegram {
+    client: reqwest::blocking::Client,
+    bot_url: String, // <api base>/bot<token>
+    chat_id: String,
+}
+
+impl Telegram {
+    pub fn new(token: &str, chat_id: &str, api_base: &str) -> reqwest::Result<Self> {
+        // Long polls outlast the regular request timeout
+        let client = crate::http::client_builder().timeout(Duration::from_secs(POLL_TIMEOUT_SECS + 30)).build()?;
+        Ok(Telegram {
+            client,
+            bot_url: format!("{}/bot{}", api_base.trim_end_matches('/'), token.trim()),
+            chat_id: chat_id.trim().to_string(),
+        })
+    }
+
+    /// Post a message to the chat
+    pub fn send(&self, text: &str) {
+        let result = self
+            .client
+            .post(format!("{}/sendMessage", self.bot_url))
+            .json(&serde_json::json!({ "chat_id": self.chat_id, "text": text }))
+            .send();
+        match result {
+            Ok(response) if !response.status().is_success() => {
+                tracing::warn!("⚠️  Telegram returned {}", response.status());
+            }
+            Err(e) => tracing::warn!("⚠️  Telegram message failed: {}", without_token(&e.to_string())),
+            Ok(_) => {}
+        }
+    }
+
+    /// Answer commands from the chat on a background thread
+    pub fn start_commands(self: &Arc<Self>) {
+        let bot = Arc::clone(self);
+        let spawned = thread::Builder::new().name("telegram".to_string()).spawn(move || {
+            let mut offset: Option<i64> = None;
+            loop {
+                match bot.updates(offset) {
+                    Ok(updates) => {
+                        for (update_id, chat_id, text) in updates {
+                            offset = Some(update_id + 1);
+                            if chat_id == bot.chat_id {
+                                bot.handle(&text);
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        tracing::warn!("⚠️  Telegram commands: {}", without_token(&e));
+                        thread::sleep(Duration::from_secs(30));
+                    }
+                }
+            }
+        });
+        match spawned {
+            Ok(_) => tracing::info!("🤖 Telegram commands enabled: /status, /pause, /resume"),
+            Err(e) => tracing::warn!(error = %e, "⚠️  Cannot start Telegram commands: {}", e),
+        }
+    }
+
+    /// New messages as (update ID, chat ID, text)
+    fn updates(&self, offset: Option<i64>) -> Result<Vec<(i64, String, String)>, String> {
+        let mut query = vec![("timeout", POLL_TIMEOUT_SECS.to_string())];
+        if let Some(offset) = offset {
+            query.push(("offset", offset.to_string()));
+        }
+        let response = self.client.get(format!("{}/getUpdates", self.bot_url)).query(&query).send().map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("HTTP {}", response.status()));
+        }
+        let body: serde_json::Value = response.json().map_err(|e| e.to_string())?;
+        let results = body.get("result").and_then(|r| r.as_array()).ok_or("no result in getUpdates response")?;
+        Ok(results
+            .iter()
+            .filter_map(|update| {
+                let update_id = update.get("update_id")?.as_i64()?;
+                let message = update.get("message");
+                let chat_id = message.and_then(|m| m.pointer("/chat/id")).map(|id| match id {
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                });
+                let text = message.and_then(|m| m.get("text")).and_then(|t| t.as_str()).unwrap_or("");
+                Some((update_id, chat_id.unwrap_or_default(), text.to_string()))
+            })
+            .collect())
+    }
+
+    fn handle(&self, text: &str) {
+        // "/status@MyMinerBot" in groups
+        let command = text.split_whitespace().next().unwrap_or("").split('@').next().unwrap_or("");
+        match command {
+            "/status" => self.send(&status()),
+            "/pause" => {
+                PAUSED.store(true, Ordering::SeqCst);
+                tracing::info!("⏸️  Pause requested over Telegram");
+                self.send("⏸️ Pausing after the current hashing slice. /resume to continue");
+            }
+            "/resume" => {
+                let was_paused = PAUSED.swap(false, Ordering::SeqCst);
+                tracing::info!("▶️  Resume requested over Telegram");
+                let reply = match (was_paused, crate::pause_reason()) {
+                    (_, Some(reason)) => format!("⏸️ Still paused ({})", reason),
+                    (true, None) => "▶️ Resumed".to_string(),
+                    (false, None) => "▶️ Not paused".to_string(),
+                };
+                self.send(&reply);
+            }
+            _ if command.starts_with('/') => self.send("Commands: /status, /pause, /resume"),
+            _ => {}
+        }
+    }
+}
+
+/// Reply to `/status`
+fn status() -> String {
+    let mut lines = Vec::new();
+    match (crate::pause_reason(), dashboard::current_task()) {
+        (Some(reason), _) => lines.push(format!("⏸️ Paused ({})", reason)),
+        (None, Some((challenge_id, wallet, running))) => lines.push(format!(
+            "⛏️ Mining {} for {} ({})",
+            challenge_id,
+            wallets::label(&wallet),
+            format_duration_secs(running.as_secs_f64())
+        )),
+        (None, None) => lines.push("⛏️ Between tasks".to_string()),
+    }
+    if let Some(hashrate) = ranking::measured_hashrate() {
+        lines.push(format!("Hashrate: {:.1} H/s", hashrate));
+    }
+    let stats = wallet_stats::snapshot();
+    let (found, accepted) = stats.values().fold((0, 0), |(f, a), s| (f + s.solutions, a + s.accepted));
+    lines.push(format!("Solutions: {} found, {} accepted ({} wallet(s), all sessions)", found, accepted, stats.len()));
+    lines.join("\n")
+}
+
+/// Errors from reqwest include the URL, and with it the bot token
+fn without_token(message: &str) -> String {
+    let Some(start) = message.find("/bot") else {
+        return message.to_string();
+    };
+    let rest = &message[start + 4..];
+    let end = rest.find(['/', ' ', ')']).unwrap_or(rest.len());
+    format!("{}/bot<token>{}", &message[..start], &rest[end..])
+}
@@ -0,0 +1,207 @@
+//! Opt-in fleet telemetry (`--telemetry-url`)
+//!
+//! Every `--telemetry-interval` seconds, and once on shutdown, the miner POSTs one JSON object
+//! with its recent stats to a collector of your choice. Nothing is sent unless a URL is
+//! configured, and the payload never contains wallet addresses, nonces or the hostname:
+//! `miner_id` is `--miner-id` if given, otherwise a random ID kept in the data directory.
+//! Reports are timed and POSTed by a background thread, so they go out on schedule in the
+//! middle of a long task and a slow collector never holds up mining; `hashes` comes from the
+//! live hash counters. Format (`schema` is bumped on incompatible changes):
+//!
+//! ```json
+//! {
+//!   "schema": "scavenger-miner-telemetry/1",
+//!   "miner_id": "rig-07",
+//!   "version": "1.0.0",
+//!   "sent_at": "2025-11-20T12:00:00Z",
+//!   "uptime_secs": 3600,
+//!   "interval_secs": 300,
+//!   "threads": 16,
+//!   "hashrate": 1234.5,
+//!   "hashes": 370350,
+//!   "solutions": 2,
+//!   "receipts": 2,
+//!   "failed_submissions": 0,
+//!   "total_solutions": 14,
+//!   "active_challenge": "**D05C12",
+//!   "active_challenges": 3
+//! }
+//! ```
+//!
+//! `hashrate`, `hashes`, `solutions`, `receipts` and `failed_submissions` cover the interval
+//! since the previous report; `total_solutions` covers the whole session.
+
+use crate::{dashboard, get_timestamp, notify, paths};
+use std::fs;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const TELEMETRY_SCHEMA: &str = "scavenger-miner-telemetry/1";
+pub const DEFAULT_TELEMETRY_INTERVAL_SECS: u64 = 300;
+
+/// File in the data directory holding the anonymous miner ID
+const TELEMETRY_ID_FILE: &str = "telemetry-id";
+
+/// The random miner ID reported when `--miner-id` isn't set, created on first use
+/// Kept in the data directory so a rig reports under the same ID across restarts
+pub fn anonymous_id() -> String {
+    let path = paths::data_dir().join(TELEMETRY_ID_FILE);
+    if let Ok(id) = fs::read_to_string(&path) {
+        let id = id.trim();
+        if !id.is_empty() {
+            return id.to_string();
+        }
+    }
+
+    let id = hex::encode(rand::random::<[u8; 8]>());
+    if let Err(e) = fs::create_dir_all(paths::data_dir()).and_then(|_| fs::write(&path, &id)) {
+        tracing::warn!(error = %e, "⚠️  Could not save the telemetry ID to {}: {}", path.display(), e);
+    }
+    id
+}
+
+/// Counters collected between reports
+#[derive(Default)]
+struct IntervalStats {
+    solutions: u64,
+    receipts: u64,
+    failures: u64,
+}
+
+/// What the reports are made of, shared between the mining loop and the telemetry thread
+struct State {
+    miner_id: String,
+    threads: usize,
+    stats: IntervalStats,
+    total_solutions: u64,
+    active_challenge: Option<String>,
+    active_challenges: usize,
+    started: Instant,
+    period_start: Instant,
+    period_hashes: u64, // `dashboard::hashes_mined()` when the interval started
+}
+
+impl State {
+    /// The report for the interval so far, starting the next one
+    fn report(&mut self) -> serde_json::Value {
+        let stats = std::mem::take(&mut self.stats);
+        let hashes_mined = dashboard::hashes_mined();
+        let hashes = hashes_mined.saturating_sub(self.period_hashes);
+        let interval_secs = self.period_start.elapsed().as_secs_f64();
+        let hashrate = if interval_secs > 0.0 { hashes as f64 / interval_secs } else { 0.0 };
+        let payload = serde_json::json!({
+            "schema": TELEMETRY_SCHEMA,
+            "miner_id": self.miner_id,
+            "version": env!("CARGO_PKG_VERSION"),
+            "sent_at": get_timestamp(),
+            "uptime_secs": self.started.elapsed().as_secs(),
+            "interval_secs": self.period_start.elapsed().as_secs(),
+            "threads": self.threads,
+            "hashrate": (hashrate * 10.0).round() / 10.0,
+            "hashes": hashes,
+            "solutions": stats.solutions,
+            "receipts": stats.receipts,
+            "failed_submissions": stats.failures,
+            "total_solutions": self.total_solutions,
+            "active_challenge": self.active_challenge,
+            "active_challenges": self.active_challenges,
+        });
+        self.period_start = Instant::now();
+        self.period_hashes = hashes_mined;
+        payload
+    }
+}
+
+/// POST a report every `interval`, and one more whenever asked, until the channel is closed
+fn send_reports(url: String, interval: Duration, state: Arc<Mutex<State>>, flushes: mpsc::Receiver<()>) {
+    let mut next = Instant::now() + interval;
+    loop {
+        match flushes.recv_timeout(next.saturating_duration_since(Instant::now())) {
+            Ok(()) => {}
+            Err(RecvTimeoutError::Timeout) => next = Instant::now() + interval,
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+        let payload = state.lock().unwrap().report();
+
+        // Plain client: the collector is not the Scavenger API, so no API credentials
+        let result = crate::http::client().and_then(|client| client.post(&url).json(&payload).send());
+        match result {
+            Ok(response) if !response.status().is_success() => {
+                tracing::warn!("⚠️  Telemetry collector returned {}", response.status());
+            }
+            Err(e) => tracing::warn!(error = %e, "⚠️  Telemetry report failed: {}", e),
+            Ok(_) => {}
+        }
+    }
+}
+
+pub struct Reporter {
+    flushes: Option<mpsc::Sender<()>>, // None unless a URL is configured
+    sender: Option<thread::JoinHandle<()>>,
+    state: Arc<Mutex<State>>,
+}
+
+impl Reporter {
+    /// A reporter that does nothing unless `url` is set
+    pub fn new(url: Option<String>, interval: Duration, miner_id: String, threads: usize) -> Self {
+        let state = Arc::new(Mutex::new(State {
+            miner_id,
+            threads,
+            stats: IntervalStats::default(),
+            total_solutions: 0,
+            active_challenge: None,
+            active_challenges: 0,
+            started: Instant::now(),
+            period_start: Instant::now(),
+            period_hashes: dashboard::hashes_mined(),
+        }));
+        let (flushes, sender) = match url {
+            Some(url) => {
+                let (tx, rx) = mpsc::channel();
+                let thread_state = Arc::clone(&state);
+                let handle = thread::Builder::new()
+                    .name("telemetry".to_string())
+                    .spawn(move || send_reports(url, interval, thread_state, rx))
+                    .expect("failed to spawn telemetry thread");
+                (Some(tx), Some(handle))
+            }
+            None => (None, None),
+        };
+        Reporter { flushes, sender, state }
+    }
+
+    /// Record a solution found by this rig
+    pub fn record_solution(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.stats.solutions += 1;
+        state.total_solutions += 1;
+    }
+
+    /// Count a submission outcome
+    pub fn observe(&self, event: &notify::Event) {
+        let mut state = self.state.lock().unwrap();
+        match event {
+            notify::Event::Receipt { .. } => state.stats.receipts += 1,
+            notify::Event::SubmitFailed { .. } => state.stats.failures += 1,
+        }
+    }
+
+    /// The challenge being mined (the last one, between tasks), and how many are active
+    pub fn set_active_challenge(&self, challenge_id: &str, active_challenges: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.active_challenge = Some(challenge_id.to_string());
+        state.active_challenges = active_challenges;
+    }
+
+    /// Send a last report and wait until it has been POSTed (on shutdown)
+    pub fn finish(&mut self) {
+        if let Some(flushes) = self.flushes.take() {
+            let _ = flushes.send(());
+        }
+        if let Some(handle) = self.sender.take() {
+            let _ = handle.join();
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! `verify`: re-check a stored solution locally
+//!
+//! Rebuilds the preimage from a solution record and its challenge, hashes it with a freshly
+//! built ROM and checks the hash against the challenge difficulty, so a "does not meet
+//! difficulty" rejection can be pinned on the nonce, the hash backend or the API. The
+//! challenge comes from `--challenges-file` if given, otherwise from the API or the
+//! challenges saved by earlier runs.
+
+use crate::{
+    backend_for_challenge, build_preimage_suffix, check_difficulty, construct_preimage_fast, find_known_challenge,
+    CliArgs, SolutionRecord,
+};
+use std::fs;
+use std::time::Instant;
+
+/// Byte offset of the first hash byte with a bit the difficulty mask doesn't allow
+fn first_failing_byte(hash: &[u8], diff_bytes: &[u8]) -> Option<usize> {
+    hash.iter().zip(diff_bytes).position(|(h, d)| h & !d != 0)
+}
+
+/// Usage: verify <solution file> [--challenges-file <file>] [--hash-backend <name>] [--backend-schedule <spec>]
+pub fn run_verify(args: &[String]) -> bool {
+    let cli = CliArgs::parse(args);
+    let Some(path) = cli.positional.first() else {
+        println!("Usage: scavenger-miner verify <solution file> [--challenges-file <file>] [--hash-backend <name>]");
+        return false;
+    };
+
+    let record: SolutionRecord = match fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()))
+    {
+        Ok(record) => record,
+        Err(e) => {
+            println!("❌ Could not read solution {}: {}", path, e);
+            return false;
+        }
+    };
+    let Ok(nonce) = u64::from_str_radix(&record.nonce, 16) else {
+        println!("❌ Nonce '{}' is not a 64-bit hex value", record.nonce);
+        return false;
+    };
+
+    println!("🔍 Verifying challenge {} for {}", record.challenge_id, record.wallet_address);
+    println!("   nonce:             {}", record.nonce);
+    println!("   status:            {}", record.status);
+
+    let challenge = match find_known_challenge(&cli, &record.challenge_id) {
+        Ok(challenge) => challenge,
+        Err(e) => {
+            println!("❌ {}", e);
+            return false;
+        }
+    };
+    println!("   difficulty:        {}", challenge.difficulty);
+    println!("   no_pre_mine:       {}", challenge.no_pre_mine);
+    println!("   latest_submission: {}", challenge.latest_submission);
+    println!("   no_pre_mine_hour:  {}", challenge.no_pre_mine_hour);
+
+    // Same backend choice as the mining loop
+    let hash_backend = match backend_for_challenge(&cli, &challenge) {
+        Ok(backend) => backend,
+        Err(e) => {
+            println!("❌ {}", e);
+            return false;
+        }
+    };
+
+    let preimage = construct_preimage_fast(nonce, &build_preimage_suffix(&record.wallet_address, &challenge));
+    println!("   preimage:          {}", String::from_utf8_lossy(&preimage));
+    if let Some(receipt) = &record.crypto_receipt {
+        if receipt.preimage.as_bytes() == preimage.as_slice() {
+            println!("   ✅ Matches the preimage in the crypto receipt");
+        } else {
+            println!("   ⚠️  Differs from the preimage in the crypto receipt: {}", receipt.preimage);
+        }
+    }
+
+    let Ok(diff_bytes) = hex::decode(&challenge.difficulty) else {
+        println!("❌ Difficulty '{}' is not valid hex", challenge.difficulty);
+        return false;
+    };
+
+    println!("🔄 Building ROM ({})...", hash_backend.describe());
+    let start = Instant::now();
+    let rom = hash_backend.build_rom(challenge.no_pre_mine.as_bytes());
+    println!("   ✓ ROM built in {:.2?}", start.elapsed());
+
+    let hash = rom.hash(&preimage);
+    println!("   hash:              {}", hex::encode(hash));
+    if check_difficulty(&hash, &diff_bytes) {
+        println!("✅ The hash meets difficulty {}", challenge.difficulty);
+        return true;
+    }
+
+    let offset = first_failing_byte(&hash, &diff_bytes).unwrap_or(0);
+    println!(
+        "❌ The hash does not meet difficulty {}: byte {} is {:02x}, the mask allows {:02x}",
+        challenge.difficulty, offset, hash[offset], diff_bytes[offset]
+    );
+    println!("   Either the nonce is wrong or the challenge needs a different hash backend (--hash-backend)");
+    false
+}
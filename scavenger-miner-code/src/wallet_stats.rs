@@ -0,0 +1,128 @@
+//! Per-wallet mining statistics, kept across sessions in `wallet_stats.json`
+//!
+//! Every finished task adds its hashes and mining time to its wallet, and a solved one its solve
+//! time as well. Submission outcomes come in from the background submitter and the retry logic
+//! alike, so updates go through one lock and are written back right away. Shown in the
+//! statistics block after every task and by `status`.
+
+use crate::difficulty::format_count;
+use crate::{format_duration_secs, paths, wallets};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+pub const WALLET_STATS_FILE: &str = "wallet_stats.json";
+
+/// Totals for one wallet
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WalletStats {
+    pub tasks: u64,
+    pub hashes: u64,
+    pub mining_secs: f64,
+    pub solutions: u64,  // Found
+    pub solve_secs: f64, // Mining time of the tasks that found them
+    pub accepted: u64,   // Submissions answered with a receipt
+    pub duplicates: u64, // Submissions the API already had
+}
+
+impl WalletStats {
+    /// Mean mining time of a solved task
+    pub fn average_solve_secs(&self) -> Option<f64> {
+        (self.solutions > 0).then(|| self.solve_secs / self.solutions as f64)
+    }
+
+    /// Hashes per second over all of the wallet's tasks
+    pub fn hashrate(&self) -> Option<f64> {
+        (self.mining_secs > 0.0).then(|| self.hashes as f64 / self.mining_secs)
+    }
+}
+
+/// Stats as last written, loaded on first use
+static STATS: Mutex<Option<BTreeMap<String, WalletStats>>> = Mutex::new(None);
+
+fn path() -> PathBuf {
+    paths::data_dir().join(WALLET_STATS_FILE)
+}
+
+/// Read the stats file (empty if there is none yet or it can't be read)
+pub fn load() -> BTreeMap<String, WalletStats> {
+    let path = path();
+    let Ok(content) = fs::read_to_string(&path) else {
+        return BTreeMap::new();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "⚠️  Ignoring unreadable wallet statistics {}: {}", path.display(), e);
+        BTreeMap::new()
+    })
+}
+
+/// Current stats of every wallet
+pub fn snapshot() -> BTreeMap<String, WalletStats> {
+    STATS.lock().unwrap().get_or_insert_with(load).clone()
+}
+
+/// Change one wallet's stats and write the file back
+fn update(wallet_address: &str, change: impl FnOnce(&mut WalletStats)) {
+    let mut guard = STATS.lock().unwrap();
+    let stats = guard.get_or_insert_with(load);
+    change(stats.entry(wallet_address.to_string()).or_default());
+
+    let path = path();
+    let tmp = path.with_extension("json.tmp");
+    let result = serde_json::to_string_pretty(&*stats)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(&tmp, json).and_then(|_| fs::rename(&tmp, &path)).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        tracing::warn!(error = %e, "⚠️  Failed to save wallet statistics {}: {}", path.display(), e);
+    }
+}
+
+/// Add a finished mining task
+pub fn record_task(wallet_address: &str, hashes: u64, secs: f64, solved: bool) {
+    if hashes == 0 {
+        return;
+    }
+    update(wallet_address, |stats| {
+        stats.tasks += 1;
+        stats.hashes += hashes;
+        stats.mining_secs += secs;
+        if solved {
+            stats.solutions += 1;
+            stats.solve_secs += secs;
+        }
+    });
+}
+
+/// Add a submission the API accepted
+pub fn record_accepted(wallet_address: &str) {
+    update(wallet_address, |stats| stats.accepted += 1);
+}
+
+/// Add a submission the API already had
+pub fn record_duplicate(wallet_address: &str) {
+    update(wallet_address, |stats| stats.duplicates += 1);
+}
+
+/// Print one line per wallet
+pub fn print_table<'a>(rows: impl IntoIterator<Item = (&'a str, WalletStats)>) {
+    println!(
+        "   {:<24}  {:>6}  {:>8}  {:>8}  {:>6}  {:>8}  {:>9}  {:>9}",
+        "wallet", "tasks", "hashes", "H/s", "found", "accepted", "duplicate", "avg solve"
+    );
+    for (wallet, stats) in rows {
+        println!(
+            "   {:<24}  {:>6}  {:>8}  {:>8}  {:>6}  {:>8}  {:>9}  {:>9}",
+            wallets::label(wallet),
+            stats.tasks,
+            format_count(stats.hashes as f64),
+            stats.hashrate().map_or("-".to_string(), |rate| format!("{:.1}", rate)),
+            stats.solutions,
+            stats.accepted,
+            stats.duplicates,
+            stats.average_solve_secs().map_or("-".to_string(), format_duration_secs)
+        );
+    }
+}
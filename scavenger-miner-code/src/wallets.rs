@@ -0,0 +1,379 @@
+//! Wallets files: where the wallets come from and what they say about each wallet
+//!
+//! A wallets source is a file, `-` for standard input or `$SCAVENGER_WALLETS`. Three formats:
+//!
+//! - plain text, one `<address> [weight] [# alias]` per line
+//! - CSV with a header row naming the columns: `address` and any of `alias`, `weight`,
+//!   `group`, `quota_per_day`, `quota_total`
+//! - JSON, a list of addresses or of objects with the same fields (also under `"wallets"`)
+//!
+//! The format comes from the file extension (`.csv`, `.json`), otherwise from the content.
+//! Errors name the line of the offending row. `group` puts a wallet into the config file's
+//! wallet group of that name (see groups.rs), and the quotas override `wallet_quotas.json`.
+
+use crate::address;
+use crate::error::MinerError;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+/// Wallets source meaning standard input
+pub const STDIN: &str = "-";
+
+/// Environment variable with a comma-separated wallet list (or a JSON one), used when no wallets file is given
+const ENV: &str = "SCAVENGER_WALLETS";
+
+/// Wallets source meaning `ENV`
+const ENV_SOURCE: &str = "$SCAVENGER_WALLETS";
+
+/// CSV and JSON fields of a wallet
+const FIELDS: &str = "address, alias, weight, group, quota_per_day, quota_total";
+
+/// Wallets source when none is given: the environment variable if set, otherwise wallets.txt
+pub fn default_source() -> &'static str {
+    if env::var_os(ENV).is_some() {
+        ENV_SOURCE
+    } else {
+        "wallets.txt"
+    }
+}
+
+/// Whether a wallets source is a file (rather than standard input or the environment)
+pub fn is_file(source: &str) -> bool {
+    source != STDIN && source != ENV_SOURCE
+}
+
+/// A wallets source as shown in messages
+pub fn source_name(source: &str) -> String {
+    if source == STDIN { "standard input".to_string() } else { source.to_string() }
+}
+
+/// Text of a wallets source: a file, `-` for standard input (read once, so it can be read again)
+/// or `$SCAVENGER_WALLETS` (one line per comma-separated entry, unless it holds JSON)
+pub fn read(source: &str) -> Result<String, MinerError> {
+    static STDIN_CONTENT: OnceLock<String> = OnceLock::new();
+    match source {
+        STDIN => {
+            if let Some(content) = STDIN_CONTENT.get() {
+                return Ok(content.clone());
+            }
+            let mut content = String::new();
+            std::io::Read::read_to_string(&mut std::io::stdin(), &mut content)?;
+            Ok(STDIN_CONTENT.get_or_init(|| content).clone())
+        }
+        ENV_SOURCE => {
+            let list = env::var(ENV).map_err(|_| MinerError::Parse(format!("{} is not set", ENV)))?;
+            if list.trim_start().starts_with(['[', '{']) {
+                return Ok(list);
+            }
+            Ok(list.split(',').map(str::trim).collect::<Vec<_>>().join("\n"))
+        }
+        path => {
+            if !Path::new(path).exists() {
+                return Err(std::io::Error::new(std::io::ErrorKind::NotFound, format!("Wallets file not found: {}", path)).into());
+            }
+            Ok(fs::read_to_string(path)?)
+        }
+    }
+}
+
+/// One wallet as listed
+#[derive(Debug, Clone)]
+pub struct WalletEntry {
+    pub line: usize,     // Line of its row, for messages
+    pub address: String, // As written until `unique` normalizes it
+    pub weight: f64,
+    pub alias: Option<String>,
+    pub group: Option<String>,
+    pub quota_per_day: Option<u64>,
+    pub quota_total: Option<u64>,
+}
+
+impl WalletEntry {
+    fn new(line: usize, address: &str) -> Self {
+        WalletEntry {
+            line,
+            address: address.to_string(),
+            weight: 1.0,
+            alias: None,
+            group: None,
+            quota_per_day: None,
+            quota_total: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Csv,
+    Json,
+}
+
+impl Format {
+    /// Format of a wallets source: by extension, otherwise JSON if it starts like JSON and CSV
+    /// if its first row starts with an `address` column
+    pub fn detect(source: &str, content: &str) -> Self {
+        let extension = Path::new(source).extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("csv") => return Format::Csv,
+            Some("json") => return Format::Json,
+            _ => {}
+        }
+        if content.trim_start().starts_with(['[', '{']) {
+            return Format::Json;
+        }
+        let first_row = content.lines().map(str::trim).find(|l| !l.is_empty() && !l.starts_with('#'));
+        match first_row.and_then(|row| row.split(',').next()) {
+            Some(column) if column.trim().trim_matches('"').eq_ignore_ascii_case("address") => Format::Csv,
+            _ => Format::Text,
+        }
+    }
+}
+
+fn parse_weight(line: usize, value: &str) -> Result<f64, String> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|w| w.is_finite() && *w > 0.0)
+        .ok_or_else(|| format!("line {}: weight '{}' is not a positive number", line, value))
+}
+
+/// `<address> [weight] [# alias]` lines
+fn parse_text(content: &str) -> Result<Vec<WalletEntry>, String> {
+    let mut entries = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let number = number + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (line, alias) = match line.split_once('#') {
+            Some((entry, alias)) => (entry.trim(), Some(alias.trim()).filter(|a| !a.is_empty())),
+            None => (line, None),
+        };
+        let mut fields = line.split_whitespace();
+        let mut entry = WalletEntry::new(number, fields.next().unwrap_or_default());
+        entry.alias = alias.map(str::to_string);
+        match (fields.next(), fields.next()) {
+            (None, _) => {}
+            (Some(weight), None) => entry.weight = parse_weight(number, weight)?,
+            (Some(_), Some(_)) => return Err(format!("line {}: expected '<address> [weight] [# alias]'", number)),
+        }
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// Fields of one CSV row, with `"quoted, fields"` and `""` for a quote inside them
+fn split_csv_row(row: &str) -> Result<Vec<String>, String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = row.chars().peekable();
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+            }
+            ',' if !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return Err("unterminated quote".to_string());
+    }
+    fields.push(field.trim().to_string());
+    Ok(fields)
+}
+
+/// CSV with a header row
+fn parse_csv(content: &str) -> Result<Vec<WalletEntry>, String> {
+    let mut rows = content
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+    let Some((header_line, header)) = rows.next() else {
+        return Ok(Vec::new());
+    };
+    let columns: Vec<String> = split_csv_row(header)
+        .map_err(|e| format!("line {}: {}", header_line, e))?
+        .into_iter()
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    for column in &columns {
+        if !FIELDS.split(", ").any(|f| f == column) {
+            return Err(format!("line {}: unknown column '{}' (expected {})", header_line, column, FIELDS));
+        }
+    }
+    if !columns.iter().any(|c| c == "address") {
+        return Err(format!("line {}: no address column in the header", header_line));
+    }
+
+    let mut entries = Vec::new();
+    for (line, row) in rows {
+        let fields = split_csv_row(row).map_err(|e| format!("line {}: {}", line, e))?;
+        if fields.len() != columns.len() {
+            return Err(format!("line {}: {} field(s), the header has {}", line, fields.len(), columns.len()));
+        }
+        let mut entry = WalletEntry::new(line, "");
+        for (column, value) in columns.iter().zip(&fields) {
+            if value.is_empty() {
+                continue;
+            }
+            let whole = |value: &str| {
+                value.parse::<u64>().map_err(|_| format!("line {}: {} '{}' is not a whole number", line, column, value))
+            };
+            match column.as_str() {
+                "address" => entry.address = value.clone(),
+                "alias" => entry.alias = Some(value.clone()),
+                "weight" => entry.weight = parse_weight(line, value)?,
+                "group" => entry.group = Some(value.clone()),
+                "quota_per_day" => entry.quota_per_day = Some(whole(value)?),
+                _ => entry.quota_total = Some(whole(value)?),
+            }
+        }
+        if entry.address.is_empty() {
+            return Err(format!("line {}: no address", line));
+        }
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// One JSON entry: an address, or an object with the CSV columns as fields
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonEntry {
+    Address(String),
+    Wallet(JsonWallet),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonWallet {
+    address: String,
+    alias: Option<String>,
+    weight: Option<f64>,
+    group: Option<String>,
+    quota_per_day: Option<u64>,
+    quota_total: Option<u64>,
+}
+
+/// Line of the first `needle` at or after byte `from`, and where it ends
+fn line_of(content: &str, needle: &str, from: usize) -> Option<(usize, usize)> {
+    let at = from + content.get(from..)?.find(needle)?;
+    Some((content[..at].matches('\n').count() + 1, at + needle.len()))
+}
+
+/// A JSON list (bare or under `"wallets"`); rows are found by their address for the line numbers
+fn parse_json(content: &str) -> Result<Vec<WalletEntry>, String> {
+    let value: serde_json::Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let list = match value.get("wallets").unwrap_or(&value) {
+        serde_json::Value::Array(list) => list.clone(),
+        _ => return Err("expected a list of wallets, or an object with a \"wallets\" list".to_string()),
+    };
+
+    let mut entries = Vec::new();
+    let mut position = 0;
+    for (index, item) in list.into_iter().enumerate() {
+        let json: JsonEntry = serde_json::from_value(item.clone()).map_err(|_| {
+            // The untagged error says nothing useful; the object's own one does
+            let reason = match serde_json::from_value::<JsonWallet>(item) {
+                Err(e) => e.to_string(),
+                Ok(_) => format!("expected an address or an object with {}", FIELDS),
+            };
+            format!("wallet #{}: {}", index + 1, reason)
+        })?;
+        let wallet = match json {
+            JsonEntry::Address(address) => JsonWallet { address, alias: None, weight: None, group: None, quota_per_day: None, quota_total: None },
+            JsonEntry::Wallet(wallet) => wallet,
+        };
+        let line = match line_of(content, &format!("\"{}\"", wallet.address), position) {
+            Some((line, end)) => {
+                position = end;
+                line
+            }
+            None => index + 1,
+        };
+        if let Some(weight) = wallet.weight {
+            if !(weight.is_finite() && weight > 0.0) {
+                return Err(format!("line {}: weight {} is not a positive number", line, weight));
+            }
+        }
+        entries.push(WalletEntry {
+            line,
+            address: wallet.address,
+            weight: wallet.weight.unwrap_or(1.0),
+            alias: wallet.alias.filter(|a| !a.trim().is_empty()),
+            group: wallet.group,
+            quota_per_day: wallet.quota_per_day,
+            quota_total: wallet.quota_total,
+        });
+    }
+    Ok(entries)
+}
+
+/// Every row of a wallets source, repeats included, addresses as written
+pub fn parse(content: &str, format: Format) -> Result<Vec<WalletEntry>, String> {
+    match format {
+        Format::Text => parse_text(content),
+        Format::Csv => parse_csv(content),
+        Format::Json => parse_json(content),
+    }
+}
+
+/// Names given to wallets (address -> alias)
+static ALIASES: Mutex<BTreeMap<String, String>> = Mutex::new(BTreeMap::new());
+
+/// Each wallet once, normalized, with its first row's settings (repeats are reported by the
+/// address checks); aliases are remembered for `label`
+pub fn unique(entries: Vec<WalletEntry>) -> Vec<WalletEntry> {
+    let mut wallets: Vec<WalletEntry> = Vec::new();
+    for mut entry in entries {
+        entry.address = address::normalize(&entry.address);
+        if wallets.iter().any(|w| w.address == entry.address) {
+            continue;
+        }
+        if let Some(alias) = &entry.alias {
+            remember_alias(&entry.address, alias);
+        }
+        wallets.push(entry);
+    }
+    wallets
+}
+
+/// Show `alias` for a wallet from now on
+pub fn remember_alias(wallet_address: &str, alias: &str) {
+    ALIASES.lock().unwrap().insert(wallet_address.to_string(), alias.to_string());
+}
+
+/// The wallets of a source, each once
+pub fn load(source: &str) -> Result<Vec<WalletEntry>, MinerError> {
+    let content = read(source)?;
+    let entries = parse(&content, Format::detect(source, &content)).map_err(MinerError::Parse)?;
+    let wallets = unique(entries);
+    if wallets.is_empty() {
+        return Err(MinerError::Parse("No valid wallet addresses found".to_string()));
+    }
+    Ok(wallets)
+}
+
+/// How a wallet appears in logs, tables and notifications: its alias, or the start of the address
+/// (files and the API always get the full address)
+pub fn label(wallet_address: &str) -> String {
+    match ALIASES.lock().unwrap().get(wallet_address) {
+        Some(alias) => alias.clone(),
+        None => format!("{}...", &wallet_address[..20.min(wallet_address.len())]),
+    }
+}
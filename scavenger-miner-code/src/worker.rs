@@ -0,0 +1,246 @@
+//! `worker`: mine nonce ranges handed out by a coordinator (see coordinator.rs)
+//!
+//! The worker asks the coordinator for a job, hashes the range it was given on every thread, and
+//! reports the outcome: the nonce if one solved the challenge, or that the range is exhausted.
+//! While hashing it reports progress every `HEARTBEAT_INTERVAL` and gives up the range when the
+//! coordinator says so (the pair was solved elsewhere or its challenge closed). The worker needs
+//! no wallets file, API access or solutions folder; the ROM is kept between jobs of the same
+//! challenge. A pool (see pool.rs) also asks for shares: hashes meeting an easier mask, sent with
+//! every progress report and the result.
+
+use crate::mine::selected_threads;
+use crate::{
+    backend_for_challenge, dashboard, http, install_shutdown_handler, mine_single_solution,
+    shutdown_requested, sleep_unless_shutdown, wallets, Challenge, CliArgs, MiningLimits, MiningResult,
+    DEFAULT_LIMIT_CHECK_INTERVAL,
+};
+use serde::Deserialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+/// Wait after the coordinator could not be reached
+const CONNECT_RETRY: Duration = Duration::from_secs(10);
+
+/// Shares found since the last report
+static SHARES: Mutex<Vec<u64>> = Mutex::new(Vec::new());
+
+/// A hashing thread found a share (see `MiningLimits::share_bits`)
+pub fn record_share(nonce: u64) {
+    SHARES.lock().unwrap().push(nonce);
+}
+
+/// Shares found since the last call
+fn take_shares() -> Vec<u64> {
+    std::mem::take(&mut *SHARES.lock().unwrap())
+}
+
+fn hex_nonces(nonces: &[u64]) -> Vec<String> {
+    nonces.iter().map(|nonce| format!("{:016x}", nonce)).collect()
+}
+
+#[derive(Deserialize)]
+struct Job {
+    id: u64,
+    wallet: String,
+    challenge: Challenge,
+    nonce_start: String,
+    nonce_count: u64,
+    #[serde(default)]
+    share_bits: Option<u32>, // Pool mode only
+}
+
+#[derive(Deserialize)]
+struct WorkReply {
+    job: Option<Job>,
+    #[serde(default)]
+    retry_secs: Option<u64>,
+}
+
+/// Talks to the coordinator (plain requests: the API credentials are never sent there)
+struct Coordinator {
+    client: reqwest::blocking::Client,
+    url: String,
+    token: Option<String>,
+}
+
+impl Coordinator {
+    fn post(&self, path: &str, body: serde_json::Value) -> Result<serde_json::Value, String> {
+        let mut request = self.client.post(format!("{}{}", self.url, path)).json(&body);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let response = http::send(request).map_err(|e| e.to_string())?;
+        let status = response.status();
+        let body: serde_json::Value = response.json().map_err(|e| e.to_string())?;
+        if !status.is_success() {
+            return Err(format!("HTTP {}: {}", status, body["error"].as_str().unwrap_or("")));
+        }
+        Ok(body)
+    }
+}
+
+/// Usage: worker --coordinator <url> [--token <secret>] [--worker-id <name>] [--threads <n> | --cpu <percent>]
+pub fn run_worker(args: &[String]) -> bool {
+    let cli = CliArgs::parse(args);
+    let Some(url) = cli.option("--coordinator") else {
+        println!("Usage: scavenger-miner worker --coordinator <url> [--token <secret>] [--worker-id <name>] [--threads <n>]");
+        return false;
+    };
+    let client = match http::client() {
+        Ok(client) => client,
+        Err(e) => {
+            println!("❌ Cannot create HTTP client: {}", e);
+            return false;
+        }
+    };
+    let coordinator = Arc::new(Coordinator {
+        client,
+        url: url.trim_end_matches('/').to_string(),
+        token: cli.option("--token").map(|t| t.to_string()),
+    });
+    let num_threads = selected_threads(&cli);
+    let worker_id = cli.option("--worker-id").map(|s| s.to_string()).unwrap_or_else(|| {
+        std::env::var("HOSTNAME").ok().filter(|h| !h.is_empty()).unwrap_or_else(|| format!("worker-{}", std::process::id()))
+    });
+    println!("🛰️  Worker {} taking jobs from {} with {} thread(s)", worker_id, coordinator.url, num_threads);
+
+    install_shutdown_handler();
+    // ROM of the last job's challenge, rebuilt when the challenge changes
+    let mut rom_cache: Option<(String, _)> = None;
+    let (mut jobs, mut found) = (0u64, 0u64);
+    while !shutdown_requested() {
+        let reply = coordinator
+            .post("/work", serde_json::json!({ "worker": worker_id, "threads": num_threads }))
+            .and_then(|body| serde_json::from_value::<WorkReply>(body).map_err(|e| e.to_string()));
+        let job = match reply {
+            Ok(WorkReply { job: Some(job), .. }) => job,
+            Ok(WorkReply { job: None, retry_secs }) => {
+                tracing::info!("💤 No work from the coordinator right now");
+                sleep_unless_shutdown(Duration::from_secs(retry_secs.unwrap_or(30).max(1)));
+                continue;
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "⚠️  Coordinator unreachable: {}", e);
+                sleep_unless_shutdown(CONNECT_RETRY);
+                continue;
+            }
+        };
+        let Ok(nonce_start) = u64::from_str_radix(&job.nonce_start, 16) else {
+            println!("❌ Coordinator sent an invalid nonce_start '{}'", job.nonce_start);
+            return false;
+        };
+
+        let challenge_key = format!("{}:{}", job.challenge.challenge_id, job.challenge.no_pre_mine);
+        let rom = match &rom_cache {
+            Some((key, rom)) if *key == challenge_key => Arc::clone(rom),
+            _ => {
+                let backend = match backend_for_challenge(&cli, &job.challenge) {
+                    Ok(backend) => backend,
+                    Err(e) => {
+                        println!("❌ {}", e);
+                        return false;
+                    }
+                };
+                let start = Instant::now();
+                tracing::info!(challenge_id = %job.challenge.challenge_id, "🔄 Initializing ROM for challenge {} ({})...", job.challenge.challenge_id, backend.describe());
+                let rom = backend.build_rom(job.challenge.no_pre_mine.as_bytes());
+                tracing::info!("   ✓ ROM initialized in {:.2?}", start.elapsed());
+                rom_cache = Some((challenge_key, Arc::clone(&rom)));
+                rom
+            }
+        };
+
+        tracing::info!(
+            challenge_id = %job.challenge.challenge_id,
+            "⛏️  Job {}: {} on challenge {} (difficulty {}), {} nonces from {:016x}",
+            job.id,
+            wallets::label(&job.wallet),
+            job.challenge.challenge_id,
+            job.challenge.difficulty,
+            job.nonce_count,
+            nonce_start,
+        );
+        let heartbeat_done = Arc::new(AtomicBool::new(false));
+        let heartbeat = {
+            let (coordinator, done, id) = (Arc::clone(&coordinator), Arc::clone(&heartbeat_done), job.id);
+            thread::Builder::new().name("worker-heartbeat".to_string()).spawn(move || heartbeat(&coordinator, id, &done))
+        };
+
+        let limits = MiningLimits {
+            max_hashes: None,
+            max_duration: None,
+            check_interval: DEFAULT_LIMIT_CHECK_INTERVAL,
+            nonce_count: Some(job.nonce_count),
+            share_bits: job.share_bits,
+        };
+        take_shares();
+        let started = Instant::now();
+        let result = mine_single_solution(rom, &job.wallet, &job.challenge, num_threads, &limits, nonce_start);
+        heartbeat_done.store(true, Ordering::SeqCst);
+        let shares_reported = heartbeat.ok().and_then(|handle| handle.join().ok()).unwrap_or(0);
+
+        let (outcome, nonce) = match result {
+            MiningResult::Found(nonce, _) => ("found", Some(format!("{:016x}", nonce))),
+            MiningResult::NotFound => ("exhausted", None),
+            MiningResult::Skipped(_) => ("cancelled", None),
+            MiningResult::DeadlinePassed(_) => ("deadline", None),
+            MiningResult::Interrupted(_) | MiningResult::TooHard(..) => ("interrupted", None),
+        };
+        // NotFound carries no count, but an exhausted range was hashed in full
+        let hashes = if outcome == "exhausted" { job.nonce_count } else { result.hashes() };
+        jobs += 1;
+        if nonce.is_some() {
+            found += 1;
+        }
+        let shares = take_shares();
+        tracing::info!(
+            "{} Job {} {} after {} hashes in {:.1?}{}",
+            if nonce.is_some() { "✅" } else { "⏹️ " },
+            job.id,
+            outcome,
+            hashes,
+            started.elapsed(),
+            if job.share_bits.is_some() { format!(", {} share(s)", shares_reported + shares.len()) } else { String::new() },
+        );
+        let body = serde_json::json!({ "outcome": outcome, "nonce": nonce, "hashes": hashes, "shares": hex_nonces(&shares) });
+        if let Err(e) = coordinator.post(&format!("/jobs/{}/result", job.id), body) {
+            tracing::warn!(error = %e, "⚠️  Failed to report job {}: {}", job.id, e);
+        }
+    }
+
+    println!("📊 {} job(s), {} solution(s) found", jobs, found);
+    true
+}
+
+/// Report progress until `done`; gives up the task when the coordinator withdraws the range.
+/// Returns the number of shares sent
+fn heartbeat(coordinator: &Coordinator, id: u64, done: &AtomicBool) -> usize {
+    let (mut last, mut sent) = (Instant::now(), 0);
+    while !done.load(Ordering::SeqCst) {
+        thread::sleep(Duration::from_millis(250));
+        if last.elapsed() < HEARTBEAT_INTERVAL {
+            continue;
+        }
+        last = Instant::now();
+        let hashes = dashboard::task_progress().map_or(0, |(hashes, ..)| hashes);
+        let shares = take_shares();
+        let body = serde_json::json!({ "hashes": hashes, "shares": hex_nonces(&shares) });
+        match coordinator.post(&format!("/jobs/{}/progress", id), body) {
+            Ok(body) if body["cancel"].as_bool() == Some(true) => {
+                tracing::info!("⏭️  Coordinator withdrew job {}", id);
+                dashboard::request_skip();
+                return sent + shares.len();
+            }
+            Ok(_) => sent += shares.len(),
+            Err(e) => {
+                tracing::warn!(error = %e, "⚠️  Progress report for job {} failed: {}", id, e);
+                // Sent again with the next report
+                SHARES.lock().unwrap().extend(shares);
+            }
+        }
+    }
+    sent
+}
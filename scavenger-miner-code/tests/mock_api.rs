@@ -0,0 +1,115 @@
+//! Submission handling against the built-in mock API (`cargo test --features mock-api`)
+#![cfg(all(unix, feature = "mock-api"))]
+
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+const BIN: &str = env!("CARGO_BIN_EXE_scavenger-miner");
+const WALLET: &str = "addr_test1qz2fxv2umyhttkxyxp8x0dlpdt3k6cwng5pxj3jhsydzer3n0d3vllmyqwsx5wktcd8cc3sq835lu7drv2xwl2wywfgs68faae";
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("scavenger-miner-mock-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// A record file in `solutions/` or one of its dated folders
+fn find_record(dir: &Path, file_name: &str) -> Option<PathBuf> {
+    let solutions = dir.join("solutions");
+    std::fs::read_dir(&solutions)
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .chain(std::iter::once(solutions))
+        .map(|d| d.join(file_name))
+        .find(|p| p.is_file())
+}
+
+/// Start the mock API and return it with its base URL
+fn start_mock(respond: &str) -> (Child, String) {
+    let mut child = Command::new(BIN)
+        .args(["mock-api", "--port", "0", "--respond", respond])
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to start mock-api");
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    let mut url = String::new();
+    stdout.read_line(&mut url).unwrap();
+    // Keep draining the request log so the mock never hits a closed pipe
+    std::thread::spawn(move || std::io::copy(&mut stdout, &mut std::io::sink()));
+    (child, url.trim().to_string())
+}
+
+/// Run the miner until the first solution record has an outcome (it is saved as `queued` before
+/// it is submitted), then stop it
+fn mine_until_recorded(dir: &Path, url: &str) -> serde_json::Value {
+    std::fs::write(dir.join("wallets.txt"), format!("{}\n", WALLET)).unwrap();
+    let mut miner = Command::new(BIN)
+        .current_dir(dir)
+        .args(["wallets.txt", "25", "--data-dir", ".", "--api-base", url, "--api-retries", "2", "--api-backoff-ms", "100"])
+        .stdout(Stdio::null())
+        .spawn()
+        .expect("failed to start miner");
+
+    let file_name = format!("{}_MOCK01.json", WALLET);
+    let deadline = Instant::now() + Duration::from_secs(60);
+    let record = loop {
+        let record = find_record(dir, &file_name)
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+            .filter(|record| record["status"] != "queued");
+        if let Some(record) = record {
+            break record;
+        }
+        if Instant::now() > deadline {
+            let _ = miner.kill();
+            panic!("no solution record {} written in {}", file_name, dir.display());
+        }
+        std::thread::sleep(Duration::from_millis(200));
+    };
+
+    let _ = Command::new("kill").args(["-TERM", &miner.id().to_string()]).status();
+    let _ = miner.wait();
+    record
+}
+
+#[test]
+fn rate_limited_submission_is_retried_and_accepted() {
+    let dir = scratch_dir("ratelimit");
+    let (mut mock, url) = start_mock("429,ok");
+
+    let record = mine_until_recorded(&dir, &url);
+    assert_eq!(record["status"], "submitted", "{}", record);
+    assert!(record["crypto_receipt"]["preimage"].as_str().unwrap().contains("**MOCK01"));
+
+    let _ = mock.kill();
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn duplicate_submission_is_recorded_as_duplicate() {
+    let dir = scratch_dir("duplicate");
+    let (mut mock, url) = start_mock("duplicate");
+
+    let record = mine_until_recorded(&dir, &url);
+    assert_eq!(record["status"], "duplicate", "{}", record);
+
+    let _ = mock.kill();
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn rejected_nonce_is_recorded_as_invalid() {
+    let dir = scratch_dir("invalid");
+    let (mut mock, url) = start_mock("invalid");
+
+    let record = mine_until_recorded(&dir, &url);
+    assert_eq!(record["status"], "invalid_nonce", "{}", record);
+
+    let _ = mock.kill();
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,38 @@
+//! End-to-end run of the miner pipeline with the tiny self-test ROM
+
+use std::path::PathBuf;
+use std::process::Command;
+
+fn scratch_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("scavenger-miner-{}-{}", name, std::process::id()))
+}
+
+#[test]
+fn selftest_mines_and_exports_a_solution() {
+    let dir = scratch_dir("selftest");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_scavenger-miner"))
+        .args(["selftest", "--dir"])
+        .arg(&dir)
+        .output()
+        .expect("failed to run scavenger-miner");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "selftest failed:\n{}", stdout);
+
+    // Single-threaded runs are deterministic
+    assert!(stdout.contains("Self-test passed (nonce 0000000000000037)"), "{}", stdout);
+
+    // Records go in a folder per day found
+    let day = std::fs::read_dir(dir.join("solutions"))
+        .expect("no solutions folder")
+        .flatten()
+        .map(|e| e.path())
+        .find(|p| p.join("addr1selftest_SELFTEST02.json").is_file())
+        .expect("solution record was not exported");
+    let record = std::fs::read_to_string(day.join("addr1selftest_SELFTEST02.json")).unwrap();
+    let record: serde_json::Value = serde_json::from_str(&record).unwrap();
+    assert_eq!(record["nonce"], "0000000000000037");
+    assert_eq!(record["status"], "selftest");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}